@@ -64,7 +64,7 @@ impl<const N: usize, const K: usize> FromWithContext<WebGl2RenderingContext, &[S
     }
 }
 
-fn create_accessor(
+pub fn create_accessor(
     context: &WebGl2RenderingContext,
     data: Vec<f32>,
     size: usize,
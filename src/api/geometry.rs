@@ -5,7 +5,15 @@ use glm::{Mat4, Vec2, Vec3, Vec4};
 use web_sys::WebGl2RenderingContext;
 
 use crate::{
-    base::{convert::FromWithContext, util::validate},
+    base::{
+        color::ColorSpace,
+        convert::FromWithContext,
+        math::{
+            normal::{self, NormalMode},
+            tangent,
+        },
+        util::validate,
+    },
     core::{
         accessor::Accessor,
         mesh::{self, AccessorProvider},
@@ -15,11 +23,21 @@ use crate::{
 #[derive(Debug, Clone)]
 pub struct Geometry {
     attributes: HashMap<String, Rc<Accessor>>,
+    color_space: ColorSpace,
 }
 
 impl Geometry {
     pub fn new(attributes: HashMap<String, Rc<Accessor>>) -> Self {
-        Self { attributes }
+        Self {
+            attributes,
+            color_space: ColorSpace::Srgb,
+        }
+    }
+
+    /// Declares the encoding of `COLOR_0`, which is sRGB unless set otherwise.
+    pub fn with_color_space(mut self, color_space: ColorSpace) -> Self {
+        self.color_space = color_space;
+        self
     }
 }
 
@@ -37,6 +55,10 @@ impl AccessorProvider for Geometry {
     fn vertex_accessors(&self) -> HashMap<String, Rc<Accessor>> {
         self.attributes.clone()
     }
+
+    fn vertex_color_space(&self) -> ColorSpace {
+        self.color_space
+    }
 }
 
 #[derive(Debug)]
@@ -45,6 +67,7 @@ pub struct TypedGeometry {
     texcoord_0: Option<Vec<Vec2>>,
     normal: Option<Vec<Vec3>>,
     color_0: Option<Vec<Vec4>>,
+    tangent: Option<Vec<Vec4>>,
 }
 
 impl TypedGeometry {
@@ -75,6 +98,7 @@ impl TypedGeometry {
             texcoord_0,
             normal,
             color_0,
+            tangent: None,
         })
     }
 
@@ -95,6 +119,9 @@ impl TypedGeometry {
         validate::assert(self.has_color() == other.has_color(), || {
             anyhow!("TypedGeometry::concat: Number of color elements must be equal")
         })?;
+        validate::assert(self.has_tangent() == other.has_tangent(), || {
+            anyhow!("TypedGeometry::concat: Number of tangent elements must be equal")
+        })?;
         self.position.extend(&other.position);
         if let Some(texcoord) = &mut self.texcoord_0 {
             texcoord.extend(other.texcoord_0.as_ref().unwrap());
@@ -105,6 +132,27 @@ impl TypedGeometry {
         if let Some(color) = &mut self.color_0 {
             color.extend(other.color_0.as_ref().unwrap());
         }
+        if let Some(tangent) = &mut self.tangent {
+            tangent.extend(other.tangent.as_ref().unwrap());
+        }
+        Ok(())
+    }
+
+    pub fn compute_normals(&mut self, mode: NormalMode) -> Result<()> {
+        self.normal = Some(normal::generate(&self.position, None, mode)?);
+        Ok(())
+    }
+
+    pub fn generate_tangents(&mut self) -> Result<()> {
+        let normal = self
+            .normal
+            .as_ref()
+            .ok_or_else(|| anyhow!("Tangent generation requires normals"))?;
+        let texcoord_0 = self
+            .texcoord_0
+            .as_ref()
+            .ok_or_else(|| anyhow!("Tangent generation requires texcoords"))?;
+        self.tangent = Some(tangent::generate(&self.position, normal, texcoord_0, None)?);
         Ok(())
     }
 
@@ -119,6 +167,10 @@ impl TypedGeometry {
     pub fn has_color(&self) -> bool {
         self.color_0.is_some()
     }
+
+    pub fn has_tangent(&self) -> bool {
+        self.tangent.is_some()
+    }
 }
 
 impl FromWithContext<WebGl2RenderingContext, TypedGeometry> for Geometry {
@@ -146,6 +198,12 @@ impl FromWithContext<WebGl2RenderingContext, TypedGeometry> for Geometry {
                 Rc::new(Accessor::from_with_context(context, color_0)?),
             );
         }
+        if let Some(tangent) = &value.tangent {
+            attributes.insert(
+                String::from(mesh::TANGENT_ATTRIBUTE),
+                Rc::new(Accessor::from_with_context(context, tangent)?),
+            );
+        }
         Ok(Geometry::new(attributes))
     }
 }
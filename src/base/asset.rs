@@ -0,0 +1,194 @@
+use std::{collections::HashMap, fmt::Debug};
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use js_sys::Uint8Array;
+use url::Url;
+use web_sys::HtmlImageElement;
+
+use super::{util::base64, web};
+
+#[async_trait(?Send)]
+pub trait AssetSource: Debug {
+    async fn read_bytes(&self, uri: &str) -> Result<Vec<u8>>;
+
+    async fn decode_image(&self, uri: &str) -> Result<HtmlImageElement> {
+        if uri.starts_with("data:") {
+            return web::fetch_image(uri).await;
+        }
+        let bytes = self.read_bytes(uri).await?;
+        let data_uri = format!("data:{};base64,{}", mime_type(uri), base64::encode(&bytes));
+        web::fetch_image(&data_uri).await
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct FetchSource;
+
+#[async_trait(?Send)]
+impl AssetSource for FetchSource {
+    async fn read_bytes(&self, uri: &str) -> Result<Vec<u8>> {
+        let array_buffer = web::fetch_array_buffer(uri).await?;
+        Ok(Uint8Array::new(&array_buffer).to_vec())
+    }
+
+    async fn decode_image(&self, uri: &str) -> Result<HtmlImageElement> {
+        web::fetch_image(uri).await
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MemorySource {
+    assets: HashMap<String, Vec<u8>>,
+}
+
+impl MemorySource {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_asset(mut self, path: &str, data: Vec<u8>) -> Self {
+        self.insert(path, data);
+        self
+    }
+
+    pub fn insert(&mut self, path: &str, data: Vec<u8>) {
+        self.assets.insert(self::asset_path(path), data);
+    }
+}
+
+#[async_trait(?Send)]
+impl AssetSource for MemorySource {
+    async fn read_bytes(&self, uri: &str) -> Result<Vec<u8>> {
+        if let Some(data) = self::decode_data_uri(uri)? {
+            return Ok(data);
+        }
+        self.assets
+            .get(&self::asset_path(uri))
+            .cloned()
+            .ok_or_else(|| anyhow!("Cannot find asset '{}'", uri))
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+#[derive(Debug, Clone)]
+pub struct FileSource {
+    root: std::path::PathBuf,
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+impl FileSource {
+    pub fn new(root: impl Into<std::path::PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+#[async_trait(?Send)]
+impl AssetSource for FileSource {
+    async fn read_bytes(&self, uri: &str) -> Result<Vec<u8>> {
+        if let Some(data) = self::decode_data_uri(uri)? {
+            return Ok(data);
+        }
+        let path = self.root.join(self::asset_path(uri));
+        std::fs::read(&path).map_err(|error| anyhow!("Cannot read {:?}: {}", path, error))
+    }
+}
+
+fn asset_path(uri: &str) -> String {
+    let path = match Url::parse(uri) {
+        Ok(url) => String::from(url.path()),
+        Err(_) => String::from(uri),
+    };
+    self::percent_decode(path.trim_start_matches('/'))
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut result = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(value)) => {
+                result.push(value);
+                i += 3;
+            }
+            (byte, _) => {
+                result.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&result).into_owned()
+}
+
+fn decode_data_uri(uri: &str) -> Result<Option<Vec<u8>>> {
+    let Some(data) = uri.strip_prefix("data:") else {
+        return Ok(None);
+    };
+    let (header, payload) = data
+        .split_once(',')
+        .ok_or_else(|| anyhow!("Invalid data uri"))?;
+    if header.ends_with(";base64") {
+        base64::decode(payload).map(Some)
+    } else {
+        Ok(Some(self::percent_decode(payload).into_bytes()))
+    }
+}
+
+fn mime_type(uri: &str) -> &'static str {
+    let path = uri.to_lowercase();
+    if path.ends_with(".png") {
+        "image/png"
+    } else if path.ends_with(".jpg") || path.ends_with(".jpeg") {
+        "image/jpeg"
+    } else if path.ends_with(".webp") {
+        "image/webp"
+    } else {
+        "application/octet-stream"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor;
+
+    use super::*;
+
+    #[test]
+    fn memory_source_works() {
+        let source = MemorySource::new().with_asset("models/Box With Spaces.bin", vec![1, 2, 3]);
+        let read = |uri| executor::block_on(source.read_bytes(uri));
+        assert_eq!(
+            read("memory:///models/Box%20With%20Spaces.bin").unwrap(),
+            vec![1, 2, 3]
+        );
+        assert_eq!(
+            read("data:application/octet-stream;base64,AQID").unwrap(),
+            vec![1, 2, 3]
+        );
+        assert!(read("memory:///missing.bin").is_err());
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn file_source_works() {
+        let source = FileSource::new(env!("CARGO_MANIFEST_DIR"));
+        let read = |uri| executor::block_on(source.read_bytes(uri));
+        let manifest = read("Cargo.toml").unwrap();
+        assert_eq!(
+            manifest,
+            std::fs::read(concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml")).unwrap()
+        );
+        assert_eq!(read("file:///Cargo.toml").unwrap(), manifest);
+        assert_eq!(
+            read("data:application/octet-stream;base64,AQID").unwrap(),
+            vec![1, 2, 3]
+        );
+        assert!(read("static/missing.bin").is_err());
+    }
+}
@@ -1,7 +1,15 @@
 use glm::Vec4;
 
+/// RGBA color with sRGB-encoded channels, as used by the named colors below.
 pub type Color = Vec4;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ColorSpace {
+    #[default]
+    Srgb,
+    Linear,
+}
+
 pub fn rgba(r: f32, g: f32, b: f32, a: f32) -> Color {
     glm::vec4(r, g, b, a)
 }
@@ -74,6 +82,40 @@ pub fn medium_slate_blue() -> Color {
     self::rgb_u8(123, 104, 238)
 }
 
+pub fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+pub fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+pub fn to_linear(color: &Color) -> Color {
+    self::rgba(
+        self::srgb_to_linear(color.x),
+        self::srgb_to_linear(color.y),
+        self::srgb_to_linear(color.z),
+        color.w,
+    )
+}
+
+pub fn to_srgb(color: &Color) -> Color {
+    self::rgba(
+        self::linear_to_srgb(color.x),
+        self::linear_to_srgb(color.y),
+        self::linear_to_srgb(color.z),
+        color.w,
+    )
+}
+
 fn rgb_u8(red: u8, green: u8, blue: u8) -> Color {
     self::rgba(
         f32::from(red) / 255.0,
@@ -82,3 +124,17 @@ fn rgb_u8(red: u8, green: u8, blue: u8) -> Color {
         1.0,
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_linear_works() {
+        let linear = to_linear(&gray());
+        assert!((linear.x - 0.2158605).abs() < 1e-5);
+        assert_eq!(linear.w, 1.0);
+        assert!((to_srgb(&linear) - gray()).norm() < 1e-5);
+        assert_eq!(to_linear(&black()), black());
+    }
+}
@@ -32,6 +32,12 @@ pub fn get_f64_parameter(context: &WebGl2RenderingContext, pname: u32) -> Result
         .ok_or_else(|| anyhow!("Cannot convert {:#?} to string", pname))
 }
 
+pub fn get_bool_parameter(context: &WebGl2RenderingContext, pname: u32) -> Result<bool> {
+    get_parameter(context, pname)?
+        .as_bool()
+        .ok_or_else(|| anyhow!("Cannot convert {:#?} to bool", pname))
+}
+
 pub fn compile_shader(
     context: &WebGl2RenderingContext,
     shader_type: u32,
@@ -1,47 +1,81 @@
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use std::{cell::RefCell, collections::HashSet, rc::Rc};
 
-use futures::channel::{self, mpsc::UnboundedReceiver};
+use futures::channel::{
+    self,
+    mpsc::{UnboundedReceiver, UnboundedSender},
+};
+use glm::Vec2;
 use wasm_bindgen::{prelude::Closure, JsCast};
-use web_sys::{HtmlCanvasElement, KeyboardEvent};
+use web_sys::{HtmlCanvasElement, KeyboardEvent, MouseEvent};
 
 enum KeyEvent {
     Up(KeyboardEvent),
     Down(KeyboardEvent),
+    PointerUp(MouseEvent),
+    PointerDown(MouseEvent),
+    PointerMove(MouseEvent),
 }
 
 pub struct KeyState {
-    pressed: HashMap<String, KeyboardEvent>,
+    pressed: HashSet<String>,
+    pointer_position: Option<Vec2>,
 }
 
 impl KeyState {
+    pub const PRIMARY_BUTTON: &'static str = "Mouse0";
+
     pub fn new() -> KeyState {
         KeyState {
-            pressed: HashMap::new(),
+            pressed: HashSet::new(),
+            pointer_position: None,
         }
     }
 
+    /// Mouse buttons are reported as `Mouse0`, `Mouse1`, ... next to the keyboard codes.
     pub fn is_pressed(&self, code: &str) -> bool {
-        self.pressed.contains_key(code)
+        self.pressed.contains(code)
+    }
+
+    /// Last pointer position over the canvas in normalized device coordinates,
+    /// from -1 to 1 with y pointing up.
+    pub fn pointer_position(&self) -> Option<Vec2> {
+        self.pointer_position
     }
 
-    fn set_pressed(&mut self, code: &str, event: KeyboardEvent) {
-        self.pressed.insert(code.into(), event);
+    fn set_pressed(&mut self, code: String) {
+        self.pressed.insert(code);
     }
 
     fn set_released(&mut self, code: &str) {
         self.pressed.remove(code);
     }
+
+    fn set_pointer_position(&mut self, canvas: &HtmlCanvasElement, event: &MouseEvent) {
+        let (width, height) = (canvas.client_width(), canvas.client_height());
+        if width > 0 && height > 0 {
+            self.pointer_position = Some(glm::vec2(
+                2.0 * event.offset_x() as f32 / width as f32 - 1.0,
+                1.0 - 2.0 * event.offset_y() as f32 / height as f32,
+            ));
+        }
+    }
+
+    fn button_code(event: &MouseEvent) -> String {
+        format!("Mouse{}", event.button())
+    }
 }
 
 pub struct KeyboardInput {
     receiver: UnboundedReceiver<KeyEvent>,
+    canvas: HtmlCanvasElement,
 }
 
 impl KeyboardInput {
     pub fn prepare(canvas: &HtmlCanvasElement) -> KeyboardInput {
         let (keyevent_sender, keyevent_receiver) = channel::mpsc::unbounded();
-        let keydown_sender = Rc::new(RefCell::new(keyevent_sender));
-        let keyup_sender = Rc::clone(&keydown_sender);
+        let sender = Rc::new(RefCell::new(keyevent_sender));
+        let keydown_sender = Rc::clone(&sender);
+        let keyup_sender = Rc::clone(&sender);
         let onkeydown = Closure::wrap(Box::new(move |event: KeyboardEvent| {
             let result = keydown_sender
                 .borrow_mut()
@@ -60,11 +94,34 @@ impl KeyboardInput {
         canvas.set_onkeyup(Some(onkeyup.as_ref().unchecked_ref()));
         onkeydown.forget();
         onkeyup.forget();
+        let onmousedown = Self::mouse_listener(&sender, KeyEvent::PointerDown);
+        let onmouseup = Self::mouse_listener(&sender, KeyEvent::PointerUp);
+        let onmousemove = Self::mouse_listener(&sender, KeyEvent::PointerMove);
+        canvas.set_onmousedown(Some(onmousedown.as_ref().unchecked_ref()));
+        canvas.set_onmouseup(Some(onmouseup.as_ref().unchecked_ref()));
+        canvas.set_onmousemove(Some(onmousemove.as_ref().unchecked_ref()));
+        onmousedown.forget();
+        onmouseup.forget();
+        onmousemove.forget();
         KeyboardInput {
             receiver: keyevent_receiver,
+            canvas: canvas.clone(),
         }
     }
 
+    fn mouse_listener(
+        sender: &Rc<RefCell<UnboundedSender<KeyEvent>>>,
+        to_event: fn(MouseEvent) -> KeyEvent,
+    ) -> Closure<dyn FnMut(MouseEvent)> {
+        let sender = Rc::clone(sender);
+        Closure::wrap(Box::new(move |event: MouseEvent| {
+            let result = sender.borrow_mut().start_send(to_event(event));
+            if let Err(error) = result {
+                error!("Cannot send mouse event: {:#?}", error);
+            }
+        }) as Box<dyn FnMut(MouseEvent)>)
+    }
+
     pub fn process(&mut self, state: &mut KeyState) {
         loop {
             match self.receiver.try_next() {
@@ -72,7 +129,18 @@ impl KeyboardInput {
                 Err(_error) => break,
                 Ok(Some(event)) => match event {
                     KeyEvent::Up(event) => state.set_released(&event.code()),
-                    KeyEvent::Down(event) => state.set_pressed(&event.code(), event),
+                    KeyEvent::Down(event) => state.set_pressed(event.code()),
+                    KeyEvent::PointerUp(event) => {
+                        state.set_pointer_position(&self.canvas, &event);
+                        state.set_released(&KeyState::button_code(&event));
+                    }
+                    KeyEvent::PointerDown(event) => {
+                        state.set_pointer_position(&self.canvas, &event);
+                        state.set_pressed(KeyState::button_code(&event));
+                    }
+                    KeyEvent::PointerMove(event) => {
+                        state.set_pointer_position(&self.canvas, &event)
+                    }
                 },
             }
         }
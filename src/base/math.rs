@@ -1,3 +1,11 @@
+pub mod aabb;
 pub mod angle;
+pub mod bvh;
+pub mod euler;
+pub mod frustum;
 pub mod matrix;
+pub mod normal;
+pub mod ray;
 pub mod resolution;
+pub mod tangent;
+pub mod triangle;
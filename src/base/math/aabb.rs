@@ -0,0 +1,101 @@
+use glm::{Mat4, Vec3};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Self { min, max }
+    }
+
+    pub fn from_points<'a, I>(points: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = &'a Vec3>,
+    {
+        points.into_iter().fold(None, |aabb: Option<Self>, point| {
+            Some(match aabb {
+                Some(aabb) => aabb.extend(point),
+                None => Self::new(*point, *point),
+            })
+        })
+    }
+
+    pub fn extend(&self, point: &Vec3) -> Self {
+        Self::new(glm::min2(&self.min, point), glm::max2(&self.max, point))
+    }
+
+    pub fn union(&self, other: &Aabb) -> Self {
+        Self::new(
+            glm::min2(&self.min, &other.min),
+            glm::max2(&self.max, &other.max),
+        )
+    }
+
+    pub fn center(&self) -> Vec3 {
+        (self.min + self.max) / 2.0
+    }
+
+    pub fn size(&self) -> Vec3 {
+        self.max - self.min
+    }
+
+    pub fn surface_area(&self) -> f32 {
+        let size = self.size();
+        2.0 * (size.x * size.y + size.y * size.z + size.z * size.x)
+    }
+
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        (0..3).all(|axis| self.min[axis] <= other.max[axis] && other.min[axis] <= self.max[axis])
+    }
+
+    pub fn closest_point(&self, point: &Vec3) -> Vec3 {
+        glm::clamp_vec(point, &self.min, &self.max)
+    }
+
+    pub fn corners(&self) -> [Vec3; 8] {
+        let (min, max) = (self.min, self.max);
+        [
+            glm::vec3(min.x, min.y, min.z),
+            glm::vec3(max.x, min.y, min.z),
+            glm::vec3(min.x, max.y, min.z),
+            glm::vec3(max.x, max.y, min.z),
+            glm::vec3(min.x, min.y, max.z),
+            glm::vec3(max.x, min.y, max.z),
+            glm::vec3(min.x, max.y, max.z),
+            glm::vec3(max.x, max.y, max.z),
+        ]
+    }
+
+    pub fn transform(&self, transform: &Mat4) -> Self {
+        let corners = self
+            .corners()
+            .map(|corner| glm::vec4_to_vec3(&(transform * corner.push(1.0))));
+        Self::from_points(&corners).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aabb_works() {
+        let points = [glm::vec3(1.0, -1.0, 0.0), glm::vec3(-1.0, 2.0, 3.0)];
+        let aabb = Aabb::from_points(&points).unwrap();
+        assert_eq!(aabb.min, glm::vec3(-1.0, -1.0, 0.0));
+        assert_eq!(aabb.center(), glm::vec3(0.0, 0.5, 1.5));
+        let moved = aabb.transform(&glm::translation(&glm::vec3(1.0, 0.0, 0.0)));
+        assert_eq!(moved.max, glm::vec3(2.0, 2.0, 3.0));
+        assert!(Aabb::from_points(&[]).is_none());
+        assert_eq!(aabb.surface_area(), 2.0 * (6.0 + 9.0 + 6.0));
+        assert!(aabb.intersects(&moved));
+        assert!(!aabb.intersects(&aabb.transform(&glm::translation(&glm::vec3(3.0, 0.0, 0.0)))));
+        assert_eq!(
+            aabb.closest_point(&glm::vec3(5.0, 0.0, -2.0)),
+            glm::vec3(1.0, 0.0, 0.0)
+        );
+    }
+}
@@ -0,0 +1,316 @@
+use anyhow::{anyhow, Result};
+use glm::Vec3;
+
+use crate::base::util::validate;
+
+use super::{aabb::Aabb, frustum::Frustum, ray::Ray};
+
+/// A bounding volume hierarchy over items identified by their index in the
+/// slice of bounds it was built from, split with a binned surface area heuristic.
+#[derive(Debug, Clone, Default)]
+pub struct Bvh {
+    nodes: Vec<BvhNode>,
+    items: Vec<usize>,
+    bounds: Vec<Aabb>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct BvhNode {
+    bounds: Aabb,
+    content: Content,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Content {
+    Leaf { start: usize, count: usize },
+    Branch { left: usize, right: usize },
+}
+
+impl Bvh {
+    const BIN_COUNT: usize = 12;
+    const MAX_LEAF_SIZE: usize = 4;
+
+    pub fn build(bounds: &[Aabb]) -> Self {
+        let mut bvh = Self {
+            nodes: vec![],
+            items: (0..bounds.len()).collect(),
+            bounds: bounds.to_vec(),
+        };
+        if !bounds.is_empty() {
+            bvh.build_node(0, bounds.len());
+        }
+        bvh
+    }
+
+    pub fn bounds(&self) -> Option<Aabb> {
+        self.nodes.first().map(|node| node.bounds)
+    }
+
+    /// Updates the node bounds after items moved, keeping the topology. The
+    /// bounds have to be given in the same order as when building.
+    pub fn refit(&mut self, bounds: &[Aabb]) -> Result<()> {
+        validate::assert(bounds.len() == self.bounds.len(), || {
+            anyhow!(
+                "Cannot refit {} items with {} bounds",
+                self.bounds.len(),
+                bounds.len()
+            )
+        })?;
+        self.bounds.copy_from_slice(bounds);
+        // Children are always stored after their parent.
+        for index in (0..self.nodes.len()).rev() {
+            self.nodes[index].bounds = match self.nodes[index].content {
+                Content::Leaf { start, count } => self.union(start, start + count),
+                Content::Branch { left, right } => {
+                    self.nodes[left].bounds.union(&self.nodes[right].bounds)
+                }
+            };
+        }
+        Ok(())
+    }
+
+    /// Returns the items whose bounds are hit by the ray, in no particular order.
+    pub fn query_ray(&self, ray: &Ray) -> Vec<usize> {
+        self.query(|bounds| ray.intersect_aabb(bounds).is_some())
+    }
+
+    pub fn query_aabb(&self, aabb: &Aabb) -> Vec<usize> {
+        self.query(|bounds| bounds.intersects(aabb))
+    }
+
+    pub fn query_frustum(&self, frustum: &Frustum) -> Vec<usize> {
+        self.query(|bounds| frustum.intersects_aabb(bounds))
+    }
+
+    /// Finds the nearest item hit by the ray, where `intersect` returns the
+    /// distance to an item; subtrees farther than the best hit are skipped.
+    pub fn raycast<F>(&self, ray: &Ray, mut intersect: F) -> Option<(usize, f32)>
+    where
+        F: FnMut(usize) -> Option<f32>,
+    {
+        let mut best: Option<(usize, f32)> = None;
+        let mut stack = vec![0];
+        while let Some(index) = stack.pop() {
+            let Some(node) = self.nodes.get(index) else {
+                break;
+            };
+            let Some(entry) = ray.intersect_aabb(&node.bounds) else {
+                continue;
+            };
+            if best.is_some_and(|(_, distance)| entry > distance) {
+                continue;
+            }
+            match node.content {
+                Content::Leaf { start, count } => {
+                    for &item in &self.items[start..start + count] {
+                        if let Some(distance) = intersect(item) {
+                            if best.is_none_or(|(_, best)| distance < best) {
+                                best = Some((item, distance));
+                            }
+                        }
+                    }
+                }
+                Content::Branch { left, right } => stack.extend([right, left]),
+            }
+        }
+        best
+    }
+
+    /// Finds the item nearest to `point`, where `closest_point` returns the
+    /// point of an item nearest to it.
+    pub fn nearest<F>(&self, point: &Vec3, mut closest_point: F) -> Option<(usize, Vec3)>
+    where
+        F: FnMut(usize) -> Option<Vec3>,
+    {
+        let distance = |bounds: &Aabb| glm::distance2(&bounds.closest_point(point), point);
+        let mut best: Option<(usize, Vec3, f32)> = None;
+        let mut stack = vec![0];
+        while let Some(index) = stack.pop() {
+            let Some(node) = self.nodes.get(index) else {
+                break;
+            };
+            if best.is_some_and(|(_, _, best)| distance(&node.bounds) > best) {
+                continue;
+            }
+            match node.content {
+                Content::Leaf { start, count } => {
+                    for &item in &self.items[start..start + count] {
+                        if let Some(closest) = closest_point(item) {
+                            let distance = glm::distance2(&closest, point);
+                            if best.is_none_or(|(_, _, best)| distance < best) {
+                                best = Some((item, closest, distance));
+                            }
+                        }
+                    }
+                }
+                Content::Branch { left, right } => {
+                    // Visit the nearer child first to tighten the bound early.
+                    if distance(&self.nodes[left].bounds) < distance(&self.nodes[right].bounds) {
+                        stack.extend([right, left]);
+                    } else {
+                        stack.extend([left, right]);
+                    }
+                }
+            }
+        }
+        best.map(|(item, closest, _)| (item, closest))
+    }
+
+    fn query<F>(&self, overlaps: F) -> Vec<usize>
+    where
+        F: Fn(&Aabb) -> bool,
+    {
+        let mut result = vec![];
+        let mut stack = vec![0];
+        while let Some(index) = stack.pop() {
+            let Some(node) = self.nodes.get(index) else {
+                break;
+            };
+            if !overlaps(&node.bounds) {
+                continue;
+            }
+            match node.content {
+                Content::Leaf { start, count } => result.extend(
+                    self.items[start..start + count]
+                        .iter()
+                        .filter(|item| overlaps(&self.bounds[**item])),
+                ),
+                Content::Branch { left, right } => stack.extend([right, left]),
+            }
+        }
+        result
+    }
+
+    fn union(&self, start: usize, end: usize) -> Aabb {
+        self.items[start + 1..end]
+            .iter()
+            .fold(self.bounds[self.items[start]], |result, item| {
+                result.union(&self.bounds[*item])
+            })
+    }
+
+    fn build_node(&mut self, start: usize, end: usize) -> usize {
+        let index = self.nodes.len();
+        let node_bounds = self.union(start, end);
+        let count = end - start;
+        self.nodes.push(BvhNode {
+            bounds: node_bounds,
+            content: Content::Leaf { start, count },
+        });
+        if count <= Self::MAX_LEAF_SIZE {
+            return index;
+        }
+        let Some(middle) = self.split(start, end, &node_bounds) else {
+            return index;
+        };
+        let left = self.build_node(start, middle);
+        let right = self.build_node(middle, end);
+        self.nodes[index].content = Content::Branch { left, right };
+        index
+    }
+
+    /// Partitions the items along the axis with the largest centroid extent and
+    /// returns the start of the second half, or `None` if a leaf is cheaper.
+    fn split(&mut self, start: usize, end: usize, node_bounds: &Aabb) -> Option<usize> {
+        let bounds = &self.bounds;
+        let centers: Vec<_> = self.items[start..end]
+            .iter()
+            .map(|item| bounds[*item].center())
+            .collect();
+        let centroids = Aabb::from_points(&centers)?;
+        let extent = centroids.size();
+        let axis = extent.imax();
+        if extent[axis] <= 0.0 {
+            return None;
+        }
+        let bin_of = |item: &usize| {
+            let offset = (bounds[*item].center()[axis] - centroids.min[axis]) / extent[axis];
+            ((offset * Self::BIN_COUNT as f32) as usize).min(Self::BIN_COUNT - 1)
+        };
+
+        let mut bins: [Option<Aabb>; Self::BIN_COUNT] = [None; Self::BIN_COUNT];
+        let mut counts = [0; Self::BIN_COUNT];
+        for item in &self.items[start..end] {
+            let bin = bin_of(item);
+            counts[bin] += 1;
+            bins[bin] = Some(bins[bin].map_or(bounds[*item], |aabb| aabb.union(&bounds[*item])));
+        }
+        let cost = |bins: &[Option<Aabb>], counts: &[usize]| {
+            let area = bins
+                .iter()
+                .flatten()
+                .copied()
+                .reduce(|a, b| a.union(&b))
+                .map_or(0.0, |aabb| aabb.surface_area());
+            area * counts.iter().sum::<usize>() as f32
+        };
+        let (split, split_cost) = (1..Self::BIN_COUNT)
+            .map(|split| {
+                let left = cost(&bins[..split], &counts[..split]);
+                let right = cost(&bins[split..], &counts[split..]);
+                (split, left + right)
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))?;
+        let leaf_cost = node_bounds.surface_area() * (end - start) as f32;
+        if split_cost >= leaf_cost && end - start <= Self::MAX_LEAF_SIZE * 4 {
+            return None;
+        }
+
+        let items = &mut self.items[start..end];
+        let mut middle = 0;
+        for index in 0..items.len() {
+            if bin_of(&items[index]) < split {
+                items.swap(index, middle);
+                middle += 1;
+            }
+        }
+        (middle > 0 && middle < items.len()).then_some(start + middle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bvh_works() {
+        let cube = |x: f32, y: f32| {
+            let center = glm::vec3(x, y, 0.0);
+            Aabb::new(center.add_scalar(-0.25), center.add_scalar(0.25))
+        };
+        let mut bounds: Vec<_> = (0..10)
+            .flat_map(|x| (0..10).map(move |y| cube(x as f32, y as f32)))
+            .collect();
+        let mut bvh = Bvh::build(&bounds);
+        assert_eq!(bvh.bounds().unwrap().max, glm::vec3(9.25, 9.25, 0.25));
+
+        let ray = Ray::new(glm::vec3(3.0, 4.0, 5.0), glm::vec3(0.0, 0.0, -1.0));
+        assert_eq!(bvh.query_ray(&ray), vec![34]);
+        let hit = bvh.raycast(&ray, |item| ray.intersect_aabb(&bounds[item]));
+        assert_eq!(hit, Some((34, 4.75)));
+
+        let mut overlapping = bvh.query_aabb(&Aabb::new(
+            glm::vec3(0.0, 0.0, 0.0),
+            glm::vec3(1.0, 1.0, 0.0),
+        ));
+        overlapping.sort();
+        assert_eq!(overlapping, vec![0, 1, 10, 11]);
+
+        let frustum = Frustum::from_matrix(&glm::ortho(-0.5, 1.5, -0.5, 0.5, -1.0, 1.0));
+        let mut visible = bvh.query_frustum(&frustum);
+        visible.sort();
+        assert_eq!(visible, vec![0, 10]);
+
+        let point = glm::vec3(20.0, 2.1, 0.0);
+        let nearest = bvh.nearest(&point, |item| Some(bounds[item].closest_point(&point)));
+        assert_eq!(nearest, Some((92, glm::vec3(9.25, 2.1, 0.0))));
+
+        bounds[92] = cube(-5.0, 0.0);
+        bvh.refit(&bounds).unwrap();
+        assert_eq!(bvh.bounds().unwrap().min, glm::vec3(-5.25, -0.25, -0.25));
+        let nearest = bvh.nearest(&point, |item| Some(bounds[item].closest_point(&point)));
+        assert_eq!(nearest.map(|(item, _)| item), Some(93));
+        assert!(bvh.refit(&bounds[1..]).is_err());
+        assert!(Bvh::build(&[]).raycast(&ray, |_| Some(0.0)).is_none());
+    }
+}
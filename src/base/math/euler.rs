@@ -0,0 +1,141 @@
+use anyhow::{anyhow, Result};
+use glm::{Mat3, Qua, Vec3};
+
+use super::angle::Angle;
+
+/// Order in which the axis rotations are applied, `Xyz` meaning `Rx * Ry * Rz`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EulerOrder {
+    #[default]
+    Xyz,
+    Xzy,
+    Yxz,
+    Yzx,
+    Zxy,
+    Zyx,
+}
+
+impl TryFrom<&str> for EulerOrder {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        match value {
+            "XYZ" => Ok(Self::Xyz),
+            "XZY" => Ok(Self::Xzy),
+            "YXZ" => Ok(Self::Yxz),
+            "YZX" => Ok(Self::Yzx),
+            "ZXY" => Ok(Self::Zxy),
+            "ZYX" => Ok(Self::Zyx),
+            _ => Err(anyhow!("Unknown Euler order: {}", value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Euler {
+    pub x: Angle,
+    pub y: Angle,
+    pub z: Angle,
+    pub order: EulerOrder,
+}
+
+impl Euler {
+    const GIMBAL_LOCK_THRESHOLD: f32 = 0.9999999;
+
+    pub fn new(x: Angle, y: Angle, z: Angle, order: EulerOrder) -> Self {
+        Self { x, y, z, order }
+    }
+
+    pub fn to_quat(self) -> Qua<f32> {
+        let axis_rotation =
+            |angle: Angle, axis: Vec3| glm::quat_angle_axis(angle.to_radians(), &axis);
+        let x = axis_rotation(self.x, glm::vec3(1.0, 0.0, 0.0));
+        let y = axis_rotation(self.y, glm::vec3(0.0, 1.0, 0.0));
+        let z = axis_rotation(self.z, glm::vec3(0.0, 0.0, 1.0));
+        match self.order {
+            EulerOrder::Xyz => x * y * z,
+            EulerOrder::Xzy => x * z * y,
+            EulerOrder::Yxz => y * x * z,
+            EulerOrder::Yzx => y * z * x,
+            EulerOrder::Zxy => z * x * y,
+            EulerOrder::Zyx => z * y * x,
+        }
+    }
+
+    pub fn from_quat(rotation: &Qua<f32>, order: EulerOrder) -> Self {
+        let m: Mat3 = glm::quat_to_mat3(rotation);
+        let asin = |value: f32| value.clamp(-1.0, 1.0).asin();
+        let unlocked = |value: f32| value.abs() < Self::GIMBAL_LOCK_THRESHOLD;
+        let (x, y, z) = match order {
+            EulerOrder::Xyz if unlocked(m[(0, 2)]) => (
+                (-m[(1, 2)]).atan2(m[(2, 2)]),
+                asin(m[(0, 2)]),
+                (-m[(0, 1)]).atan2(m[(0, 0)]),
+            ),
+            EulerOrder::Xyz => (m[(2, 1)].atan2(m[(1, 1)]), asin(m[(0, 2)]), 0.0),
+            EulerOrder::Xzy if unlocked(m[(0, 1)]) => (
+                m[(2, 1)].atan2(m[(1, 1)]),
+                m[(0, 2)].atan2(m[(0, 0)]),
+                asin(-m[(0, 1)]),
+            ),
+            EulerOrder::Xzy => ((-m[(1, 2)]).atan2(m[(2, 2)]), 0.0, asin(-m[(0, 1)])),
+            EulerOrder::Yxz if unlocked(m[(1, 2)]) => (
+                asin(-m[(1, 2)]),
+                m[(0, 2)].atan2(m[(2, 2)]),
+                m[(1, 0)].atan2(m[(1, 1)]),
+            ),
+            EulerOrder::Yxz => (asin(-m[(1, 2)]), (-m[(2, 0)]).atan2(m[(0, 0)]), 0.0),
+            EulerOrder::Yzx if unlocked(m[(1, 0)]) => (
+                (-m[(1, 2)]).atan2(m[(1, 1)]),
+                (-m[(2, 0)]).atan2(m[(0, 0)]),
+                asin(m[(1, 0)]),
+            ),
+            EulerOrder::Yzx => (0.0, m[(0, 2)].atan2(m[(2, 2)]), asin(m[(1, 0)])),
+            EulerOrder::Zxy if unlocked(m[(2, 1)]) => (
+                asin(m[(2, 1)]),
+                (-m[(2, 0)]).atan2(m[(2, 2)]),
+                (-m[(0, 1)]).atan2(m[(1, 1)]),
+            ),
+            EulerOrder::Zxy => (asin(m[(2, 1)]), 0.0, m[(1, 0)].atan2(m[(0, 0)])),
+            EulerOrder::Zyx if unlocked(m[(2, 0)]) => (
+                m[(2, 1)].atan2(m[(2, 2)]),
+                asin(-m[(2, 0)]),
+                m[(1, 0)].atan2(m[(0, 0)]),
+            ),
+            EulerOrder::Zyx => (0.0, asin(-m[(2, 0)]), (-m[(0, 1)]).atan2(m[(1, 1)])),
+        };
+        Self::new(
+            Angle::from_radians(x),
+            Angle::from_radians(y),
+            Angle::from_radians(z),
+            order,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_quat_works() {
+        let orders = ["XYZ", "XZY", "YXZ", "YZX", "ZXY", "ZYX"];
+        for order in orders.map(|name| EulerOrder::try_from(name).unwrap()) {
+            let euler = Euler::new(
+                Angle::from_degrees(30.0),
+                Angle::from_degrees(-45.0),
+                Angle::from_degrees(60.0),
+                order,
+            );
+            let decoded = Euler::from_quat(&euler.to_quat(), order);
+            let angles =
+                |euler: &Euler| Vec3::from([euler.x, euler.y, euler.z].map(Angle::to_radians));
+            assert!(
+                (angles(&decoded) - angles(&euler)).norm() < 1e-4,
+                "{:?}",
+                order
+            );
+        }
+        assert!(EulerOrder::try_from("XXY").is_err());
+    }
+}
@@ -0,0 +1,56 @@
+use glm::{Mat4, Vec3, Vec4};
+
+use super::aabb::Aabb;
+
+/// The six planes bounding a view volume; normals point inwards.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frustum {
+    planes: [Vec4; 6],
+}
+
+impl Frustum {
+    /// Extracts the planes of a projection or view-projection matrix, which
+    /// gives a frustum in the space the matrix transforms from.
+    pub fn from_matrix(matrix: &Mat4) -> Self {
+        let row = |index: usize| matrix.row(index).transpose();
+        let w = row(3);
+        let planes = [0, 1, 2]
+            .map(|index| [w + row(index), w - row(index)])
+            .concat();
+        Self {
+            planes: planes.try_into().unwrap(),
+        }
+    }
+
+    /// Conservative test; boxes near a corner of the frustum may be reported as visible.
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        self.planes.iter().all(|plane| {
+            let normal = plane.xyz();
+            let farthest = Vec3::from_fn(|axis, _| {
+                if normal[axis] >= 0.0 {
+                    aabb.max[axis]
+                } else {
+                    aabb.min[axis]
+                }
+            });
+            normal.dot(&farthest) + plane.w >= 0.0
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intersects_aabb_works() {
+        let projection = glm::perspective(1.0, 90_f32.to_radians(), 1.0, 10.0);
+        let frustum = Frustum::from_matrix(&projection);
+        let cube = |center: Vec3| Aabb::new(center.add_scalar(-0.5), center.add_scalar(0.5));
+        assert!(frustum.intersects_aabb(&cube(glm::vec3(0.0, 0.0, -5.0))));
+        assert!(frustum.intersects_aabb(&cube(glm::vec3(0.0, 0.0, -10.2))));
+        assert!(!frustum.intersects_aabb(&cube(glm::vec3(0.0, 0.0, 5.0))));
+        assert!(!frustum.intersects_aabb(&cube(glm::vec3(0.0, 0.0, -11.0))));
+        assert!(!frustum.intersects_aabb(&cube(glm::vec3(8.0, 0.0, -5.0))));
+    }
+}
@@ -1,4 +1,4 @@
-use glm::{Mat3, Mat4, Vec3};
+use glm::{Mat3, Mat4, Qua, Vec3};
 
 use super::angle::Angle;
 
@@ -54,3 +54,44 @@ pub fn get_position(m: &Mat4) -> Vec3 {
 pub fn get_rotation_matrix(m: &Mat4) -> Mat3 {
     glm::mat4_to_mat3(m)
 }
+
+pub fn compose(translation: &Vec3, rotation: &Qua<f32>, scale: &Vec3) -> Mat4 {
+    glm::translation(translation) * glm::quat_to_mat4(rotation) * glm::scaling(scale)
+}
+
+pub fn decompose(m: &Mat4) -> (Vec3, Qua<f32>, Vec3) {
+    let translation = get_position(m);
+    let mut rotation_matrix = get_rotation_matrix(m);
+    let mut scale = glm::vec3(
+        rotation_matrix.column(0).norm(),
+        rotation_matrix.column(1).norm(),
+        rotation_matrix.column(2).norm(),
+    );
+    if rotation_matrix.determinant() < 0.0 {
+        scale.x = -scale.x;
+    }
+    for i in 0..3 {
+        if scale[i] != 0.0 {
+            let mut column = rotation_matrix.column_mut(i);
+            column /= scale[i];
+        }
+    }
+    let rotation = glm::mat3_to_quat(&rotation_matrix);
+    (translation, rotation, scale)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decompose_works() {
+        let translation = glm::vec3(1.0, -2.0, 3.0);
+        let rotation = glm::quat_angle_axis(0.7, &glm::vec3(0.0, 1.0, 0.0));
+        let scale = glm::vec3(2.0, 0.5, 1.5);
+        let (t, r, s) = decompose(&compose(&translation, &rotation, &scale));
+        assert!((t - translation).norm() < 1e-5);
+        assert!(glm::quat_dot(&r, &rotation).abs() > 1.0 - 1e-5);
+        assert!((s - scale).norm() < 1e-5);
+    }
+}
@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use glm::Vec3;
+
+use crate::base::{math::angle::Angle, util::validate};
+
+const EPSILON: f32 = 1e-12;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub enum NormalMode {
+    #[default]
+    Flat,
+    Smooth {
+        crease_angle: Angle,
+    },
+}
+
+/// Returns one normal per triangle corner, in index order.
+pub fn generate(
+    positions: &[Vec3],
+    indices: Option<&[u32]>,
+    mode: NormalMode,
+) -> Result<Vec<Vec3>> {
+    let corners: Vec<usize> = match indices {
+        Some(indices) => indices.iter().map(|index| *index as usize).collect(),
+        None => (0..positions.len()).collect(),
+    };
+    validate::divisible_by(corners.len(), 3, || {
+        anyhow!("Triangle index count must be a multiple of 3")
+    })?;
+    validate::assert(corners.iter().all(|index| *index < positions.len()), || {
+        anyhow!("Triangle index out of bounds")
+    })?;
+    let weighted: Vec<Vec3> = corners
+        .chunks(3)
+        .map(|triangle| {
+            let edge1 = positions[triangle[1]] - positions[triangle[0]];
+            let edge2 = positions[triangle[2]] - positions[triangle[0]];
+            edge1.cross(&edge2)
+        })
+        .collect();
+    let faces: Vec<Vec3> = weighted
+        .iter()
+        .map(|normal| self::normalize_or(normal, &glm::vec3(0.0, 0.0, 1.0)))
+        .collect();
+    let crease_angle = match mode {
+        NormalMode::Flat => {
+            return Ok((0..corners.len()).map(|corner| faces[corner / 3]).collect())
+        }
+        NormalMode::Smooth { crease_angle } => crease_angle,
+    };
+    let threshold = crease_angle.cos() - 1e-6;
+    let mut shared: HashMap<[u32; 3], Vec<usize>> = HashMap::new();
+    for (corner, index) in corners.iter().enumerate() {
+        let position = positions[*index];
+        let key = [position.x, position.y, position.z].map(f32::to_bits);
+        shared.entry(key).or_default().push(corner);
+    }
+    let mut normals = vec![Vec3::zeros(); corners.len()];
+    for group in shared.values() {
+        for corner in group {
+            let face = corner / 3;
+            let mut sum = Vec3::zeros();
+            let mut seen = Vec::with_capacity(group.len());
+            for other in group {
+                let other_face = other / 3;
+                if !seen.contains(&other_face) && faces[face].dot(&faces[other_face]) >= threshold {
+                    seen.push(other_face);
+                    sum += weighted[other_face];
+                }
+            }
+            normals[*corner] = self::normalize_or(&sum, &faces[face]);
+        }
+    }
+    Ok(normals)
+}
+
+fn normalize_or(vector: &Vec3, fallback: &Vec3) -> Vec3 {
+    let length_squared = vector.norm_squared();
+    if length_squared > EPSILON {
+        vector / length_squared.sqrt()
+    } else {
+        *fallback
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_4;
+
+    use super::*;
+
+    #[test]
+    fn generate_works() {
+        let positions = [
+            glm::vec3(0.0, 0.0, 0.0),
+            glm::vec3(1.0, 0.0, 0.0),
+            glm::vec3(0.0, 1.0, 0.0),
+            glm::vec3(0.0, 0.0, -1.0),
+        ];
+        let indices = [0, 1, 2, 0, 1, 3];
+        let flat = generate(&positions, Some(&indices), NormalMode::Flat).unwrap();
+        assert_eq!(flat.len(), 6);
+        assert_eq!(flat[0], glm::vec3(0.0, 0.0, 1.0));
+        assert_eq!(flat[3], glm::vec3(0.0, 1.0, 0.0));
+
+        let smooth = NormalMode::Smooth {
+            crease_angle: Angle::from_degrees(100.0),
+        };
+        let normals = generate(&positions, Some(&indices), smooth).unwrap();
+        let expected = glm::vec3(0.0, FRAC_PI_4.sin(), FRAC_PI_4.cos());
+        assert!((normals[0] - expected).norm() < 1e-5);
+        assert!((normals[4] - expected).norm() < 1e-5);
+
+        let creased = NormalMode::Smooth {
+            crease_angle: Angle::from_degrees(60.0),
+        };
+        let normals = generate(&positions, Some(&indices), creased).unwrap();
+        assert_eq!(normals, flat);
+        assert!(generate(&positions, Some(&[0, 1]), NormalMode::Flat).is_err());
+    }
+}
@@ -0,0 +1,129 @@
+use glm::{Mat4, Vec3};
+
+use super::aabb::Aabb;
+
+const EPSILON: f32 = 1e-7;
+
+/// A half-line; distances returned by the intersection tests are measured in
+/// multiples of `direction`, which makes them survive affine transforms.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ray {
+    pub origin: Vec3,
+    pub direction: Vec3,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TriangleHit {
+    pub distance: f32,
+    pub barycentric: Vec3,
+}
+
+impl Ray {
+    pub fn new(origin: Vec3, direction: Vec3) -> Self {
+        Self {
+            origin,
+            direction: direction.normalize(),
+        }
+    }
+
+    pub fn at(&self, distance: f32) -> Vec3 {
+        self.origin + self.direction * distance
+    }
+
+    /// Keeps the direction unnormalized so that distances stay comparable.
+    pub fn transform(&self, transform: &Mat4) -> Self {
+        Self {
+            origin: glm::vec4_to_vec3(&(transform * self.origin.push(1.0))),
+            direction: glm::vec4_to_vec3(&(transform * self.direction.push(0.0))),
+        }
+    }
+
+    /// Returns the distance at which the ray enters the box, or zero if it starts inside.
+    pub fn intersect_aabb(&self, aabb: &Aabb) -> Option<f32> {
+        let mut near = 0.0_f32;
+        let mut far = f32::INFINITY;
+        for axis in 0..3 {
+            let inverse = 1.0 / self.direction[axis];
+            let t1 = (aabb.min[axis] - self.origin[axis]) * inverse;
+            let t2 = (aabb.max[axis] - self.origin[axis]) * inverse;
+            if t1.is_nan() || t2.is_nan() {
+                continue;
+            }
+            near = near.max(t1.min(t2));
+            far = far.min(t1.max(t2));
+        }
+        Some(near).filter(|near| *near <= far)
+    }
+
+    pub fn intersect_sphere(&self, center: &Vec3, radius: f32) -> Option<f32> {
+        let offset = self.origin - center;
+        let a = self.direction.norm_squared();
+        let b = offset.dot(&self.direction);
+        let c = offset.norm_squared() - radius * radius;
+        let discriminant = b * b - a * c;
+        if discriminant < 0.0 || a < EPSILON {
+            return None;
+        }
+        let root = discriminant.sqrt();
+        [(-b - root) / a, (-b + root) / a]
+            .into_iter()
+            .find(|distance| *distance >= 0.0)
+    }
+
+    /// Möller–Trumbore intersection; both faces of the triangle are hit.
+    pub fn intersect_triangle(&self, triangle: &[Vec3; 3]) -> Option<TriangleHit> {
+        let edge1 = triangle[1] - triangle[0];
+        let edge2 = triangle[2] - triangle[0];
+        let p = self.direction.cross(&edge2);
+        let determinant = edge1.dot(&p);
+        if determinant.abs() < EPSILON {
+            return None;
+        }
+        let inverse = 1.0 / determinant;
+        let offset = self.origin - triangle[0];
+        let u = offset.dot(&p) * inverse;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+        let q = offset.cross(&edge1);
+        let v = self.direction.dot(&q) * inverse;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+        let distance = edge2.dot(&q) * inverse;
+        (distance >= 0.0).then(|| TriangleHit {
+            distance,
+            barycentric: glm::vec3(1.0 - u - v, u, v),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intersect_works() {
+        let ray = Ray::new(glm::vec3(0.0, 0.0, 5.0), glm::vec3(0.0, 0.0, -2.0));
+        let aabb = Aabb::new(glm::vec3(-1.0, -1.0, -1.0), glm::vec3(1.0, 1.0, 1.0));
+        assert_eq!(ray.intersect_aabb(&aabb), Some(4.0));
+        assert_eq!(ray.intersect_sphere(&Vec3::zeros(), 2.0), Some(3.0));
+
+        let triangle = [
+            glm::vec3(-1.0, -1.0, 0.0),
+            glm::vec3(1.0, -1.0, 0.0),
+            glm::vec3(-1.0, 1.0, 0.0),
+        ];
+        let hit = ray.intersect_triangle(&triangle).unwrap();
+        assert_eq!(hit.distance, 5.0);
+        assert_eq!(hit.barycentric, glm::vec3(0.0, 0.5, 0.5));
+
+        let scaled = ray.transform(&glm::scaling(&glm::vec3(2.0, 2.0, 2.0)));
+        assert_eq!(scaled.intersect_sphere(&Vec3::zeros(), 4.0), Some(3.0));
+        let missed = Ray::new(glm::vec3(0.0, 3.0, 5.0), glm::vec3(0.0, 0.0, -1.0));
+        assert!(missed.intersect_aabb(&aabb).is_none());
+        assert!(missed.intersect_triangle(&triangle).is_none());
+        let behind = Ray::new(glm::vec3(0.0, 0.0, 5.0), glm::vec3(0.0, 0.0, 1.0));
+        assert!(behind.intersect_sphere(&Vec3::zeros(), 2.0).is_none());
+    }
+}
@@ -1,6 +1,6 @@
 use glm::Vec2;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Resolution {
     pub width: i32,
     pub height: i32,
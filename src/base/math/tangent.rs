@@ -0,0 +1,168 @@
+use anyhow::{anyhow, Result};
+use bevy_mikktspace::Geometry;
+use glm::{Vec2, Vec3, Vec4};
+
+use crate::base::util::validate;
+
+const EPSILON: f32 = 1e-8;
+
+/// MikkTSpace tangents with the bitangent sign in `w`. Indexed vertices that
+/// MikkTSpace would split at a UV seam keep the tangent of their last face.
+pub fn generate(
+    positions: &[Vec3],
+    normals: &[Vec3],
+    texcoords: &[Vec2],
+    indices: Option<&[u32]>,
+) -> Result<Vec<Vec4>> {
+    let count = positions.len();
+    validate::assert(normals.len() == count && texcoords.len() == count, || {
+        anyhow!("Positions, normals and texcoords must have equal length")
+    })?;
+    let indices: Vec<usize> = match indices {
+        Some(indices) => indices.iter().map(|index| *index as usize).collect(),
+        None => (0..count).collect(),
+    };
+    validate::divisible_by(indices.len(), 3, || {
+        anyhow!("Triangle index count must be a multiple of 3")
+    })?;
+    validate::assert(indices.iter().all(|index| *index < count), || {
+        anyhow!("Triangle index out of bounds")
+    })?;
+    let mut triangles = Triangles {
+        positions,
+        normals,
+        texcoords,
+        indices: &indices,
+        tangents: normals
+            .iter()
+            .map(|normal| self::perpendicular(normal).push(1.0))
+            .collect(),
+    };
+    validate::assert(bevy_mikktspace::generate_tangents(&mut triangles), || {
+        anyhow!("Cannot generate tangents")
+    })?;
+    Ok(triangles.tangents)
+}
+
+struct Triangles<'a> {
+    positions: &'a [Vec3],
+    normals: &'a [Vec3],
+    texcoords: &'a [Vec2],
+    indices: &'a [usize],
+    tangents: Vec<Vec4>,
+}
+
+impl Triangles<'_> {
+    fn index(&self, face: usize, vert: usize) -> usize {
+        self.indices[face * 3 + vert]
+    }
+}
+
+impl Geometry for Triangles<'_> {
+    fn num_faces(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    fn num_vertices_of_face(&self, _face: usize) -> usize {
+        3
+    }
+
+    fn position(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.positions[self.index(face, vert)].into()
+    }
+
+    fn normal(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.normals[self.index(face, vert)].into()
+    }
+
+    fn tex_coord(&self, face: usize, vert: usize) -> [f32; 2] {
+        self.texcoords[self.index(face, vert)].into()
+    }
+
+    fn set_tangent_encoded(&mut self, tangent: [f32; 4], face: usize, vert: usize) {
+        let index = self.index(face, vert);
+        self.tangents[index] = Vec4::from(tangent);
+    }
+}
+
+fn project(vector: &Vec3, normal: &Vec3) -> Vec3 {
+    let projected = vector - normal * normal.dot(vector);
+    let length = projected.norm();
+    if length > EPSILON {
+        projected / length
+    } else {
+        Vec3::zeros()
+    }
+}
+
+fn perpendicular(normal: &Vec3) -> Vec3 {
+    let axis = if normal.x.abs() < 0.9 {
+        glm::vec3(1.0, 0.0, 0.0)
+    } else {
+        glm::vec3(0.0, 1.0, 0.0)
+    };
+    self::project(&axis, normal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate_works() {
+        let positions = [
+            glm::vec3(0.0, 0.0, 0.0),
+            glm::vec3(1.0, 0.0, 0.0),
+            glm::vec3(0.0, 1.0, 0.0),
+            glm::vec3(1.0, 1.0, 0.0),
+        ];
+        let normals = [glm::vec3(0.0, 0.0, 1.0); 4];
+        let texcoords = [
+            glm::vec2(0.0, 0.0),
+            glm::vec2(1.0, 0.0),
+            glm::vec2(0.0, 1.0),
+            glm::vec2(1.0, 1.0),
+        ];
+        let tangents =
+            generate(&positions, &normals, &texcoords, Some(&[0, 1, 3, 0, 3, 2])).unwrap();
+        for tangent in tangents {
+            assert!((tangent - glm::vec4(1.0, 0.0, 0.0, 1.0)).norm() < 1e-5);
+        }
+        let mirrored: Vec<_> = texcoords.iter().map(|uv| glm::vec2(-uv.x, uv.y)).collect();
+        let tangents = generate(&positions[..3], &normals[..3], &mirrored[..3], None).unwrap();
+        assert!((tangents[0] - glm::vec4(-1.0, 0.0, 0.0, -1.0)).norm() < 1e-5);
+        assert!(generate(&positions, &normals, &texcoords, Some(&[0, 1])).is_err());
+    }
+
+    #[test]
+    fn mikktspace_works() {
+        // One side of the MikkTSpace regression cube, with tangents from the C implementation.
+        let points = [
+            (glm::vec3(1.0, -1.0, 1.0), glm::vec2(0.0, 0.0)),
+            (glm::vec3(1.0, -1.0, -1.0), glm::vec2(0.0, 1.0)),
+            (glm::vec3(1.0, 1.0, -1.0), glm::vec2(1.0, 1.0)),
+            (glm::vec3(1.0, 1.0, 1.0), glm::vec2(1.0, 0.0)),
+            (glm::vec3(1.0, 0.0, 0.0), glm::vec2(0.5, 0.5)),
+        ];
+        let positions: Vec<_> = points.iter().map(|(point, _)| point / 2.0).collect();
+        let normals: Vec<_> = points.iter().map(|(point, _)| point.normalize()).collect();
+        let texcoords: Vec<_> = points.iter().map(|(_, texcoord)| *texcoord).collect();
+        let indices = [0, 1, 4, 1, 2, 4, 2, 3, 4, 3, 0, 4];
+        let tangents = generate(&positions, &normals, &texcoords, Some(&indices)).unwrap();
+        let expected = [
+            glm::vec4(0.40824825, 0.81649655, 0.40824825, -1.0),
+            glm::vec4(0.40824825, 0.81649655, -0.40824825, -1.0),
+            glm::vec4(-0.40824825, 0.81649655, 0.40824825, -1.0),
+            glm::vec4(-0.40824825, 0.81649655, -0.40824825, -1.0),
+            glm::vec4(0.0, 1.0, 0.0, -1.0),
+        ];
+        for (tangent, expected) in tangents.iter().zip(expected) {
+            assert!(
+                (tangent - expected).norm() < 1e-5,
+                "{} != {}",
+                tangent,
+                expected
+            );
+        }
+    }
+}
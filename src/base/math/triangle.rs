@@ -0,0 +1,70 @@
+use glm::Vec3;
+
+use super::aabb::Aabb;
+
+pub fn bounds(triangle: &[Vec3; 3]) -> Aabb {
+    Aabb::from_points(triangle).unwrap()
+}
+
+/// Returns the point of the triangle nearest to `point`, following the
+/// Voronoi region tests from Ericson's Real-Time Collision Detection.
+pub fn closest_point(triangle: &[Vec3; 3], point: &Vec3) -> Vec3 {
+    let [a, b, c] = triangle;
+    let ab = b - a;
+    let ac = c - a;
+    let ap = point - a;
+    let d1 = ab.dot(&ap);
+    let d2 = ac.dot(&ap);
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return *a;
+    }
+    let bp = point - b;
+    let d3 = ab.dot(&bp);
+    let d4 = ac.dot(&bp);
+    if d3 >= 0.0 && d4 <= d3 {
+        return *b;
+    }
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        return a + ab * (d1 / (d1 - d3));
+    }
+    let cp = point - c;
+    let d5 = ab.dot(&cp);
+    let d6 = ac.dot(&cp);
+    if d6 >= 0.0 && d5 <= d6 {
+        return *c;
+    }
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        return a + ac * (d2 / (d2 - d6));
+    }
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && d4 - d3 >= 0.0 && d5 - d6 >= 0.0 {
+        return b + (c - b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+    }
+    let denominator = 1.0 / (va + vb + vc);
+    a + ab * (vb * denominator) + ac * (vc * denominator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn closest_point_works() {
+        let triangle = [
+            glm::vec3(0.0, 0.0, 0.0),
+            glm::vec3(2.0, 0.0, 0.0),
+            glm::vec3(0.0, 2.0, 0.0),
+        ];
+        let closest = |x, y, z| self::closest_point(&triangle, &glm::vec3(x, y, z));
+        assert_eq!(closest(0.5, 0.5, 3.0), glm::vec3(0.5, 0.5, 0.0));
+        assert_eq!(closest(-1.0, -1.0, 0.0), triangle[0]);
+        assert_eq!(closest(1.0, -1.0, 1.0), glm::vec3(1.0, 0.0, 0.0));
+        assert_eq!(closest(2.0, 2.0, 0.0), glm::vec3(1.0, 1.0, 0.0));
+        assert_eq!(
+            self::bounds(&triangle),
+            Aabb::new(Vec3::zeros(), glm::vec3(2.0, 2.0, 0.0))
+        );
+    }
+}
@@ -1,6 +1,7 @@
 #[macro_use]
 pub mod web;
 pub mod application;
+pub mod asset;
 pub mod color;
 pub mod convert;
 pub mod gl;
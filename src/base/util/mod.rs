@@ -1,5 +1,7 @@
+pub mod base64;
 pub mod cache;
 pub mod coll;
 pub mod level;
 pub mod shared_ref;
 pub mod validate;
+pub mod value;
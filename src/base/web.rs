@@ -10,6 +10,8 @@ use web_sys::{
     WebGl2RenderingContext, Window,
 };
 
+use crate::base::util::base64;
+
 // Straight taken from https://rustwasm.github.io/book/game-of-life/debugging.html
 #[allow(unused_macros)]
 macro_rules! log {
@@ -166,17 +168,6 @@ async fn fetch(uri: &str) -> Result<Response> {
         .map_err(|err| anyhow!("Error while casting {} to Response: {:#?}", uri, err))
 }
 
-pub async fn fetch_json(uri: &str) -> Result<JsValue> {
-    let response = self::fetch(uri).await?;
-    JsFuture::from(
-        response
-            .json()
-            .map_err(|err| anyhow!("Error while fetching Response from {}: {:#?}", uri, err))?,
-    )
-    .await
-    .map_err(|err| anyhow!("Error while fetching JSON from {}: {:#?}", uri, err))
-}
-
 pub async fn fetch_array_buffer(uri: &str) -> Result<ArrayBuffer> {
     let array_buffer = self::fetch(uri)
         .await?
@@ -220,3 +211,19 @@ pub fn set_document_title(title: &str) -> Result<()> {
     self::document()?.set_title(title);
     Ok(())
 }
+
+pub fn download(data: &[u8], file_name: &str, mime_type: &str) -> Result<()> {
+    let anchor = self::document()?
+        .create_element("a")
+        .map_err(|err| anyhow!("Cannot create anchor element: {:#?}", err))?;
+    let href = format!("data:{};base64,{}", mime_type, base64::encode(data));
+    anchor
+        .set_attribute("href", &href)
+        .and_then(|_| anchor.set_attribute("download", file_name))
+        .map_err(|err| anyhow!("Cannot set anchor attributes: {:#?}", err))?;
+    js_sys::Reflect::get(&anchor, &JsValue::from_str("click"))
+        .and_then(|click| click.dyn_into::<js_sys::Function>())
+        .and_then(|click| click.call0(&anchor))
+        .map(|_| ())
+        .map_err(|err| anyhow!("Cannot download {}: {:#?}", file_name, err))
+}
@@ -4,7 +4,7 @@ use glm::Vec3;
 
 use crate::{
     base::{
-        color::{self, Color},
+        color::{self, Color, ColorSpace},
         util::{level::Level, shared_ref::SharedRef},
     },
     core::{
@@ -16,8 +16,17 @@ use crate::{
 
 #[derive(Debug, Clone, Copy)]
 pub enum LightType {
-    Directional { direction: Vec3 },
-    Point { position: Vec3 },
+    Directional {
+        direction: Vec3,
+    },
+    Point {
+        position: Vec3,
+    },
+    Spot {
+        position: Vec3,
+        direction: Vec3,
+        cone: Cone,
+    },
 }
 
 impl LightType {
@@ -29,6 +38,14 @@ impl LightType {
         Self::Point { position }
     }
 
+    pub fn spot(position: Vec3, direction: Vec3, cone: Cone) -> Self {
+        Self::Spot {
+            position,
+            direction,
+            cone,
+        }
+    }
+
     pub fn is_directional(&self) -> bool {
         matches!(self, Self::Directional { .. })
     }
@@ -42,29 +59,45 @@ impl LightType {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
+pub struct Cone {
+    pub inner_angle: f32,
+    pub outer_angle: f32,
+}
+
+#[derive(Debug, Clone)]
 pub struct Light {
     pub light_type: Option<LightType>,
     pub color: Color,
+    /// sRGB for colors picked by hand, linear for colors from glTF or with
+    /// intensity premultiplied; shaders always receive the linear color.
+    pub color_space: ColorSpace,
     pub attenuation: Attenuation,
+    pub range: Option<f32>,
 }
 
 impl Light {
     const NONE_TYPE: i32 = 0;
     const DIRECTIONAL_TYPE: i32 = 1;
     const POINT_TYPE: i32 = 2;
+    const SPOT_TYPE: i32 = 3;
 
     const LIGHT_TYPE_MEMBER: &str = "lightType";
     const COLOR_MEMBER: &str = "color";
     const DIRECTION_MEMBER: &str = "direction";
     const POSITION_MEMBER: &str = "position";
     const ATTENUATION_MEMBER: &str = "attenuation";
+    const RANGE_MEMBER: &str = "range";
+    const INNER_CONE_COS_MEMBER: &str = "innerConeCos";
+    const OUTER_CONE_COS_MEMBER: &str = "outerConeCos";
 
     pub fn directional(color: Color, direction: Vec3) -> Self {
         Self {
             light_type: LightType::directional(direction).into(),
             color,
+            color_space: ColorSpace::Srgb,
             attenuation: Attenuation::default(),
+            range: None,
         }
     }
 
@@ -76,22 +109,70 @@ impl Light {
         )
     }
 
+    pub fn spot(color: Color, position: Vec3, direction: Vec3, cone: Cone) -> Self {
+        Self::new(
+            color,
+            LightType::spot(position, direction, cone),
+            Attenuation(1.0, 0.0, 0.1),
+        )
+    }
+
     fn new(color: Color, light_type: LightType, attenuation: Attenuation) -> Self {
         Self {
             light_type: light_type.into(),
             color,
+            color_space: ColorSpace::Srgb,
             attenuation,
+            range: None,
+        }
+    }
+
+    pub fn with_attenuation(mut self, attenuation: Attenuation) -> Self {
+        self.attenuation = attenuation;
+        self
+    }
+
+    pub fn with_range(mut self, range: Option<f32>) -> Self {
+        self.range = range;
+        self
+    }
+
+    pub fn with_color_space(mut self, color_space: ColorSpace) -> Self {
+        self.color_space = color_space;
+        self
+    }
+
+    pub fn srgb_color(&self) -> Color {
+        match self.color_space {
+            ColorSpace::Srgb => self.color,
+            ColorSpace::Linear => color::to_srgb(&self.color),
+        }
+    }
+
+    pub fn linear_color(&self) -> Color {
+        match self.color_space {
+            ColorSpace::Srgb => color::to_linear(&self.color),
+            ColorSpace::Linear => self.color,
         }
     }
 
     pub fn update_from_node(&mut self, node: &RefCell<Node>) {
         if let Some(light_type) = &mut self.light_type {
+            let node = node.borrow();
             match light_type {
                 LightType::Directional { direction } => {
-                    *direction = node.borrow().direction();
+                    *direction = node.world_direction();
                 }
                 LightType::Point { position } => {
-                    *position = node.borrow().position();
+                    *position = node.world_position();
+                }
+                LightType::Spot {
+                    position,
+                    direction,
+                    ..
+                } => {
+                    *position = node.world_position();
+                    *direction = node.world_direction();
                 }
             }
         }
@@ -106,6 +187,14 @@ impl Light {
                 LightType::Point { position } => {
                     node.borrow_mut().set_position(position);
                 }
+                LightType::Spot {
+                    position,
+                    direction,
+                    ..
+                } => {
+                    node.borrow_mut().set_position(position);
+                    node.borrow_mut().set_direction(direction);
+                }
             }
         }
     }
@@ -160,8 +249,44 @@ impl UpdateUniform for Light {
                         level,
                     )
                 }
+                LightType::Spot {
+                    position,
+                    direction,
+                    cone,
+                } => {
+                    Self::SPOT_TYPE.update_uniform_with_level(
+                        context,
+                        &program::join_name(name, Self::LIGHT_TYPE_MEMBER),
+                        program,
+                        level,
+                    );
+                    position.update_uniform_with_level(
+                        context,
+                        &program::join_name(name, Self::POSITION_MEMBER),
+                        program,
+                        level,
+                    );
+                    direction.update_uniform_with_level(
+                        context,
+                        &program::join_name(name, Self::DIRECTION_MEMBER),
+                        program,
+                        level,
+                    );
+                    cone.inner_angle.cos().update_uniform_with_level(
+                        context,
+                        &program::join_name(name, Self::INNER_CONE_COS_MEMBER),
+                        program,
+                        level,
+                    );
+                    cone.outer_angle.cos().update_uniform_with_level(
+                        context,
+                        &program::join_name(name, Self::OUTER_CONE_COS_MEMBER),
+                        program,
+                        level,
+                    );
+                }
             }
-            self.color.update_uniform_with_level(
+            self.linear_color().update_uniform_with_level(
                 context,
                 &program::join_name(name, Self::COLOR_MEMBER),
                 program,
@@ -173,6 +298,12 @@ impl UpdateUniform for Light {
                 program,
                 level,
             );
+            self.range.unwrap_or_default().update_uniform_with_level(
+                context,
+                &program::join_name(name, Self::RANGE_MEMBER),
+                program,
+                level,
+            );
         } else {
             Self::NONE_TYPE.update_uniform_with_level(
                 context,
@@ -189,7 +320,9 @@ impl Default for Light {
         Self {
             light_type: None,
             color: color::white(),
+            color_space: ColorSpace::Srgb,
             attenuation: Attenuation::default(),
+            range: None,
         }
     }
 }
@@ -197,6 +330,14 @@ impl Default for Light {
 #[derive(Debug, Clone, Copy)]
 pub struct Attenuation(f32, f32, f32);
 
+impl Attenuation {
+    pub const INVERSE_SQUARE: Self = Self(0.0, 0.0, 1.0);
+
+    pub fn new(constant: f32, linear: f32, quadratic: f32) -> Self {
+        Self(constant, linear, quadratic)
+    }
+}
+
 impl Default for Attenuation {
     fn default() -> Self {
         Self(1.0, 0.0, 0.0)
@@ -227,6 +368,18 @@ impl Lights {
         light_node
     }
 
+    pub fn add_node(&mut self, light_node: Rc<LightNode>) {
+        self.light_nodes.push(light_node);
+    }
+
+    pub fn light_nodes(&self) -> &[Rc<LightNode>] {
+        &self.light_nodes
+    }
+
+    pub fn light_count(&self) -> usize {
+        self.light_nodes.len()
+    }
+
     pub fn update(&self) {
         for light_node in self.light_nodes.iter() {
             light_node.update_light();
@@ -269,6 +422,12 @@ impl LightNode {
         Rc::new(me)
     }
 
+    pub fn attach(node: SharedRef<Node>, light: RefCell<Light>) -> Rc<Self> {
+        let me = Self { node, light };
+        me.update_light();
+        Rc::new(me)
+    }
+
     pub fn add_child(&self, child: SharedRef<Node>) {
         self.node.borrow_mut().add_child(child)
     }
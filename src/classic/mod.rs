@@ -1,4 +1,5 @@
 pub mod light;
+pub mod picking;
 pub mod render_target;
 pub mod renderer;
 pub mod shadow;
@@ -0,0 +1,283 @@
+use std::cell::{Cell, RefCell};
+
+use anyhow::{anyhow, Result};
+use glm::Vec2;
+use web_sys::{
+    WebGl2RenderingContext, WebGlBuffer, WebGlFramebuffer, WebGlRenderbuffer, WebGlSync,
+};
+
+use crate::{
+    base::{
+        gl,
+        math::resolution::Resolution,
+        util::shared_ref::{SharedRef, WeakRef},
+    },
+    core::{
+        camera::Camera,
+        node::{Node, Visit},
+        scene::Scene,
+    },
+    material::id::IdMaterial,
+};
+
+#[derive(Debug, Clone)]
+pub struct Pick {
+    pub node: SharedRef<Node>,
+    pub primitive: usize,
+}
+
+#[derive(Debug)]
+struct PendingRead {
+    sync: WebGlSync,
+    byte_length: usize,
+    nodes: Vec<WeakRef<Node>>,
+}
+
+/// Renders object and primitive IDs into an integer render target and reads
+/// them back asynchronously, so that picking never stalls the pipeline.
+#[derive(Debug)]
+pub struct PickingPass {
+    framebuffer: WebGlFramebuffer,
+    id_buffer: WebGlRenderbuffer,
+    depth_buffer: WebGlRenderbuffer,
+    pixel_buffer: WebGlBuffer,
+    resolution: Cell<Option<Resolution>>,
+    material: IdMaterial,
+    nodes: RefCell<Vec<WeakRef<Node>>>,
+    pending: RefCell<Option<PendingRead>>,
+}
+
+impl PickingPass {
+    const BYTES_PER_PIXEL: usize = 4 * size_of::<u32>();
+
+    pub fn initialize(context: &WebGl2RenderingContext) -> Result<Self> {
+        Ok(Self {
+            framebuffer: gl::create_framebuffer(context)?,
+            id_buffer: gl::create_renderbuffer(context)?,
+            depth_buffer: gl::create_renderbuffer(context)?,
+            pixel_buffer: gl::create_buffer(context)?,
+            resolution: Cell::new(None),
+            material: IdMaterial::new(),
+            nodes: RefCell::new(vec![]),
+            pending: RefCell::new(None),
+        })
+    }
+
+    /// Renders the visible meshes at the resolution of the drawing buffer.
+    pub fn render(
+        &self,
+        context: &WebGl2RenderingContext,
+        scene: &Scene,
+        camera: &RefCell<Camera>,
+    ) -> Result<()> {
+        let resolution = Resolution::new(
+            context.drawing_buffer_width(),
+            context.drawing_buffer_height(),
+        );
+        self.bind(context, resolution)?;
+        context.viewport(0, 0, resolution.width, resolution.height);
+        context.clear_bufferuiv_with_u32_array(WebGl2RenderingContext::COLOR, 0, &[0, 0, 0, 0]);
+        context.clear(WebGl2RenderingContext::DEPTH_BUFFER_BIT);
+
+        let camera_matrix = camera.borrow().matrix();
+        let mut nodes = self.nodes.borrow_mut();
+        nodes.clear();
+        let mut result = Ok(());
+        scene.traverse(&mut |node: &Node| {
+            if !node.is_visible() {
+                return Visit::SkipChildren;
+            }
+            if let Some(mesh) = node.mesh() {
+                nodes.push(node.weak_ref());
+                let object_id = nodes.len() as u32;
+                mesh.render_with_materials(context, node, &camera_matrix, |index, material| {
+                    let variant = self
+                        .material
+                        .variant(context, material, object_id, index as u32);
+                    variant.map_err(|error| result = Err(error)).ok()
+                });
+            }
+            Visit::Continue
+        });
+        context.bind_framebuffer(WebGl2RenderingContext::FRAMEBUFFER, None);
+        result
+    }
+
+    /// Starts reading back the pixel under a position in normalized device coordinates.
+    pub fn request_at(&self, context: &WebGl2RenderingContext, position: &Vec2) -> Result<()> {
+        let resolution = self
+            .resolution
+            .get()
+            .ok_or_else(|| anyhow!("Picking pass has not been rendered"))?;
+        let x = (position.x + 1.0) / 2.0 * resolution.width as f32;
+        let y = (position.y + 1.0) / 2.0 * resolution.height as f32;
+        self.request_region(
+            context,
+            (x as i32).clamp(0, resolution.width - 1),
+            (y as i32).clamp(0, resolution.height - 1),
+            1,
+            1,
+        )
+    }
+
+    /// Starts reading back a region in pixels, with the origin at the bottom left.
+    /// A request that has not completed yet is replaced.
+    pub fn request_region(
+        &self,
+        context: &WebGl2RenderingContext,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+    ) -> Result<()> {
+        let byte_length = (width * height) as usize * Self::BYTES_PER_PIXEL;
+        context.bind_framebuffer(WebGl2RenderingContext::FRAMEBUFFER, Some(&self.framebuffer));
+        context.bind_buffer(
+            WebGl2RenderingContext::PIXEL_PACK_BUFFER,
+            Some(&self.pixel_buffer),
+        );
+        context.buffer_data_with_i32(
+            WebGl2RenderingContext::PIXEL_PACK_BUFFER,
+            byte_length as i32,
+            WebGl2RenderingContext::STREAM_READ,
+        );
+        let result = context.read_pixels_with_i32(
+            x,
+            y,
+            width,
+            height,
+            WebGl2RenderingContext::RGBA_INTEGER,
+            WebGl2RenderingContext::UNSIGNED_INT,
+            0,
+        );
+        context.bind_buffer(WebGl2RenderingContext::PIXEL_PACK_BUFFER, None);
+        context.bind_framebuffer(WebGl2RenderingContext::FRAMEBUFFER, None);
+        result.map_err(|error| anyhow!("Cannot read picking pixels: {:#?}", error))?;
+        let sync = context
+            .fence_sync(WebGl2RenderingContext::SYNC_GPU_COMMANDS_COMPLETE, 0)
+            .ok_or_else(|| anyhow!("Cannot create fence sync"))?;
+        let pending = PendingRead {
+            sync,
+            byte_length,
+            nodes: self.nodes.borrow().clone(),
+        };
+        if let Some(previous) = self.pending.replace(Some(pending)) {
+            context.delete_sync(Some(&previous.sync));
+        }
+        Ok(())
+    }
+
+    /// Returns the distinct objects of the last request once the GPU has finished it.
+    pub fn poll(&self, context: &WebGl2RenderingContext) -> Option<Vec<Pick>> {
+        let status = {
+            let pending = self.pending.borrow();
+            context.client_wait_sync_with_u32(&pending.as_ref()?.sync, 0, 0)
+        };
+        if status == WebGl2RenderingContext::TIMEOUT_EXPIRED {
+            return None;
+        }
+        let pending = self.pending.take()?;
+        context.delete_sync(Some(&pending.sync));
+        let mut bytes = vec![0; pending.byte_length];
+        context.bind_buffer(
+            WebGl2RenderingContext::PIXEL_PACK_BUFFER,
+            Some(&self.pixel_buffer),
+        );
+        context.get_buffer_sub_data_with_i32_and_u8_array(
+            WebGl2RenderingContext::PIXEL_PACK_BUFFER,
+            0,
+            &mut bytes,
+        );
+        context.bind_buffer(WebGl2RenderingContext::PIXEL_PACK_BUFFER, None);
+        Some(self::decode(&bytes, &pending.nodes))
+    }
+
+    fn bind(&self, context: &WebGl2RenderingContext, resolution: Resolution) -> Result<()> {
+        context.bind_framebuffer(WebGl2RenderingContext::FRAMEBUFFER, Some(&self.framebuffer));
+        if self.resolution.get() == Some(resolution) {
+            return Ok(());
+        }
+        for (buffer, format, attachment) in [
+            (
+                &self.id_buffer,
+                WebGl2RenderingContext::RGBA32UI,
+                WebGl2RenderingContext::COLOR_ATTACHMENT0,
+            ),
+            (
+                &self.depth_buffer,
+                WebGl2RenderingContext::DEPTH_COMPONENT16,
+                WebGl2RenderingContext::DEPTH_ATTACHMENT,
+            ),
+        ] {
+            context.bind_renderbuffer(WebGl2RenderingContext::RENDERBUFFER, Some(buffer));
+            context.renderbuffer_storage(
+                WebGl2RenderingContext::RENDERBUFFER,
+                format,
+                resolution.width,
+                resolution.height,
+            );
+            context.framebuffer_renderbuffer(
+                WebGl2RenderingContext::FRAMEBUFFER,
+                attachment,
+                WebGl2RenderingContext::RENDERBUFFER,
+                Some(buffer),
+            );
+        }
+        gl::check_framebuffer_status(context, WebGl2RenderingContext::FRAMEBUFFER)?;
+        self.resolution.set(Some(resolution));
+        Ok(())
+    }
+}
+
+fn decode(bytes: &[u8], nodes: &[WeakRef<Node>]) -> Vec<Pick> {
+    let mut ids: Vec<(u32, u32)> = vec![];
+    for pixel in bytes.chunks_exact(PickingPass::BYTES_PER_PIXEL) {
+        let object = u32::from_ne_bytes(pixel[0..4].try_into().unwrap());
+        let primitive = u32::from_ne_bytes(pixel[4..8].try_into().unwrap());
+        if object != 0 && !ids.contains(&(object, primitive)) {
+            ids.push((object, primitive));
+        }
+    }
+    ids.into_iter()
+        .filter_map(|(object, primitive)| {
+            let node = nodes.get(object as usize - 1)?.upgrade()?;
+            Some(Pick {
+                node,
+                primitive: primitive as usize,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+
+    #[test]
+    fn decode_works() {
+        let first = Node::with_name("First");
+        let second = Node::with_name("Second");
+        let nodes = [first.borrow().weak_ref(), second.borrow().weak_ref()];
+        let bytes: Vec<u8> = [
+            [0, 0, 0, 0],
+            [2, 1, 0, 0],
+            [1, 0, 0, 0],
+            [2, 1, 0, 0],
+            [3, 0, 0, 0],
+        ]
+        .iter()
+        .flat_map(|pixel| pixel.iter().flat_map(|value: &u32| value.to_ne_bytes()))
+        .collect();
+
+        let picks = self::decode(&bytes, &nodes);
+        assert_eq!(picks.len(), 2);
+        assert!(Rc::ptr_eq(&picks[0].node, &second));
+        assert_eq!(picks[0].primitive, 1);
+        assert!(Rc::ptr_eq(&picks[1].node, &first));
+        assert_eq!(picks[1].primitive, 0);
+        drop((picks, first));
+        assert_eq!(self::decode(&bytes, &nodes).len(), 1);
+    }
+}
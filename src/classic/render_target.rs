@@ -4,7 +4,7 @@ use anyhow::Result;
 use web_sys::{WebGl2RenderingContext, WebGlFramebuffer};
 
 use crate::{
-    base::{gl, math::resolution::Resolution},
+    base::{color::ColorSpace, gl, math::resolution::Resolution},
     core::{image::Image, sampler::Sampler, texture::Texture},
 };
 
@@ -77,7 +77,7 @@ impl RenderTarget {
         context: &WebGl2RenderingContext,
         resolution: Resolution,
     ) -> Result<Rc<Texture>> {
-        Texture::initialize(
+        Texture::initialize_with_color_space(
             context,
             Sampler::new(
                 Some(WebGl2RenderingContext::LINEAR as i32),
@@ -87,6 +87,7 @@ impl RenderTarget {
             )?
             .into(),
             Image::from(resolution).into(),
+            ColorSpace::Linear,
         )
     }
 }
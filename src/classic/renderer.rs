@@ -1,4 +1,4 @@
-use std::{cell::RefCell, rc::Rc};
+use std::cell::RefCell;
 
 use web_sys::WebGl2RenderingContext;
 
@@ -7,11 +7,15 @@ use crate::{
         color::{self, Color},
         gl,
         math::resolution::Resolution,
-        util::{level::Level, shared_ref::SharedRef},
+        util::level::Level,
         web,
     },
     core::{
-        camera::Camera, material, mesh::Mesh, node::Node, program::UpdateProgramUniforms,
+        camera::Camera,
+        material::{self, OutputEncoding},
+        mesh::Mesh,
+        node::{Node, Visit},
+        program::UpdateProgramUniforms,
         scene::Scene,
     },
 };
@@ -142,47 +146,48 @@ impl Renderer {
         render_target: Option<&RenderTarget>,
         lights: &Lights,
     ) {
-        let nodes = scene.all_nodes();
-
         let resolution = self::get_resolution(context, render_target);
         lights.update();
         camera
             .borrow_mut()
             .set_aspect_ratio(resolution.aspect_ratio());
 
-        let meshes = Self::filter_meshes(&nodes);
-        self.shadow_pass(context, &meshes);
+        self.shadow_pass(context, scene);
 
         let camera = &camera.borrow();
 
         self::bind_render_target(context, render_target);
-        gl::set_clear_color(context, &self.clear_color);
+        let output_encoding = OutputEncoding {
+            linear: render_target.is_some(),
+        };
+        if output_encoding.linear {
+            gl::set_clear_color(context, &color::to_linear(&self.clear_color));
+        } else {
+            gl::set_clear_color(context, &self.clear_color);
+        }
         context.clear(clear_mask);
         self::viewport(context, resolution);
 
-        meshes.into_iter().for_each(|(mesh, node)| {
-            Self::update_lights(context, &mesh, lights);
-            self.update_shadow(context, &mesh);
-            mesh.update_uniform(
-                context,
-                "viewPosition",
-                &camera.world_position(),
-                Level::Ignore,
-            );
-            mesh.render(
-                context,
-                &node.as_ref().borrow(),
-                &camera.matrix(),
-                self.global_uniform_updater.as_ref(),
-            );
+        scene.traverse(&mut |node: &Node| {
+            if !node.is_visible() {
+                return Visit::SkipChildren;
+            }
+            if let Some(mesh) = node.mesh() {
+                Self::update_lights(context, mesh, lights);
+                self.update_shadow(context, mesh);
+                mesh.update_uniform(
+                    context,
+                    "viewPosition",
+                    &camera.world_position(),
+                    Level::Ignore,
+                );
+                mesh.render(context, node, &camera.matrix(), &output_encoding);
+            }
+            Visit::Continue
         });
     }
 
-    fn shadow_pass(
-        &self,
-        context: &WebGl2RenderingContext,
-        meshes: &[(Rc<Mesh>, &SharedRef<Node>)],
-    ) {
+    fn shadow_pass(&self, context: &WebGl2RenderingContext, scene: &Scene) {
         if let Some(shadow) = self.shadow() {
             shadow.bind(context);
             context.clear_color(1.0, 0.0, 0.0, 1.0);
@@ -191,13 +196,19 @@ impl Renderer {
             );
             let material = shadow.material();
             shadow.update(context);
-            meshes.iter().for_each(|(mesh, node)| {
-                mesh.render_triangle_based(
-                    context,
-                    &node.as_ref().borrow(),
-                    self.global_uniform_updater.as_ref(),
-                    material,
-                );
+            scene.traverse(&mut |node: &Node| {
+                if !node.is_visible() {
+                    return Visit::SkipChildren;
+                }
+                if let Some(mesh) = node.mesh().filter(|_| node.casts_shadow()) {
+                    mesh.render_triangle_based(
+                        context,
+                        node,
+                        self.global_uniform_updater.as_ref(),
+                        material,
+                    );
+                }
+                Visit::Continue
             });
         }
     }
@@ -220,13 +231,6 @@ impl Renderer {
             });
         }
     }
-
-    fn filter_meshes(nodes: &[SharedRef<Node>]) -> Vec<(Rc<Mesh>, &SharedRef<Node>)> {
-        nodes
-            .iter()
-            .filter_map(|node| node.borrow().mesh().map(|mesh| (Rc::clone(mesh), node)))
-            .collect()
-    }
 }
 
 pub fn get_canvas_resolution(context: &WebGl2RenderingContext) -> Resolution {
@@ -32,8 +32,7 @@ impl UpdateUniformValue for Sampler2D {
         context: &WebGl2RenderingContext,
         location: Option<&WebGlUniformLocation>,
     ) {
-        self.unit.active_texture(context);
-        self.texture.bind(context);
+        self.texture.bind_to_unit(context, self.unit);
         self.unit.update_uniform_value(context, location);
     }
 
@@ -1,7 +1,7 @@
 use std::{mem::size_of, rc::Rc};
 
-use anyhow::{anyhow, Result};
-use js_sys::{Float32Array, Uint16Array};
+use anyhow::{anyhow, bail, Result};
+use js_sys::{Float32Array, Int16Array, Int8Array, Uint16Array, Uint32Array, Uint8Array};
 use web_sys::{WebGl2RenderingContext, WebGlBuffer};
 
 use crate::base::{
@@ -14,6 +14,7 @@ use super::buffer_view::BufferView;
 #[derive(Debug, Clone, Copy)]
 pub enum AccessorType {
     Vec { size: i32 },
+    Mat { size: i32 },
     Scalar,
 }
 
@@ -22,6 +23,10 @@ impl AccessorType {
         Self::Vec { size }
     }
 
+    pub fn mat(size: i32) -> AccessorType {
+        Self::Mat { size }
+    }
+
     pub fn scalar() -> AccessorType {
         Self::Scalar
     }
@@ -29,6 +34,7 @@ impl AccessorType {
     pub fn size(&self) -> i32 {
         match self {
             Self::Vec { size } => *size,
+            Self::Mat { size } => size * size,
             Self::Scalar => 1,
         }
     }
@@ -93,6 +99,22 @@ impl Accessor {
         })
     }
 
+    pub fn accessor_type(&self) -> AccessorType {
+        self.accessor_type
+    }
+
+    pub fn normalized(&self) -> bool {
+        self.normalized
+    }
+
+    pub fn min(&self) -> Option<&[f32]> {
+        self.min.as_deref()
+    }
+
+    pub fn max(&self) -> Option<&[f32]> {
+        self.max.as_deref()
+    }
+
     pub fn set_vertex_attribute(&self, context: &WebGl2RenderingContext, location: u32) {
         if let Some(buffer_view) = &self.buffer_view {
             self.buffer_data(context, buffer_view, WebGl2RenderingContext::ARRAY_BUFFER);
@@ -118,6 +140,103 @@ impl Accessor {
         }
     }
 
+    pub fn read_f32(&self) -> Result<Vec<f32>> {
+        let size = self.accessor_type.size() as usize;
+        let count = self.count as usize;
+        if let Some(buffer_view) = &self.buffer_view {
+            let data = self.read_components(buffer_view)?;
+            let stride = if buffer_view.byte_stride > 0 {
+                buffer_view.byte_stride as usize / self.component_byte_length()
+            } else {
+                size
+            };
+            Ok((0..count)
+                .flat_map(|i| data[i * stride..i * stride + size].iter().copied())
+                .collect())
+        } else {
+            Ok(vec![0.0; count * size])
+        }
+    }
+
+    /// Reads unsigned integer indices exactly, which `read_f32` cannot do above 2^24.
+    pub fn read_indices(&self) -> Result<Vec<u32>> {
+        let Some(buffer_view) = &self.buffer_view else {
+            return Ok(vec![0; self.count as usize]);
+        };
+        let byte_offset = self.byte_offset;
+        let count = self.count as u32;
+        Ok(match self.component_type {
+            WebGl2RenderingContext::UNSIGNED_BYTE => buffer_view
+                .get_uint8_array(byte_offset, count)
+                .to_vec()
+                .into_iter()
+                .map(u32::from)
+                .collect(),
+            WebGl2RenderingContext::UNSIGNED_SHORT => buffer_view
+                .get_uint16_array(byte_offset, count)
+                .to_vec()
+                .into_iter()
+                .map(u32::from)
+                .collect(),
+            WebGl2RenderingContext::UNSIGNED_INT => {
+                buffer_view.get_uint32_array(byte_offset, count).to_vec()
+            }
+            _ => bail!(
+                "Cannot read component type {} as indices",
+                self.component_type
+            ),
+        })
+    }
+
+    fn read_components(&self, buffer_view: &BufferView) -> Result<Vec<f32>> {
+        let byte_offset = self.byte_offset;
+        let array_length = self.get_array_length(buffer_view) as u32;
+        let normalized = self.normalized;
+        Ok(match self.component_type {
+            WebGl2RenderingContext::BYTE => self::dequantize(
+                buffer_view
+                    .get_int8_array(byte_offset, array_length)
+                    .to_vec(),
+                normalized,
+                i8::MAX.into(),
+            ),
+            WebGl2RenderingContext::UNSIGNED_BYTE => self::dequantize(
+                buffer_view
+                    .get_uint8_array(byte_offset, array_length)
+                    .to_vec(),
+                normalized,
+                u8::MAX.into(),
+            ),
+            WebGl2RenderingContext::SHORT => self::dequantize(
+                buffer_view
+                    .get_int16_array(byte_offset, array_length)
+                    .to_vec(),
+                normalized,
+                i16::MAX.into(),
+            ),
+            WebGl2RenderingContext::UNSIGNED_SHORT => self::dequantize(
+                buffer_view
+                    .get_uint16_array(byte_offset, array_length)
+                    .to_vec(),
+                normalized,
+                u16::MAX.into(),
+            ),
+            WebGl2RenderingContext::UNSIGNED_INT => buffer_view
+                .get_uint32_array(byte_offset, array_length)
+                .to_vec()
+                .into_iter()
+                .map(|value| value as f32)
+                .collect(),
+            WebGl2RenderingContext::FLOAT => buffer_view
+                .get_float32_array(byte_offset, array_length)
+                .to_vec(),
+            _ => bail!(
+                "Cannot read component type {} as float data",
+                self.component_type
+            ),
+        })
+    }
+
     fn buffer_data(
         &self,
         context: &WebGl2RenderingContext,
@@ -145,9 +264,21 @@ impl Accessor {
     fn get_typed_view(&self, buffer_view: &BufferView) -> TypedView {
         let array_length = self.get_array_length(buffer_view);
         match self.component_type {
+            WebGl2RenderingContext::BYTE => {
+                TypedView::from(buffer_view.get_int8_array(self.byte_offset, array_length as u32))
+            }
+            WebGl2RenderingContext::UNSIGNED_BYTE => {
+                TypedView::from(buffer_view.get_uint8_array(self.byte_offset, array_length as u32))
+            }
+            WebGl2RenderingContext::SHORT => {
+                TypedView::from(buffer_view.get_int16_array(self.byte_offset, array_length as u32))
+            }
             WebGl2RenderingContext::UNSIGNED_SHORT => {
                 TypedView::from(buffer_view.get_uint16_array(self.byte_offset, array_length as u32))
             }
+            WebGl2RenderingContext::UNSIGNED_INT => {
+                TypedView::from(buffer_view.get_uint32_array(self.byte_offset, array_length as u32))
+            }
             WebGl2RenderingContext::FLOAT => TypedView::from(
                 buffer_view.get_float32_array(self.byte_offset, array_length as u32),
             ),
@@ -178,21 +309,70 @@ impl Accessor {
     }
 }
 
+fn dequantize<T>(values: Vec<T>, normalized: bool, max: f32) -> Vec<f32>
+where
+    T: Into<f32>,
+{
+    values
+        .into_iter()
+        .map(Into::into)
+        .map(|value: f32| {
+            if normalized {
+                (value / max).max(-1.0)
+            } else {
+                value
+            }
+        })
+        .collect()
+}
+
 #[derive(Debug, Clone)]
 pub enum TypedView {
+    Int8(Int8Array),
+    Uint8(Uint8Array),
+    Int16(Int16Array),
     Uint16(Uint16Array),
+    Uint32(Uint32Array),
     Float32(Float32Array),
 }
 
 impl TypedView {
     pub fn as_object(&self) -> &js_sys::Object {
         match self {
+            Self::Int8(array) => array,
+            Self::Uint8(array) => array,
+            Self::Int16(array) => array,
             Self::Uint16(array) => array,
+            Self::Uint32(array) => array,
             Self::Float32(array) => array,
         }
     }
 }
 
+impl From<Int8Array> for TypedView {
+    fn from(array: Int8Array) -> Self {
+        Self::Int8(array)
+    }
+}
+
+impl From<Int16Array> for TypedView {
+    fn from(array: Int16Array) -> Self {
+        Self::Int16(array)
+    }
+}
+
+impl From<Uint32Array> for TypedView {
+    fn from(array: Uint32Array) -> Self {
+        Self::Uint32(array)
+    }
+}
+
+impl From<Uint8Array> for TypedView {
+    fn from(array: Uint8Array) -> Self {
+        Self::Uint8(array)
+    }
+}
+
 impl From<Uint16Array> for TypedView {
     fn from(array: Uint16Array) -> Self {
         Self::Uint16(array)
@@ -204,3 +384,24 @@ impl From<Float32Array> for TypedView {
         Self::Float32(array)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dequantize_works() {
+        assert_eq!(
+            dequantize(vec![-128i8, -127, 0, 127], true, i8::MAX.into()),
+            vec![-1.0, -1.0, 0.0, 1.0]
+        );
+        assert_eq!(
+            dequantize(vec![0u16, 65535], true, u16::MAX.into()),
+            vec![0.0, 1.0]
+        );
+        assert_eq!(
+            dequantize(vec![-3i16, 1000], false, i16::MAX.into()),
+            vec![-3.0, 1000.0]
+        );
+    }
+}
@@ -0,0 +1,242 @@
+use anyhow::{anyhow, bail, Result};
+use glm::{Qua, Vec3};
+
+use crate::base::util::{shared_ref::WeakRef, validate};
+
+use super::node::Node;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interpolation {
+    Linear,
+    Step,
+    CubicSpline,
+}
+
+impl TryFrom<&str> for Interpolation {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        match value {
+            "LINEAR" => Ok(Self::Linear),
+            "STEP" => Ok(Self::Step),
+            "CUBICSPLINE" => Ok(Self::CubicSpline),
+            _ => bail!("Unknown interpolation: {}", value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Property {
+    Translation,
+    Rotation,
+    Scale,
+    Weights,
+}
+
+impl TryFrom<&str> for Property {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        match value {
+            "translation" => Ok(Self::Translation),
+            "rotation" => Ok(Self::Rotation),
+            "scale" => Ok(Self::Scale),
+            "weights" => Ok(Self::Weights),
+            _ => bail!("Unknown animation path: {}", value),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Sampler {
+    input: Vec<f32>,
+    output: Vec<f32>,
+    interpolation: Interpolation,
+    component_count: usize,
+}
+
+impl Sampler {
+    pub fn new(input: Vec<f32>, output: Vec<f32>, interpolation: Interpolation) -> Result<Self> {
+        validate::not_empty(&input, || anyhow!("Animation sampler input is empty"))?;
+        let values_per_key = match interpolation {
+            Interpolation::CubicSpline => 3 * input.len(),
+            _ => input.len(),
+        };
+        let component_count = output.len() / values_per_key;
+        validate::assert(component_count * values_per_key == output.len(), || {
+            anyhow!("Animation sampler output does not match input")
+        })?;
+        Ok(Self {
+            input,
+            output,
+            interpolation,
+            component_count,
+        })
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.input[self.input.len() - 1]
+    }
+
+    pub fn sample(&self, time: f32, property: Property) -> Vec<f32> {
+        let last = self.input.len() - 1;
+        if time <= self.input[0] {
+            return self.value(0).to_vec();
+        }
+        if time >= self.input[last] {
+            return self.value(last).to_vec();
+        }
+        let next = self.input.partition_point(|key_time| *key_time <= time);
+        let previous = next - 1;
+        let delta = self.input[next] - self.input[previous];
+        let t = (time - self.input[previous]) / delta;
+        match self.interpolation {
+            Interpolation::Step => self.value(previous).to_vec(),
+            Interpolation::Linear if property == Property::Rotation => {
+                let from = Self::quat(self.value(previous));
+                let to = Self::quat(self.value(next));
+                let rotation = glm::quat_slerp(&from, &to, t);
+                rotation.coords.as_slice().to_vec()
+            }
+            Interpolation::Linear => self
+                .value(previous)
+                .iter()
+                .zip(self.value(next))
+                .map(|(from, to)| from + (to - from) * t)
+                .collect(),
+            Interpolation::CubicSpline => {
+                let t2 = t * t;
+                let t3 = t2 * t;
+                let value = self
+                    .value(previous)
+                    .iter()
+                    .zip(self.out_tangent(previous))
+                    .zip(self.in_tangent(next))
+                    .zip(self.value(next))
+                    .map(|(((from, out_tangent), in_tangent), to)| {
+                        (2.0 * t3 - 3.0 * t2 + 1.0) * from
+                            + (t3 - 2.0 * t2 + t) * delta * out_tangent
+                            + (-2.0 * t3 + 3.0 * t2) * to
+                            + (t3 - t2) * delta * in_tangent
+                    })
+                    .collect::<Vec<_>>();
+                if property == Property::Rotation {
+                    Self::quat(&value).normalize().coords.as_slice().to_vec()
+                } else {
+                    value
+                }
+            }
+        }
+    }
+
+    fn value(&self, key: usize) -> &[f32] {
+        match self.interpolation {
+            Interpolation::CubicSpline => self.component(3 * key + 1),
+            _ => self.component(key),
+        }
+    }
+
+    fn in_tangent(&self, key: usize) -> &[f32] {
+        self.component(3 * key)
+    }
+
+    fn out_tangent(&self, key: usize) -> &[f32] {
+        self.component(3 * key + 2)
+    }
+
+    fn component(&self, index: usize) -> &[f32] {
+        let start = index * self.component_count;
+        &self.output[start..start + self.component_count]
+    }
+
+    fn quat(value: &[f32]) -> Qua<f32> {
+        Qua::new(value[3], value[0], value[1], value[2])
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Channel {
+    sampler: Sampler,
+    node: WeakRef<Node>,
+    property: Property,
+}
+
+impl Channel {
+    pub fn new(sampler: Sampler, node: WeakRef<Node>, property: Property) -> Self {
+        Self {
+            sampler,
+            node,
+            property,
+        }
+    }
+
+    pub fn apply(&self, time: f32) {
+        if let Some(node) = self.node.upgrade() {
+            let value = self.sampler.sample(time, self.property);
+            let mut node = node.borrow_mut();
+            match self.property {
+                Property::Translation => node.set_position(&Vec3::from_column_slice(&value)),
+                Property::Rotation => node.set_rotation(&Sampler::quat(&value)),
+                Property::Scale => node.set_scale(&Vec3::from_column_slice(&value)),
+                Property::Weights => node.set_weights(value),
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Animation {
+    channels: Vec<Channel>,
+    duration: f32,
+    #[allow(dead_code)]
+    name: Option<String>,
+}
+
+impl Animation {
+    pub fn new(channels: Vec<Channel>, name: Option<String>) -> Self {
+        let duration = channels
+            .iter()
+            .map(|channel| channel.sampler.duration())
+            .fold(0.0, f32::max);
+        Self {
+            channels,
+            duration,
+            name,
+        }
+    }
+
+    pub fn apply(&self, time: f32) {
+        let time = if self.duration > 0.0 {
+            time % self.duration
+        } else {
+            0.0
+        };
+        for channel in self.channels.iter() {
+            channel.apply(time);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sample_works() {
+        let input = vec![0.0, 1.0, 2.0];
+        let output = vec![0.0, 2.0, 4.0];
+        let linear = Sampler::new(input.clone(), output.clone(), Interpolation::Linear).unwrap();
+        assert_eq!(linear.sample(0.5, Property::Weights), vec![1.0]);
+        assert_eq!(linear.sample(3.0, Property::Weights), vec![4.0]);
+        let step = Sampler::new(input, output, Interpolation::Step).unwrap();
+        assert_eq!(step.sample(1.5, Property::Weights), vec![2.0]);
+    }
+
+    #[test]
+    fn cubic_spline_sample_works() {
+        let input = vec![0.0, 1.0];
+        let output = vec![0.0, 0.0, 0.0, 0.0, 1.0, 0.0];
+        let sampler = Sampler::new(input, output, Interpolation::CubicSpline).unwrap();
+        assert_eq!(sampler.sample(0.5, Property::Weights), vec![0.5]);
+    }
+}
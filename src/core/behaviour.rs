@@ -0,0 +1,50 @@
+use std::{
+    fmt::Debug,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::base::input::KeyState;
+
+use super::node::Node;
+
+/// Per-frame logic attached to a node. Behaviours run while their node is
+/// mutably borrowed, so they must not borrow the node itself through a shared
+/// reference; other nodes, including ancestors, can be borrowed.
+pub trait Behaviour: Debug + CloneBehaviour {
+    fn on_attach(&mut self, _node: &mut Node) {}
+
+    fn update(&mut self, node: &mut Node, dt: f32, key_state: &KeyState);
+
+    fn on_detach(&mut self, _node: &mut Node) {}
+}
+
+/// Identifies a behaviour added to a node; copies made by `Node::deep_clone`
+/// keep the identifier of their original.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BehaviourId(usize);
+
+impl BehaviourId {
+    pub(crate) fn next() -> Self {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+        Self(NEXT_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+pub trait CloneBehaviour {
+    fn clone_box(&self) -> Box<dyn Behaviour>;
+}
+
+impl<T> CloneBehaviour for T
+where
+    T: Behaviour + Clone + 'static,
+{
+    fn clone_box(&self) -> Box<dyn Behaviour> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn Behaviour> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
@@ -1,6 +1,8 @@
 use std::mem;
 
-use js_sys::{ArrayBuffer, Float32Array, Uint16Array};
+use js_sys::{
+    ArrayBuffer, Float32Array, Int16Array, Int8Array, Uint16Array, Uint32Array, Uint8Array,
+};
 
 #[derive(Debug, Clone)]
 pub struct Buffer {
@@ -23,6 +25,22 @@ impl Buffer {
     pub fn get_uint16_array(&self, byte_offset: u32, length: u32) -> Uint16Array {
         self.buffer_type.get_uint16_array(byte_offset, length)
     }
+
+    pub fn get_uint8_array(&self, byte_offset: u32, length: u32) -> Uint8Array {
+        self.buffer_type.get_uint8_array(byte_offset, length)
+    }
+
+    pub fn get_uint32_array(&self, byte_offset: u32, length: u32) -> Uint32Array {
+        self.buffer_type.get_uint32_array(byte_offset, length)
+    }
+
+    pub fn get_int16_array(&self, byte_offset: u32, length: u32) -> Int16Array {
+        self.buffer_type.get_int16_array(byte_offset, length)
+    }
+
+    pub fn get_int8_array(&self, byte_offset: u32, length: u32) -> Int8Array {
+        self.buffer_type.get_int8_array(byte_offset, length)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -57,6 +75,44 @@ impl BufferType {
         }
     }
 
+    pub fn get_uint8_array(&self, byte_offset: u32, length: u32) -> Uint8Array {
+        match self {
+            Self::ArrayBuffer(array_buffer) => {
+                Uint8Array::new_with_byte_offset_and_length(array_buffer, byte_offset, length)
+            }
+            Self::Float32Array(_) => Self::panic_forbidden_conversion("Float32Array", "Uint8Array"),
+        }
+    }
+
+    pub fn get_uint32_array(&self, byte_offset: u32, length: u32) -> Uint32Array {
+        match self {
+            Self::ArrayBuffer(array_buffer) => {
+                Uint32Array::new_with_byte_offset_and_length(array_buffer, byte_offset, length)
+            }
+            Self::Float32Array(_) => {
+                Self::panic_forbidden_conversion("Float32Array", "Uint32Array")
+            }
+        }
+    }
+
+    pub fn get_int16_array(&self, byte_offset: u32, length: u32) -> Int16Array {
+        match self {
+            Self::ArrayBuffer(array_buffer) => {
+                Int16Array::new_with_byte_offset_and_length(array_buffer, byte_offset, length)
+            }
+            Self::Float32Array(_) => Self::panic_forbidden_conversion("Float32Array", "Int16Array"),
+        }
+    }
+
+    pub fn get_int8_array(&self, byte_offset: u32, length: u32) -> Int8Array {
+        match self {
+            Self::ArrayBuffer(array_buffer) => {
+                Int8Array::new_with_byte_offset_and_length(array_buffer, byte_offset, length)
+            }
+            Self::Float32Array(_) => Self::panic_forbidden_conversion("Float32Array", "Int8Array"),
+        }
+    }
+
     fn panic_forbidden_conversion(from: &str, to: &str) -> ! {
         panic!("Cannot convert {} to {}", from, to)
     }
@@ -1,7 +1,7 @@
 use std::rc::Rc;
 
 use anyhow::{anyhow, Result};
-use js_sys::{Float32Array, Uint16Array};
+use js_sys::{Float32Array, Int16Array, Int8Array, Uint16Array, Uint32Array, Uint8Array};
 use web_sys::WebGl2RenderingContext;
 
 use crate::base::util::validate;
@@ -44,6 +44,10 @@ impl BufferView {
         })
     }
 
+    pub fn to_vec(&self) -> Vec<u8> {
+        self.get_uint8_array(0, self.byte_length).to_vec()
+    }
+
     pub fn get_float32_array(&self, byte_offset: u32, length: u32) -> Float32Array {
         self.buffer
             .get_float32_array(self.byte_offset + byte_offset, length)
@@ -54,6 +58,26 @@ impl BufferView {
             .get_uint16_array(self.byte_offset + byte_offset, length)
     }
 
+    pub fn get_uint8_array(&self, byte_offset: u32, length: u32) -> Uint8Array {
+        self.buffer
+            .get_uint8_array(self.byte_offset + byte_offset, length)
+    }
+
+    pub fn get_uint32_array(&self, byte_offset: u32, length: u32) -> Uint32Array {
+        self.buffer
+            .get_uint32_array(self.byte_offset + byte_offset, length)
+    }
+
+    pub fn get_int16_array(&self, byte_offset: u32, length: u32) -> Int16Array {
+        self.buffer
+            .get_int16_array(self.byte_offset + byte_offset, length)
+    }
+
+    pub fn get_int8_array(&self, byte_offset: u32, length: u32) -> Int8Array {
+        self.buffer
+            .get_int8_array(self.byte_offset + byte_offset, length)
+    }
+
     pub fn unbind(context: &WebGl2RenderingContext, has_indices: bool) {
         context.bind_buffer(WebGl2RenderingContext::ARRAY_BUFFER, None);
         if has_indices {
@@ -1,9 +1,9 @@
 use std::rc::Weak;
 
-use glm::{Mat4, Vec3};
+use glm::{Mat4, Vec2, Vec3};
 
 use crate::base::{
-    math::matrix,
+    math::{frustum::Frustum, matrix, ray::Ray},
     util::{
         level::Level,
         shared_ref::{self, SharedRef, WeakRef},
@@ -18,7 +18,6 @@ use super::{
 #[derive(Debug, Clone)]
 pub struct Camera {
     camera_type: CameraType,
-    #[allow(dead_code)]
     name: Option<String>,
     node: WeakRef<Node>,
 }
@@ -52,6 +51,7 @@ impl Camera {
                 y_fov,
                 z_far,
                 z_near,
+                ..Default::default()
             }),
             name,
         )
@@ -128,10 +128,36 @@ impl Camera {
         projection_matrix * view_matrix
     }
 
+    pub fn frustum(&self) -> Frustum {
+        Frustum::from_matrix(&self.view_projection_matrix())
+    }
+
+    /// Builds a world-space ray through a point given in normalized device
+    /// coordinates, from -1 to 1 with y pointing up.
+    pub fn ray(&self, position: &Vec2) -> Ray {
+        let inverse = (self.projection_matrix() * self.view_matrix())
+            .try_inverse()
+            .unwrap_or_else(matrix::identity);
+        let unproject = |depth: f32| {
+            let point = inverse * glm::vec4(position.x, position.y, depth, 1.0);
+            glm::vec4_to_vec3(&point) / point.w
+        };
+        let near = unproject(-1.0);
+        Ray::new(near, unproject(0.0) - near)
+    }
+
     pub fn world_position(&self) -> Vec3 {
         matrix::get_position(&self.model_matrix())
     }
 
+    pub fn camera_type(&self) -> &CameraType {
+        &self.camera_type
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
     pub fn node(&self) -> Option<SharedRef<Node>> {
         self.node.upgrade()
     }
@@ -140,6 +166,15 @@ impl Camera {
         self.node = Weak::clone(node);
     }
 
+    pub fn fixed_aspect_ratio(&self) -> Option<f32> {
+        match &self.camera_type {
+            CameraType::Perspective(perspective) if perspective.fixed_aspect_ratio => {
+                Some(perspective.aspect_ratio)
+            }
+            _ => None,
+        }
+    }
+
     pub fn set_aspect_ratio(&mut self, aspect_ratio: f32) {
         if let CameraType::Perspective(perspective) = &mut self.camera_type {
             perspective.aspect_ratio = aspect_ratio;
@@ -205,6 +240,7 @@ pub struct Perspective {
     pub y_fov: f32,
     pub z_far: Option<f32>,
     pub z_near: f32,
+    pub fixed_aspect_ratio: bool,
 }
 
 impl Default for Perspective {
@@ -214,6 +250,7 @@ impl Default for Perspective {
             y_fov: 60_f32.to_radians(),
             z_near: 0.1,
             z_far: Some(1000.0),
+            fixed_aspect_ratio: false,
         }
     }
 }
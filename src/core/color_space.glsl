@@ -0,0 +1,21 @@
+uniform bool u_LinearOutput;
+
+vec3 srgbToLinear(vec3 color) {
+    return mix(color / 12.92, pow((color + 0.055) / 1.055, vec3(2.4)), step(0.04045, color));
+}
+
+vec4 srgbToLinear(vec4 color) {
+    return vec4(srgbToLinear(color.rgb), color.a);
+}
+
+vec3 linearToSrgb(vec3 color) {
+    color = max(color, vec3(0.0));
+    return mix(color * 12.92, 1.055 * pow(color, vec3(1.0 / 2.4)) - 0.055, step(0.0031308, color));
+}
+
+vec4 encodeOutput(vec4 color) {
+    if (u_LinearOutput) {
+        return color;
+    }
+    return vec4(linearToSrgb(color.rgb), color.a);
+}
@@ -0,0 +1,23 @@
+use std::collections::BTreeMap;
+
+use crate::base::util::value::{FromValue, Value};
+
+/// Application data carried over from glTF `extras` and unrecognized extensions.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CustomProperties {
+    pub extras: Value,
+    pub extensions: BTreeMap<String, Value>,
+}
+
+impl CustomProperties {
+    pub fn new(extras: Option<Value>, extensions: BTreeMap<String, Value>) -> Self {
+        Self {
+            extras: extras.unwrap_or_default(),
+            extensions,
+        }
+    }
+
+    pub fn extra<T: FromValue>(&self, path: &str) -> Option<T> {
+        self.extras.pointer(path).and_then(Value::to)
+    }
+}
@@ -1,7 +1,13 @@
-use anyhow::{anyhow, Result};
+use std::rc::Rc;
+
+use anyhow::{anyhow, bail, Result};
 use web_sys::{HtmlCanvasElement, HtmlImageElement, WebGl2RenderingContext};
 
-use crate::base::{math::resolution::Resolution, web};
+use crate::base::{
+    asset::AssetSource, color::ColorSpace, math::resolution::Resolution, util::base64, web,
+};
+
+use super::ktx2::{self, Ktx2};
 
 #[derive(Debug, Clone)]
 pub struct Image {
@@ -12,18 +18,6 @@ pub struct Image {
 }
 
 impl Image {
-    pub fn new(
-        html_image: HtmlImageElement,
-        name: Option<String>,
-        mime_type: Option<String>,
-    ) -> Self {
-        Self {
-            image_type: ImageType::HtmlImageElement(html_image),
-            name,
-            mime_type,
-        }
-    }
-
     pub fn new_with_type(
         image_type: ImageType,
         name: Option<String>,
@@ -36,18 +30,30 @@ impl Image {
         }
     }
 
-    pub async fn fetch(uri: &str) -> Result<Self> {
-        let html_image = web::fetch_image(uri).await?;
-        Ok(Self::new(html_image, None, None))
+    pub async fn load(source: &dyn AssetSource, uri: &str) -> Result<Self> {
+        let image_type = ImageType::load(source, uri, None).await?;
+        Ok(Self::new_with_type(image_type, None, None))
     }
 
-    pub fn tex_image_2d(&self, context: &WebGl2RenderingContext) -> Result<()> {
-        self.image_type.tex_image_2d(context)
+    pub fn tex_image_2d(
+        &self,
+        context: &WebGl2RenderingContext,
+        color_space: ColorSpace,
+    ) -> Result<()> {
+        self.image_type.tex_image_2d(context, color_space)
     }
 
     pub fn resolution(&self) -> Resolution {
         self.image_type.resolution()
     }
+
+    pub fn provides_mipmaps(&self) -> bool {
+        matches!(self.image_type, ImageType::Ktx2(_))
+    }
+
+    pub fn to_data_url(&self) -> Result<String> {
+        self.image_type.to_data_url()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -55,12 +61,41 @@ pub enum ImageType {
     HtmlImageElement(HtmlImageElement),
     HtmlCanvasElement(HtmlCanvasElement),
     Buffer(Resolution),
+    Ktx2(Rc<Ktx2>),
 }
 
 impl ImageType {
-    pub fn tex_image_2d(&self, context: &WebGl2RenderingContext) -> Result<()> {
+    pub async fn load(
+        source: &dyn AssetSource,
+        uri: &str,
+        mime_type: Option<&str>,
+    ) -> Result<Self> {
+        if mime_type == Some(ktx2::MIME_TYPE) || uri.ends_with(".ktx2") {
+            let data = source.read_bytes(uri).await?;
+            return Ok(Self::Ktx2(Rc::new(Ktx2::parse(&data)?)));
+        }
+        Ok(Self::HtmlImageElement(source.decode_image(uri).await?))
+    }
+
+    /// Decodes an image embedded in a buffer, such as a GLB binary chunk.
+    pub async fn decode(data: &[u8], mime_type: &str) -> Result<Self> {
+        if mime_type == ktx2::MIME_TYPE {
+            return Ok(Self::Ktx2(Rc::new(Ktx2::parse(data)?)));
+        }
+        let data_uri = format!("data:{};base64,{}", mime_type, base64::encode(data));
+        Ok(Self::HtmlImageElement(web::fetch_image(&data_uri).await?))
+    }
+
+    pub fn tex_image_2d(
+        &self,
+        context: &WebGl2RenderingContext,
+        color_space: ColorSpace,
+    ) -> Result<()> {
         let target = WebGl2RenderingContext::TEXTURE_2D;
-        let internal_format = WebGl2RenderingContext::RGBA as i32;
+        let internal_format = match (self, color_space) {
+            (Self::Buffer(_), _) | (_, ColorSpace::Linear) => WebGl2RenderingContext::RGBA,
+            (_, ColorSpace::Srgb) => WebGl2RenderingContext::SRGB8_ALPHA8,
+        } as i32;
         let format = WebGl2RenderingContext::RGBA;
         let image_type = WebGl2RenderingContext::UNSIGNED_BYTE;
         match self {
@@ -95,11 +130,29 @@ impl ImageType {
                     image_type,
                     None
                 ),
-
+            Self::Ktx2(ktx2) => return ktx2.tex_image_2d(context, color_space),
         }
         .map_err(|error| anyhow!("Error while specifying: {:#?}", error))
     }
 
+    pub fn to_data_url(&self) -> Result<String> {
+        let canvas = match self {
+            Self::HtmlImageElement(html_image) => {
+                let canvas = web::new_canvas(html_image.width(), html_image.height())?;
+                web::get_2d_context(&canvas)?
+                    .draw_image_with_html_image_element(html_image, 0.0, 0.0)
+                    .map_err(|error| anyhow!("Cannot draw image: {:#?}", error))?;
+                canvas
+            }
+            Self::HtmlCanvasElement(canvas) => canvas.clone(),
+            Self::Buffer(_) => bail!("Cannot read back image stored only on the GPU"),
+            Self::Ktx2(_) => bail!("Cannot encode KTX2 image as data URL"),
+        };
+        canvas
+            .to_data_url()
+            .map_err(|error| anyhow!("Cannot encode image: {:#?}", error))
+    }
+
     pub fn resolution(&self) -> Resolution {
         match self {
             Self::HtmlImageElement(html_image) => {
@@ -109,6 +162,7 @@ impl ImageType {
                 Resolution::new(canvas.width() as i32, canvas.height() as i32)
             }
             Self::Buffer(resolution) => *resolution,
+            Self::Ktx2(ktx2) => ktx2.resolution(),
         }
     }
 }
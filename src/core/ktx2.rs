@@ -0,0 +1,605 @@
+mod basis;
+mod block;
+
+use anyhow::{anyhow, bail, Result};
+use web_sys::WebGl2RenderingContext;
+
+use crate::base::{color::ColorSpace, gl, math::resolution::Resolution, util::validate};
+
+use self::basis::{Etc1sBlock, Etc1sDecoder};
+
+pub const MIME_TYPE: &str = "image/ktx2";
+
+const IDENTIFIER: [u8; 12] = [
+    0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A,
+];
+const LEVEL_INDEX_OFFSET: usize = 80;
+const LEVEL_INDEX_ENTRY_SIZE: usize = 24;
+const SUPERCOMPRESSION_NONE: u32 = 0;
+const SUPERCOMPRESSION_BASIS_LZ: u32 = 1;
+const COLOR_MODEL_ETC1S: u8 = 163;
+const COLOR_MODEL_UASTC: u8 = 166;
+const CHANNEL_ETC1S_AAA: u8 = 15;
+
+/// Pixel formats that can be uploaded, either directly or after transcoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockFormat {
+    Rgba8,
+    Bc1,
+    Bc3,
+    Bc7,
+    Etc2Rgb,
+    Etc2Rgba,
+    Astc4x4,
+}
+
+impl BlockFormat {
+    fn from_vk_format(vk_format: u32) -> Option<Self> {
+        match vk_format {
+            37 | 43 => Some(Self::Rgba8),
+            131 | 132 => Some(Self::Bc1),
+            137 | 138 => Some(Self::Bc3),
+            145 | 146 => Some(Self::Bc7),
+            147 | 148 => Some(Self::Etc2Rgb),
+            151 | 152 => Some(Self::Etc2Rgba),
+            157 | 158 => Some(Self::Astc4x4),
+            _ => None,
+        }
+    }
+
+    fn extension(&self, color_space: ColorSpace) -> Option<&'static str> {
+        match (self, color_space) {
+            (Self::Rgba8, _) => None,
+            (Self::Bc1 | Self::Bc3, ColorSpace::Srgb) => Some("WEBGL_compressed_texture_s3tc_srgb"),
+            (Self::Bc1 | Self::Bc3, ColorSpace::Linear) => Some("WEBGL_compressed_texture_s3tc"),
+            (Self::Bc7, _) => Some("EXT_texture_compression_bptc"),
+            (Self::Etc2Rgb | Self::Etc2Rgba, _) => Some("WEBGL_compressed_texture_etc"),
+            (Self::Astc4x4, _) => Some("WEBGL_compressed_texture_astc"),
+        }
+    }
+
+    fn internal_format(&self, color_space: ColorSpace) -> u32 {
+        let srgb = color_space == ColorSpace::Srgb;
+        match self {
+            Self::Rgba8 if srgb => WebGl2RenderingContext::SRGB8_ALPHA8,
+            Self::Rgba8 => WebGl2RenderingContext::RGBA8,
+            Self::Bc1 if srgb => 0x8C4C,
+            Self::Bc1 => 0x83F0,
+            Self::Bc3 if srgb => 0x8C4F,
+            Self::Bc3 => 0x83F3,
+            Self::Bc7 if srgb => 0x8E8D,
+            Self::Bc7 => 0x8E8C,
+            Self::Etc2Rgb if srgb => 0x9275,
+            Self::Etc2Rgb => 0x9274,
+            Self::Etc2Rgba if srgb => 0x9279,
+            Self::Etc2Rgba => 0x9278,
+            Self::Astc4x4 if srgb => 0x93D0,
+            Self::Astc4x4 => 0x93B0,
+        }
+    }
+
+    fn is_supported(&self, context: &WebGl2RenderingContext, color_space: ColorSpace) -> bool {
+        self.extension(color_space)
+            .is_none_or(|extension| matches!(context.get_extension(extension), Ok(Some(_))))
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Encoding {
+    Raw(BlockFormat),
+    Etc1s(Box<Etc1sDecoder>),
+}
+
+/// A 2D KTX2 texture with all of its mip levels, largest first.
+///
+/// Basis Universal data is only supported in ETC1S mode, which transcodes to
+/// ETC2, S3TC, BPTC, ASTC 4x4 or RGBA8. UASTC textures and Zstandard
+/// supercompression are not supported; files using them fail to parse with an
+/// error naming the limitation.
+#[derive(Debug, Clone)]
+pub struct Ktx2 {
+    encoding: Encoding,
+    width: usize,
+    height: usize,
+    levels: Vec<Vec<u8>>,
+    has_alpha: bool,
+}
+
+impl Ktx2 {
+    pub fn is_ktx2(data: &[u8]) -> bool {
+        data.starts_with(&IDENTIFIER)
+    }
+
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        validate::assert(Self::is_ktx2(data), || anyhow!("Missing KTX2 identifier"))?;
+        let vk_format = self::read_u32(data, 12)?;
+        let width = self::read_u32(data, 20)? as usize;
+        let height = self::read_u32(data, 24)?.max(1) as usize;
+        let depth = self::read_u32(data, 28)?;
+        let layer_count = self::read_u32(data, 32)?;
+        let face_count = self::read_u32(data, 36)?;
+        let level_count = self::read_u32(data, 40)?.max(1) as usize;
+        let supercompression = self::read_u32(data, 44)?;
+        validate::assert(depth == 0 && layer_count <= 1 && face_count == 1, || {
+            anyhow!("Only single 2D KTX2 textures are supported")
+        })?;
+        let dfd_offset = usize::try_from(self::read_u32(data, 48)?)?;
+        validate::assert(dfd_offset < data.len(), || {
+            anyhow!("KTX2 data format descriptor is out of bounds")
+        })?;
+        let sgd_offset = self::read_u64(data, 64)?;
+        let sgd_length = self::read_u64(data, 72)?;
+        let levels = (0..level_count)
+            .map(|level| {
+                let entry = LEVEL_INDEX_OFFSET + level * LEVEL_INDEX_ENTRY_SIZE;
+                let offset = self::read_u64(data, entry)?;
+                let length = self::read_u64(data, entry + 8)?;
+                Ok(self::slice(data, offset, length)?.to_vec())
+            })
+            .collect::<Result<_>>()?;
+        // Basic data format descriptor block, following the total size.
+        let descriptor = dfd_offset + 4;
+        let color_model = *data
+            .get(descriptor + 8)
+            .ok_or_else(|| anyhow!("KTX2 data format descriptor is truncated"))?;
+        let descriptor_size = (self::read_u32(data, descriptor + 4)? >> 16) as usize;
+        let channels = (0..descriptor_size.saturating_sub(24) / 16)
+            .map(|sample| Ok(self::read_u32(data, descriptor + 24 + sample * 16)? >> 24 & 0xF))
+            .collect::<Result<Vec<_>>>()?;
+        let encoding = match (vk_format, supercompression, color_model) {
+            (0, SUPERCOMPRESSION_BASIS_LZ, COLOR_MODEL_ETC1S) => {
+                let global_data = self::slice(data, sgd_offset, sgd_length)?;
+                Encoding::Etc1s(Box::new(Etc1sDecoder::parse(global_data, level_count)?))
+            }
+            (0, _, COLOR_MODEL_UASTC) => bail!("UASTC textures are not supported, only ETC1S"),
+            (format, SUPERCOMPRESSION_NONE, _) => Encoding::Raw(
+                BlockFormat::from_vk_format(format)
+                    .ok_or_else(|| anyhow!("Unsupported KTX2 format {}", format))?,
+            ),
+            (_, scheme, _) => bail!(
+                "Unsupported KTX2 supercompression scheme {}, only BasisLZ is supported",
+                scheme
+            ),
+        };
+        Ok(Self {
+            encoding,
+            width,
+            height,
+            levels,
+            has_alpha: channels.contains(&(CHANNEL_ETC1S_AAA as u32)),
+        })
+    }
+
+    pub fn resolution(&self) -> Resolution {
+        Resolution::new(self.width as i32, self.height as i32)
+    }
+
+    fn level_size(&self, level: usize) -> (usize, usize) {
+        ((self.width >> level).max(1), (self.height >> level).max(1))
+    }
+
+    /// Returns the data of a mip level in the given format.
+    pub fn transcode(&self, level: usize, format: BlockFormat) -> Result<Vec<u8>> {
+        let data = self
+            .levels
+            .get(level)
+            .ok_or_else(|| anyhow!("Missing KTX2 level {}", level))?;
+        let decoder = match &self.encoding {
+            Encoding::Raw(native) if *native == format => return Ok(data.clone()),
+            Encoding::Raw(native) => bail!("Cannot convert {:?} to {:?}", native, format),
+            Encoding::Etc1s(decoder) => decoder,
+        };
+        let (width, height) = self.level_size(level);
+        let (blocks_x, blocks_y) = (width.div_ceil(4), height.div_ceil(4));
+        let desc = decoder.image_desc(level)?;
+        validate::assert(!Etc1sDecoder::is_p_frame(desc), || {
+            anyhow!("ETC1S video frames are not supported")
+        })?;
+        let slice = |range: &std::ops::Range<usize>| {
+            data.get(range.clone())
+                .ok_or_else(|| anyhow!("ETC1S slice is out of bounds in level {}", level))
+        };
+        let color = decoder.decode_slice(slice(&desc.rgb_slice)?, blocks_x, blocks_y)?;
+        let alpha: Vec<[u8; 16]> = match &desc.alpha_slice {
+            Some(range) => decoder
+                .decode_slice(slice(range)?, blocks_x, blocks_y)?
+                .iter()
+                .map(Etc1sBlock::decode_alpha)
+                .collect(),
+            None => vec![[255; 16]; color.len()],
+        };
+        let blocks = color.iter().zip(alpha.iter());
+        let pixels = blocks.clone().map(|(color, alpha)| {
+            let mut pixels = color.decode();
+            for (pixel, alpha) in pixels.iter_mut().zip(alpha) {
+                pixel[3] = *alpha;
+            }
+            pixels
+        });
+        Ok(match format {
+            BlockFormat::Etc2Rgb => color.iter().flat_map(Etc1sBlock::encode_etc1).collect(),
+            BlockFormat::Etc2Rgba => blocks
+                .flat_map(|(color, alpha)| [block::encode_eac_alpha(alpha), color.encode_etc1()])
+                .flatten()
+                .collect(),
+            BlockFormat::Bc1 => color
+                .iter()
+                .flat_map(|color| block::encode_bc1(&color.decode()))
+                .collect(),
+            BlockFormat::Bc3 => blocks
+                .flat_map(|(color, alpha)| {
+                    [block::encode_bc4(alpha), block::encode_bc1(&color.decode())]
+                })
+                .flatten()
+                .collect(),
+            BlockFormat::Bc7 => pixels
+                .flat_map(|pixels| block::encode_bc7(&pixels))
+                .collect(),
+            BlockFormat::Astc4x4 => pixels
+                .flat_map(|pixels| block::encode_astc(&pixels))
+                .collect(),
+            BlockFormat::Rgba8 => {
+                let mut rgba = vec![0; width * height * 4];
+                for (index, pixels) in pixels.enumerate() {
+                    let (block_x, block_y) = (index % blocks_x * 4, index / blocks_x * 4);
+                    for (texel, pixel) in pixels.iter().enumerate() {
+                        let (x, y) = (block_x + texel % 4, block_y + texel / 4);
+                        if x < width && y < height {
+                            let offset = (y * width + x) * 4;
+                            rgba[offset..offset + 4].copy_from_slice(pixel);
+                        }
+                    }
+                }
+                rgba
+            }
+        })
+    }
+
+    /// Picks the format to upload, preferring compressed formats the context supports.
+    pub fn select_format(
+        &self,
+        context: &WebGl2RenderingContext,
+        color_space: ColorSpace,
+    ) -> Result<BlockFormat> {
+        match &self.encoding {
+            Encoding::Raw(format) if format.is_supported(context, color_space) => Ok(*format),
+            Encoding::Raw(format) => bail!("{:?} textures are not supported", format),
+            Encoding::Etc1s(_) => {
+                let candidates = if self.has_alpha {
+                    [
+                        BlockFormat::Etc2Rgba,
+                        BlockFormat::Bc7,
+                        BlockFormat::Astc4x4,
+                        BlockFormat::Bc3,
+                    ]
+                } else {
+                    [
+                        BlockFormat::Etc2Rgb,
+                        BlockFormat::Bc7,
+                        BlockFormat::Astc4x4,
+                        BlockFormat::Bc1,
+                    ]
+                };
+                let is_block_aligned =
+                    self.width.is_multiple_of(4) && self.height.is_multiple_of(4);
+                Ok(candidates
+                    .into_iter()
+                    .filter(|_| is_block_aligned)
+                    .find(|format| format.is_supported(context, color_space))
+                    .unwrap_or(BlockFormat::Rgba8))
+            }
+        }
+    }
+
+    pub fn tex_image_2d(
+        &self,
+        context: &WebGl2RenderingContext,
+        color_space: ColorSpace,
+    ) -> Result<()> {
+        let format = self.select_format(context, color_space)?;
+        let target = WebGl2RenderingContext::TEXTURE_2D;
+        let internal_format = format.internal_format(color_space);
+        for level in 0..self.levels.len() {
+            let (width, height) = self.level_size(level);
+            let data = self.transcode(level, format)?;
+            if format == BlockFormat::Rgba8 {
+                // Compressed uploads ignore UNPACK_FLIP_Y_WEBGL, so RGBA8 must too.
+                let flip_y =
+                    gl::get_bool_parameter(context, WebGl2RenderingContext::UNPACK_FLIP_Y_WEBGL)
+                        .unwrap_or_default();
+                context.pixel_storei(WebGl2RenderingContext::UNPACK_FLIP_Y_WEBGL, 0);
+                let result = context
+                    .tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(
+                        target,
+                        level as i32,
+                        internal_format as i32,
+                        width as i32,
+                        height as i32,
+                        0,
+                        WebGl2RenderingContext::RGBA,
+                        WebGl2RenderingContext::UNSIGNED_BYTE,
+                        Some(&data),
+                    );
+                context.pixel_storei(
+                    WebGl2RenderingContext::UNPACK_FLIP_Y_WEBGL,
+                    i32::from(flip_y),
+                );
+                result.map_err(|error| anyhow!("Error while specifying: {:#?}", error))?;
+            } else {
+                context.compressed_tex_image_2d_with_u8_array(
+                    target,
+                    level as i32,
+                    internal_format,
+                    width as i32,
+                    height as i32,
+                    0,
+                    &data,
+                );
+            }
+        }
+        context.tex_parameteri(
+            target,
+            WebGl2RenderingContext::TEXTURE_MAX_LEVEL,
+            self.levels.len() as i32 - 1,
+        );
+        Ok(())
+    }
+}
+
+fn slice(data: &[u8], offset: usize, length: usize) -> Result<&[u8]> {
+    offset
+        .checked_add(length)
+        .and_then(|end| data.get(offset..end))
+        .ok_or_else(|| anyhow!("KTX2 range {}+{} is out of bounds", offset, length))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32> {
+    Ok(u32::from_le_bytes(
+        self::slice(data, offset, 4)?.try_into()?,
+    ))
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Result<usize> {
+    Ok(usize::try_from(u64::from_le_bytes(
+        self::slice(data, offset, 8)?.try_into()?,
+    ))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct BitWriter {
+        bytes: Vec<u8>,
+        bits: usize,
+    }
+
+    impl BitWriter {
+        fn write(&mut self, value: u32, count: u32) {
+            for bit in 0..count {
+                if self.bits.is_multiple_of(8) {
+                    self.bytes.push(0);
+                }
+                *self.bytes.last_mut().unwrap() |= (((value >> bit) & 1) as u8) << (self.bits % 8);
+                self.bits += 1;
+            }
+        }
+
+        /// Writes a Huffman table whose only code is `symbol`, one bit long.
+        fn write_table(&mut self, symbol_count: u32, symbol: u32) {
+            self.write(symbol_count, 14);
+            if symbol_count == 0 {
+                return;
+            }
+            // Code length codes, in transmission order: 0 is at position 4 and 1 at position 18.
+            self.write(19, 5);
+            for position in 0..19 {
+                let uses_zero = symbol_count > 1 && position == 4;
+                self.write((uses_zero || position == 18) as u32, 3);
+            }
+            for index in 0..symbol_count {
+                let size = (index == symbol) as u32;
+                self.write(if symbol_count > 1 { size } else { 0 }, 1);
+            }
+        }
+    }
+
+    fn build(vk_format: u32, supercompression: u32, global_data: &[u8], level: &[u8]) -> Vec<u8> {
+        let words = |values: &[u32]| {
+            values
+                .iter()
+                .flat_map(|value| value.to_le_bytes())
+                .collect::<Vec<_>>()
+        };
+        let color_model = if vk_format == 0 { COLOR_MODEL_ETC1S } else { 0 };
+        let sgd_offset = 148;
+        let level_offset = sgd_offset + global_data.len() as u64;
+        let mut data = IDENTIFIER.to_vec();
+        data.extend(words(&[
+            vk_format,
+            1,
+            4,
+            4,
+            0,
+            0,
+            1,
+            1,
+            supercompression,
+            104,
+            44,
+            0,
+            0,
+        ]));
+        for value in [
+            sgd_offset,
+            global_data.len() as u64,
+            level_offset,
+            level.len() as u64,
+            0,
+        ] {
+            data.extend(value.to_le_bytes());
+        }
+        data.extend(words(&[
+            44,
+            0,
+            2 | 40 << 16,
+            color_model as u32 | 2 << 16,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+        ]));
+        data.extend(global_data);
+        data.extend(level);
+        data
+    }
+
+    #[test]
+    fn parse_works() {
+        let pixels: Vec<u8> = (0..64).collect();
+        let ktx2 = Ktx2::parse(&self::build(37, SUPERCOMPRESSION_NONE, &[], &pixels)).unwrap();
+        assert_eq!(ktx2.resolution().width, 4);
+        assert_eq!(ktx2.transcode(0, BlockFormat::Rgba8).unwrap(), pixels);
+        assert!(ktx2.transcode(0, BlockFormat::Bc1).is_err());
+        assert!(Ktx2::parse(&pixels).is_err());
+
+        let mut truncated = self::build(37, SUPERCOMPRESSION_NONE, &[], &pixels);
+        truncated[LEVEL_INDEX_OFFSET..LEVEL_INDEX_OFFSET + 8]
+            .copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(Ktx2::parse(&truncated).is_err());
+
+        let mut uastc = self::etc1s_file(0);
+        assert_eq!(uastc[116], COLOR_MODEL_ETC1S);
+        uastc[116] = COLOR_MODEL_UASTC;
+        let error = Ktx2::parse(&uastc).unwrap_err().to_string();
+        assert!(error.contains("UASTC"));
+    }
+
+    /// A 4x4 gray ETC1S texture with a selector byte per row, first row lowest.
+    fn etc1s_file(selector_rows: u32) -> Vec<u8> {
+        let mut endpoints = BitWriter::default();
+        endpoints.write_table(0, 0);
+        endpoints.write_table(32, 4);
+        endpoints.write_table(0, 0);
+        endpoints.write_table(8, 2);
+        endpoints.write(0, 1 + 4);
+        let mut selectors = BitWriter::default();
+        selectors.write(0b100, 3);
+        selectors.write(selector_rows, 32);
+        let mut tables = BitWriter::default();
+        tables.write_table(257, 3);
+        tables.write_table(1, 0);
+        tables.write_table(2, 0);
+        tables.write_table(0, 0);
+        tables.write(0, 13);
+        let sections = [endpoints.bytes, selectors.bytes, tables.bytes];
+        let mut global_data = [1u16, 1].map(u16::to_le_bytes).concat();
+        for length in sections.iter().map(Vec::len).chain([0]) {
+            global_data.extend((length as u32).to_le_bytes());
+        }
+        global_data.extend([0u32, 0, 1, 0, 0].map(u32::to_le_bytes).concat());
+        global_data.extend(sections.concat());
+        self::build(0, SUPERCOMPRESSION_BASIS_LZ, &global_data, &[0])
+    }
+
+    fn decode_bc7(block: &[u8]) -> block::Pixels {
+        let bits = u128::from_le_bytes(block.try_into().unwrap());
+        assert_eq!(bits & 0x7F, 0x40, "Expected BC7 mode 6");
+        let end = |i: usize| {
+            [0, 1, 2, 3].map(|channel| {
+                let value = (bits >> (7 + channel * 14 + i * 7)) & 0x7F;
+                (value << 1 | (bits >> (63 + i)) & 1) as u32
+            })
+        };
+        let (end0, end1) = (end(0), end(1));
+        std::array::from_fn(|i| {
+            let index = match i {
+                0 => (bits >> 65) & 0x7,
+                _ => (bits >> (68 + (i - 1) * 4)) & 0xF,
+            };
+            let weight =
+                [0, 4, 9, 13, 17, 21, 26, 30, 34, 38, 43, 47, 51, 55, 60, 64][index as usize];
+            [0, 1, 2, 3].map(|c| ((end0[c] * (64 - weight) + end1[c] * weight + 32) >> 6) as u8)
+        })
+    }
+
+    fn decode_astc(block: &[u8]) -> block::Pixels {
+        let bits = u128::from_le_bytes(block.try_into().unwrap());
+        assert_eq!(bits & 0x7FF, 0x042, "Expected a 4x4 grid of 2-bit weights");
+        assert_eq!((bits >> 11) & 0x3, 0, "Expected a single partition");
+        assert_eq!((bits >> 13) & 0xF, 12, "Expected LDR RGBA direct endpoints");
+        let value = |i: usize| ((bits >> (17 + i * 8)) & 0xFF) as u32;
+        let end0 = [0, 2, 4, 6].map(value);
+        let end1 = [1, 3, 5, 7].map(value);
+        assert!(end1[..3].iter().sum::<u32>() >= end0[..3].iter().sum::<u32>());
+        std::array::from_fn(|i| {
+            let index = (bits >> (127 - i * 2)) & 1 | ((bits >> (126 - i * 2)) & 1) << 1;
+            let weight = [0, 21, 43, 64][index as usize];
+            [0, 1, 2, 3].map(|c| {
+                ((end0[c] * 257 * (64 - weight) + end1[c] * 257 * weight + 32) >> 14) as u8
+            })
+        })
+    }
+
+    fn max_difference(a: &[u8], b: &[u8]) -> u8 {
+        assert_eq!(a.len(), b.len());
+        a.iter().zip(b).map(|(a, b)| a.abs_diff(*b)).max().unwrap()
+    }
+
+    #[test]
+    fn transcode_etc1s_works() {
+        let ktx2 = Ktx2::parse(&self::etc1s_file(0xE4E4_E4E4)).unwrap();
+
+        let pixels = ktx2.transcode(0, BlockFormat::Rgba8).unwrap();
+        assert_eq!(pixels.len(), 64);
+        let row: Vec<u8> = pixels[..16].chunks(4).map(|pixel| pixel[0]).collect();
+        assert_eq!(row, [136, 156, 174, 194]);
+        assert_eq!(pixels[3], 255);
+        let etc = ktx2.transcode(0, BlockFormat::Etc2Rgb).unwrap();
+        assert_eq!(etc[..4], [160, 160, 160, 0b0100_1010]);
+        assert_eq!(ktx2.transcode(0, BlockFormat::Bc1).unwrap().len(), 8);
+    }
+
+    #[test]
+    fn transcode_rgba8_rows_top_down() {
+        let ktx2 = Ktx2::parse(&self::etc1s_file(0xFFAA_5500)).unwrap();
+        let pixels = ktx2.transcode(0, BlockFormat::Rgba8).unwrap();
+        let rows: Vec<Vec<u8>> = pixels
+            .chunks(16)
+            .map(|row| row.chunks(4).map(|pixel| pixel[0]).collect())
+            .collect();
+        // The first row of the file comes first, as in the compressed blocks.
+        assert_eq!(rows, [[136; 4], [156; 4], [174; 4], [194; 4]]);
+    }
+
+    #[test]
+    fn transcode_etc1s_to_bptc_and_astc_works() {
+        let ktx2 = Ktx2::parse(&self::etc1s_file(0xE4E4_E4E4)).unwrap();
+        let pixels = ktx2.transcode(0, BlockFormat::Rgba8).unwrap();
+        let bc7 = ktx2.transcode(0, BlockFormat::Bc7).unwrap();
+        assert!(self::max_difference(&self::decode_bc7(&bc7).concat(), &pixels) <= 2);
+        let astc = ktx2.transcode(0, BlockFormat::Astc4x4).unwrap();
+        assert!(self::max_difference(&self::decode_astc(&astc).concat(), &pixels) <= 2);
+    }
+
+    #[test]
+    fn encode_bc7_and_astc_works() {
+        let gradient: block::Pixels = std::array::from_fn(|i| {
+            let i = i as u8;
+            [i * 16, 255 - i * 16, 64 + i * 4, 255 - i * 8]
+        });
+        let decoded = self::decode_bc7(&block::encode_bc7(&gradient));
+        assert!(self::max_difference(&decoded.concat(), &gradient.concat()) <= 4);
+
+        let steps: block::Pixels = std::array::from_fn(|i| {
+            let step = [0, 1, 2, 3][i % 4] * 60;
+            [200 - step, 20 + step, 100, 255 - step / 2]
+        });
+        let decoded = self::decode_astc(&block::encode_astc(&steps));
+        assert!(self::max_difference(&decoded.concat(), &steps.concat()) <= 2);
+    }
+}
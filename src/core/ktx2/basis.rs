@@ -0,0 +1,481 @@
+use std::ops::Range;
+
+use anyhow::{anyhow, bail, Result};
+
+use crate::base::util::validate;
+
+const MAX_SYMBOL_COUNT_BITS: u32 = 14;
+const MAX_CODE_SIZE: usize = 16;
+const CODE_LENGTH_SYMBOL_COUNT: usize = 21;
+const CODE_LENGTH_ORDER: [usize; CODE_LENGTH_SYMBOL_COUNT] = [
+    17, 18, 19, 20, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15, 16,
+];
+const SMALL_ZERO_RUN: u32 = 17;
+const LARGE_ZERO_RUN: u32 = 18;
+const SMALL_REPEAT: u32 = 19;
+
+const COLOR5_PAL0_PREV_HI: u8 = 9;
+const COLOR5_PAL1_PREV_HI: u8 = 21;
+
+const ENDPOINT_PRED_REPEAT_LAST_SYMBOL: u32 = 256;
+const ENDPOINT_PRED_MIN_REPEAT_COUNT: u32 = 3;
+const ENDPOINT_PRED_COUNT_VLC_BITS: u32 = 4;
+const SELECTOR_HISTORY_RLE_COUNT_THRESHOLD: u32 = 3;
+const SELECTOR_HISTORY_RLE_COUNT_TOTAL: u32 = 64;
+
+const IMAGE_DESC_SIZE: usize = 20;
+const IMAGE_FLAG_P_FRAME: u32 = 0x02;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Endpoint {
+    pub color5: [u8; 3],
+    pub intensity: u8,
+}
+
+/// Selectors are stored row by row, ordered from darkest (0) to brightest (3).
+pub type Selectors = [u8; 16];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Etc1sBlock {
+    pub endpoint: Endpoint,
+    pub selectors: Selectors,
+}
+
+#[derive(Debug, Clone)]
+pub struct ImageDesc {
+    pub flags: u32,
+    pub rgb_slice: Range<usize>,
+    pub alpha_slice: Option<Range<usize>>,
+}
+
+/// Decoder for the BasisLZ supercompressed ETC1S data of a KTX2 file.
+#[derive(Debug, Clone)]
+pub struct Etc1sDecoder {
+    endpoints: Vec<Endpoint>,
+    selectors: Vec<Selectors>,
+    tables: SliceTables,
+    image_descs: Vec<ImageDesc>,
+}
+
+impl Etc1sDecoder {
+    pub fn parse(global_data: &[u8], image_count: usize) -> Result<Self> {
+        let read_u16 =
+            |offset: usize| u16::from_le_bytes([global_data[offset], global_data[offset + 1]]);
+        let read_u32 = |offset: usize| {
+            u32::from_le_bytes(global_data[offset..offset + 4].try_into().unwrap()) as usize
+        };
+        let header_size = 20 + image_count * IMAGE_DESC_SIZE;
+        validate::assert(global_data.len() >= header_size, || {
+            anyhow!("BasisLZ global data is truncated")
+        })?;
+        let endpoint_count = read_u16(0) as usize;
+        let selector_count = read_u16(2) as usize;
+        let endpoints_length = read_u32(4);
+        let selectors_length = read_u32(8);
+        let tables_length = read_u32(12);
+        let image_descs = (0..image_count)
+            .map(|index| {
+                let offset = 20 + index * IMAGE_DESC_SIZE;
+                let (rgb_offset, rgb_length) = (read_u32(offset + 4), read_u32(offset + 8));
+                let (alpha_offset, alpha_length) = (read_u32(offset + 12), read_u32(offset + 16));
+                ImageDesc {
+                    flags: read_u32(offset) as u32,
+                    rgb_slice: rgb_offset..rgb_offset.saturating_add(rgb_length),
+                    alpha_slice: (alpha_length > 0)
+                        .then_some(alpha_offset..alpha_offset.saturating_add(alpha_length)),
+                }
+            })
+            .collect();
+        let endpoints_start = header_size;
+        let selectors_start = endpoints_start.saturating_add(endpoints_length);
+        let tables_start = selectors_start.saturating_add(selectors_length);
+        let section = |start: usize, length: usize| {
+            global_data
+                .get(start..start.saturating_add(length))
+                .ok_or_else(|| anyhow!("BasisLZ global data section is out of bounds"))
+        };
+        Ok(Self {
+            endpoints: self::decode_endpoints(
+                section(endpoints_start, endpoints_length)?,
+                endpoint_count,
+            )?,
+            selectors: self::decode_selectors(
+                section(selectors_start, selectors_length)?,
+                selector_count,
+            )?,
+            tables: SliceTables::parse(section(tables_start, tables_length)?)?,
+            image_descs,
+        })
+    }
+
+    pub fn image_desc(&self, index: usize) -> Result<&ImageDesc> {
+        self.image_descs
+            .get(index)
+            .ok_or_else(|| anyhow!("Missing BasisLZ image descriptor {}", index))
+    }
+
+    pub fn decode_slice(
+        &self,
+        data: &[u8],
+        blocks_x: usize,
+        blocks_y: usize,
+    ) -> Result<Vec<Etc1sBlock>> {
+        let tables = &self.tables;
+        let mut reader = BitReader::new(data);
+        let mut history = SelectorHistory::new(tables.selector_history_size);
+        let mut blocks = Vec::with_capacity(blocks_x * blocks_y);
+        // Per column: prediction bits of the next row of 2x2 groups and endpoint of the row above.
+        let mut pred_bits = vec![0u32; blocks_x];
+        let mut above = vec![0usize; blocks_x];
+        let mut row = vec![0usize; blocks_x];
+        let mut cur_pred_bits = 0;
+        let mut prev_pred_symbol = 0;
+        let mut pred_repeat_count = 0;
+        let mut prev_endpoint = 0;
+        let mut selector_rle_count = 0;
+        let selector_count = self.selectors.len() as u32;
+        let history_rle_symbol = selector_count + tables.selector_history_size as u32;
+        for block_y in 0..blocks_y {
+            for block_x in 0..blocks_x {
+                if block_x % 2 == 0 {
+                    if block_y % 2 == 0 {
+                        if pred_repeat_count > 0 {
+                            pred_repeat_count -= 1;
+                            cur_pred_bits = prev_pred_symbol;
+                        } else {
+                            cur_pred_bits = tables.endpoint_pred.decode(&mut reader)?;
+                            if cur_pred_bits == ENDPOINT_PRED_REPEAT_LAST_SYMBOL {
+                                pred_repeat_count = reader
+                                    .read_vlc(ENDPOINT_PRED_COUNT_VLC_BITS)?
+                                    + ENDPOINT_PRED_MIN_REPEAT_COUNT
+                                    - 1;
+                                cur_pred_bits = prev_pred_symbol;
+                            } else {
+                                prev_pred_symbol = cur_pred_bits;
+                            }
+                        }
+                        pred_bits[block_x] = cur_pred_bits >> 4;
+                    } else {
+                        cur_pred_bits = pred_bits[block_x];
+                    }
+                }
+                let endpoint = match cur_pred_bits & 3 {
+                    0 if block_x > 0 => prev_endpoint,
+                    1 if block_y > 0 => above[block_x],
+                    2 if block_x > 0 && block_y > 0 => above[block_x - 1],
+                    3 => {
+                        let endpoint =
+                            tables.delta_endpoint.decode(&mut reader)? as usize + prev_endpoint;
+                        if endpoint >= self.endpoints.len() {
+                            endpoint - self.endpoints.len()
+                        } else {
+                            endpoint
+                        }
+                    }
+                    _ => bail!(
+                        "Invalid ETC1S endpoint prediction at block {}, {}",
+                        block_x,
+                        block_y
+                    ),
+                };
+                cur_pred_bits >>= 2;
+                row[block_x] = endpoint;
+                prev_endpoint = endpoint;
+
+                let symbol = if selector_rle_count > 0 {
+                    selector_rle_count -= 1;
+                    selector_count
+                } else {
+                    let symbol = tables.selector.decode(&mut reader)?;
+                    if symbol == history_rle_symbol {
+                        let run = tables.selector_history_rle.decode(&mut reader)?;
+                        selector_rle_count = if run == SELECTOR_HISTORY_RLE_COUNT_TOTAL - 1 {
+                            reader.read_vlc(7)? + SELECTOR_HISTORY_RLE_COUNT_THRESHOLD
+                        } else {
+                            run + SELECTOR_HISTORY_RLE_COUNT_THRESHOLD
+                        };
+                        validate::assert(
+                            selector_rle_count as usize <= blocks_x * blocks_y,
+                            || anyhow!("Invalid ETC1S selector run length"),
+                        )?;
+                        selector_rle_count -= 1;
+                        selector_count
+                    } else {
+                        symbol
+                    }
+                };
+                let selector = if symbol >= selector_count {
+                    history.get((symbol - selector_count) as usize)?
+                } else {
+                    history.add(symbol as usize);
+                    symbol as usize
+                };
+                blocks.push(Etc1sBlock {
+                    endpoint: *self
+                        .endpoints
+                        .get(endpoint)
+                        .ok_or_else(|| anyhow!("ETC1S endpoint {} out of bounds", endpoint))?,
+                    selectors: *self
+                        .selectors
+                        .get(selector)
+                        .ok_or_else(|| anyhow!("ETC1S selector {} out of bounds", selector))?,
+                });
+            }
+            std::mem::swap(&mut above, &mut row);
+        }
+        Ok(blocks)
+    }
+
+    pub fn is_p_frame(desc: &ImageDesc) -> bool {
+        desc.flags & IMAGE_FLAG_P_FRAME != 0
+    }
+}
+
+#[derive(Debug, Clone)]
+struct SliceTables {
+    endpoint_pred: Huffman,
+    delta_endpoint: Huffman,
+    selector: Huffman,
+    selector_history_rle: Huffman,
+    selector_history_size: usize,
+}
+
+impl SliceTables {
+    fn parse(data: &[u8]) -> Result<Self> {
+        let mut reader = BitReader::new(data);
+        Ok(Self {
+            endpoint_pred: Huffman::read(&mut reader)?,
+            delta_endpoint: Huffman::read(&mut reader)?,
+            selector: Huffman::read(&mut reader)?,
+            selector_history_rle: Huffman::read(&mut reader)?,
+            selector_history_size: reader.read_bits(13)? as usize,
+        })
+    }
+}
+
+/// Approximate move-to-front buffer of recently used selector indices.
+struct SelectorHistory {
+    values: Vec<usize>,
+    rover: usize,
+}
+
+impl SelectorHistory {
+    fn new(size: usize) -> Self {
+        Self {
+            values: vec![0; size],
+            rover: 0,
+        }
+    }
+
+    fn add(&mut self, value: usize) {
+        if self.values.is_empty() {
+            return;
+        }
+        self.values[self.rover] = value;
+        self.rover += 1;
+        if self.rover == self.values.len() {
+            self.rover = self.values.len() / 2;
+        }
+    }
+
+    fn get(&mut self, index: usize) -> Result<usize> {
+        let value = *self
+            .values
+            .get(index)
+            .ok_or_else(|| anyhow!("ETC1S selector history index {} out of bounds", index))?;
+        if index > 0 {
+            self.values.swap(index / 2, index);
+        }
+        Ok(value)
+    }
+}
+
+fn decode_endpoints(data: &[u8], count: usize) -> Result<Vec<Endpoint>> {
+    let mut reader = BitReader::new(data);
+    let color5_models = [
+        Huffman::read(&mut reader)?,
+        Huffman::read(&mut reader)?,
+        Huffman::read(&mut reader)?,
+    ];
+    let intensity_model = Huffman::read(&mut reader)?;
+    let grayscale = reader.read_bits(1)? == 1;
+    let mut prev_color5 = [16u8; 3];
+    let mut prev_intensity = 0;
+    let mut endpoints = Vec::with_capacity(count);
+    for _ in 0..count {
+        let intensity = ((intensity_model.decode(&mut reader)? + prev_intensity) & 7) as u8;
+        prev_intensity = intensity as u32;
+        let channels = if grayscale { 1 } else { 3 };
+        for channel in prev_color5.iter_mut().take(channels) {
+            let model = match *channel {
+                value if value <= COLOR5_PAL0_PREV_HI => &color5_models[0],
+                value if value <= COLOR5_PAL1_PREV_HI => &color5_models[1],
+                _ => &color5_models[2],
+            };
+            *channel = ((*channel as u32 + model.decode(&mut reader)?) & 31) as u8;
+        }
+        let color5 = if grayscale {
+            [prev_color5[0]; 3]
+        } else {
+            prev_color5
+        };
+        endpoints.push(Endpoint { color5, intensity });
+    }
+    Ok(endpoints)
+}
+
+fn decode_selectors(data: &[u8], count: usize) -> Result<Vec<Selectors>> {
+    let mut reader = BitReader::new(data);
+    validate::assert(reader.read_bits(1)? == 0, || {
+        anyhow!("Global ETC1S selector codebooks are not supported")
+    })?;
+    validate::assert(reader.read_bits(1)? == 0, || {
+        anyhow!("Hybrid ETC1S selector codebooks are not supported")
+    })?;
+    let raw = reader.read_bits(1)? == 1;
+    let model = if raw {
+        None
+    } else {
+        Some(Huffman::read(&mut reader)?)
+    };
+    let mut prev_rows = [0u32; 4];
+    let mut selectors = Vec::with_capacity(count);
+    for index in 0..count {
+        let mut selector = [0; 16];
+        for (y, prev_row) in prev_rows.iter_mut().enumerate() {
+            let row = match &model {
+                Some(model) if index > 0 => model.decode(&mut reader)? ^ *prev_row,
+                _ => reader.read_bits(8)?,
+            };
+            *prev_row = row;
+            for x in 0..4 {
+                selector[y * 4 + x] = ((row >> (x * 2)) & 3) as u8;
+            }
+        }
+        selectors.push(selector);
+    }
+    Ok(selectors)
+}
+
+/// Canonical Huffman code, read from the stream starting with the most significant code bit.
+#[derive(Debug, Clone, Default)]
+struct Huffman {
+    counts: [u32; MAX_CODE_SIZE + 1],
+    symbols: Vec<u32>,
+}
+
+impl Huffman {
+    fn new(code_sizes: &[u8]) -> Result<Self> {
+        let mut counts = [0; MAX_CODE_SIZE + 1];
+        for size in code_sizes {
+            validate::assert(*size as usize <= MAX_CODE_SIZE, || {
+                anyhow!("Huffman code size {} is too large", size)
+            })?;
+            counts[*size as usize] += 1;
+        }
+        counts[0] = 0;
+        let mut symbols: Vec<u32> = (0..code_sizes.len() as u32)
+            .filter(|symbol| code_sizes[*symbol as usize] > 0)
+            .collect();
+        symbols.sort_by_key(|symbol| code_sizes[*symbol as usize]);
+        Ok(Self { counts, symbols })
+    }
+
+    fn read(reader: &mut BitReader) -> Result<Self> {
+        let symbol_count = reader.read_bits(MAX_SYMBOL_COUNT_BITS)? as usize;
+        if symbol_count == 0 {
+            return Ok(Self::default());
+        }
+        let mut code_length_sizes = [0u8; CODE_LENGTH_SYMBOL_COUNT];
+        let code_length_count = reader.read_bits(5)? as usize;
+        validate::assert(code_length_count <= CODE_LENGTH_SYMBOL_COUNT, || {
+            anyhow!("Invalid Huffman code length count {}", code_length_count)
+        })?;
+        for symbol in CODE_LENGTH_ORDER.iter().take(code_length_count) {
+            code_length_sizes[*symbol] = reader.read_bits(3)? as u8;
+        }
+        let code_lengths = Self::new(&code_length_sizes)?;
+        let mut code_sizes = vec![0u8; symbol_count];
+        let mut position = 0;
+        while position < symbol_count {
+            let code = code_lengths.decode(reader)?;
+            let (size, run) = match code {
+                0..=16 => (code as u8, 1),
+                SMALL_ZERO_RUN => (0, reader.read_bits(3)? + 3),
+                LARGE_ZERO_RUN => (0, reader.read_bits(7)? + 11),
+                _ => {
+                    validate::assert(position > 0, || {
+                        anyhow!("Huffman repeat without previous size")
+                    })?;
+                    let run = if code == SMALL_REPEAT {
+                        reader.read_bits(2)? + 3
+                    } else {
+                        reader.read_bits(7)? + 7
+                    };
+                    (code_sizes[position - 1], run)
+                }
+            };
+            let end = position + run as usize;
+            validate::assert(end <= symbol_count, || {
+                anyhow!("Huffman code size run overflows")
+            })?;
+            code_sizes[position..end].fill(size);
+            position = end;
+        }
+        Self::new(&code_sizes)
+    }
+
+    fn decode(&self, reader: &mut BitReader) -> Result<u32> {
+        let (mut code, mut first, mut index) = (0, 0, 0);
+        for count in self.counts.iter().skip(1) {
+            code |= reader.read_bits(1)?;
+            if code < first + count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        bail!("Invalid Huffman code")
+    }
+}
+
+/// Reads bits least significant first; reads past the end yield zeros.
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    fn read_bits(&mut self, count: u32) -> Result<u32> {
+        validate::assert(self.position / 8 <= self.data.len(), || {
+            anyhow!("Unexpected end of BasisLZ data")
+        })?;
+        let mut value = 0;
+        for bit in 0..count {
+            let byte = self.data.get(self.position / 8).copied().unwrap_or(0);
+            value |= (((byte >> (self.position % 8)) & 1) as u32) << bit;
+            self.position += 1;
+        }
+        Ok(value)
+    }
+
+    fn read_vlc(&mut self, chunk_bits: u32) -> Result<u32> {
+        let chunk_size = 1 << chunk_bits;
+        let mut value = 0;
+        let mut shift = 0;
+        loop {
+            let chunk = self.read_bits(chunk_bits + 1)?;
+            value |= (chunk & (chunk_size - 1)) << shift;
+            shift += chunk_bits;
+            if chunk & chunk_size == 0 {
+                return Ok(value);
+            }
+            validate::assert(shift < 32, || anyhow!("Invalid variable length code"))?;
+        }
+    }
+}
@@ -0,0 +1,344 @@
+use super::basis::{Etc1sBlock, Selectors};
+
+pub type Pixels = [[u8; 4]; 16];
+
+const ETC1_INTENSITIES: [[i32; 4]; 8] = [
+    [-8, -2, 2, 8],
+    [-17, -5, 5, 17],
+    [-29, -9, 9, 29],
+    [-42, -13, 13, 42],
+    [-60, -18, 18, 60],
+    [-80, -24, 24, 80],
+    [-106, -33, 33, 106],
+    [-183, -47, 47, 183],
+];
+
+/// ETC1 pixel index for each selector, which are ordered from darkest to brightest.
+const ETC1_SELECTOR_INDICES: [u8; 4] = [3, 2, 0, 1];
+
+const BC7_WEIGHTS_4: [u32; 16] = [0, 4, 9, 13, 17, 21, 26, 30, 34, 38, 43, 47, 51, 55, 60, 64];
+const ASTC_WEIGHTS_2: [u32; 4] = [0, 21, 43, 64];
+/// Block mode of a 4x4 weight grid with weights in 0..=3, no dual plane.
+const ASTC_BLOCK_MODE_4X4_WEIGHTS_2: u128 = 0x042;
+const ASTC_CEM_LDR_RGBA_DIRECT: u128 = 12;
+
+const EAC_MODIFIERS: [[i32; 8]; 16] = [
+    [-3, -6, -9, -15, 2, 5, 8, 14],
+    [-3, -7, -10, -13, 2, 6, 9, 12],
+    [-2, -5, -8, -13, 1, 4, 7, 12],
+    [-2, -4, -6, -13, 1, 3, 5, 12],
+    [-3, -6, -8, -12, 2, 5, 7, 11],
+    [-3, -7, -9, -11, 2, 6, 8, 10],
+    [-4, -7, -8, -11, 3, 6, 7, 10],
+    [-3, -5, -8, -11, 2, 4, 7, 10],
+    [-2, -6, -8, -10, 1, 5, 7, 9],
+    [-2, -5, -8, -10, 1, 4, 7, 9],
+    [-2, -4, -8, -10, 1, 3, 7, 9],
+    [-2, -5, -7, -10, 1, 4, 6, 9],
+    [-3, -4, -7, -10, 2, 3, 6, 9],
+    [-1, -2, -3, -10, 0, 1, 2, 9],
+    [-4, -6, -8, -9, 3, 5, 7, 8],
+    [-3, -5, -7, -9, 2, 4, 6, 8],
+];
+
+impl Etc1sBlock {
+    pub fn colors(&self) -> [[u8; 3]; 4] {
+        let intensities = ETC1_INTENSITIES[self.endpoint.intensity as usize];
+        intensities.map(|intensity| {
+            self.endpoint.color5.map(|channel| {
+                let base = ((channel << 3) | (channel >> 2)) as i32;
+                (base + intensity).clamp(0, 255) as u8
+            })
+        })
+    }
+
+    pub fn decode(&self) -> Pixels {
+        let colors = self.colors();
+        self.selectors.map(|selector| {
+            let [r, g, b] = colors[selector as usize];
+            [r, g, b, 255]
+        })
+    }
+
+    /// Alpha slices store their values in the color channels.
+    pub fn decode_alpha(&self) -> [u8; 16] {
+        let colors = self.colors();
+        self.selectors.map(|selector| colors[selector as usize][1])
+    }
+
+    /// ETC1S blocks are differential ETC1 blocks with identical sub-blocks.
+    pub fn encode_etc1(&self) -> [u8; 8] {
+        let [r, g, b] = self.endpoint.color5;
+        let intensity = self.endpoint.intensity;
+        let (msb, lsb) = self::column_major_bits(&self.selectors, |selector| {
+            ETC1_SELECTOR_INDICES[selector as usize]
+        });
+        let mut block = [
+            r << 3,
+            g << 3,
+            b << 3,
+            (intensity << 5) | (intensity << 2) | 0b10,
+            0,
+            0,
+            0,
+            0,
+        ];
+        block[4..6].copy_from_slice(&msb.to_be_bytes());
+        block[6..8].copy_from_slice(&lsb.to_be_bytes());
+        block
+    }
+}
+
+fn column_major_bits(selectors: &Selectors, index: impl Fn(u8) -> u8) -> (u16, u16) {
+    let mut msb = 0;
+    let mut lsb = 0;
+    for y in 0..4 {
+        for x in 0..4 {
+            let value = index(selectors[y * 4 + x]) as u16;
+            msb |= (value >> 1) << (x * 4 + y);
+            lsb |= (value & 1) << (x * 4 + y);
+        }
+    }
+    (msb, lsb)
+}
+
+pub fn encode_eac_alpha(alpha: &[u8; 16]) -> [u8; 8] {
+    let min = *alpha.iter().min().unwrap() as i32;
+    let max = *alpha.iter().max().unwrap() as i32;
+    let base = (min + max + 1) / 2;
+    let mut distinct: Vec<i32> = alpha.iter().map(|value| *value as i32).collect();
+    distinct.sort_unstable();
+    distinct.dedup();
+    let nearest = |modifiers: &[i32; 8], multiplier: i32, value: i32| {
+        (0..8)
+            .map(|index| {
+                let decoded = (base + modifiers[index] * multiplier).clamp(0, 255);
+                ((decoded - value).abs(), index)
+            })
+            .min()
+            .unwrap()
+    };
+    let mut best = (i32::MAX, 0, 1);
+    for (table, modifiers) in EAC_MODIFIERS.iter().enumerate() {
+        let span = modifiers[7] - modifiers[3];
+        let estimate = ((max - min + span / 2) / span).clamp(1, 15);
+        for multiplier in (estimate - 1).max(1)..=(estimate + 1).min(15) {
+            let error = distinct
+                .iter()
+                .map(|value| nearest(modifiers, multiplier, *value).0)
+                .sum();
+            if error < best.0 {
+                best = (error, table, multiplier);
+            }
+        }
+    }
+    let (_, table, multiplier) = best;
+    let mut indices = 0u64;
+    for x in 0..4 {
+        for y in 0..4 {
+            let index = nearest(&EAC_MODIFIERS[table], multiplier, alpha[y * 4 + x] as i32).1;
+            indices = (indices << 3) | index as u64;
+        }
+    }
+    let mut block = [
+        base as u8,
+        ((multiplier as u8) << 4) | table as u8,
+        0,
+        0,
+        0,
+        0,
+        0,
+        0,
+    ];
+    block[2..].copy_from_slice(&indices.to_be_bytes()[2..]);
+    block
+}
+
+/// Encodes a four-color BC1 block between the darkest and brightest pixels.
+pub fn encode_bc1(pixels: &Pixels) -> [u8; 8] {
+    let luma = |pixel: &&[u8; 4]| pixel[0] as u32 * 2 + pixel[1] as u32 * 4 + pixel[2] as u32;
+    let brightest = self::to_rgb565(pixels.iter().max_by_key(luma).unwrap());
+    let darkest = self::to_rgb565(pixels.iter().min_by_key(luma).unwrap());
+    let (color0, color1) = (brightest.max(darkest), brightest.min(darkest));
+    let mut indices = 0u32;
+    if color0 != color1 {
+        let (end0, end1) = (self::from_rgb565(color0), self::from_rgb565(color1));
+        let palette = [
+            end0,
+            end1,
+            self::mix(&end0, &end1, 2, 1),
+            self::mix(&end0, &end1, 1, 2),
+        ];
+        for (i, pixel) in pixels.iter().enumerate() {
+            let index = self::nearest_color(&palette, pixel);
+            indices |= (index as u32) << (i * 2);
+        }
+    }
+    let mut block = [0; 8];
+    block[0..2].copy_from_slice(&color0.to_le_bytes());
+    block[2..4].copy_from_slice(&color1.to_le_bytes());
+    block[4..8].copy_from_slice(&indices.to_le_bytes());
+    block
+}
+
+/// Encodes an eight-value BC4 block, as used for the alpha of BC3.
+pub fn encode_bc4(values: &[u8; 16]) -> [u8; 8] {
+    let (max, min) = (*values.iter().max().unwrap(), *values.iter().min().unwrap());
+    let mut indices = 0u64;
+    if max != min {
+        let (max, min) = (max as i32, min as i32);
+        let palette: Vec<i32> = (0..8)
+            .map(|index| match index {
+                0 => max,
+                1 => min,
+                _ => ((8 - index) * max + (index - 1) * min) / 7,
+            })
+            .collect();
+        for (i, value) in values.iter().enumerate() {
+            let index = (0..8)
+                .min_by_key(|index| (palette[*index] - *value as i32).abs())
+                .unwrap();
+            indices |= (index as u64) << (i * 3);
+        }
+    }
+    let mut block = [max, min, 0, 0, 0, 0, 0, 0];
+    block[2..].copy_from_slice(&indices.to_le_bytes()[..6]);
+    block
+}
+
+/// Encodes a BC7 mode 6 block: one RGBA line with 7-bit endpoints, p-bits and 4-bit indices.
+pub fn encode_bc7(pixels: &Pixels) -> [u8; 16] {
+    let (end0, end1) = self::farthest_pair(pixels);
+    let (mut end0, mut end1) = (self::quantize_bc7(&end0), self::quantize_bc7(&end1));
+    let palette = BC7_WEIGHTS_4.map(|weight| self::interpolate(&end0, &end1, weight));
+    let mut indices = pixels.map(|pixel| self::nearest(&palette, &pixel) as u8);
+    // The index of the first pixel is stored without its most significant bit.
+    if indices[0] >= 8 {
+        std::mem::swap(&mut end0, &mut end1);
+        indices = indices.map(|index| 15 - index);
+    }
+    let mut bits = 1u128 << 6;
+    for channel in 0..4 {
+        for (i, end) in [end0, end1].iter().enumerate() {
+            bits |= ((end[channel] >> 1) as u128) << (7 + channel * 14 + i * 7);
+        }
+    }
+    bits |= ((end0[0] & 1) as u128) << 63 | ((end1[0] & 1) as u128) << 64;
+    bits |= (indices[0] as u128) << 65;
+    for (i, index) in indices.iter().enumerate().skip(1) {
+        bits |= (*index as u128) << (68 + (i - 1) * 4);
+    }
+    bits.to_le_bytes()
+}
+
+/// Encodes a single partition LDR RGBA ASTC 4x4 block with 8-bit endpoints and 2-bit weights.
+pub fn encode_astc(pixels: &Pixels) -> [u8; 16] {
+    let (mut end0, mut end1) = self::farthest_pair(pixels);
+    // A smaller sum of the second endpoint's color would enable blue contraction.
+    let sum = |end: &[u8; 4]| end[..3].iter().map(|value| *value as u32).sum::<u32>();
+    if sum(&end1) < sum(&end0) {
+        std::mem::swap(&mut end0, &mut end1);
+    }
+    let palette = ASTC_WEIGHTS_2.map(|weight| self::interpolate_astc(&end0, &end1, weight));
+    let mut bits = ASTC_BLOCK_MODE_4X4_WEIGHTS_2 | ASTC_CEM_LDR_RGBA_DIRECT << 13;
+    for channel in 0..4 {
+        bits |= (end0[channel] as u128) << (17 + channel * 16);
+        bits |= (end1[channel] as u128) << (25 + channel * 16);
+    }
+    // Weights are stored bit-reversed from the top of the block.
+    for (i, pixel) in pixels.iter().enumerate() {
+        let weight = self::nearest(&palette, pixel);
+        for bit in 0..2 {
+            bits |= (((weight >> bit) & 1) as u128) << (127 - i * 2 - bit);
+        }
+    }
+    bits.to_le_bytes()
+}
+
+fn to_rgb565(pixel: &[u8; 4]) -> u16 {
+    let [r, g, b, _] = pixel.map(|channel| channel as u16);
+    ((r * 31 + 127) / 255) << 11 | ((g * 63 + 127) / 255) << 5 | ((b * 31 + 127) / 255)
+}
+
+fn from_rgb565(color: u16) -> [i32; 3] {
+    let (r, g, b) = ((color >> 11) & 31, (color >> 5) & 63, color & 31);
+    [
+        (r << 3) | (r >> 2),
+        (g << 2) | (g >> 4),
+        (b << 3) | (b >> 2),
+    ]
+    .map(|channel| channel as i32)
+}
+
+fn mix(a: &[i32; 3], b: &[i32; 3], weight_a: i32, weight_b: i32) -> [i32; 3] {
+    [0, 1, 2].map(|i| (a[i] * weight_a + b[i] * weight_b) / (weight_a + weight_b))
+}
+
+fn nearest_color(palette: &[[i32; 3]; 4], pixel: &[u8; 4]) -> usize {
+    (0..4)
+        .min_by_key(|index| {
+            (0..3)
+                .map(|channel| (palette[*index][channel] - pixel[channel] as i32).pow(2))
+                .sum::<i32>()
+        })
+        .unwrap()
+}
+
+/// Returns the two pixels farthest apart.
+fn farthest_pair(pixels: &Pixels) -> ([u8; 4], [u8; 4]) {
+    let distance = |a: &[u8; 4], b: &[u8; 4]| {
+        (0..4)
+            .map(|channel| (a[channel] as i32 - b[channel] as i32).pow(2))
+            .sum::<i32>()
+    };
+    let mut pair = (pixels[0], pixels[0]);
+    for (i, a) in pixels.iter().enumerate() {
+        for b in pixels.iter().skip(i + 1) {
+            if distance(a, b) > distance(&pair.0, &pair.1) {
+                pair = (*a, *b);
+            }
+        }
+    }
+    pair
+}
+
+/// Rounds each channel to seven bits with a p-bit shared by all channels as the lowest bit.
+fn quantize_bc7(end: &[u8; 4]) -> [u8; 4] {
+    [0, 1]
+        .map(|p_bit| {
+            end.map(|value| (((value as i32 - p_bit + 1) / 2).clamp(0, 127) * 2 + p_bit) as u8)
+        })
+        .into_iter()
+        .min_by_key(|quantized| {
+            (0..4)
+                .map(|channel| (quantized[channel] as i32 - end[channel] as i32).pow(2))
+                .sum::<i32>()
+        })
+        .unwrap()
+}
+
+/// Interpolates between endpoints with a weight out of 64.
+fn interpolate(end0: &[u8; 4], end1: &[u8; 4], weight: u32) -> [u8; 4] {
+    [0, 1, 2, 3].map(|channel| {
+        let (value0, value1) = (end0[channel] as u32, end1[channel] as u32);
+        ((value0 * (64 - weight) + value1 * weight + 32) >> 6) as u8
+    })
+}
+
+/// Interpolates like ASTC, which expands endpoints to 16 bits before rounding.
+fn interpolate_astc(end0: &[u8; 4], end1: &[u8; 4], weight: u32) -> [u8; 4] {
+    [0, 1, 2, 3].map(|channel| {
+        let (value0, value1) = (end0[channel] as u32 * 257, end1[channel] as u32 * 257);
+        ((value0 * (64 - weight) + value1 * weight + 32) >> 14) as u8
+    })
+}
+
+fn nearest<const N: usize>(palette: &[[u8; 4]; N], pixel: &[u8; 4]) -> usize {
+    (0..N)
+        .min_by_key(|index| {
+            (0..4)
+                .map(|channel| (palette[*index][channel] as i32 - pixel[channel] as i32).pow(2))
+                .sum::<i32>()
+        })
+        .unwrap()
+}
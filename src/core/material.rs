@@ -1,6 +1,7 @@
-use std::{borrow::Cow, rc::Rc};
+use std::{borrow::Cow, fmt, rc::Rc};
 
 use anyhow::Result;
+use glm::{Mat3, Vec2, Vec3, Vec4};
 use web_sys::WebGl2RenderingContext;
 
 use crate::base::{
@@ -12,6 +13,7 @@ use crate::base::{
 };
 
 use super::{
+    custom_properties::CustomProperties,
     program::{Program, UpdateProgramUniforms, UpdateUniform},
     texture::Texture,
 };
@@ -30,16 +32,25 @@ pub trait GenericMaterial: UpdateProgramUniforms {
     fn double_sided(&self) -> bool {
         false
     }
+
+    fn pbr_properties(&self) -> PbrProperties {
+        PbrProperties::default()
+    }
+
+    fn needs_tangents(&self) -> bool {
+        false
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct Material {
-    #[allow(dead_code)]
     name: Option<String>,
     double_sided: bool,
     program: Program,
     generic_material: SharedRef<dyn GenericMaterial>,
     alpha_mode: AlphaMode,
+    custom_properties: CustomProperties,
+    defines: Vec<String>,
 }
 
 impl Material {
@@ -49,11 +60,35 @@ impl Material {
         double_sided: bool,
         generic_material: SharedRef<dyn GenericMaterial>,
         alpha_mode: AlphaMode,
+    ) -> Result<Rc<Self>> {
+        Self::initialize_with_defines(
+            context,
+            name,
+            double_sided,
+            generic_material,
+            alpha_mode,
+            &[],
+        )
+    }
+
+    pub fn initialize_with_defines(
+        context: &WebGl2RenderingContext,
+        name: Option<String>,
+        double_sided: bool,
+        generic_material: SharedRef<dyn GenericMaterial>,
+        alpha_mode: AlphaMode,
+        defines: &[&str],
     ) -> Result<Rc<Self>> {
         let program = Program::initialize(
             context,
-            &generic_material.borrow().vertex_shader(),
-            &generic_material.borrow().fragment_shader(),
+            &self::add_defines(
+                &self::expand_includes(generic_material.borrow().vertex_shader()),
+                defines,
+            ),
+            &self::add_defines(
+                &self::expand_includes(generic_material.borrow().fragment_shader()),
+                defines,
+            ),
         )?;
         Ok(Rc::new(Self {
             name,
@@ -61,6 +96,53 @@ impl Material {
             generic_material,
             program,
             alpha_mode,
+            custom_properties: CustomProperties::default(),
+            defines: defines.iter().map(|define| String::from(*define)).collect(),
+        }))
+    }
+
+    pub fn with_custom_properties(&self, custom_properties: CustomProperties) -> Rc<Self> {
+        Rc::new(Self {
+            custom_properties,
+            ..self.clone()
+        })
+    }
+
+    pub fn variant(&self, context: &WebGl2RenderingContext, defines: &[&str]) -> Result<Rc<Self>> {
+        let variant = Self::initialize_with_defines(
+            context,
+            self.name.clone(),
+            self.double_sided,
+            Rc::clone(&self.generic_material),
+            self.alpha_mode.clone(),
+            defines,
+        )?;
+        Ok(variant.with_custom_properties(self.custom_properties.clone()))
+    }
+
+    /// Keeps the vertex stage, with the same defines, and the alpha mode, and
+    /// replaces the fragment stage. The extra defines only apply to the new stage.
+    pub fn with_fragment_shader(
+        &self,
+        context: &WebGl2RenderingContext,
+        fragment_shader: Source<'_>,
+        fragment_defines: &[&str],
+    ) -> Result<Rc<Self>> {
+        let defines: Vec<_> = self.defines.iter().map(String::as_str).collect();
+        let program = Program::initialize(
+            context,
+            &self::add_defines(
+                &self::expand_includes(self.generic_material.borrow().vertex_shader()),
+                &defines,
+            ),
+            &self::add_defines(
+                &self::expand_includes(fragment_shader),
+                &[&defines[..], fragment_defines].concat(),
+            ),
+        )?;
+        Ok(Rc::new(Self {
+            program,
+            ..self.clone()
         }))
     }
 
@@ -110,6 +192,30 @@ impl Material {
         self.program.has_uniform(name)
     }
 
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn custom_properties(&self) -> &CustomProperties {
+        &self.custom_properties
+    }
+
+    pub fn double_sided(&self) -> bool {
+        self.double_sided
+    }
+
+    pub fn alpha_mode(&self) -> &AlphaMode {
+        &self.alpha_mode
+    }
+
+    pub fn pbr_properties(&self) -> PbrProperties {
+        self.generic_material.borrow().pbr_properties()
+    }
+
+    pub fn needs_tangents(&self) -> bool {
+        self.generic_material.borrow().needs_tangents()
+    }
+
     fn update_setting(context: &WebGl2RenderingContext, setting: u32, value: bool) {
         if value {
             context.enable(setting);
@@ -145,20 +251,163 @@ where
     }
 }
 
+const COLOR_SPACE_INCLUDE: &str = "#include <color_space>";
+
+fn expand_includes(source: Source<'_>) -> Source<'_> {
+    if source.contains(COLOR_SPACE_INCLUDE) {
+        source
+            .replace(COLOR_SPACE_INCLUDE, include_str!("color_space.glsl"))
+            .into()
+    } else {
+        source
+    }
+}
+
+fn add_defines<'a>(source: &'a str, defines: &[&str]) -> Source<'a> {
+    if defines.is_empty() {
+        return source.into();
+    }
+    let (version, body) = match source.split_once('\n') {
+        Some((first_line, rest)) if first_line.starts_with("#version") => (first_line, rest),
+        _ => ("", source),
+    };
+    let mut result = String::from(version);
+    result.push('\n');
+    for define in defines {
+        result.push_str(&format!("#define {}\n", define));
+    }
+    result.push_str(body);
+    result.into()
+}
+
 #[derive(Debug, Clone)]
 pub struct TextureRef {
     texture: Rc<Texture>,
     tex_coord: u32,
+    transform: TextureTransform,
 }
 
 impl TextureRef {
     pub fn new(texture: Rc<Texture>, tex_coord: u32) -> Self {
-        Self { texture, tex_coord }
+        Self {
+            texture,
+            tex_coord,
+            transform: TextureTransform::default(),
+        }
+    }
+
+    pub fn with_transform(mut self, transform: TextureTransform) -> Self {
+        self.transform = transform;
+        self
     }
 
     pub fn texture(&self) -> &Texture {
         &self.texture
     }
+
+    pub fn tex_coord(&self) -> u32 {
+        self.tex_coord
+    }
+
+    pub fn transform(&self) -> &TextureTransform {
+        &self.transform
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TextureTransform {
+    pub offset: Vec2,
+    pub rotation: f32,
+    pub scale: Vec2,
+}
+
+impl TextureTransform {
+    pub fn matrix(&self) -> Mat3 {
+        let (sin, cos) = self.rotation.sin_cos();
+        let translation = glm::mat3(
+            1.0,
+            0.0,
+            self.offset.x,
+            0.0,
+            1.0,
+            self.offset.y,
+            0.0,
+            0.0,
+            1.0,
+        );
+        let rotation = glm::mat3(cos, sin, 0.0, -sin, cos, 0.0, 0.0, 0.0, 1.0);
+        let scale = glm::mat3(
+            self.scale.x,
+            0.0,
+            0.0,
+            0.0,
+            self.scale.y,
+            0.0,
+            0.0,
+            0.0,
+            1.0,
+        );
+        translation * rotation * scale
+    }
+}
+
+impl Default for TextureTransform {
+    fn default() -> Self {
+        Self {
+            offset: Vec2::zeros(),
+            rotation: 0.0,
+            scale: glm::vec2(1.0, 1.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PbrProperties {
+    pub base_color_factor: Vec4,
+    pub base_color_texture: Option<TextureRef>,
+    pub metallic_factor: f32,
+    pub roughness_factor: f32,
+    pub metallic_roughness_texture: Option<TextureRef>,
+    pub emissive_factor: Vec3,
+    pub emissive_texture: Option<TextureRef>,
+    pub normal_texture: Option<TextureRef>,
+    pub normal_scale: f32,
+    pub unlit: bool,
+    pub clearcoat: Option<Clearcoat>,
+    pub transmission: Option<Transmission>,
+}
+
+impl Default for PbrProperties {
+    fn default() -> Self {
+        Self {
+            base_color_factor: glm::vec4(1.0, 1.0, 1.0, 1.0),
+            base_color_texture: None,
+            metallic_factor: 0.0,
+            roughness_factor: 1.0,
+            metallic_roughness_texture: None,
+            emissive_factor: Vec3::zeros(),
+            emissive_texture: None,
+            normal_texture: None,
+            normal_scale: 1.0,
+            unlit: false,
+            clearcoat: None,
+            transmission: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Clearcoat {
+    pub factor: f32,
+    pub texture: Option<TextureRef>,
+    pub roughness_factor: f32,
+    pub roughness_texture: Option<TextureRef>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Transmission {
+    pub factor: f32,
+    pub texture: Option<TextureRef>,
 }
 
 #[derive(Debug, Clone)]
@@ -207,6 +456,80 @@ impl UpdateProgramUniforms for AlphaMode {
     }
 }
 
+/// Uniform values set after a material's own, so that nodes sharing a material
+/// can differ in some of its parameters. The material sets its own values again
+/// after drawing; uniforms missing from a program are skipped.
+#[derive(Clone, Default)]
+pub struct MaterialOverrides {
+    values: Vec<(String, Rc<dyn UpdateUniform>)>,
+}
+
+impl MaterialOverrides {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with<T>(mut self, name: &str, value: T) -> Self
+    where
+        T: UpdateUniform + 'static,
+    {
+        self.set(name, value);
+        self
+    }
+
+    pub fn set<T>(&mut self, name: &str, value: T)
+    where
+        T: UpdateUniform + 'static,
+    {
+        let value: Rc<dyn UpdateUniform> = Rc::new(value);
+        match self.values.iter_mut().find(|(key, _)| key == name) {
+            Some((_, existing)) => *existing = value,
+            None => self.values.push((String::from(name), value)),
+        }
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.values.retain(|(key, _)| key != name);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.values.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Sets the values the program has a uniform for.
+    pub fn apply(&self, context: &WebGl2RenderingContext, program: &Program) {
+        for (name, value) in &self.values {
+            if program.has_uniform(name) {
+                value.update_uniform(context, name, program);
+            }
+        }
+    }
+}
+
+impl fmt::Debug for MaterialOverrides {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.values.iter().map(|(name, _)| name))
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct OutputEncoding {
+    pub linear: bool,
+}
+
+impl UpdateProgramUniforms for OutputEncoding {
+    fn update_program_uniforms(&self, context: &WebGl2RenderingContext, program: &Program) {
+        self.linear
+            .update_uniform_with_level(context, "u_LinearOutput", program, Level::Ignore);
+    }
+}
+
 #[derive(Debug, Clone)]
 struct DefaultGlobalUniformUpdater;
 
@@ -217,3 +540,23 @@ impl UpdateProgramUniforms for DefaultGlobalUniformUpdater {
 pub fn default_uniform_updater() -> Box<dyn UpdateProgramUniforms> {
     Box::new(DefaultGlobalUniformUpdater)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn texture_transform_works() {
+        // The example of KHR_texture_transform, which flips the texture vertically.
+        let transform = TextureTransform {
+            offset: glm::vec2(0.0, 1.0),
+            rotation: std::f32::consts::FRAC_PI_2,
+            scale: glm::vec2(0.5, 0.5),
+        };
+        let expected = glm::mat3(0.0, 0.5, 0.0, -0.5, 0.0, 1.0, 0.0, 0.0, 1.0);
+        assert!((transform.matrix() - expected).abs().max() < 1e-6);
+        let uv = transform.matrix() * glm::vec3(1.0, 0.0, 1.0);
+        assert!(glm::distance(&uv.xy(), &glm::vec2(0.0, 0.5)) < 1e-6);
+        assert_eq!(TextureTransform::default().matrix(), Mat3::identity());
+    }
+}
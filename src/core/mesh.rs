@@ -1,32 +1,57 @@
 use std::{collections::HashMap, rc::Rc};
 
 use anyhow::{anyhow, bail, Result};
+use glm::{Vec2, Vec3};
 use web_sys::{WebGl2RenderingContext, WebGlVertexArrayObject};
 
 use crate::base::{
+    color::ColorSpace,
+    convert::FromWithContext,
     gl,
-    util::{level::Level, validate},
+    math::{
+        aabb::Aabb,
+        bvh::Bvh,
+        ray::{Ray, TriangleHit},
+        tangent, triangle,
+    },
+    util::{cache::Cached, level::Level, validate},
 };
 
 use super::{
     accessor::Accessor,
     buffer_view::BufferView,
     camera::CameraMatrix,
+    custom_properties::CustomProperties,
     material::Material,
+    morph::{MorphTarget, MorphTargets},
     node::Node,
     program::{UpdateProgramUniforms, UpdateUniform},
+    skin::Skin,
 };
 
 #[derive(Debug, Clone)]
 pub struct Mesh {
     primitives: Vec<Primitive>,
-    #[allow(dead_code)]
+    weights: Vec<f32>,
     name: Option<String>,
+    custom_properties: CustomProperties,
 }
 
 impl Mesh {
-    pub fn new(primitives: Vec<Primitive>, name: Option<String>) -> Rc<Self> {
-        Rc::new(Self { primitives, name })
+    pub fn new(primitives: Vec<Primitive>, weights: Vec<f32>, name: Option<String>) -> Rc<Self> {
+        Rc::new(Self {
+            primitives,
+            weights,
+            name,
+            custom_properties: CustomProperties::default(),
+        })
+    }
+
+    pub fn with_custom_properties(&self, custom_properties: CustomProperties) -> Rc<Self> {
+        Rc::new(Self {
+            custom_properties,
+            ..self.clone()
+        })
     }
 
     pub fn primitive(
@@ -36,8 +61,8 @@ impl Mesh {
         material: Rc<Material>,
         mode: u32,
     ) -> Result<Rc<Self>> {
-        let primitive = Primitive::new(context, attributes, indices, material, mode)?;
-        Ok(Self::new(vec![primitive], None))
+        let primitive = Primitive::new(context, attributes, indices, material, mode, vec![])?;
+        Ok(Self::new(vec![primitive], vec![], None))
     }
 
     pub fn initialize_with_mode<T>(
@@ -49,13 +74,16 @@ impl Mesh {
     where
         T: AccessorProvider,
     {
-        Self::primitive(
+        let primitive = Primitive::new(
             context,
             provider.vertex_accessors(),
             provider.index_accessor(),
             material,
             mode,
-        )
+            vec![],
+        )?
+        .with_vertex_color_space(provider.vertex_color_space());
+        Ok(Self::new(vec![primitive], vec![], None))
     }
 
     pub fn initialize<T>(
@@ -95,8 +123,15 @@ impl Mesh {
         camera_matrix: &CameraMatrix,
         global_uniform_updater: &dyn UpdateProgramUniforms,
     ) {
+        let weights = node.weights().unwrap_or(&self.weights);
         for primitive in self.primitives.iter() {
-            primitive.render(context, node, camera_matrix, global_uniform_updater);
+            primitive.render(
+                context,
+                node,
+                weights,
+                camera_matrix,
+                global_uniform_updater,
+            );
         }
     }
 
@@ -107,9 +142,44 @@ impl Mesh {
         global_uniform_updater: &dyn UpdateProgramUniforms,
         material: &Material,
     ) {
+        let weights = node.weights().unwrap_or(&self.weights);
         for primitive in self.primitives.iter() {
             if primitive.is_triangle_based() {
-                primitive.render_with_material(context, node, global_uniform_updater, material)
+                primitive.render_with_material(
+                    context,
+                    node,
+                    weights,
+                    global_uniform_updater,
+                    material,
+                )
+            }
+        }
+    }
+
+    /// Renders the triangle-based primitives with materials derived from their own,
+    /// given the primitive index; primitives without a material are skipped.
+    pub fn render_with_materials<F>(
+        &self,
+        context: &WebGl2RenderingContext,
+        node: &Node,
+        global_uniform_updater: &dyn UpdateProgramUniforms,
+        mut material_for: F,
+    ) where
+        F: FnMut(usize, &Rc<Material>) -> Option<Rc<Material>>,
+    {
+        let weights = node.weights().unwrap_or(&self.weights);
+        for (index, primitive) in self.primitives.iter().enumerate() {
+            if !primitive.is_triangle_based() {
+                continue;
+            }
+            if let Some(material) = material_for(index, &primitive.material) {
+                primitive.render_with_material(
+                    context,
+                    node,
+                    weights,
+                    global_uniform_updater,
+                    &material,
+                )
             }
         }
     }
@@ -119,6 +189,62 @@ impl Mesh {
             .iter()
             .any(|primitive| primitive.has_uniform(name))
     }
+
+    pub fn primitives(&self) -> &[Primitive] {
+        &self.primitives
+    }
+
+    pub fn weights(&self) -> &[f32] {
+        &self.weights
+    }
+
+    pub fn bounds(&self) -> Option<Aabb> {
+        self.primitives
+            .iter()
+            .filter_map(Primitive::bounds)
+            .reduce(|a, b| a.union(&b))
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn custom_properties(&self) -> &CustomProperties {
+        &self.custom_properties
+    }
+
+    /// Intersects a ray given in the mesh's local space with the triangles of
+    /// the rest pose; skinning and morph targets are not taken into account.
+    pub fn raycast(&self, ray: &Ray) -> Vec<MeshHit> {
+        let Some(bounds) = self.bounds() else {
+            return vec![];
+        };
+        if ray
+            .intersect_sphere(&bounds.center(), bounds.size().norm() / 2.0)
+            .is_none()
+        {
+            return vec![];
+        }
+        self.primitives
+            .iter()
+            .enumerate()
+            .flat_map(|(index, primitive)| {
+                primitive.raycast(ray).into_iter().map(move |hit| MeshHit {
+                    primitive: index,
+                    ..hit
+                })
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MeshHit {
+    pub primitive: usize,
+    pub triangle: usize,
+    pub distance: f32,
+    pub barycentric: Vec3,
+    pub normal: Vec3,
 }
 
 #[derive(Debug, Clone)]
@@ -126,17 +252,39 @@ pub struct Primitive {
     vertex_array: WebGlVertexArrayObject,
     attributes: HashMap<String, Rc<Accessor>>,
     indices: Option<Rc<Accessor>>,
+    morph_targets: MorphTargets,
     material: Rc<Material>,
     mode: u32,
     vertex_count: i32,
+    vertex_color_space: ColorSpace,
+    triangles: Cached<Option<Triangles>>,
+}
+
+#[derive(Debug, Clone)]
+struct Triangles {
+    positions: Vec<Vec3>,
+    indices: Vec<[usize; 3]>,
+    bvh: Bvh,
+}
+
+impl Triangles {
+    fn triangle(&self, index: usize) -> [Vec3; 3] {
+        self.indices[index].map(|corner| self.positions[corner])
+    }
 }
 
 pub const POSITION_ATTRIBUTE: &str = "POSITION";
 pub const NORMAL_ATTRIBUTE: &str = "NORMAL";
 pub const TEXCOORD_0_ATTRIBUTE: &str = "TEXCOORD_0";
+pub const TANGENT_ATTRIBUTE: &str = "TANGENT";
 pub const COLOR_0_ATTRIBUTE: &str = "COLOR_0";
+pub const JOINTS_0_ATTRIBUTE: &str = "JOINTS_0";
+pub const WEIGHTS_0_ATTRIBUTE: &str = "WEIGHTS_0";
 
 impl Primitive {
+    const USE_SHADOW_UNIFORM: &'static str = "useShadow";
+    const LINEAR_VERTEX_COLORS_UNIFORM: &'static str = "u_LinearVertexColors";
+
     const MODES: [u32; 7] = [
         WebGl2RenderingContext::POINTS,
         WebGl2RenderingContext::LINES,
@@ -153,6 +301,7 @@ impl Primitive {
         indices: Option<Rc<Accessor>>,
         material: Rc<Material>,
         mode: u32,
+        targets: Vec<MorphTarget>,
     ) -> Result<Self> {
         validate::contains(&mode, &Self::MODES, |value| {
             anyhow!("Unknown mode: {}", value)
@@ -160,21 +309,33 @@ impl Primitive {
         validate::assert(attributes.contains_key(POSITION_ATTRIBUTE), || {
             anyhow!("Missing attribute {}", POSITION_ATTRIBUTE)
         })?;
+        let attributes =
+            Self::with_tangents(context, attributes, indices.as_deref(), &material, mode)?;
         let vertex_array = gl::create_vertex_array(context)?;
         let effective_mode = material.preferred_mode().unwrap_or(mode);
         let vertex_count = Self::get_vertex_count(&attributes)?;
+        let morph_targets = MorphTargets::initialize(context, targets, vertex_count)?;
         let me = Self {
             vertex_array,
             attributes,
             indices,
+            morph_targets,
             material,
             mode: effective_mode,
             vertex_count,
+            vertex_color_space: ColorSpace::Srgb,
+            triangles: Cached::new(),
         };
         me.set_vertex_array(context);
         Ok(me)
     }
 
+    /// glTF vertex colors are linear, while colors of the built-in geometries are sRGB.
+    pub fn with_vertex_color_space(mut self, color_space: ColorSpace) -> Self {
+        self.vertex_color_space = color_space;
+        self
+    }
+
     pub fn set_vertex_array(&self, context: &WebGl2RenderingContext) {
         self.set_vertex_array_with_level(context, Level::default())
     }
@@ -191,6 +352,7 @@ impl Primitive {
                 level.error(|| format!("Attribute '{}' not found", attribute));
             }
         }
+        self.morph_targets.set_vertex_attributes(context, program);
         if let Some(accessor) = &self.indices {
             accessor.set_indices(context);
         }
@@ -198,10 +360,59 @@ impl Primitive {
         BufferView::unbind(context, self.indices.is_some());
     }
 
+    pub fn attributes(&self) -> &HashMap<String, Rc<Accessor>> {
+        &self.attributes
+    }
+
+    pub fn indices(&self) -> Option<&Rc<Accessor>> {
+        self.indices.as_ref()
+    }
+
+    pub fn morph_targets(&self) -> &[MorphTarget] {
+        self.morph_targets.targets()
+    }
+
+    pub fn material(&self) -> &Rc<Material> {
+        &self.material
+    }
+
+    pub fn mode(&self) -> u32 {
+        self.mode
+    }
+
     pub fn has_attribute(&self, name: &str) -> bool {
         self.attributes.contains_key(name)
     }
 
+    pub fn bounds(&self) -> Option<Aabb> {
+        let accessor = self.attributes.get(POSITION_ATTRIBUTE)?;
+        match (accessor.min(), accessor.max()) {
+            (Some(min), Some(max))
+                if accessor.component_type == WebGl2RenderingContext::FLOAT
+                    && min.len() == 3
+                    && max.len() == 3 =>
+            {
+                Some(Aabb::new(
+                    Vec3::from_column_slice(min),
+                    Vec3::from_column_slice(max),
+                ))
+            }
+            _ => {
+                let positions: Vec<_> = accessor
+                    .read_f32()
+                    .ok()?
+                    .chunks(3)
+                    .map(Vec3::from_column_slice)
+                    .collect();
+                Aabb::from_points(&positions)
+            }
+        }
+    }
+
+    pub fn is_skinned(&self) -> bool {
+        self.has_attribute(JOINTS_0_ATTRIBUTE) && self.has_attribute(WEIGHTS_0_ATTRIBUTE)
+    }
+
     pub fn has_uniform(&self, name: &str) -> bool {
         self.material.has_uniform(name)
     }
@@ -222,44 +433,87 @@ impl Primitive {
         &self,
         context: &WebGl2RenderingContext,
         node: &Node,
+        weights: &[f32],
         camera_matrix: &CameraMatrix,
         global_uniform_updater: &dyn UpdateProgramUniforms,
     ) {
         self.material.use_program(context);
         camera_matrix.update_program_uniforms(context, self.material.program());
-        self.render_generic(context, node, global_uniform_updater, &self.material)
+        self.render_generic(
+            context,
+            node,
+            weights,
+            global_uniform_updater,
+            &self.material,
+        )
     }
 
     fn render_with_material(
         &self,
         context: &WebGl2RenderingContext,
         node: &Node,
+        weights: &[f32],
         global_uniform_updater: &dyn UpdateProgramUniforms,
         material: &Material,
     ) {
         material.use_program(context);
-        self.render_generic(context, node, global_uniform_updater, material)
+        self.render_generic(context, node, weights, global_uniform_updater, material)
     }
 
     fn render_generic(
         &self,
         context: &WebGl2RenderingContext,
         node: &Node,
+        weights: &[f32],
         global_uniform_updater: &dyn UpdateProgramUniforms,
         material: &Material,
     ) {
         let program = material.program();
         global_uniform_updater.update_program_uniforms(context, program);
         material.update(context);
+        node.update_material_overrides(context, program);
+        if !node.receives_shadow() {
+            false.update_uniform_with_level(
+                context,
+                Self::USE_SHADOW_UNIFORM,
+                program,
+                Level::Ignore,
+            );
+        }
         node.global_transform()
             .update_uniform(context, "u_ModelMatrix", program);
         node.normal_transform()
             .update_uniform(context, "u_NormalMatrix", program);
         self.has_attribute(COLOR_0_ATTRIBUTE)
             .update_uniform(context, "u_UseColor_0", program);
+        (self.vertex_color_space == ColorSpace::Linear).update_uniform_with_level(
+            context,
+            Self::LINEAR_VERTEX_COLORS_UNIFORM,
+            program,
+            Level::Ignore,
+        );
+        self.update_skin(context, node, material);
+        self.morph_targets
+            .update_uniforms(context, program, weights);
         self.draw(context);
     }
 
+    fn update_skin(&self, context: &WebGl2RenderingContext, node: &Node, material: &Material) {
+        let program = material.program();
+        if !program.has_uniform(Skin::JOINT_TEXTURE_UNIFORM) {
+            return;
+        }
+        match node.skin() {
+            Some(skin) if self.is_skinned() => skin.update_node_uniforms(context, node, program),
+            _ => false.update_uniform_with_level(
+                context,
+                Skin::USE_SKINNING_UNIFORM,
+                program,
+                Level::Ignore,
+            ),
+        }
+    }
+
     fn draw(&self, context: &WebGl2RenderingContext) {
         context.bind_vertex_array(Some(&self.vertex_array));
         if let Some(indices) = &self.indices {
@@ -270,6 +524,114 @@ impl Primitive {
         context.bind_vertex_array(None);
     }
 
+    /// Intersects a ray given in the mesh's local space with the triangles,
+    /// reporting the hits with a primitive index of zero.
+    pub fn raycast(&self, ray: &Ray) -> Vec<MeshHit> {
+        if self
+            .bounds()
+            .is_some_and(|bounds| ray.intersect_aabb(&bounds).is_none())
+        {
+            return vec![];
+        }
+        self.with_triangles(vec![], |triangles| {
+            triangles
+                .bvh
+                .query_ray(ray)
+                .into_iter()
+                .filter_map(|index| {
+                    let triangle = triangles.triangle(index);
+                    let TriangleHit {
+                        distance,
+                        barycentric,
+                    } = ray.intersect_triangle(&triangle)?;
+                    let normal = (triangle[1] - triangle[0]).cross(&(triangle[2] - triangle[0]));
+                    Some(MeshHit {
+                        primitive: 0,
+                        triangle: index,
+                        distance,
+                        barycentric,
+                        normal: normal.normalize(),
+                    })
+                })
+                .collect()
+        })
+    }
+
+    /// Returns the point on the triangles nearest to a point in local space.
+    pub fn closest_point(&self, point: &Vec3) -> Option<Vec3> {
+        self.with_triangles(None, |triangles| {
+            let (_, closest) = triangles.bvh.nearest(point, |index| {
+                Some(triangle::closest_point(&triangles.triangle(index), point))
+            })?;
+            Some(closest)
+        })
+    }
+
+    fn with_triangles<T, F>(&self, default: T, f: F) -> T
+    where
+        F: FnOnce(&Triangles) -> T,
+    {
+        self.triangles.with_cached_ref(
+            || self.read_triangles(),
+            |triangles| match triangles {
+                Some(triangles) => f(triangles),
+                None => default,
+            },
+        )
+    }
+
+    fn read_triangles(&self) -> Option<Triangles> {
+        let positions: Vec<_> = self
+            .attributes
+            .get(POSITION_ATTRIBUTE)?
+            .read_f32()
+            .ok()?
+            .chunks(3)
+            .map(Vec3::from_column_slice)
+            .collect();
+        let vertices: Vec<usize> = match &self.indices {
+            Some(indices) => indices
+                .read_indices()
+                .ok()?
+                .into_iter()
+                .map(|index| index as usize)
+                .collect(),
+            None => (0..positions.len()).collect(),
+        };
+        if vertices.iter().any(|vertex| *vertex >= positions.len()) {
+            return None;
+        }
+        let v = &vertices;
+        let indices = match self.mode {
+            WebGl2RenderingContext::TRIANGLES => v
+                .chunks_exact(3)
+                .map(|corners| [corners[0], corners[1], corners[2]])
+                .collect(),
+            WebGl2RenderingContext::TRIANGLE_STRIP => (2..v.len())
+                .map(|i| {
+                    if i.is_multiple_of(2) {
+                        [v[i - 2], v[i - 1], v[i]]
+                    } else {
+                        [v[i - 1], v[i - 2], v[i]]
+                    }
+                })
+                .collect(),
+            WebGl2RenderingContext::TRIANGLE_FAN => {
+                (2..v.len()).map(|i| [v[0], v[i - 1], v[i]]).collect()
+            }
+            _ => vec![],
+        };
+        let bounds: Vec<_> = indices
+            .iter()
+            .map(|corners: &[usize; 3]| triangle::bounds(&corners.map(|corner| positions[corner])))
+            .collect();
+        Some(Triangles {
+            bvh: Bvh::build(&bounds),
+            positions,
+            indices,
+        })
+    }
+
     fn is_triangle_based(&self) -> bool {
         self.mode == WebGl2RenderingContext::TRIANGLES
             || self.mode == WebGl2RenderingContext::TRIANGLE_STRIP
@@ -293,6 +655,51 @@ impl Primitive {
         }
     }
 
+    fn with_tangents(
+        context: &WebGl2RenderingContext,
+        mut attributes: HashMap<String, Rc<Accessor>>,
+        indices: Option<&Accessor>,
+        material: &Material,
+        mode: u32,
+    ) -> Result<HashMap<String, Rc<Accessor>>> {
+        if !material.needs_tangents()
+            || mode != WebGl2RenderingContext::TRIANGLES
+            || attributes.contains_key(TANGENT_ATTRIBUTE)
+        {
+            return Ok(attributes);
+        }
+        let (Some(normal), Some(texcoord_0)) = (
+            attributes.get(NORMAL_ATTRIBUTE),
+            attributes.get(TEXCOORD_0_ATTRIBUTE),
+        ) else {
+            return Ok(attributes);
+        };
+        let position: Vec<_> = attributes
+            .get(POSITION_ATTRIBUTE)
+            .ok_or_else(|| anyhow!("Missing {} attribute", POSITION_ATTRIBUTE))?
+            .read_f32()?
+            .chunks(3)
+            .map(Vec3::from_column_slice)
+            .collect();
+        let normal: Vec<_> = normal
+            .read_f32()?
+            .chunks(3)
+            .map(Vec3::from_column_slice)
+            .collect();
+        let texcoord_0: Vec<_> = texcoord_0
+            .read_f32()?
+            .chunks(2)
+            .map(Vec2::from_column_slice)
+            .collect();
+        let indices = indices.map(|indices| indices.read_indices()).transpose()?;
+        let tangent = tangent::generate(&position, &normal, &texcoord_0, indices.as_deref())?;
+        attributes.insert(
+            String::from(TANGENT_ATTRIBUTE),
+            Rc::new(Accessor::from_with_context(context, &tangent)?),
+        );
+        Ok(attributes)
+    }
+
     fn attribute_to_variable_name(attribute: &str) -> String {
         format!("a_{}", attribute.to_lowercase())
     }
@@ -304,4 +711,8 @@ pub trait AccessorProvider {
     fn index_accessor(&self) -> Option<Rc<Accessor>> {
         None
     }
+
+    fn vertex_color_space(&self) -> ColorSpace {
+        ColorSpace::Srgb
+    }
 }
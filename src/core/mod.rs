@@ -1,13 +1,21 @@
 pub mod accessor;
+pub mod animation;
+pub mod behaviour;
 pub mod buffer;
 pub mod buffer_view;
 pub mod camera;
+pub mod custom_properties;
 pub mod image;
+pub mod ktx2;
 pub mod material;
 pub mod mesh;
+pub mod morph;
 pub mod node;
+pub mod prefab;
 pub mod program;
 pub mod renderer;
 pub mod sampler;
 pub mod scene;
+pub mod scene_bvh;
+pub mod skin;
 pub mod texture;
@@ -0,0 +1,208 @@
+use std::{collections::HashMap, rc::Rc};
+
+use anyhow::{anyhow, Result};
+use js_sys::Float32Array;
+use web_sys::{WebGl2RenderingContext, WebGlTexture};
+
+use crate::base::{gl, util::validate};
+
+use super::{
+    accessor::Accessor,
+    mesh::{NORMAL_ATTRIBUTE, POSITION_ATTRIBUTE, TANGENT_ATTRIBUTE},
+    program::{Program, UpdateUniform},
+    texture::TextureUnit,
+};
+
+pub type MorphTarget = HashMap<String, Rc<Accessor>>;
+
+#[derive(Debug, Clone)]
+pub struct MorphTargets {
+    targets: Vec<MorphTarget>,
+    vertex_count: i32,
+    texture: Option<WebGlTexture>,
+}
+
+impl MorphTargets {
+    pub const DEFINE: &str = "MORPH_TARGETS";
+    pub const ATTRIBUTES: [&str; 3] = [POSITION_ATTRIBUTE, NORMAL_ATTRIBUTE, TANGENT_ATTRIBUTE];
+    pub const MAX_ATTRIBUTE_TARGETS: usize = 4;
+    pub const TEXTURE_WIDTH: i32 = 2048;
+    pub const WEIGHTS_UNIFORM: &str = "u_MorphWeights[0]";
+    pub const TEXTURE_UNIFORM: &str = "u_MorphTexture";
+    pub const VERTEX_COUNT_UNIFORM: &str = "u_MorphVertexCount";
+    pub const TEXTURE_UNIT: TextureUnit = TextureUnit(13);
+
+    pub fn initialize(
+        context: &WebGl2RenderingContext,
+        targets: Vec<MorphTarget>,
+        vertex_count: i32,
+    ) -> Result<Self> {
+        for accessor in targets.iter().flat_map(|target| target.values()) {
+            validate::assert(accessor.count == vertex_count, || {
+                anyhow!("Morph target accessor count differs from vertex count")
+            })?;
+        }
+        let texture = if Self::uses_texture(&targets) {
+            Some(Self::create_texture(context, &targets, vertex_count)?)
+        } else {
+            None
+        };
+        Ok(Self {
+            targets,
+            vertex_count,
+            texture,
+        })
+    }
+
+    pub fn defines(targets: &[MorphTarget]) -> Vec<String> {
+        if targets.is_empty() {
+            return vec![];
+        }
+        let attributes = Self::attributes(targets);
+        let mut defines = vec![
+            String::from(Self::DEFINE),
+            format!("MORPH_TARGET_COUNT {}", targets.len()),
+            format!("MORPH_ATTRIBUTE_COUNT {}", attributes.len()),
+        ];
+        defines.extend(
+            attributes
+                .iter()
+                .enumerate()
+                .map(|(slot, attribute)| format!("MORPH_{} {}", attribute, slot)),
+        );
+        if Self::uses_texture(targets) {
+            defines.push(String::from("MORPH_TEXTURE"));
+            defines.push(format!("MORPH_TEXTURE_WIDTH {}", Self::TEXTURE_WIDTH));
+        }
+        defines
+    }
+
+    pub fn targets(&self) -> &[MorphTarget] {
+        &self.targets
+    }
+
+    pub fn set_vertex_attributes(&self, context: &WebGl2RenderingContext, program: &Program) {
+        if self.texture.is_some() {
+            return;
+        }
+        for (index, target) in self.targets.iter().enumerate() {
+            for (attribute, accessor) in target.iter() {
+                let name = Self::attribute_to_variable_name(attribute, index);
+                if let Some(location) = program.get_attribute_location(&name) {
+                    accessor.set_vertex_attribute(context, *location);
+                }
+            }
+        }
+    }
+
+    pub fn update_uniforms(
+        &self,
+        context: &WebGl2RenderingContext,
+        program: &Program,
+        weights: &[f32],
+    ) {
+        if !program.has_uniform(Self::WEIGHTS_UNIFORM) {
+            return;
+        }
+        let mut weights = weights.to_vec();
+        weights.resize(self.targets.len(), 0.0);
+        weights.update_uniform(context, Self::WEIGHTS_UNIFORM, program);
+        if let Some(texture) = &self.texture {
+            Self::TEXTURE_UNIT.active_texture(context);
+            context.bind_texture(WebGl2RenderingContext::TEXTURE_2D, Some(texture));
+            Self::TEXTURE_UNIT.update_uniform(context, Self::TEXTURE_UNIFORM, program);
+            self.vertex_count
+                .update_uniform(context, Self::VERTEX_COUNT_UNIFORM, program);
+        }
+    }
+
+    fn attributes(targets: &[MorphTarget]) -> Vec<&'static str> {
+        Self::ATTRIBUTES
+            .into_iter()
+            .filter(|attribute| targets.iter().any(|target| target.contains_key(*attribute)))
+            .collect()
+    }
+
+    /// Tangent deltas are only read from the texture, as they would not fit in
+    /// the vertex attributes next to positions and normals.
+    fn uses_texture(targets: &[MorphTarget]) -> bool {
+        targets.len() > Self::MAX_ATTRIBUTE_TARGETS
+            || targets
+                .iter()
+                .any(|target| target.contains_key(TANGENT_ATTRIBUTE))
+    }
+
+    fn attribute_to_variable_name(attribute: &str, index: usize) -> String {
+        format!("a_morph_{}_{}", attribute.to_lowercase(), index)
+    }
+
+    fn create_texture(
+        context: &WebGl2RenderingContext,
+        targets: &[MorphTarget],
+        vertex_count: i32,
+    ) -> Result<WebGlTexture> {
+        let data = Self::pack_deltas(targets, vertex_count as usize)?;
+        let texel_count = data.len() as i32 / 4;
+        let height = (texel_count + Self::TEXTURE_WIDTH - 1) / Self::TEXTURE_WIDTH;
+        validate::assert(height <= Self::TEXTURE_WIDTH, || {
+            anyhow!("Too many morph target deltas: {}", texel_count)
+        })?;
+        let mut data = data;
+        data.resize((4 * height * Self::TEXTURE_WIDTH) as usize, 0.0);
+        let texture = gl::create_texture(context)?;
+        context.bind_texture(WebGl2RenderingContext::TEXTURE_2D, Some(&texture));
+        for (parameter, value) in [
+            (
+                WebGl2RenderingContext::TEXTURE_MIN_FILTER,
+                WebGl2RenderingContext::NEAREST,
+            ),
+            (
+                WebGl2RenderingContext::TEXTURE_MAG_FILTER,
+                WebGl2RenderingContext::NEAREST,
+            ),
+        ] {
+            context.tex_parameteri(WebGl2RenderingContext::TEXTURE_2D, parameter, value as i32);
+        }
+        let flip_y = gl::get_bool_parameter(context, WebGl2RenderingContext::UNPACK_FLIP_Y_WEBGL)
+            .unwrap_or_default();
+        context.pixel_storei(WebGl2RenderingContext::UNPACK_FLIP_Y_WEBGL, 0);
+        let result = context
+            .tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_array_buffer_view(
+                WebGl2RenderingContext::TEXTURE_2D,
+                0,
+                WebGl2RenderingContext::RGBA32F as i32,
+                Self::TEXTURE_WIDTH,
+                height,
+                0,
+                WebGl2RenderingContext::RGBA,
+                WebGl2RenderingContext::FLOAT,
+                Some(&Float32Array::from(data.as_slice())),
+            );
+        context.pixel_storei(
+            WebGl2RenderingContext::UNPACK_FLIP_Y_WEBGL,
+            i32::from(flip_y),
+        );
+        context.bind_texture(WebGl2RenderingContext::TEXTURE_2D, None);
+        result
+            .map(|_| texture)
+            .map_err(|error| anyhow!("Cannot upload morph target texture: {:#?}", error))
+    }
+
+    fn pack_deltas(targets: &[MorphTarget], vertex_count: usize) -> Result<Vec<f32>> {
+        let attributes = Self::attributes(targets);
+        let mut data = vec![0.0; 4 * vertex_count * attributes.len() * targets.len()];
+        for (index, target) in targets.iter().enumerate() {
+            for (slot, attribute) in attributes.iter().enumerate() {
+                if let Some(accessor) = target.get(*attribute) {
+                    let deltas = accessor.read_f32()?;
+                    let offset = (index * attributes.len() + slot) * vertex_count;
+                    for (vertex, delta) in deltas.chunks_exact(3).enumerate() {
+                        let texel = 4 * (offset + vertex);
+                        data[texel..texel + 3].copy_from_slice(delta);
+                    }
+                }
+            }
+        }
+        Ok(data)
+    }
+}
@@ -1,39 +1,66 @@
 use std::{
     cell::{Ref, RefCell},
-    collections::VecDeque,
-    ptr,
+    collections::HashMap,
+    mem, ptr,
     rc::{Rc, Weak},
 };
 
-use glm::{Mat3, Mat4, Vec3};
+use anyhow::{anyhow, Result};
+use glm::{Mat3, Mat4, Qua, Vec3};
 use web_sys::WebGl2RenderingContext;
 
 use crate::base::{
-    math::{angle::Angle, matrix},
+    input::KeyState,
+    math::{
+        angle::Angle,
+        euler::{Euler, EulerOrder},
+        matrix,
+        ray::Ray,
+    },
     util::{
         cache::Cached,
         shared_ref::{self, SharedRef, WeakRef},
+        validate,
     },
 };
 
 use super::{
+    behaviour::{Behaviour, BehaviourId},
     camera::{Camera, CameraMatrix},
-    mesh::Mesh,
-    program::UpdateProgramUniforms,
+    custom_properties::CustomProperties,
+    material::MaterialOverrides,
+    mesh::{Mesh, MeshHit},
+    prefab::Prefab,
+    program::{Program, UpdateProgramUniforms},
+    skin::Skin,
 };
 
+/// Copies made by `Node::deep_clone`, by the address of their original.
+type NodeClones = HashMap<*const RefCell<Node>, SharedRef<Node>>;
+
 #[derive(Debug, Clone)]
 pub struct Node {
     me: WeakRef<Node>,
     children: Vec<SharedRef<Node>>,
-    local_transform: Mat4,
+    translation: Vec3,
+    rotation: Qua<f32>,
+    scale: Vec3,
+    local_transform: Cached<Mat4>,
     camera: Option<SharedRef<Camera>>,
     mesh: Option<Rc<Mesh>>,
+    skin: Option<Rc<Skin>>,
+    weights: Option<Vec<f32>>,
     parent: WeakRef<Node>,
     global_transform: Cached<Mat4>,
     normal_transform: Cached<Mat4>,
-    #[allow(dead_code)]
     name: Option<String>,
+    custom_properties: CustomProperties,
+    visible: bool,
+    cast_shadow: bool,
+    receive_shadow: bool,
+    behaviours: Vec<(BehaviourId, Box<dyn Behaviour>)>,
+    material_overrides: MaterialOverrides,
+    prefab: Option<Rc<Prefab>>,
 }
 
 impl Node {
@@ -43,16 +70,29 @@ impl Node {
         camera: Option<SharedRef<Camera>>,
         name: Option<String>,
     ) -> SharedRef<Self> {
+        let (translation, rotation, scale) = matrix::decompose(&local_transform);
         let node = shared_ref::cyclic(|me| Self {
             me: Weak::clone(me),
             camera: camera.clone(),
             children: vec![],
-            local_transform,
+            translation,
+            rotation,
+            scale,
+            local_transform: Cached::new(),
             mesh,
+            skin: None,
+            weights: None,
             parent: shared_ref::weak(),
             global_transform: Cached::new(),
             normal_transform: Cached::new(),
             name,
+            custom_properties: CustomProperties::default(),
+            visible: true,
+            cast_shadow: true,
+            receive_shadow: true,
+            behaviours: vec![],
+            material_overrides: MaterialOverrides::default(),
+            prefab: None,
         });
         if let Some(camera) = camera {
             camera.borrow_mut().set_node(&node.borrow().me);
@@ -86,6 +126,9 @@ impl Node {
         camera_matrix: &CameraMatrix,
         global_uniform_updater: &dyn UpdateProgramUniforms,
     ) {
+        if !self.visible {
+            return;
+        }
         if let Some(mesh) = &self.mesh {
             mesh.render(context, self, camera_matrix, global_uniform_updater);
         }
@@ -97,23 +140,152 @@ impl Node {
     }
 
     pub fn add_child(&mut self, node: SharedRef<Node>) {
+        let previous_parent = node.borrow().parent.upgrade();
+        match previous_parent {
+            Some(parent) if ptr::eq(parent.as_ptr(), self) => self.remove_child(&node),
+            Some(parent) => parent.borrow_mut().remove_child(&node),
+            None => false,
+        };
         node.borrow_mut().set_parent(&self.me);
         self.children.push(node);
     }
 
+    pub fn remove_child(&mut self, node: &RefCell<Node>) -> bool {
+        let index = self
+            .children
+            .iter()
+            .position(|child| ptr::eq(child.as_ptr(), node.as_ptr()));
+        if let Some(index) = index {
+            self.children.remove(index);
+            node.borrow_mut().set_parent(&shared_ref::weak());
+        }
+        index.is_some()
+    }
+
+    pub fn detach(&mut self) {
+        if let Some(parent) = self.parent.upgrade() {
+            parent
+                .borrow_mut()
+                .children
+                .retain(|child| !ptr::eq(child.as_ptr(), self));
+            self.set_parent(&shared_ref::weak());
+        }
+    }
+
+    /// Moves the node under a new parent; with `keep_world_transform` the local
+    /// transform is adjusted so that the node stays where it is in the world.
+    pub fn reparent(&mut self, parent: &RefCell<Node>, keep_world_transform: bool) -> Result<()> {
+        validate::assert(!self.is_ancestor_of(parent), || {
+            anyhow!("Node cannot be reparented to itself or one of its descendants")
+        })?;
+        let world_transform = self.global_transform();
+        self.detach();
+        let mut parent = parent.borrow_mut();
+        parent.children.push(self.me.upgrade().unwrap());
+        self.set_parent(&parent.me);
+        if keep_world_transform {
+            let parent_inverse = parent
+                .global_transform()
+                .try_inverse()
+                .unwrap_or_else(matrix::identity);
+            self.set_local_transform(&(parent_inverse * world_transform));
+        }
+        Ok(())
+    }
+
+    /// Copies the whole subtree. Meshes are shared, cameras are duplicated so
+    /// that each camera keeps pointing back to a single node, and skins are
+    /// copied to use the copies of their joints that are part of the subtree.
+    pub fn deep_clone(&self) -> SharedRef<Node> {
+        let mut clones = HashMap::new();
+        let result = self.deep_clone_into(&mut clones);
+        Self::remap_skins(&clones);
+        result
+    }
+
+    /// Copies several subtrees at once, so that skins in one of them can use
+    /// the copies of joints in another.
+    pub fn deep_clone_all(nodes: &[SharedRef<Node>]) -> Vec<SharedRef<Node>> {
+        let mut clones = HashMap::new();
+        let result = nodes
+            .iter()
+            .map(|node| node.borrow().deep_clone_into(&mut clones))
+            .collect();
+        Self::remap_skins(&clones);
+        result
+    }
+
+    fn deep_clone_into(&self, clones: &mut NodeClones) -> SharedRef<Node> {
+        let camera = self
+            .camera
+            .as_ref()
+            .map(|camera| shared_ref::new(camera.borrow().clone()));
+        let result = Self::new(
+            matrix::identity(),
+            self.mesh.clone(),
+            camera,
+            self.name.clone(),
+        );
+        {
+            let mut node = result.borrow_mut();
+            node.translation = self.translation;
+            node.rotation = self.rotation;
+            node.scale = self.scale;
+            node.skin = self.skin.clone();
+            node.weights = self.weights.clone();
+            node.custom_properties = self.custom_properties.clone();
+            node.visible = self.visible;
+            node.cast_shadow = self.cast_shadow;
+            node.receive_shadow = self.receive_shadow;
+            node.material_overrides = self.material_overrides.clone();
+            node.prefab = self.prefab.clone();
+            for child in self.children.iter() {
+                node.add_child(child.borrow().deep_clone_into(clones));
+            }
+            for (id, behaviour) in self.behaviours.iter() {
+                let mut behaviour = behaviour.clone();
+                behaviour.on_attach(&mut node);
+                node.behaviours.push((*id, behaviour));
+            }
+        }
+        if let Some(prefab) = &self.prefab {
+            prefab.link(&result);
+        }
+        clones.insert(self.me.as_ptr(), Rc::clone(&result));
+        result
+    }
+
+    /// Skins shared by several copied nodes stay shared between their copies.
+    fn remap_skins(clones: &NodeClones) {
+        let mut skins: HashMap<*const Skin, Rc<Skin>> = HashMap::new();
+        for node in clones.values() {
+            let mut node = node.borrow_mut();
+            if let Some(skin) = node.skin.take() {
+                let remapped = skins.entry(Rc::as_ptr(&skin)).or_insert_with(|| {
+                    skin.remap(|joint| clones.get(&joint.as_ptr()).map(Rc::clone))
+                });
+                node.skin = Some(Rc::clone(remapped));
+            }
+        }
+    }
+
     pub fn global_transform(&self) -> Mat4 {
         self.global_transform.get(|| {
             if let Some(parent) = self.parent.upgrade() {
-                parent.borrow().global_transform() * self.local_transform
+                parent.borrow().global_transform() * self.local_transform()
             } else {
-                self.local_transform
+                self.local_transform()
             }
         })
     }
 
     pub fn normal_transform(&self) -> Mat4 {
-        self.normal_transform
-            .get(|| self.global_transform().try_inverse().unwrap().transpose())
+        self.normal_transform.get(|| {
+            self.global_transform()
+                .try_inverse()
+                .unwrap_or_else(matrix::identity)
+                .transpose()
+        })
     }
 
     pub fn is_ancestor_of(&self, node: &RefCell<Node>) -> bool {
@@ -139,16 +311,19 @@ impl Node {
     }
 
     pub fn apply_transform(&mut self, transform: &Mat4) {
-        self.local_transform *= transform;
-        self.reset_transforms();
+        let transform = self.local_transform() * transform;
+        self.set_local_transform(&transform);
     }
 
-    pub fn local_transform(&self) -> &Mat4 {
-        &self.local_transform
+    pub fn local_transform(&self) -> Mat4 {
+        self.local_transform
+            .get(|| matrix::compose(&self.translation, &self.rotation, &self.scale))
     }
 
+    /// Decomposes the transform into translation, rotation and scale; shear is lost.
     pub fn set_local_transform(&mut self, transform: &Mat4) {
-        self.local_transform.copy_from(transform);
+        (self.translation, self.rotation, self.scale) = matrix::decompose(transform);
+        self.reset_transforms();
     }
 
     pub fn world_position(&self) -> Vec3 {
@@ -156,34 +331,92 @@ impl Node {
     }
 
     pub fn position(&self) -> Vec3 {
-        let transform = self.local_transform;
-        glm::vec3(transform[(0, 3)], transform[(1, 3)], transform[(2, 3)])
+        self.translation
     }
 
     pub fn set_position(&mut self, position: &Vec3) {
-        self.local_transform[(0, 3)] = position[0];
-        self.local_transform[(1, 3)] = position[1];
-        self.local_transform[(2, 3)] = position[2];
+        self.translation = *position;
+        self.reset_transforms();
+    }
+
+    pub fn rotation(&self) -> Qua<f32> {
+        self.rotation
+    }
+
+    pub fn set_rotation(&mut self, rotation: &Qua<f32>) {
+        self.rotation = rotation.normalize();
+        self.reset_transforms();
+    }
+
+    pub fn scale(&self) -> Vec3 {
+        self.scale
+    }
+
+    pub fn set_scale(&mut self, scale: &Vec3) {
+        self.scale = *scale;
         self.reset_transforms();
     }
 
+    pub fn euler_angles(&self, order: EulerOrder) -> Euler {
+        Euler::from_quat(&self.rotation, order)
+    }
+
+    pub fn set_euler_angles(&mut self, euler: &Euler) {
+        self.set_rotation(&euler.to_quat())
+    }
+
+    /// Rotates around an axis given in the node's local space.
+    pub fn rotate(&mut self, axis: &Vec3, angle: Angle) {
+        let rotation = self.rotation * glm::quat_angle_axis(angle.to_radians(), &axis.normalize());
+        self.set_rotation(&rotation);
+    }
+
+    /// Rotates around an axis given in world space.
+    pub fn rotate_world(&mut self, axis: &Vec3, angle: Angle) {
+        let axis = glm::quat_rotate_vec3(&glm::quat_inverse(&self.parent_rotation()), axis);
+        let rotation = glm::quat_angle_axis(angle.to_radians(), &axis.normalize()) * self.rotation;
+        self.set_rotation(&rotation);
+    }
+
     pub fn rotate_x(&mut self, angle: Angle) {
-        let m = matrix::rotation_x(angle);
-        self.apply_transform(&m);
+        self.rotate(&glm::vec3(1.0, 0.0, 0.0), angle);
     }
 
     pub fn rotate_y(&mut self, angle: Angle) {
-        let m = matrix::rotation_y(angle);
-        self.apply_transform(&m);
+        self.rotate(&glm::vec3(0.0, 1.0, 0.0), angle);
+    }
+
+    pub fn rotate_z(&mut self, angle: Angle) {
+        self.rotate(&glm::vec3(0.0, 0.0, 1.0), angle);
+    }
+
+    /// Moves along the node's own axes.
+    pub fn translate(&mut self, offset: &Vec3) {
+        let position = self.translation + glm::quat_rotate_vec3(&self.rotation, offset);
+        self.set_position(&position);
+    }
+
+    /// Moves by an offset given in world space.
+    pub fn translate_world(&mut self, offset: &Vec3) {
+        let offset = match self.parent.upgrade() {
+            Some(parent) => {
+                let inverse = parent.borrow().global_transform().try_inverse();
+                inverse.map_or(*offset, |inverse| glm::mat4_to_mat3(&inverse) * offset)
+            }
+            None => *offset,
+        };
+        let position = self.translation + offset;
+        self.set_position(&position);
     }
 
     pub fn look_at(&mut self, target: &Vec3) {
-        self.local_transform = matrix::look_at(&self.world_position(), target);
-        self.reset_transforms()
+        let (_, rotation, _) = matrix::decompose(&matrix::look_at(&self.world_position(), target));
+        let rotation = glm::quat_inverse(&self.parent_rotation()) * rotation;
+        self.set_rotation(&rotation);
     }
 
     pub fn rotation_matrix(&self) -> Mat3 {
-        matrix::get_rotation_matrix(&self.local_transform)
+        glm::quat_to_mat3(&self.rotation)
     }
 
     pub fn direction(&self) -> Vec3 {
@@ -191,12 +424,24 @@ impl Node {
         self.rotation_matrix() * forward
     }
 
+    pub fn world_direction(&self) -> Vec3 {
+        glm::quat_rotate_vec3(&self.parent_rotation(), &self.direction())
+    }
+
     pub fn set_direction(&mut self, direction: &Vec3) {
-        let position = self.position();
-        let target_position = position + direction;
+        let target_position = self.world_position() + direction;
         self.look_at(&target_position);
     }
 
+    fn parent_rotation(&self) -> Qua<f32> {
+        self.parent
+            .upgrade()
+            .map_or_else(glm::quat_identity, |parent| {
+                let parent = parent.borrow();
+                parent.parent_rotation() * parent.rotation
+            })
+    }
+
     pub fn transfer_camera(&mut self, destination: &RefCell<Self>) {
         if let Some(camera) = self.camera.take() {
             destination.borrow_mut().attach_camera(camera)
@@ -208,6 +453,55 @@ impl Node {
         camera.borrow_mut().set_node(&self.me);
     }
 
+    pub fn add_behaviour<B>(&mut self, mut behaviour: B) -> BehaviourId
+    where
+        B: Behaviour + 'static,
+    {
+        let id = BehaviourId::next();
+        behaviour.on_attach(self);
+        self.behaviours.push((id, Box::new(behaviour)));
+        id
+    }
+
+    /// Returns whether the behaviour was found. Behaviours cannot remove
+    /// themselves while they are being updated.
+    pub fn remove_behaviour(&mut self, id: BehaviourId) -> bool {
+        let Some(index) = self.behaviours.iter().position(|(other, _)| *other == id) else {
+            return false;
+        };
+        let (_, mut behaviour) = self.behaviours.remove(index);
+        behaviour.on_detach(self);
+        true
+    }
+
+    pub fn clear_behaviours(&mut self) {
+        for (_, mut behaviour) in mem::take(&mut self.behaviours) {
+            behaviour.on_detach(self);
+        }
+    }
+
+    /// Runs the behaviours of the node and then those of its descendants, so
+    /// that children see the state their parents have for the current frame.
+    pub fn update(node: &RefCell<Node>, dt: f32, key_state: &KeyState) {
+        let children = {
+            let mut node = node.borrow_mut();
+            let mut behaviours = mem::take(&mut node.behaviours);
+            for (_, behaviour) in behaviours.iter_mut() {
+                behaviour.update(&mut node, dt, key_state);
+            }
+            behaviours.append(&mut node.behaviours);
+            node.behaviours = behaviours;
+            node.children.clone()
+        };
+        for child in children.iter() {
+            Self::update(child, dt, key_state);
+        }
+    }
+
+    pub fn weak_ref(&self) -> WeakRef<Node> {
+        Weak::clone(&self.me)
+    }
+
     pub fn camera(&self) -> Option<&SharedRef<Camera>> {
         self.camera.as_ref()
     }
@@ -216,24 +510,189 @@ impl Node {
         self.mesh.as_ref()
     }
 
-    pub fn descendants(&self) -> Vec<SharedRef<Node>> {
-        fn extend_queue(queue: &mut VecDeque<WeakRef<Node>>, nodes: &[SharedRef<Node>]) {
-            queue.extend(nodes.iter().map(Rc::downgrade));
+    pub fn skin(&self) -> Option<&Rc<Skin>> {
+        self.skin.as_ref()
+    }
+
+    pub fn set_skin(&mut self, skin: Rc<Skin>) {
+        self.skin = Some(skin);
+    }
+
+    pub fn weights(&self) -> Option<&[f32]> {
+        self.weights.as_deref()
+    }
+
+    pub fn set_weights(&mut self, weights: Vec<f32>) {
+        self.weights = Some(weights);
+    }
+
+    /// Material parameters that differ from the shared materials, for the meshes
+    /// of this node and its descendants.
+    pub fn material_overrides(&self) -> &MaterialOverrides {
+        &self.material_overrides
+    }
+
+    /// Applies the overrides of the ancestors and then those of this node, so
+    /// that the nearest one wins. Meshes update their material before each draw,
+    /// which resets the overridden uniforms for the next node.
+    pub fn update_material_overrides(&self, context: &WebGl2RenderingContext, program: &Program) {
+        let mut ancestors = vec![];
+        let mut parent = self.parent.upgrade();
+        while let Some(node) = parent {
+            parent = node.borrow().parent.upgrade();
+            ancestors.push(node);
+        }
+        for ancestor in ancestors.iter().rev() {
+            ancestor.borrow().material_overrides.apply(context, program);
         }
-        fn pop_front(queue: &mut VecDeque<WeakRef<Node>>) -> SharedRef<Node> {
-            queue.pop_front().unwrap().upgrade().unwrap()
+        self.material_overrides.apply(context, program);
+    }
+
+    /// Warns about names that no mesh of the subtree has a uniform for, as
+    /// they are most likely misspelled.
+    pub fn set_material_overrides(&mut self, material_overrides: MaterialOverrides) {
+        let mut meshes = vec![];
+        self.traverse(&mut |node: &Node| {
+            meshes.extend(node.mesh.clone());
+            Visit::Continue
+        });
+        if !meshes.is_empty() {
+            for name in material_overrides.names() {
+                if !meshes.iter().any(|mesh| mesh.has_uniform(name)) {
+                    warn!(
+                        "Material override {} does not match any uniform below {:?}",
+                        name, self.name
+                    );
+                }
+            }
+        }
+        self.material_overrides = material_overrides;
+    }
+
+    /// The prefab this node is an instance of.
+    pub fn prefab(&self) -> Option<&Rc<Prefab>> {
+        self.prefab.as_ref()
+    }
+
+    pub fn set_prefab(&mut self, prefab: Option<Rc<Prefab>>) {
+        self.prefab = prefab;
+    }
+
+    pub fn children(&self) -> &[SharedRef<Node>] {
+        &self.children
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Hidden nodes are skipped together with all of their descendants.
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+    }
+
+    pub fn casts_shadow(&self) -> bool {
+        self.cast_shadow
+    }
+
+    pub fn set_cast_shadow(&mut self, cast_shadow: bool) {
+        self.cast_shadow = cast_shadow;
+    }
+
+    /// Only has an effect on materials that use shadows.
+    pub fn receives_shadow(&self) -> bool {
+        self.receive_shadow
+    }
+
+    pub fn set_receive_shadow(&mut self, receive_shadow: bool) {
+        self.receive_shadow = receive_shadow;
+    }
+
+    pub fn custom_properties(&self) -> &CustomProperties {
+        &self.custom_properties
+    }
+
+    pub fn set_custom_properties(&mut self, custom_properties: CustomProperties) {
+        self.custom_properties = custom_properties;
+    }
+
+    pub fn traverse<V>(&self, visitor: &mut V) -> Visit
+    where
+        V: Visitor + ?Sized,
+    {
+        match visitor.enter(self) {
+            Visit::Stop => return Visit::Stop,
+            Visit::SkipChildren => {}
+            Visit::Continue => {
+                for child in self.children.iter() {
+                    if child.borrow().traverse(visitor) == Visit::Stop {
+                        return Visit::Stop;
+                    }
+                }
+            }
         }
-        let mut result = vec![];
-        let mut queue = VecDeque::new();
-        queue.push_back(self.me.clone());
-        while !queue.is_empty() {
-            let node = pop_front(&mut queue);
-            result.push(Rc::clone(&node));
-            extend_queue(&mut queue, &node.borrow().children);
+        match visitor.leave(self) {
+            Visit::Stop => Visit::Stop,
+            _ => Visit::Continue,
         }
+    }
+
+    /// Returns the first node of the subtree, in pre-order, that satisfies the predicate.
+    pub fn find<P>(&self, predicate: P) -> Option<SharedRef<Node>>
+    where
+        P: Fn(&Node) -> bool,
+    {
+        let mut result = None;
+        self.traverse(&mut |node: &Node| {
+            if predicate(node) {
+                result = node.me.upgrade();
+                Visit::Stop
+            } else {
+                Visit::Continue
+            }
+        });
         result
     }
 
+    /// Collects the intersections with the visible meshes of the subtree, unsorted.
+    pub fn raycast(&self, ray: &Ray) -> Vec<RaycastHit> {
+        let mut hits = vec![];
+        self.traverse(&mut |node: &Node| {
+            if !node.visible {
+                return Visit::SkipChildren;
+            }
+            let transform = node.global_transform();
+            if let (Some(mesh), Some(inverse)) = (&node.mesh, transform.try_inverse()) {
+                hits.extend(
+                    mesh.raycast(&ray.transform(&inverse))
+                        .into_iter()
+                        .map(|hit| RaycastHit::new(node.me.upgrade().unwrap(), ray, &inverse, hit)),
+                );
+            }
+            Visit::Continue
+        });
+        hits
+    }
+
+    /// Follows a slash-separated path of names, starting at one of the given nodes.
+    pub fn find_by_path_in(nodes: &[SharedRef<Node>], path: &str) -> Option<SharedRef<Node>> {
+        let find_named = |nodes: &[SharedRef<Node>], name: &str| {
+            nodes
+                .iter()
+                .find(|node| node.borrow().name() == Some(name))
+                .cloned()
+        };
+        let mut segments = path.split('/');
+        let first = find_named(nodes, segments.next()?)?;
+        segments.try_fold(first, |node, name| {
+            find_named(node.borrow().children(), name)
+        })
+    }
+
     pub fn max_by_key<K>(nodes: &[SharedRef<Node>], key: K) -> usize
     where
         K: Fn(Ref<Node>) -> usize,
@@ -251,12 +710,234 @@ impl Node {
     }
 
     fn reset_transforms(&self) {
-        self.normal_transform.clear();
-        let was_present = self.global_transform.clear();
-        if was_present {
-            self.children
-                .iter()
-                .for_each(|child| child.borrow().reset_transforms())
+        self.traverse(&mut |node: &Node| {
+            node.local_transform.clear();
+            node.normal_transform.clear();
+            let was_present = node.global_transform.clear();
+            if was_present {
+                Visit::Continue
+            } else {
+                Visit::SkipChildren
+            }
+        });
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RaycastHit {
+    pub node: SharedRef<Node>,
+    pub primitive: usize,
+    pub triangle: usize,
+    pub barycentric: Vec3,
+    pub distance: f32,
+    pub point: Vec3,
+    pub normal: Vec3,
+}
+
+impl RaycastHit {
+    /// Converts a hit of the mesh of `node` against the world-space `ray`
+    /// transformed by `inverse`, the inverse of the node's global transform.
+    pub fn new(node: SharedRef<Node>, ray: &Ray, inverse: &Mat4, hit: MeshHit) -> Self {
+        let normal_transform = glm::mat4_to_mat3(&inverse.transpose());
+        Self {
+            node,
+            primitive: hit.primitive,
+            triangle: hit.triangle,
+            barycentric: hit.barycentric,
+            distance: hit.distance,
+            point: ray.at(hit.distance),
+            normal: (normal_transform * hit.normal).normalize(),
+        }
+    }
+}
+
+/// Tells a traversal how to proceed after a node has been visited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Visit {
+    Continue,
+    SkipChildren,
+    Stop,
+}
+
+/// Visits nodes in pre-order through `enter` and in post-order through `leave`.
+pub trait Visitor {
+    fn enter(&mut self, node: &Node) -> Visit;
+
+    fn leave(&mut self, _node: &Node) -> Visit {
+        Visit::Continue
+    }
+}
+
+impl<F> Visitor for F
+where
+    F: FnMut(&Node) -> Visit,
+{
+    fn enter(&mut self, node: &Node) -> Visit {
+        self(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reparent_works() {
+        let first = Node::with_name("First");
+        let second = Node::with_name("Second");
+        let child = Node::with_name("Child");
+        first.borrow_mut().set_position(&glm::vec3(1.0, 0.0, 0.0));
+        second.borrow_mut().set_position(&glm::vec3(0.0, 2.0, 0.0));
+        first.borrow_mut().add_child(Rc::clone(&child));
+        assert_eq!(child.borrow().world_position(), glm::vec3(1.0, 0.0, 0.0));
+
+        child.borrow_mut().reparent(&second, true).unwrap();
+        assert!(first.borrow().children().is_empty());
+        assert_eq!(second.borrow().children().len(), 1);
+        assert_eq!(child.borrow().world_position(), glm::vec3(1.0, 0.0, 0.0));
+        assert_eq!(child.borrow().position(), glm::vec3(1.0, -2.0, 0.0));
+        assert!(second.borrow_mut().reparent(&child, false).is_err());
+
+        let copy = second.borrow().deep_clone();
+        assert_eq!(copy.borrow().children().len(), 1);
+        assert!(!Rc::ptr_eq(&copy.borrow().children()[0], &child));
+
+        first.borrow_mut().add_child(Rc::clone(&child));
+        assert!(second.borrow().children().is_empty());
+        child.borrow_mut().detach();
+        assert!(first.borrow().children().is_empty());
+        assert_eq!(child.borrow().world_position(), glm::vec3(1.0, -2.0, 0.0));
+    }
+
+    #[test]
+    fn look_at_works() {
+        let parent = Node::with_name("Parent");
+        let child = Node::with_name("Child");
+        parent.borrow_mut().set_position(&glm::vec3(1.0, 0.0, 0.0));
+        parent
+            .borrow_mut()
+            .set_rotation(&glm::quat_angle_axis(1.0, &glm::vec3(0.0, 1.0, 0.0)));
+        child.borrow_mut().set_position(&glm::vec3(0.0, 0.0, 2.0));
+        parent.borrow_mut().add_child(Rc::clone(&child));
+
+        let target = glm::vec3(-3.0, 1.0, 5.0);
+        child.borrow_mut().look_at(&target);
+        let expected = (target - child.borrow().world_position()).normalize();
+        assert!((child.borrow().world_direction() - expected).norm() < 1e-5);
+
+        let direction = glm::vec3(0.0, 0.0, 1.0);
+        child.borrow_mut().set_direction(&direction);
+        assert!((child.borrow().world_direction() - direction).norm() < 1e-5);
+
+        let flat = Node::with_name("Flat");
+        flat.borrow_mut().set_scale(&glm::vec3(0.0, 1.0, 1.0));
+        assert_eq!(flat.borrow().normal_transform(), matrix::identity());
+    }
+
+    #[test]
+    fn find_works() {
+        let car = Node::with_name("Car");
+        let wheel = Node::with_name("Wheel.FL");
+        let nut = Node::with_name("Nut");
+        wheel.borrow_mut().add_child(Rc::clone(&nut));
+        car.borrow_mut().add_child(Rc::clone(&wheel));
+        let roots = [Rc::clone(&car)];
+
+        let found = Node::find_by_path_in(&roots, "Car/Wheel.FL/Nut").unwrap();
+        assert!(Rc::ptr_eq(&found, &nut));
+        assert!(Node::find_by_path_in(&roots, "Car/Nut").is_none());
+        let found = car.borrow().find(|node| node.name() == Some("Nut"));
+        let found = found.unwrap();
+        assert!(Rc::ptr_eq(&found, &nut));
+
+        let mut entered = vec![];
+        let mut left = vec![];
+        struct Recorder<'a>(&'a mut Vec<String>, &'a mut Vec<String>);
+        impl Visitor for Recorder<'_> {
+            fn enter(&mut self, node: &Node) -> Visit {
+                self.0.push(node.name().unwrap().into());
+                Visit::Continue
+            }
+            fn leave(&mut self, node: &Node) -> Visit {
+                self.1.push(node.name().unwrap().into());
+                if node.name() == Some("Wheel.FL") {
+                    Visit::Stop
+                } else {
+                    Visit::Continue
+                }
+            }
+        }
+        let result = car
+            .borrow()
+            .traverse(&mut Recorder(&mut entered, &mut left));
+        assert_eq!(result, Visit::Stop);
+        assert_eq!(entered, ["Car", "Wheel.FL", "Nut"]);
+        assert_eq!(left, ["Nut", "Wheel.FL"]);
+    }
+
+    #[test]
+    fn update_works() {
+        #[derive(Debug, Clone)]
+        struct Recorder(SharedRef<Vec<String>>);
+        impl Behaviour for Recorder {
+            fn on_attach(&mut self, node: &mut Node) {
+                self.0
+                    .borrow_mut()
+                    .push(format!("+{}", node.name().unwrap()));
+            }
+            fn update(&mut self, node: &mut Node, dt: f32, _key_state: &KeyState) {
+                node.translate(&glm::vec3(dt, 0.0, 0.0));
+                let x = node.world_position().x;
+                self.0
+                    .borrow_mut()
+                    .push(format!("{}@{}", node.name().unwrap(), x));
+            }
+            fn on_detach(&mut self, node: &mut Node) {
+                self.0
+                    .borrow_mut()
+                    .push(format!("-{}", node.name().unwrap()));
+            }
+        }
+
+        let log = shared_ref::new(vec![]);
+        let parent = Node::with_name("Parent");
+        let child = Node::with_name("Child");
+        parent.borrow_mut().add_child(Rc::clone(&child));
+        child.borrow_mut().add_behaviour(Recorder(Rc::clone(&log)));
+        let id = parent.borrow_mut().add_behaviour(Recorder(Rc::clone(&log)));
+        Node::update(&parent, 1.0, &KeyState::new());
+        child.borrow_mut().clear_behaviours();
+        Node::update(&parent, 1.0, &KeyState::new());
+        assert_eq!(
+            *log.borrow(),
+            ["+Child", "+Parent", "Parent@1", "Child@2", "-Child", "Parent@2"]
+        );
+
+        log.borrow_mut().clear();
+        let copy = parent.borrow().deep_clone();
+        assert!(copy.borrow_mut().remove_behaviour(id));
+        assert!(!copy.borrow_mut().remove_behaviour(id));
+        assert!(parent.borrow_mut().remove_behaviour(id));
+        assert_eq!(*log.borrow(), ["+Parent", "-Parent", "-Parent"]);
+    }
+
+    #[test]
+    fn flags_works() {
+        let parent = Node::with_name("Parent");
+        let child = Node::with_name("Child");
+        parent.borrow_mut().add_child(Rc::clone(&child));
+        {
+            let child = child.borrow();
+            assert!(child.is_visible() && child.casts_shadow() && child.receives_shadow());
         }
+        child.borrow_mut().set_visible(false);
+        child.borrow_mut().set_cast_shadow(false);
+        parent.borrow_mut().set_receive_shadow(false);
+
+        let copy = parent.borrow().deep_clone();
+        let copy = copy.borrow();
+        assert!(copy.is_visible() && copy.casts_shadow() && !copy.receives_shadow());
+        let child = copy.children()[0].borrow();
+        assert!(!child.is_visible() && !child.casts_shadow() && child.receives_shadow());
     }
 }
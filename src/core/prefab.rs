@@ -0,0 +1,173 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::base::{
+    math::matrix,
+    util::shared_ref::{SharedRef, WeakRef},
+};
+
+use super::{material::MaterialOverrides, node::Node, scene::Scene};
+
+/// A template subtree instantiated by copying its hierarchy while sharing
+/// meshes and materials; skins are copied to use the copied joints. Each
+/// instance is a node holding the copies as children and linking back to the
+/// prefab, so that edits of the template can be pushed to all instances.
+#[derive(Debug)]
+pub struct Prefab {
+    name: Option<String>,
+    nodes: Vec<SharedRef<Node>>,
+    instances: RefCell<Vec<WeakRef<Node>>>,
+}
+
+impl Prefab {
+    /// Copies the nodes, so that the template does not change with them.
+    pub fn new(name: Option<String>, nodes: &[SharedRef<Node>]) -> Rc<Self> {
+        Rc::new(Self {
+            name,
+            nodes: Node::deep_clone_all(nodes),
+            instances: RefCell::new(vec![]),
+        })
+    }
+
+    pub fn from_scene(name: Option<String>, scene: &Scene) -> Rc<Self> {
+        Self::new(name, scene.nodes())
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// The template; changes reach existing instances through [`Prefab::propagate`].
+    pub fn nodes(&self) -> &[SharedRef<Node>] {
+        &self.nodes
+    }
+
+    pub fn instantiate(self: &Rc<Self>) -> SharedRef<Node> {
+        self.instantiate_with(MaterialOverrides::default())
+    }
+
+    /// The overrides apply to all meshes of the instance and can be changed later
+    /// on the returned node.
+    pub fn instantiate_with(self: &Rc<Self>, overrides: MaterialOverrides) -> SharedRef<Node> {
+        let instance = Node::new(matrix::identity(), None, None, self.name.clone());
+        {
+            let mut node = instance.borrow_mut();
+            node.set_prefab(Some(Rc::clone(self)));
+            self.populate(&mut node);
+            node.set_material_overrides(overrides);
+        }
+        self.link(&instance);
+        instance
+    }
+
+    /// Registers a node as an instance; done by `Node::deep_clone` for copies of instances.
+    pub fn link(&self, instance: &SharedRef<Node>) {
+        self.instances.borrow_mut().push(Rc::downgrade(instance));
+    }
+
+    pub fn instances(&self) -> Vec<SharedRef<Node>> {
+        let mut instances = self.instances.borrow_mut();
+        instances.retain(|instance| instance.strong_count() > 0);
+        instances
+            .iter()
+            .filter_map(|instance| instance.upgrade())
+            .collect()
+    }
+
+    /// Replaces the content of the instances with fresh copies of the template,
+    /// keeping the transforms and overrides of the instance nodes themselves.
+    /// Changes made to the copies inside an instance are lost. Returns the
+    /// number of instances updated.
+    pub fn propagate(&self) -> usize {
+        let instances = self.instances();
+        for instance in instances.iter() {
+            self.populate(&mut instance.borrow_mut());
+        }
+        instances.len()
+    }
+
+    fn populate(&self, instance: &mut Node) {
+        for child in instance.children().to_vec() {
+            instance.remove_child(&child);
+        }
+        for node in Node::deep_clone_all(&self.nodes) {
+            instance.add_child(node);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::core::skin::Skin;
+
+    #[test]
+    fn prefab_works() {
+        let body = Node::with_name("Body");
+        let wheel = Node::with_name("Wheel");
+        wheel.borrow_mut().set_position(&glm::vec3(1.0, 0.0, 0.0));
+        body.borrow_mut().add_child(wheel);
+        let prefab = Prefab::new(Some(String::from("Car")), &[body]);
+
+        let first = prefab.instantiate();
+        let second = prefab.instantiate_with(
+            MaterialOverrides::new().with("baseColor", glm::vec4(1.0, 0.0, 0.0, 1.0)),
+        );
+        second.borrow_mut().set_position(&glm::vec3(0.0, 0.0, 5.0));
+        assert_eq!(first.borrow().name(), Some("Car"));
+        assert!(Rc::ptr_eq(first.borrow().prefab().unwrap(), &prefab));
+        assert!(!second.borrow().material_overrides().is_empty());
+        let copy = Node::find_by_path_in(&[Rc::clone(&second)], "Car/Body/Wheel").unwrap();
+        assert!(!Rc::ptr_eq(
+            &copy,
+            &prefab.nodes()[0].borrow().children()[0]
+        ));
+        assert_eq!(copy.borrow().world_position(), glm::vec3(1.0, 0.0, 5.0));
+
+        let third = second.borrow().deep_clone();
+        assert_eq!(prefab.instances().len(), 3);
+        drop(first);
+        assert_eq!(prefab.instances().len(), 2);
+
+        let template = Rc::clone(&prefab.nodes()[0].borrow().children()[0]);
+        template
+            .borrow_mut()
+            .set_position(&glm::vec3(2.0, 0.0, 0.0));
+        assert_eq!(prefab.propagate(), 2);
+        let copy = Node::find_by_path_in(&[Rc::clone(&third)], "Car/Body/Wheel").unwrap();
+        assert_eq!(copy.borrow().world_position(), glm::vec3(2.0, 0.0, 5.0));
+        assert_eq!(second.borrow().position(), glm::vec3(0.0, 0.0, 5.0));
+        assert!(!second.borrow().material_overrides().is_empty());
+    }
+
+    #[test]
+    fn prefab_skin_works() {
+        let skeleton = Node::with_name("Skeleton");
+        let joint = Node::with_name("Joint");
+        skeleton.borrow_mut().add_child(Rc::clone(&joint));
+        let body = Node::with_name("Body");
+        let skin = Skin::new(&[Rc::clone(&joint)], None, Some(&skeleton), None).unwrap();
+        body.borrow_mut().set_skin(skin);
+        let prefab = Prefab::new(None, &[skeleton, Rc::clone(&body)]);
+
+        let instance = prefab.instantiate();
+        let instance = instance.borrow();
+        let [skeleton, body] = instance.children() else {
+            panic!("Instance should have two children");
+        };
+        let body = body.borrow();
+        let skin = body.skin().unwrap();
+        let joint = Rc::clone(&skeleton.borrow().children()[0]);
+        assert!(Rc::ptr_eq(&skin.joints()[0].upgrade().unwrap(), &joint));
+        assert!(Rc::ptr_eq(
+            &skin.skeleton().unwrap().upgrade().unwrap(),
+            skeleton
+        ));
+        let template = prefab.nodes()[1].borrow();
+        assert!(!Rc::ptr_eq(template.skin().unwrap(), skin));
+        assert!(!Rc::ptr_eq(
+            &template.skin().unwrap().joints()[0].upgrade().unwrap(),
+            &joint
+        ));
+    }
+}
@@ -1,7 +1,7 @@
 use std::{collections::HashMap, fmt::Debug};
 
 use anyhow::Result;
-use glm::{Mat4, Vec2, Vec3, Vec4};
+use glm::{Mat3, Mat4, Vec2, Vec3, Vec4};
 use web_sys::{WebGl2RenderingContext, WebGlProgram, WebGlUniformLocation};
 
 use crate::base::{convert::FromWithContext, gl, util::level::Level};
@@ -106,6 +106,20 @@ impl UpdateUniformValue for i32 {
     }
 }
 
+impl UpdateUniformValue for u32 {
+    fn update_uniform_value(
+        &self,
+        context: &WebGl2RenderingContext,
+        location: Option<&WebGlUniformLocation>,
+    ) {
+        context.uniform1ui(location, *self)
+    }
+
+    fn value_type(&self) -> u32 {
+        WebGl2RenderingContext::UNSIGNED_INT
+    }
+}
+
 impl UpdateUniformValue for f32 {
     fn update_uniform_value(
         &self,
@@ -120,6 +134,20 @@ impl UpdateUniformValue for f32 {
     }
 }
 
+impl UpdateUniformValue for Vec<f32> {
+    fn update_uniform_value(
+        &self,
+        context: &WebGl2RenderingContext,
+        location: Option<&WebGlUniformLocation>,
+    ) {
+        context.uniform1fv_with_f32_array(location, self)
+    }
+
+    fn value_type(&self) -> u32 {
+        WebGl2RenderingContext::FLOAT
+    }
+}
+
 impl UpdateUniformValue for Vec2 {
     fn update_uniform_value(
         &self,
@@ -162,6 +190,20 @@ impl UpdateUniformValue for Vec4 {
     }
 }
 
+impl UpdateUniformValue for Mat3 {
+    fn update_uniform_value(
+        &self,
+        context: &WebGl2RenderingContext,
+        location: Option<&WebGlUniformLocation>,
+    ) {
+        context.uniform_matrix3fv_with_f32_array(location, false, self.as_slice());
+    }
+
+    fn value_type(&self) -> u32 {
+        WebGl2RenderingContext::FLOAT_MAT3
+    }
+}
+
 impl UpdateUniformValue for Mat4 {
     fn update_uniform_value(
         &self,
@@ -48,20 +48,29 @@ impl Renderer {
         scene: &Scene,
         camera: &RefCell<Camera>,
     ) {
-        context.viewport(
-            0,
-            0,
+        let (width, height) = (
             context.drawing_buffer_width(),
             context.drawing_buffer_height(),
         );
+        context.viewport(0, 0, width, height);
         gl::set_clear_color(context, &self.properties.clear_color);
         context.clear(
             WebGl2RenderingContext::COLOR_BUFFER_BIT | WebGl2RenderingContext::DEPTH_BUFFER_BIT,
         );
         let canvas = web::get_canvas(context).expect("Canvas not found");
-        camera
-            .borrow_mut()
-            .set_aspect_ratio(canvas.client_width() as f32 / canvas.client_height() as f32);
+        let canvas_aspect_ratio = canvas.client_width() as f32 / canvas.client_height() as f32;
+        let fixed_aspect_ratio = camera.borrow().fixed_aspect_ratio();
+        match fixed_aspect_ratio {
+            Some(aspect_ratio) if aspect_ratio > canvas_aspect_ratio => {
+                let viewport_height = (height as f32 * canvas_aspect_ratio / aspect_ratio) as i32;
+                context.viewport(0, (height - viewport_height) / 2, width, viewport_height);
+            }
+            Some(aspect_ratio) => {
+                let viewport_width = (width as f32 * aspect_ratio / canvas_aspect_ratio) as i32;
+                context.viewport((width - viewport_width) / 2, 0, viewport_width, height);
+            }
+            None => camera.borrow_mut().set_aspect_ratio(canvas_aspect_ratio),
+        }
         scene.render(context, camera, self.global_uniform_updater.as_ref())
     }
 }
@@ -1,7 +1,11 @@
+use std::{collections::HashMap, rc::Rc};
+
 use anyhow::{anyhow, Result};
-use web_sys::WebGl2RenderingContext;
+use web_sys::{WebGl2RenderingContext, WebGlSampler};
+
+use crate::base::util::{cache::Cached, validate};
 
-use crate::base::util::validate;
+use super::texture::TextureUnit;
 
 #[derive(Debug, Clone)]
 pub struct Sampler {
@@ -9,6 +13,8 @@ pub struct Sampler {
     min_filter: i32,
     wrap_s: i32,
     wrap_t: i32,
+    anisotropy: f32,
+    gl_sampler: Cached<Option<WebGlSampler>>,
 }
 
 impl Default for Sampler {
@@ -18,6 +24,8 @@ impl Default for Sampler {
             min_filter: Self::DEFAULT_MIN_FILTER,
             wrap_s: WebGl2RenderingContext::REPEAT as i32,
             wrap_t: WebGl2RenderingContext::REPEAT as i32,
+            anisotropy: 1.0,
+            gl_sampler: Cached::new(),
         }
     }
 }
@@ -46,6 +54,10 @@ impl Sampler {
     const DEFAULT_MAG_FILTER: i32 = WebGl2RenderingContext::LINEAR as i32;
     const DEFAULT_MIN_FILTER: i32 = WebGl2RenderingContext::LINEAR_MIPMAP_LINEAR as i32;
 
+    const ANISOTROPIC_EXTENSION: &str = "EXT_texture_filter_anisotropic";
+    const TEXTURE_MAX_ANISOTROPY: u32 = 0x84FE;
+    const MAX_TEXTURE_MAX_ANISOTROPY: u32 = 0x84FF;
+
     pub fn new(
         mag_filter: Option<i32>,
         min_filter: Option<i32>,
@@ -73,30 +85,76 @@ impl Sampler {
             min_filter: min_filter.unwrap_or(Self::DEFAULT_MIN_FILTER),
             wrap_s,
             wrap_t,
+            ..Default::default()
         })
     }
 
-    pub fn set_texture_parameters(&self, context: &WebGl2RenderingContext) {
-        context.tex_parameteri(
-            WebGl2RenderingContext::TEXTURE_2D,
-            WebGl2RenderingContext::TEXTURE_MAG_FILTER,
-            self.mag_filter,
-        );
-        context.tex_parameteri(
-            WebGl2RenderingContext::TEXTURE_2D,
-            WebGl2RenderingContext::TEXTURE_MIN_FILTER,
-            self.min_filter,
-        );
-        context.tex_parameteri(
-            WebGl2RenderingContext::TEXTURE_2D,
-            WebGl2RenderingContext::TEXTURE_WRAP_S,
-            self.wrap_s,
-        );
-        context.tex_parameteri(
-            WebGl2RenderingContext::TEXTURE_2D,
-            WebGl2RenderingContext::TEXTURE_WRAP_T,
-            self.wrap_t,
-        );
+    pub fn with_anisotropy(mut self, anisotropy: f32) -> Self {
+        self.anisotropy = anisotropy.max(1.0);
+        self
+    }
+
+    pub fn mag_filter(&self) -> i32 {
+        self.mag_filter
+    }
+
+    pub fn min_filter(&self) -> i32 {
+        self.min_filter
+    }
+
+    pub fn wrap_s(&self) -> i32 {
+        self.wrap_s
+    }
+
+    pub fn wrap_t(&self) -> i32 {
+        self.wrap_t
+    }
+
+    pub fn bind(&self, context: &WebGl2RenderingContext, unit: TextureUnit) {
+        self.gl_sampler.with_cached_ref(
+            || self.create_gl_sampler(context),
+            |gl_sampler| context.bind_sampler(unit.0 as u32, gl_sampler.as_ref()),
+        )
+    }
+
+    fn create_gl_sampler(&self, context: &WebGl2RenderingContext) -> Option<WebGlSampler> {
+        let gl_sampler = context.create_sampler()?;
+        let parameters = [
+            (WebGl2RenderingContext::TEXTURE_MAG_FILTER, self.mag_filter),
+            (WebGl2RenderingContext::TEXTURE_MIN_FILTER, self.min_filter),
+            (WebGl2RenderingContext::TEXTURE_WRAP_S, self.wrap_s),
+            (WebGl2RenderingContext::TEXTURE_WRAP_T, self.wrap_t),
+        ];
+        for (name, value) in parameters {
+            context.sampler_parameteri(&gl_sampler, name, value);
+        }
+        if self.anisotropy > 1.0 {
+            if let Ok(Some(_)) = context.get_extension(Self::ANISOTROPIC_EXTENSION) {
+                let max = context
+                    .get_parameter(Self::MAX_TEXTURE_MAX_ANISOTROPY)
+                    .ok()
+                    .and_then(|value| value.as_f64())
+                    .unwrap_or(1.0) as f32;
+                context.sampler_parameterf(
+                    &gl_sampler,
+                    Self::TEXTURE_MAX_ANISOTROPY,
+                    self.anisotropy.min(max),
+                );
+            } else {
+                warn!("{} is not supported", Self::ANISOTROPIC_EXTENSION);
+            }
+        }
+        Some(gl_sampler)
+    }
+
+    fn key(&self) -> [u32; 5] {
+        [
+            self.mag_filter as u32,
+            self.min_filter as u32,
+            self.wrap_s as u32,
+            self.wrap_t as u32,
+            self.anisotropy.to_bits(),
+        ]
     }
 
     pub fn has_mipmap_filter(&self) -> bool {
@@ -113,3 +171,44 @@ impl Sampler {
         }
     }
 }
+
+#[derive(Debug, Default)]
+pub struct SamplerCache {
+    samplers: HashMap<[u32; 5], Rc<Sampler>>,
+}
+
+impl SamplerCache {
+    pub fn get(&mut self, sampler: Sampler) -> Rc<Sampler> {
+        Rc::clone(
+            self.samplers
+                .entry(sampler.key())
+                .or_insert_with(|| Rc::new(sampler)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sampler_cache_works() {
+        let mut cache = SamplerCache::default();
+        let repeat = cache.get(Sampler::default());
+        let clamp = cache.get(
+            Sampler::new(
+                None,
+                None,
+                WebGl2RenderingContext::CLAMP_TO_EDGE as i32,
+                WebGl2RenderingContext::CLAMP_TO_EDGE as i32,
+            )
+            .unwrap(),
+        );
+        assert!(Rc::ptr_eq(&repeat, &cache.get(Sampler::default())));
+        assert!(!Rc::ptr_eq(&repeat, &clamp));
+        assert!(!Rc::ptr_eq(
+            &repeat,
+            &cache.get(Sampler::default().with_anisotropy(8.0))
+        ));
+    }
+}
@@ -1,10 +1,18 @@
-use std::cell::RefCell;
+use std::{cell::RefCell, ptr};
 
 use web_sys::WebGl2RenderingContext;
 
-use crate::base::util::shared_ref::SharedRef;
+use crate::base::{
+    input::KeyState,
+    math::{aabb::Aabb, ray::Ray},
+    util::shared_ref::SharedRef,
+};
 
-use super::{camera::Camera, node::Node, program::UpdateProgramUniforms};
+use super::{
+    camera::Camera,
+    node::{Node, RaycastHit, Visit, Visitor},
+    program::UpdateProgramUniforms,
+};
 
 #[derive(Debug)]
 pub struct Scene {
@@ -32,16 +40,42 @@ impl Scene {
         }
     }
 
+    /// Runs the behaviours of all nodes in hierarchy order.
+    pub fn update(&self, dt: f32, key_state: &KeyState) {
+        for node in self.nodes.iter() {
+            Node::update(node, dt, key_state);
+        }
+    }
+
     pub fn contains_node(&self, node: &RefCell<Node>) -> bool {
         self.nodes
             .iter()
             .any(|root| root.borrow().is_ancestor_of(node))
     }
 
+    pub fn nodes(&self) -> &[SharedRef<Node>] {
+        &self.nodes
+    }
+
     pub fn add_node(&mut self, node: SharedRef<Node>) {
         self.nodes.push(node)
     }
 
+    /// Removes a root node, or detaches the node from its parent when it is nested.
+    pub fn remove_node(&mut self, node: &RefCell<Node>) -> bool {
+        let count = self.nodes.len();
+        self.nodes
+            .retain(|root| !ptr::eq(root.as_ptr(), node.as_ptr()));
+        if self.nodes.len() != count {
+            true
+        } else if self.contains_node(node) {
+            node.borrow_mut().detach();
+            true
+        } else {
+            false
+        }
+    }
+
     pub fn contains_camera(&self, camera: &RefCell<Camera>) -> bool {
         camera
             .borrow()
@@ -55,15 +89,60 @@ impl Scene {
             .any(|node| node.borrow().has_some_camera())
     }
 
-    pub fn all_nodes(&self) -> Vec<SharedRef<Node>> {
-        let mut result = vec![];
+    pub fn traverse<V>(&self, visitor: &mut V) -> Visit
+    where
+        V: Visitor + ?Sized,
+    {
         for node in self.nodes.iter() {
-            result.extend(node.borrow().descendants())
+            if node.borrow().traverse(visitor) == Visit::Stop {
+                return Visit::Stop;
+            }
         }
-        result
+        Visit::Continue
+    }
+
+    pub fn find<P>(&self, predicate: P) -> Option<SharedRef<Node>>
+    where
+        P: Fn(&Node) -> bool,
+    {
+        self.nodes
+            .iter()
+            .find_map(|node| node.borrow().find(&predicate))
+    }
+
+    pub fn find_by_name(&self, name: &str) -> Option<SharedRef<Node>> {
+        self.find(|node| node.name() == Some(name))
+    }
+
+    /// Returns the intersections with all visible meshes, nearest first.
+    pub fn raycast(&self, ray: &Ray) -> Vec<RaycastHit> {
+        let mut hits: Vec<_> = self
+            .nodes
+            .iter()
+            .flat_map(|node| node.borrow().raycast(ray))
+            .collect();
+        hits.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        hits
+    }
+
+    /// Follows a slash-separated path of names, starting at the root nodes.
+    pub fn find_by_path(&self, path: &str) -> Option<SharedRef<Node>> {
+        Node::find_by_path_in(&self.nodes, path)
     }
 
     pub fn depth(&self) -> usize {
         Node::max_by_key(&self.nodes, |node| node.depth())
     }
+
+    pub fn bounds(&self) -> Option<Aabb> {
+        let mut result: Option<Aabb> = None;
+        self.traverse(&mut |node: &Node| {
+            if let Some(bounds) = node.mesh().and_then(|mesh| mesh.bounds()) {
+                let bounds = bounds.transform(&node.global_transform());
+                result = Some(result.map_or(bounds, |result| result.union(&bounds)));
+            }
+            Visit::Continue
+        });
+        result
+    }
 }
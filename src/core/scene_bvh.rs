@@ -0,0 +1,156 @@
+use std::rc::Rc;
+
+use glm::{Mat4, Vec3};
+
+use crate::base::{
+    math::{aabb::Aabb, bvh::Bvh, frustum::Frustum, ray::Ray},
+    util::shared_ref::{SharedRef, WeakRef},
+};
+
+use super::{
+    mesh::{Mesh, MeshHit},
+    node::{Node, RaycastHit, Visit},
+    scene::Scene,
+};
+
+#[derive(Debug, Clone)]
+pub struct PrimitiveRef {
+    pub node: SharedRef<Node>,
+    pub primitive: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    node: WeakRef<Node>,
+    mesh: Rc<Mesh>,
+    primitive: usize,
+    bounds: Aabb,
+    transform: Mat4,
+}
+
+/// A bounding volume hierarchy over the world-space bounds of the primitives
+/// in a scene. It is meant for static geometry: moved nodes are handled by
+/// refitting, while added, removed, shown or hidden nodes need a rebuild.
+#[derive(Debug, Clone)]
+pub struct SceneBvh {
+    entries: Vec<Entry>,
+    bvh: Bvh,
+}
+
+impl SceneBvh {
+    pub fn build(scene: &Scene) -> Self {
+        let mut entries = vec![];
+        scene.traverse(&mut |node: &Node| {
+            if !node.is_visible() {
+                return Visit::SkipChildren;
+            }
+            if let Some(mesh) = node.mesh() {
+                for (index, primitive) in mesh.primitives().iter().enumerate() {
+                    if let Some(bounds) = primitive.bounds() {
+                        entries.push(Entry {
+                            node: node.weak_ref(),
+                            mesh: Rc::clone(mesh),
+                            primitive: index,
+                            bounds,
+                            transform: node.global_transform(),
+                        });
+                    }
+                }
+            }
+            Visit::Continue
+        });
+        let bvh = Bvh::build(&Self::world_bounds(&entries));
+        Self { entries, bvh }
+    }
+
+    pub fn bounds(&self) -> Option<Aabb> {
+        self.bvh.bounds()
+    }
+
+    /// Picks up transform changes of the nodes, returning whether anything moved.
+    pub fn refit(&mut self) -> bool {
+        let mut moved = false;
+        for entry in self.entries.iter_mut() {
+            let Some(node) = entry.node.upgrade() else {
+                continue;
+            };
+            let transform = node.borrow().global_transform();
+            if transform != entry.transform {
+                entry.transform = transform;
+                moved = true;
+            }
+        }
+        if moved {
+            self.bvh
+                .refit(&Self::world_bounds(&self.entries))
+                .expect("Entries are the ones the hierarchy was built from.");
+        }
+        moved
+    }
+
+    pub fn raycast(&self, ray: &Ray) -> Option<RaycastHit> {
+        let mut hits: Vec<(usize, SharedRef<Node>, Mat4, MeshHit)> = vec![];
+        let (item, _) = self.bvh.raycast(ray, |item| {
+            let entry = &self.entries[item];
+            let node = entry.node.upgrade()?;
+            let inverse = entry.transform.try_inverse()?;
+            let hit = entry.mesh.primitives()[entry.primitive]
+                .raycast(&ray.transform(&inverse))
+                .into_iter()
+                .min_by(|a, b| a.distance.total_cmp(&b.distance))?;
+            let distance = hit.distance;
+            hits.push((item, node, inverse, hit));
+            Some(distance)
+        })?;
+        let (_, node, inverse, hit) = hits.into_iter().rev().find(|hit| hit.0 == item)?;
+        let primitive = self.entries[item].primitive;
+        Some(RaycastHit::new(
+            node,
+            ray,
+            &inverse,
+            MeshHit { primitive, ..hit },
+        ))
+    }
+
+    pub fn query_frustum(&self, frustum: &Frustum) -> Vec<PrimitiveRef> {
+        self.primitives(self.bvh.query_frustum(frustum))
+    }
+
+    pub fn query_aabb(&self, aabb: &Aabb) -> Vec<PrimitiveRef> {
+        self.primitives(self.bvh.query_aabb(aabb))
+    }
+
+    /// Finds the point on the primitives nearest to `point`. Distances are
+    /// measured in the local space of each node, so they are only exact for
+    /// uniformly scaled nodes.
+    pub fn nearest(&self, point: &Vec3) -> Option<(PrimitiveRef, Vec3)> {
+        let (item, closest) = self.bvh.nearest(point, |item| {
+            let entry = &self.entries[item];
+            let inverse = entry.transform.try_inverse()?;
+            let local = glm::vec4_to_vec3(&(inverse * point.push(1.0)));
+            let closest = entry.mesh.primitives()[entry.primitive].closest_point(&local)?;
+            Some(glm::vec4_to_vec3(&(entry.transform * closest.push(1.0))))
+        })?;
+        Some((self.primitives(vec![item]).pop()?, closest))
+    }
+
+    fn primitives(&self, items: Vec<usize>) -> Vec<PrimitiveRef> {
+        items
+            .into_iter()
+            .filter_map(|item| {
+                let entry = &self.entries[item];
+                Some(PrimitiveRef {
+                    node: entry.node.upgrade()?,
+                    primitive: entry.primitive,
+                })
+            })
+            .collect()
+    }
+
+    fn world_bounds(entries: &[Entry]) -> Vec<Aabb> {
+        entries
+            .iter()
+            .map(|entry| entry.bounds.transform(&entry.transform))
+            .collect()
+    }
+}
@@ -0,0 +1,224 @@
+use std::rc::Rc;
+
+use anyhow::{anyhow, Result};
+use glm::Mat4;
+use js_sys::Float32Array;
+use web_sys::{WebGl2RenderingContext, WebGlTexture};
+
+use crate::base::{
+    gl,
+    math::matrix,
+    util::{
+        cache::Cached,
+        level::Level,
+        shared_ref::{SharedRef, WeakRef},
+        validate,
+    },
+};
+
+use super::{
+    node::Node,
+    program::{Program, UpdateUniform},
+    texture::TextureUnit,
+};
+
+#[derive(Debug, Clone)]
+pub struct Skin {
+    joints: Vec<WeakRef<Node>>,
+    inverse_bind_matrices: Vec<Mat4>,
+    skeleton: Option<WeakRef<Node>>,
+    joint_texture: Cached<Option<WebGlTexture>>,
+    name: Option<String>,
+}
+
+impl Skin {
+    pub const JOINT_TEXTURE_UNIFORM: &str = "u_JointTexture";
+    pub const USE_SKINNING_UNIFORM: &str = "u_UseSkinning";
+    pub const JOINT_TEXTURE_UNIT: TextureUnit = TextureUnit(14);
+
+    pub fn new(
+        joints: &[SharedRef<Node>],
+        inverse_bind_matrices: Option<Vec<Mat4>>,
+        skeleton: Option<&SharedRef<Node>>,
+        name: Option<String>,
+    ) -> Result<Rc<Self>> {
+        validate::not_empty(joints, || anyhow!("Skin must have at least one joint"))?;
+        let inverse_bind_matrices =
+            inverse_bind_matrices.unwrap_or_else(|| vec![matrix::identity(); joints.len()]);
+        validate::assert(inverse_bind_matrices.len() >= joints.len(), || {
+            anyhow!("Not enough inverse bind matrices for skin joints")
+        })?;
+        Ok(Rc::new(Self {
+            joints: joints.iter().map(Rc::downgrade).collect(),
+            inverse_bind_matrices,
+            skeleton: skeleton.map(Rc::downgrade),
+            joint_texture: Cached::new(),
+            name,
+        }))
+    }
+
+    pub fn joints(&self) -> &[WeakRef<Node>] {
+        &self.joints
+    }
+
+    pub fn inverse_bind_matrices(&self) -> &[Mat4] {
+        &self.inverse_bind_matrices[..self.joints.len()]
+    }
+
+    pub fn skeleton(&self) -> Option<&WeakRef<Node>> {
+        self.skeleton.as_ref()
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Returns a copy whose joints and skeleton are replaced by the nodes `map`
+    /// returns for them, keeping the others; used for copies of the hierarchy.
+    pub fn remap<F>(&self, map: F) -> Rc<Self>
+    where
+        F: Fn(&WeakRef<Node>) -> Option<SharedRef<Node>>,
+    {
+        let remap = |node: &WeakRef<Node>| {
+            map(node).map_or_else(|| WeakRef::clone(node), |node| Rc::downgrade(&node))
+        };
+        Rc::new(Self {
+            joints: self.joints.iter().map(remap).collect(),
+            inverse_bind_matrices: self.inverse_bind_matrices.clone(),
+            skeleton: self.skeleton.as_ref().map(remap),
+            joint_texture: Cached::new(),
+            name: self.name.clone(),
+        })
+    }
+
+    pub fn joint_matrices(&self, node: &Node) -> Vec<Mat4> {
+        let inverse_global_transform = node
+            .global_transform()
+            .try_inverse()
+            .unwrap_or_else(matrix::identity);
+        self.joints
+            .iter()
+            .zip(self.inverse_bind_matrices.iter())
+            .map(|(joint, inverse_bind_matrix)| {
+                let joint_transform = joint
+                    .upgrade()
+                    .map_or_else(matrix::identity, |joint| joint.borrow().global_transform());
+                inverse_global_transform * joint_transform * inverse_bind_matrix
+            })
+            .collect()
+    }
+
+    pub fn update_node_uniforms(
+        &self,
+        context: &WebGl2RenderingContext,
+        node: &Node,
+        program: &Program,
+    ) {
+        self.upload_joint_matrices(context, &self.joint_matrices(node));
+        Self::JOINT_TEXTURE_UNIT.update_uniform(context, Self::JOINT_TEXTURE_UNIFORM, program);
+        true.update_uniform_with_level(context, Self::USE_SKINNING_UNIFORM, program, Level::Ignore);
+    }
+
+    fn upload_joint_matrices(&self, context: &WebGl2RenderingContext, joint_matrices: &[Mat4]) {
+        let data: Vec<f32> = joint_matrices
+            .iter()
+            .flat_map(|joint_matrix| joint_matrix.as_slice().to_vec())
+            .collect();
+        Self::JOINT_TEXTURE_UNIT.active_texture(context);
+        self.joint_texture.with_cached_ref(
+            || Self::create_joint_texture(context),
+            |joint_texture| {
+                context.bind_texture(WebGl2RenderingContext::TEXTURE_2D, joint_texture.as_ref())
+            },
+        );
+        let result = context
+            .tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_array_buffer_view(
+                WebGl2RenderingContext::TEXTURE_2D,
+                0,
+                WebGl2RenderingContext::RGBA32F as i32,
+                4 * joint_matrices.len() as i32,
+                1,
+                0,
+                WebGl2RenderingContext::RGBA,
+                WebGl2RenderingContext::FLOAT,
+                Some(&Float32Array::from(data.as_slice())),
+            );
+        if let Err(error) = result {
+            error!("Error while uploading joint matrices: {:#?}", error);
+        }
+    }
+
+    fn create_joint_texture(context: &WebGl2RenderingContext) -> Option<WebGlTexture> {
+        let texture = match gl::create_texture(context) {
+            Ok(texture) => texture,
+            Err(error) => {
+                error!("Cannot create joint texture: {:#?}", error);
+                return None;
+            }
+        };
+        context.bind_texture(WebGl2RenderingContext::TEXTURE_2D, Some(&texture));
+        for (parameter, value) in [
+            (
+                WebGl2RenderingContext::TEXTURE_MIN_FILTER,
+                WebGl2RenderingContext::NEAREST,
+            ),
+            (
+                WebGl2RenderingContext::TEXTURE_MAG_FILTER,
+                WebGl2RenderingContext::NEAREST,
+            ),
+            (
+                WebGl2RenderingContext::TEXTURE_WRAP_S,
+                WebGl2RenderingContext::CLAMP_TO_EDGE,
+            ),
+            (
+                WebGl2RenderingContext::TEXTURE_WRAP_T,
+                WebGl2RenderingContext::CLAMP_TO_EDGE,
+            ),
+        ] {
+            context.tex_parameteri(WebGl2RenderingContext::TEXTURE_2D, parameter, value as i32);
+        }
+        Some(texture)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skin_works() {
+        let root = Node::with_name("Root");
+        let joint = Node::with_name("Joint");
+        joint.borrow_mut().set_position(&glm::vec3(0.0, 1.0, 0.0));
+        root.borrow_mut().add_child(Rc::clone(&joint));
+        let inverse_bind_matrix = glm::translation(&glm::vec3(0.0, -1.0, 0.0));
+        let skin = Skin::new(
+            &[Rc::clone(&root), Rc::clone(&joint)],
+            Some(vec![matrix::identity(), inverse_bind_matrix]),
+            Some(&root),
+            None,
+        )
+        .unwrap();
+        let mesh = Node::new_empty();
+        assert_eq!(
+            skin.joint_matrices(&mesh.borrow()),
+            vec![matrix::identity(); 2]
+        );
+
+        root.borrow_mut().set_position(&glm::vec3(2.0, 0.0, 0.0));
+        let moved = glm::translation(&glm::vec3(2.0, 0.0, 0.0));
+        assert_eq!(skin.joint_matrices(&mesh.borrow()), vec![moved; 2]);
+
+        let copy = Node::with_name("Copy");
+        let remapped =
+            skin.remap(|node| Rc::ptr_eq(&node.upgrade()?, &joint).then(|| Rc::clone(&copy)));
+        assert!(Rc::ptr_eq(&remapped.joints()[0].upgrade().unwrap(), &root));
+        assert!(Rc::ptr_eq(&remapped.joints()[1].upgrade().unwrap(), &copy));
+        assert_eq!(
+            remapped.joint_matrices(&mesh.borrow())[1],
+            inverse_bind_matrix
+        );
+        assert!(Skin::new(&[], None, None, None).is_err());
+        assert!(Skin::new(&[root], Some(vec![]), None, None).is_err());
+    }
+}
@@ -3,7 +3,12 @@ use std::rc::Rc;
 use anyhow::Result;
 use web_sys::{WebGl2RenderingContext, WebGlTexture};
 
-use crate::base::{gl, math::resolution::Resolution};
+use crate::base::{
+    asset::{AssetSource, FetchSource},
+    color::ColorSpace,
+    gl,
+    math::resolution::Resolution,
+};
 
 use super::{image::Image, program::UpdateUniformValue, sampler::Sampler};
 
@@ -12,6 +17,7 @@ pub struct Texture {
     texture: WebGlTexture,
     sampler: Rc<Sampler>,
     source: Rc<Image>,
+    color_space: ColorSpace,
 }
 
 impl Texture {
@@ -19,35 +25,101 @@ impl Texture {
         context: &WebGl2RenderingContext,
         sampler: Rc<Sampler>,
         source: Rc<Image>,
+    ) -> Result<Rc<Self>> {
+        Self::initialize_with_color_space(context, sampler, source, ColorSpace::Linear)
+    }
+
+    pub fn initialize_with_color_space(
+        context: &WebGl2RenderingContext,
+        sampler: Rc<Sampler>,
+        source: Rc<Image>,
+        color_space: ColorSpace,
     ) -> Result<Rc<Self>> {
         let texture = gl::create_texture(context)?;
         let me = Self {
             texture,
             sampler,
             source,
+            color_space,
         };
         me.store_data(context)?;
         Ok(Rc::new(me))
     }
 
     pub async fn fetch(context: &WebGl2RenderingContext, uri: &str) -> Result<Rc<Self>> {
-        let image = Rc::new(Image::fetch(uri).await?);
-        Self::initialize(context, Rc::default(), image)
+        Self::load(context, &FetchSource, uri).await
+    }
+
+    pub async fn fetch_with_color_space(
+        context: &WebGl2RenderingContext,
+        uri: &str,
+        color_space: ColorSpace,
+    ) -> Result<Rc<Self>> {
+        Self::load_with_color_space(context, &FetchSource, uri, color_space).await
+    }
+
+    pub async fn load(
+        context: &WebGl2RenderingContext,
+        source: &dyn AssetSource,
+        uri: &str,
+    ) -> Result<Rc<Self>> {
+        Self::load_with_color_space(context, source, uri, ColorSpace::Linear).await
+    }
+
+    pub async fn load_with_color_space(
+        context: &WebGl2RenderingContext,
+        source: &dyn AssetSource,
+        uri: &str,
+        color_space: ColorSpace,
+    ) -> Result<Rc<Self>> {
+        let image = Rc::new(Image::load(source, uri).await?);
+        Self::initialize_with_color_space(context, Rc::default(), image, color_space)
     }
 
     pub fn texture(&self) -> &WebGlTexture {
         &self.texture
     }
 
+    pub fn sampler(&self) -> &Sampler {
+        &self.sampler
+    }
+
+    pub fn source(&self) -> &Image {
+        &self.source
+    }
+
+    pub fn with_sampler(&self, context: &WebGl2RenderingContext, sampler: Rc<Sampler>) -> Rc<Self> {
+        if sampler.has_mipmap_filter()
+            && !self.sampler.has_mipmap_filter()
+            && !self.source.provides_mipmaps()
+        {
+            self.bind(context);
+            sampler.generate_mipmap(context);
+        }
+        Rc::new(Self {
+            texture: self.texture.clone(),
+            sampler,
+            source: Rc::clone(&self.source),
+            color_space: self.color_space,
+        })
+    }
+
     pub fn bind(&self, context: &WebGl2RenderingContext) {
         context.bind_texture(WebGl2RenderingContext::TEXTURE_2D, Some(&self.texture));
     }
 
+    pub fn bind_to_unit(&self, context: &WebGl2RenderingContext, unit: TextureUnit) {
+        unit.active_texture(context);
+        self.bind(context);
+        self.sampler.bind(context, unit);
+    }
+
     pub fn store_data(&self, context: &WebGl2RenderingContext) -> Result<()> {
         self.bind(context);
-        self.source.tex_image_2d(context)?;
-        self.sampler.set_texture_parameters(context);
-        self.sampler.generate_mipmap(context);
+        self.source.tex_image_2d(context, self.color_space)?;
+        if !self.source.provides_mipmaps() {
+            self.sampler.generate_mipmap(context);
+        }
         Ok(())
     }
 
@@ -1,4 +1,4 @@
-use std::{f32::consts::TAU, rc::Rc};
+use std::rc::Rc;
 
 use anyhow::Result;
 use async_trait::async_trait;
@@ -7,7 +7,8 @@ use web_sys::WebGl2RenderingContext;
 use crate::{
     api::geometry::Geometry,
     base::{
-        application::{self, Application, AsyncCreator},
+        application::{self, Application, AsyncCreator, Loop},
+        color::ColorSpace,
         convert::FromWithContext,
         input::KeyState,
         math::angle::Angle,
@@ -21,6 +22,7 @@ use crate::{
         node::Node,
         scene::Scene,
     },
+    extras::behaviours::Spin,
     geometry::box_geom::BoxGeometry,
     material::basic::{BasicMaterial, SurfaceMaterial},
 };
@@ -28,7 +30,6 @@ use crate::{
 struct Example {
     renderer: Renderer,
     scene: Scene,
-    mesh: SharedRef<Node>,
     camera: SharedRef<Camera>,
 }
 
@@ -45,7 +46,8 @@ impl AsyncCreator for Example {
             .set_position(&glm::vec3(0.0, 0.0, 2.0));
         scene.add_node(camera_node);
 
-        let geometry = Geometry::from_with_context(context, BoxGeometry::default())?;
+        let geometry = Geometry::from_with_context(context, BoxGeometry::default())?
+            .with_color_space(ColorSpace::Srgb);
         let material = <Rc<Material>>::from_with_context(
             context,
             shared_ref::new(SurfaceMaterial {
@@ -58,11 +60,21 @@ impl AsyncCreator for Example {
         )?;
         let mesh = Mesh::initialize(context, &geometry, material)?;
         let mesh = Node::new_with_mesh(mesh);
-        scene.add_node(Rc::clone(&mesh));
+        {
+            let mut mesh = mesh.borrow_mut();
+            mesh.add_behaviour(Spin::new(
+                glm::vec3(0.0, 1.0, 0.0),
+                Angle::from_degrees(48.0),
+            ));
+            mesh.add_behaviour(Spin::new(
+                glm::vec3(1.0, 0.0, 0.0),
+                Angle::from_degrees(36.0),
+            ));
+        }
+        scene.add_node(mesh);
 
         Ok(Box::new(Example {
             renderer,
-            mesh,
             scene,
             camera,
         }))
@@ -74,13 +86,8 @@ impl Application for Example {
         "Spinning cube"
     }
 
-    fn update(&mut self, _key_state: &KeyState) {
-        self.mesh
-            .borrow_mut()
-            .rotate_y(Angle::from_radians(TAU) / 450.0);
-        self.mesh
-            .borrow_mut()
-            .rotate_x(Angle::from_radians(TAU) / 600.0);
+    fn update(&mut self, key_state: &KeyState) {
+        self.scene.update(Loop::SECS_PER_UPDATE as f32, key_state);
     }
 
     fn render(&self, context: &WebGl2RenderingContext) {
@@ -6,7 +6,7 @@ use web_sys::WebGl2RenderingContext;
 
 use crate::{
     base::{
-        application::{self, Application, AsyncCreator},
+        application::{self, Application, AsyncCreator, Loop},
         color,
         convert::FromWithContext,
         input::KeyState,
@@ -14,13 +14,17 @@ use crate::{
     },
     classic::renderer::Renderer,
     core::{
+        behaviour::BehaviourId,
         camera::{Camera, Perspective},
         mesh::Mesh,
         node::Node,
         scene::Scene,
     },
     extras::{
-        axes_helper::AxesHelper, camera_controller::CameraController, grid_helper::GridHelper,
+        axes_helper::AxesHelper,
+        behaviours::{Bob, Follow, LookAt},
+        camera_controller::CameraController,
+        grid_helper::GridHelper,
     },
 };
 
@@ -29,6 +33,42 @@ struct Example {
     scene: Scene,
     camera: Rc<RefCell<Camera>>,
     controller: CameraController,
+    axes: Rc<RefCell<Node>>,
+    companion: Rc<RefCell<Node>>,
+    follow: BehaviourId,
+    was_pressed: [bool; 3],
+}
+
+impl Example {
+    const KEY_PICK_UP: &'static str = "KeyP";
+    const KEY_STAMP: &'static str = "KeyO";
+    const KEY_RELEASE: &'static str = "KeyB";
+
+    fn toggle_pick_up(&mut self) -> Result<()> {
+        let holder = self.camera.borrow().node();
+        let holder = holder.expect("Camera should be attached to a node.");
+        if holder.borrow().is_ancestor_of(&self.axes) {
+            {
+                let mut axes = self.axes.borrow_mut();
+                let transform = axes.global_transform();
+                axes.detach();
+                axes.set_local_transform(&transform);
+            }
+            self.scene.add_node(Rc::clone(&self.axes));
+        } else {
+            self.scene.remove_node(&self.axes);
+            self.axes.borrow_mut().reparent(&holder, true)?;
+        }
+        Ok(())
+    }
+
+    fn stamp(&mut self) {
+        let axes = self.axes.borrow();
+        let copy = axes.deep_clone();
+        copy.borrow_mut()
+            .set_local_transform(&axes.global_transform());
+        self.scene.add_node(copy);
+    }
 }
 
 #[async_trait(?Send)]
@@ -39,7 +79,7 @@ impl AsyncCreator for Example {
 
         let camera = Camera::new(Perspective::default());
         let camera_node = Node::new_with_camera(Rc::clone(&camera));
-        scene.add_node(camera_node);
+        scene.add_node(Rc::clone(&camera_node));
 
         let controller = CameraController::make_for_camera(&camera)
             .expect("Camera controller should be created.");
@@ -52,8 +92,23 @@ impl AsyncCreator for Example {
                 ..Default::default()
             },
         )?;
+        let companion = Node::with_name("Companion");
+        let follow = companion.borrow_mut().add_behaviour(Follow::new(
+            &camera_node,
+            glm::vec3(1.0, -0.5, -3.0),
+            2.0,
+        ));
+        let marker = Node::new_with_mesh(Rc::clone(&axes));
         let axes = Node::new_with_mesh(axes);
-        scene.add_node(axes);
+        scene.add_node(Rc::clone(&axes));
+        {
+            let mut marker = marker.borrow_mut();
+            marker.set_scale(&glm::vec3(0.2, 0.2, 0.2));
+            marker.add_behaviour(Bob::new(glm::vec3(0.0, 0.2, 0.0), 0.5));
+            marker.add_behaviour(LookAt::new(&axes));
+        }
+        companion.borrow_mut().add_child(marker);
+        scene.add_node(Rc::clone(&companion));
 
         let grid = <Rc<Mesh>>::from_with_context(
             context,
@@ -73,6 +128,10 @@ impl AsyncCreator for Example {
             scene,
             camera,
             controller,
+            axes,
+            companion,
+            follow,
+            was_pressed: [false; 3],
         }))
     }
 }
@@ -83,7 +142,25 @@ impl Application for Example {
     }
 
     fn update(&mut self, key_state: &KeyState) {
-        self.controller.update(key_state)
+        self.controller.update(key_state);
+        self.scene.update(Loop::SECS_PER_UPDATE as f32, key_state);
+
+        let is_pressed = [Self::KEY_PICK_UP, Self::KEY_STAMP, Self::KEY_RELEASE]
+            .map(|key| key_state.is_pressed(key));
+        let [pick_up, stamp, release] =
+            [0, 1, 2].map(|index| is_pressed[index] && !self.was_pressed[index]);
+        self.was_pressed = is_pressed;
+        if pick_up {
+            if let Err(error) = self.toggle_pick_up() {
+                warn!("Axes could not be picked up: {}", error);
+            }
+        }
+        if stamp {
+            self.stamp();
+        }
+        if release {
+            self.companion.borrow_mut().remove_behaviour(self.follow);
+        }
     }
 
     fn render(&self, context: &WebGl2RenderingContext) {
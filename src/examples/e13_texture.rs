@@ -8,6 +8,7 @@ use crate::{
     api::geometry::Geometry,
     base::{
         application::{self, Application, AsyncCreator},
+        color::ColorSpace,
         convert::FromWithContext,
         input::KeyState,
         util::shared_ref::SharedRef,
@@ -46,7 +47,7 @@ impl AsyncCreator for Example {
         let geometry = Geometry::from_with_context(context, Rectangle::default())?;
         let material = material::texture::create(
             context,
-            Texture::fetch(context, "images/grid.png").await?,
+            Texture::fetch_with_color_space(context, "images/grid.png", ColorSpace::Srgb).await?,
             TextureUnit(0),
             Default::default(),
         )?;
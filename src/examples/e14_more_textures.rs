@@ -8,6 +8,7 @@ use crate::{
     api::geometry::Geometry,
     base::{
         application::{self, Application, AsyncCreator},
+        color::ColorSpace,
         convert::FromWithContext,
         input::KeyState,
         math::{angle::Angle, matrix},
@@ -17,6 +18,7 @@ use crate::{
         camera::{Camera, Perspective},
         mesh::Mesh,
         node::Node,
+        sampler::Sampler,
         scene::Scene,
         texture::{Texture, TextureUnit},
     },
@@ -46,7 +48,9 @@ impl AsyncCreator for Example {
 
         let material = material::texture::create(
             context,
-            Texture::fetch(context, "images/grid.png").await?,
+            Texture::fetch_with_color_space(context, "images/grid.png", ColorSpace::Srgb)
+                .await?
+                .with_sampler(context, Rc::new(Sampler::default().with_anisotropy(8.0))),
             TextureUnit(0),
             Default::default(),
         )?;
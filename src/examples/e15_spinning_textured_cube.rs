@@ -7,11 +7,11 @@ use web_sys::WebGl2RenderingContext;
 use crate::{
     api::geometry::Geometry,
     base::{
-        application::{self, Application, AsyncCreator},
+        application::{self, Application, AsyncCreator, Loop},
+        color::ColorSpace,
         convert::FromWithContext,
         input::KeyState,
         math::angle::Angle,
-        util::shared_ref::SharedRef,
     },
     classic::renderer::{Renderer, RendererOptions},
     core::{
@@ -21,6 +21,7 @@ use crate::{
         scene::Scene,
         texture::{Texture, TextureUnit},
     },
+    extras::behaviours::Spin,
     geometry::box_geom::BoxGeometry,
     material,
 };
@@ -28,7 +29,6 @@ use crate::{
 struct Example {
     renderer: Renderer,
     scene: Scene,
-    mesh: SharedRef<Node>,
     camera: Rc<RefCell<Camera>>,
 }
 
@@ -48,17 +48,27 @@ impl AsyncCreator for Example {
         let geometry = Geometry::from_with_context(context, BoxGeometry::default())?;
         let material = material::texture::create(
             context,
-            Texture::fetch(context, "images/crate.png").await?,
+            Texture::fetch_with_color_space(context, "images/crate.png", ColorSpace::Srgb).await?,
             TextureUnit(0),
             Default::default(),
         )?;
         let mesh = Mesh::initialize(context, &geometry, material)?;
         let mesh = Node::new_with_mesh(mesh);
-        scene.add_node(Rc::clone(&mesh));
+        {
+            let mut mesh = mesh.borrow_mut();
+            mesh.add_behaviour(Spin::new(
+                glm::vec3(0.0, 1.0, 0.0),
+                Angle::from_degrees(48.0),
+            ));
+            mesh.add_behaviour(Spin::new(
+                glm::vec3(1.0, 0.0, 0.0),
+                Angle::from_degrees(36.0),
+            ));
+        }
+        scene.add_node(mesh);
 
         Ok(Box::new(Example {
             renderer,
-            mesh,
             scene,
             camera,
         }))
@@ -70,9 +80,8 @@ impl Application for Example {
         "Spinning textured cube"
     }
 
-    fn update(&mut self, _key_state: &KeyState) {
-        self.mesh.borrow_mut().rotate_y(Angle::COMPLETE / 450.0);
-        self.mesh.borrow_mut().rotate_x(Angle::COMPLETE / 600.0);
+    fn update(&mut self, key_state: &KeyState) {
+        self.scene.update(Loop::SECS_PER_UPDATE as f32, key_state);
     }
 
     fn render(&self, context: &WebGl2RenderingContext) {
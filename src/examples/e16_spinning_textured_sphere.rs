@@ -8,6 +8,7 @@ use crate::{
     api::geometry::Geometry,
     base::{
         application::{self, Application, AsyncCreator},
+        color::ColorSpace,
         convert::FromWithContext,
         input::KeyState,
         math::angle::Angle,
@@ -55,12 +56,13 @@ impl AsyncCreator for Example {
         )?;
         let material = material::texture::create(
             context,
-            Texture::fetch(context, "images/earth.jpg").await?,
+            Texture::fetch_with_color_space(context, "images/earth.jpg", ColorSpace::Srgb).await?,
             TextureUnit(0),
             Default::default(),
         )?;
         let mesh = Mesh::initialize(context, &geometry, material)?;
         let mesh = Node::new_with_mesh(mesh);
+        mesh.borrow_mut().rotate_z(Angle::from_degrees(-23.4));
         scene.add_node(Rc::clone(&mesh));
 
         Ok(Box::new(Example {
@@ -8,6 +8,7 @@ use crate::{
     api::geometry::Geometry,
     base::{
         application::{self, Application, AsyncCreator},
+        color::ColorSpace,
         convert::FromWithContext,
         input::KeyState,
         math::angle::Angle,
@@ -57,7 +58,8 @@ impl AsyncCreator for Example {
             )?;
             let material = material::texture::create(
                 context,
-                Texture::fetch(context, "images/sky-earth.jpg").await?,
+                Texture::fetch_with_color_space(context, "images/sky-earth.jpg", ColorSpace::Srgb)
+                    .await?,
                 TextureUnit(0),
                 Default::default(),
             )?;
@@ -75,7 +77,8 @@ impl AsyncCreator for Example {
             )?;
             let material = material::texture::create(
                 context,
-                Texture::fetch(context, "images/grass.jpg").await?,
+                Texture::fetch_with_color_space(context, "images/grass.jpg", ColorSpace::Srgb)
+                    .await?,
                 TextureUnit(1),
                 material::texture::Properties {
                     repeat_uv: glm::vec2(50.0, 50.0),
@@ -2,6 +2,8 @@
 
 precision highp float;
 
+#include <color_space>
+
 uniform sampler2D textureSampler;
 in vec2 v_UV;
 uniform float time;
@@ -9,5 +11,5 @@ out vec4 fragColor;
 
 void main() {
     vec2 shiftUV = v_UV + vec2(0.0, 0.2 * sin(6.0 * v_UV.x + time));
-    fragColor = texture(textureSampler, shiftUV);
+    fragColor = encodeOutput(texture(textureSampler, shiftUV));
 }
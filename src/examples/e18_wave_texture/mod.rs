@@ -8,6 +8,7 @@ use crate::{
     api::geometry::Geometry,
     base::{
         application::{self, Application, AsyncCreator},
+        color::ColorSpace,
         convert::FromWithContext,
         input::KeyState,
         util::shared_ref::{self, SharedRef},
@@ -50,7 +51,8 @@ impl AsyncCreator for Example {
         }
         let wave_material = shared_ref::new(WaveMaterial {
             texture_sampler: Sampler2D::new(
-                Texture::fetch(context, "images/grid.png").await?,
+                Texture::fetch_with_color_space(context, "images/grid.png", ColorSpace::Srgb)
+                    .await?,
                 TextureUnit(0),
             ),
             time: 0.0,
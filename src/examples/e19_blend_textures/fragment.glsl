@@ -2,6 +2,8 @@
 
 precision highp float;
 
+#include <color_space>
+
 uniform sampler2D textureSampler1;
 uniform sampler2D textureSampler2;
 in vec2 v_UV;
@@ -12,5 +14,5 @@ void main() {
     vec4 color1 = texture(textureSampler1, v_UV);
     vec4 color2 = texture(textureSampler2, v_UV);
     float s = (sin(time) + 1.0) / 2.0;
-    fragColor = s * color1 + (1.0 - s) * color2;
+    fragColor = encodeOutput(s * color1 + (1.0 - s) * color2);
 }
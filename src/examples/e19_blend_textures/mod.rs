@@ -8,6 +8,7 @@ use crate::{
     api::geometry::Geometry,
     base::{
         application::{self, Application, AsyncCreator},
+        color::ColorSpace,
         convert::FromWithContext,
         input::KeyState,
         util::shared_ref::{self, SharedRef},
@@ -50,11 +51,13 @@ impl AsyncCreator for Example {
         }
         let blend_material = shared_ref::new(BlendMaterial {
             texture_sampler_1: Sampler2D::new(
-                Texture::fetch(context, "images/grid.png").await?,
+                Texture::fetch_with_color_space(context, "images/grid.png", ColorSpace::Srgb)
+                    .await?,
                 TextureUnit(0),
             ),
             texture_sampler_2: Sampler2D::new(
-                Texture::fetch(context, "images/crate.png").await?,
+                Texture::fetch_with_color_space(context, "images/crate.png", ColorSpace::Srgb)
+                    .await?,
                 TextureUnit(1),
             ),
             time: 0.0,
@@ -2,6 +2,8 @@
 
 precision highp float;
 
+#include <color_space>
+
 uniform sampler2D noise;
 uniform sampler2D image;
 in vec2 v_UV;
@@ -12,5 +14,5 @@ void main() {
     vec2 uvShift = v_UV + vec2(-0.033, 0.07) * time;
     vec4 noiseValues = texture(noise, uvShift);
     vec2 uvNoise = v_UV + 0.4*noiseValues.rg;
-    fragColor = texture(image, uvNoise);
+    fragColor = encodeOutput(texture(image, uvNoise));
 }
@@ -8,6 +8,7 @@ use crate::{
     api::geometry::Geometry,
     base::{
         application::{self, Application, AsyncCreator},
+        color::ColorSpace,
         convert::FromWithContext,
         input::KeyState,
         util::shared_ref::{self, SharedRef},
@@ -54,7 +55,8 @@ impl AsyncCreator for Example {
                 TextureUnit(0),
             ),
             image: Sampler2D::new(
-                Texture::fetch(context, "images/grid.png").await?,
+                Texture::fetch_with_color_space(context, "images/grid.png", ColorSpace::Srgb)
+                    .await?,
                 TextureUnit(1),
             ),
             time: 0.0,
@@ -8,7 +8,7 @@ use crate::{
     api::geometry::Geometry,
     base::{
         application::{self, Application, AsyncCreator},
-        color,
+        color::{self, ColorSpace},
         convert::FromWithContext,
         input::KeyState,
         math::angle::Angle,
@@ -81,7 +81,7 @@ impl AsyncCreator for Example {
         )?;
         let material = material::texture::create(
             context,
-            Texture::initialize(
+            Texture::initialize_with_color_space(
                 context,
                 Default::default(),
                 Rc::new(Image::try_from(TextTexture {
@@ -90,6 +90,7 @@ impl AsyncCreator for Example {
                     font_style: "blue",
                     ..Default::default()
                 })?),
+                ColorSpace::Srgb,
             )?,
             TextureUnit(0),
             Default::default(),
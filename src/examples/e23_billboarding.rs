@@ -8,7 +8,7 @@ use crate::{
     api::geometry::{Geometry, TypedGeometry},
     base::{
         application::{self, Application, AsyncCreator},
-        color,
+        color::{self, ColorSpace},
         convert::FromWithContext,
         input::KeyState,
         math::{angle::Angle, matrix},
@@ -77,7 +77,7 @@ impl AsyncCreator for Example {
 }
 
 fn create_label(context: &WebGl2RenderingContext) -> Result<SharedRef<Node>> {
-    let texture = Texture::initialize(
+    let texture = Texture::initialize_with_color_space(
         context,
         Default::default(),
         Rc::new(Image::try_from(TextTexture {
@@ -89,6 +89,7 @@ fn create_label(context: &WebGl2RenderingContext) -> Result<SharedRef<Node>> {
             font_style: "blue",
             ..Default::default()
         })?),
+        ColorSpace::Srgb,
     )?;
     let material = material::texture::create(context, texture, TextureUnit(0), Default::default())?;
     let mut typed_geometry = TypedGeometry::try_from(Rectangle {
@@ -106,7 +107,7 @@ async fn create_crate_mesh(context: &WebGl2RenderingContext) -> Result<SharedRef
     let geometry = Geometry::from_with_context(context, BoxGeometry::default())?;
     let material = material::texture::create(
         context,
-        Texture::fetch(context, "images/crate.png").await?,
+        Texture::fetch_with_color_space(context, "images/crate.png", ColorSpace::Srgb).await?,
         TextureUnit(1),
         Default::default(),
     )?;
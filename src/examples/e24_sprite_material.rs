@@ -9,7 +9,7 @@ use crate::{
     api::geometry::Geometry,
     base::{
         application::{self, Application, AsyncCreator},
-        color,
+        color::{self, ColorSpace},
         convert::FromWithContext,
         input::KeyState,
         math::angle::Angle,
@@ -124,7 +124,12 @@ async fn create_sprite_material(
 ) -> Result<SharedRef<SpriteMaterial>> {
     let material = shared_ref::new(SpriteMaterial {
         properties,
-        texture: Texture::fetch(context, "images/rolling-ball.png").await?,
+        texture: Texture::fetch_with_color_space(
+            context,
+            "images/rolling-ball.png",
+            ColorSpace::Srgb,
+        )
+        .await?,
         unit: TextureUnit(0),
     });
     Ok(material)
@@ -8,7 +8,7 @@ use crate::{
     api::geometry::Geometry,
     base::{
         application::{self, Application, AsyncCreator},
-        color,
+        color::{self, ColorSpace},
         convert::FromWithContext,
         input::KeyState,
         math::angle::Angle,
@@ -61,7 +61,8 @@ impl AsyncCreator for Example {
             let geometry = Geometry::from_with_context(context, BoxGeometry::default())?;
             let material = material::texture::create(
                 context,
-                Texture::fetch(context, "images/crate.png").await?,
+                Texture::fetch_with_color_space(context, "images/crate.png", ColorSpace::Srgb)
+                    .await?,
                 TextureUnit(0),
                 Default::default(),
             )?;
@@ -148,7 +149,8 @@ async fn create_hud(context: &WebGl2RenderingContext) -> Result<(Scene, SharedRe
             )?,
             material::texture::create(
                 context,
-                Texture::fetch(context, "images/crate-sim.png").await?,
+                Texture::fetch_with_color_space(context, "images/crate-sim.png", ColorSpace::Srgb)
+                    .await?,
                 TextureUnit(0),
                 Default::default(),
             )?,
@@ -169,7 +171,8 @@ async fn create_hud(context: &WebGl2RenderingContext) -> Result<(Scene, SharedRe
             )?,
             material::texture::create(
                 context,
-                Texture::fetch(context, "images/version-1.png").await?,
+                Texture::fetch_with_color_space(context, "images/version-1.png", ColorSpace::Srgb)
+                    .await?,
                 TextureUnit(1),
                 Default::default(),
             )?,
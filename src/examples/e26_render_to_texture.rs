@@ -8,7 +8,7 @@ use crate::{
     api::geometry::Geometry,
     base::{
         application::{self, Application, AsyncCreator},
-        color,
+        color::{self, ColorSpace},
         convert::FromWithContext,
         input::KeyState,
         math::{angle::Angle, resolution::Resolution},
@@ -71,7 +71,12 @@ impl AsyncCreator for Example {
                 )?,
                 material::texture::create(
                     context,
-                    Texture::fetch(context, "images/sky-earth.jpg").await?,
+                    Texture::fetch_with_color_space(
+                        context,
+                        "images/sky-earth.jpg",
+                        ColorSpace::Srgb,
+                    )
+                    .await?,
                     TextureUnit(0),
                     Default::default(),
                 )?,
@@ -91,7 +96,8 @@ impl AsyncCreator for Example {
                 )?,
                 material::texture::create(
                     context,
-                    Texture::fetch(context, "images/grass.jpg").await?,
+                    Texture::fetch_with_color_space(context, "images/grass.jpg", ColorSpace::Srgb)
+                        .await?,
                     TextureUnit(1),
                     Properties {
                         repeat_uv: glm::vec2(50.0, 50.0),
@@ -107,7 +113,8 @@ impl AsyncCreator for Example {
             &Geometry::from_with_context(context, Sphere::default())?,
             material::texture::create(
                 context,
-                Texture::fetch(context, "images/grid.png").await?,
+                Texture::fetch_with_color_space(context, "images/grid.png", ColorSpace::Srgb)
+                    .await?,
                 TextureUnit(2),
                 Default::default(),
             )?,
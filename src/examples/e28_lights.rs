@@ -8,7 +8,7 @@ use crate::{
     api::geometry::Geometry,
     base::{
         application::{self, Application, AsyncCreator},
-        color,
+        color::{self, ColorSpace},
         convert::FromWithContext,
         input::KeyState,
         util::shared_ref::SharedRef,
@@ -105,7 +105,12 @@ impl AsyncCreator for Example {
                     LambertMaterial {
                         ambient: color::rgb(0.1, 0.1, 0.1),
                         texture: Sampler2D::new(
-                            Texture::fetch(context, "images/grid.png").await?,
+                            Texture::fetch_with_color_space(
+                                context,
+                                "images/grid.png",
+                                ColorSpace::Srgb,
+                            )
+                            .await?,
                             TextureUnit(0),
                         )
                         .into(),
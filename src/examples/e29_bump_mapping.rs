@@ -5,10 +5,11 @@ use async_trait::async_trait;
 use web_sys::WebGl2RenderingContext;
 
 use crate::{
-    api::geometry::Geometry,
+    api::geometry::{Geometry, TypedGeometry},
     base::{
         application::{self, Application, AsyncCreator},
-        color,
+        asset::FetchSource,
+        color::{self, ColorSpace},
         convert::FromWithContext,
         input::KeyState,
         util::shared_ref::SharedRef,
@@ -54,27 +55,37 @@ impl AsyncCreator for Example {
         point.add_to_scene(&mut scene);
 
         {
+            let mut geometry = TypedGeometry::try_from(Rectangle {
+                width: 2.0,
+                height: 2.0,
+                ..Default::default()
+            })?;
+            geometry.generate_tangents()?;
             let mesh = Node::new_with_mesh(Mesh::initialize(
                 context,
-                &Geometry::from_with_context(
-                    context,
-                    Rectangle {
-                        width: 2.0,
-                        height: 2.0,
-                        ..Default::default()
-                    },
-                )?,
+                &Geometry::from_with_context(context, geometry)?,
                 material::lambert::create(
                     context,
                     LambertMaterial {
                         ambient: color::rgb(0.3, 0.3, 0.3),
                         texture: Sampler2D::new(
-                            Texture::fetch(context, "images/brick-color.png").await?,
+                            Texture::fetch_with_color_space(
+                                context,
+                                "images/brick-color.png",
+                                ColorSpace::Srgb,
+                            )
+                            .await?,
                             TextureUnit(0),
                         )
                         .into(),
                         bump_texture: Sampler2D::new(
-                            Texture::fetch(context, "images/brick-bump.png").await?,
+                            Texture::load_with_color_space(
+                                context,
+                                &FetchSource,
+                                "images/brick-bump.png",
+                                ColorSpace::Linear,
+                            )
+                            .await?,
                             TextureUnit(1),
                         )
                         .into(),
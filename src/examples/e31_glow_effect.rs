@@ -8,7 +8,7 @@ use crate::{
     api::geometry::Geometry,
     base::{
         application::{self, Application, AsyncCreator},
-        color,
+        color::{self, ColorSpace},
         convert::FromWithContext,
         input::KeyState,
         math::angle::Angle,
@@ -75,7 +75,12 @@ impl AsyncCreator for Example {
                 )?,
                 material::texture::create(
                     context,
-                    Texture::fetch(context, "images/sky-earth.jpg").await?,
+                    Texture::fetch_with_color_space(
+                        context,
+                        "images/sky-earth.jpg",
+                        ColorSpace::Srgb,
+                    )
+                    .await?,
                     TextureUnit(0),
                     Default::default(),
                 )?,
@@ -95,7 +100,8 @@ impl AsyncCreator for Example {
                 )?,
                 material::texture::create(
                     context,
-                    Texture::fetch(context, "images/grass.jpg").await?,
+                    Texture::fetch_with_color_space(context, "images/grass.jpg", ColorSpace::Srgb)
+                        .await?,
                     TextureUnit(1),
                     material::texture::Properties {
                         repeat_uv: glm::vec2(50.0, 50.0),
@@ -112,7 +118,8 @@ impl AsyncCreator for Example {
             &Geometry::from_with_context(context, Sphere::default())?,
             material::texture::create(
                 context,
-                Texture::fetch(context, "images/grid.png").await?,
+                Texture::fetch_with_color_space(context, "images/grid.png", ColorSpace::Srgb)
+                    .await?,
                 TextureUnit(2),
                 Default::default(),
             )?,
@@ -140,7 +147,7 @@ impl AsyncCreator for Example {
             )?);
             glow_sphere
                 .borrow_mut()
-                .set_local_transform(sphere.borrow().local_transform());
+                .set_local_transform(&sphere.borrow().local_transform());
             glow_scene.add_node(glow_sphere);
         }
 
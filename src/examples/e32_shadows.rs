@@ -8,7 +8,7 @@ use crate::{
     api::geometry::Geometry,
     base::{
         application::{self, Application, AsyncCreator},
-        color,
+        color::{self, ColorSpace},
         convert::FromWithContext,
         input::KeyState,
         math::angle::Angle,
@@ -38,6 +38,26 @@ struct Example {
     scene: Scene,
     camera: SharedRef<Camera>,
     lights: Lights,
+    selected: SharedRef<Node>,
+    was_pressed: [bool; 2],
+}
+
+impl Example {
+    const KEY_TOGGLE_SELECTED: &'static str = "KeyV";
+
+    fn select_at_pointer(&mut self, key_state: &KeyState) {
+        let Some(position) = key_state.pointer_position() else {
+            return;
+        };
+        let ray = self.camera.borrow().ray(&position);
+        if let Some(hit) = self.scene.raycast(&ray).into_iter().next() {
+            debug!(
+                "Selected primitive {}, triangle {} {:?} at {:?}, normal {:?}",
+                hit.primitive, hit.triangle, hit.barycentric, hit.point, hit.normal
+            );
+            self.selected = hit.node;
+        }
+    }
 }
 
 #[async_trait(?Send)]
@@ -56,6 +76,7 @@ impl AsyncCreator for Example {
             DirectionalLightHelper::default()
                 .create_mesh(context, &directional_light.light().borrow())?,
         );
+        directional_helper.borrow_mut().set_cast_shadow(false);
         directional_light.add_child(directional_helper);
 
         let resolution = renderer::get_canvas_resolution(context).scale(1.0);
@@ -92,7 +113,8 @@ impl AsyncCreator for Example {
             context,
             PhongMaterial {
                 texture: Sampler2D::new(
-                    Texture::fetch(context, "images/grid.png").await?,
+                    Texture::fetch_with_color_space(context, "images/grid.png", ColorSpace::Srgb)
+                        .await?,
                     TextureUnit(0),
                 )
                 .into(),
@@ -120,11 +142,11 @@ impl AsyncCreator for Example {
             Rc::clone(&phong_material),
         )?);
         {
-            sphere2
-                .borrow_mut()
-                .set_position(&glm::vec3(1.0, 2.2, -0.5));
-            scene.add_node(sphere2);
+            let mut sphere = sphere2.borrow_mut();
+            sphere.set_position(&glm::vec3(1.0, 2.2, -0.5));
+            sphere.set_receive_shadow(false);
         }
+        scene.add_node(Rc::clone(&sphere2));
 
         let floor = Node::new_with_mesh(Mesh::initialize(
             context,
@@ -147,6 +169,8 @@ impl AsyncCreator for Example {
             renderer,
             scene,
             lights,
+            selected: sphere2,
+            was_pressed: [false; 2],
         }))
     }
 }
@@ -158,6 +182,18 @@ impl Application for Example {
 
     fn update(&mut self, key_state: &KeyState) {
         self.controller.update(key_state);
+        let is_pressed = [KeyState::PRIMARY_BUTTON, Self::KEY_TOGGLE_SELECTED]
+            .map(|key| key_state.is_pressed(key));
+        let [select, toggle] = [0, 1].map(|index| is_pressed[index] && !self.was_pressed[index]);
+        self.was_pressed = is_pressed;
+        if select {
+            self.select_at_pointer(key_state);
+        }
+        if toggle {
+            let mut selected = self.selected.borrow_mut();
+            let visible = selected.is_visible();
+            selected.set_visible(!visible);
+        }
     }
 
     fn render(&self, context: &WebGl2RenderingContext) {
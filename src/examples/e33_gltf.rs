@@ -1,16 +1,28 @@
-use std::fmt;
+use std::{cell::Cell, fmt};
 
 use anyhow::Result;
 use async_trait::async_trait;
+use glm::Vec2;
 use web_sys::WebGl2RenderingContext;
 
 use crate::{
     base::{
         application::{self, Application, AsyncCreator},
+        asset::MemorySource,
         gl::diagnostic::GlDiagnostics,
         input::KeyState,
+        math::{aabb::Aabb, angle::Angle, normal::NormalMode},
+        util::shared_ref::SharedRef,
+        web,
+    },
+    classic::picking::PickingPass,
+    core::{node::Node, scene::Scene, scene_bvh::SceneBvh},
+    gltf::{
+        self,
+        core::Root,
+        load::{extension::ExtensionHandlers, LoadOptions},
+        user::scene_controller,
     },
-    gltf::{self, core::Root},
 };
 
 enum Variant {
@@ -39,6 +51,98 @@ impl fmt::Display for Variant {
     }
 }
 
+const EMBEDDED_TRIANGLE: &str = "EmbeddedTriangle";
+
+const EMBEDDED_TRIANGLE_GLTF: &str = r#"{
+    "asset": { "version": "2.0" },
+    "scene": 0,
+    "scenes": [{ "nodes": [0] }],
+    "extensionsUsed": ["EXAMPLE_spawn_point"],
+    "nodes": [{
+        "name": "Spawn",
+        "children": [1],
+        "extras": { "collision": { "solid": true } },
+        "extensions": { "EXAMPLE_spawn_point": { "team": "blue" } }
+    }, {
+        "name": "Triangle",
+        "mesh": 0
+    }],
+    "meshes": [{ "primitives": [{ "attributes": { "POSITION": 0 } }] }],
+    "buffers": [{ "uri": "triangle.bin", "byteLength": 36 }],
+    "bufferViews": [{ "buffer": 0, "byteLength": 36, "target": 34962 }],
+    "accessors": [{
+        "bufferView": 0,
+        "componentType": 5126,
+        "count": 3,
+        "type": "VEC3",
+        "min": [0.0, 0.0, 0.0],
+        "max": [1.0, 1.0, 0.0]
+    }]
+}"#;
+
+fn embedded_triangle() -> MemorySource {
+    let positions: [f32; 9] = [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0];
+    MemorySource::new()
+        .with_asset("triangle.gltf", EMBEDDED_TRIANGLE_GLTF.as_bytes().to_vec())
+        .with_asset(
+            "triangle.bin",
+            positions
+                .iter()
+                .flat_map(|value| value.to_le_bytes())
+                .collect(),
+        )
+}
+
+async fn load(context: &WebGl2RenderingContext, name: &str) -> Result<Root> {
+    if name == EMBEDDED_TRIANGLE {
+        let options = LoadOptions {
+            normals: NormalMode::Smooth {
+                crease_angle: Angle::from_degrees(60.0),
+            },
+            extensions: ExtensionHandlers::new().on_node("EXAMPLE_spawn_point", |node, value| {
+                let node = node.borrow();
+                let team: Option<String> = value.get("team").and_then(|team| team.to());
+                let solid: Option<bool> = node.custom_properties().extra("collision/solid");
+                debug!("Spawn point for team {:?}, solid: {:?}", team, solid);
+                Ok(())
+            }),
+            scene_controller: scene_controller::Properties {
+                key_next_camera: None,
+                ..Default::default()
+            },
+        };
+        let root = gltf::load::load_with_options(
+            context,
+            &embedded_triangle(),
+            "memory:///triangle.gltf",
+            options,
+        )
+        .await?;
+        if let Some(scene) = root.current_scene() {
+            self::describe_embedded_triangle(scene);
+        }
+        Ok(root)
+    } else {
+        gltf::load::load(context, &khronos_sample(name, Default::default())).await
+    }
+}
+
+fn describe_embedded_triangle(scene: &Scene) {
+    let name = |node: Option<SharedRef<Node>>| {
+        node.and_then(|node| node.borrow().name().map(String::from))
+    };
+    let spawn = scene.find_by_name("Spawn");
+    let triangle = scene.find_by_path("Spawn/Triangle");
+    let solid =
+        scene.find(|node| node.custom_properties().extra::<bool>("collision/solid") == Some(true));
+    debug!(
+        "Spawn: {:?}, triangle: {:?}, solid: {:?}",
+        name(spawn),
+        name(triangle),
+        name(solid)
+    );
+}
+
 fn khronos_sample(name: &str, variant: Variant) -> String {
     format!(
         "https://raw.githubusercontent.com/KhronosGroup/glTF-Sample-Models/master/2.0/{}/{}/{}.{}",
@@ -51,6 +155,80 @@ fn khronos_sample(name: &str, variant: Variant) -> String {
 
 struct Example {
     root: Root,
+    export_pressed: bool,
+    picking: PickingPass,
+    pick_at: Cell<Option<Vec2>>,
+    pick_pressed: bool,
+    bvh: Option<SceneBvh>,
+    query_pressed: bool,
+}
+
+impl Example {
+    const KEY_EXPORT_GLB: &str = "KeyX";
+    const KEY_EXPORT_GLTF: &str = "KeyZ";
+    const KEY_QUERY: &str = "KeyB";
+
+    fn pick(&self, context: &WebGl2RenderingContext) -> Result<()> {
+        if let Some(position) = self.pick_at.take() {
+            if let (Some(scene), Some(camera)) =
+                (self.root.current_scene(), self.root.current_camera())
+            {
+                self.picking.render(context, scene, camera)?;
+                self.picking.request_at(context, &position)?;
+            }
+        }
+        if let Some(picks) = self.picking.poll(context) {
+            for pick in picks {
+                debug!(
+                    "Picked node {:?}, primitive {}",
+                    pick.node.borrow().name(),
+                    pick.primitive
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn query(&self) {
+        let (Some(bvh), Some(camera)) = (&self.bvh, self.root.current_camera()) else {
+            return;
+        };
+        let camera = camera.borrow();
+        let position = camera.world_position();
+        let visible = bvh.query_frustum(&camera.frustum());
+        let around = bvh.query_aabb(&Aabb::new(
+            position.add_scalar(-1.0),
+            position.add_scalar(1.0),
+        ));
+        let ahead = bvh.raycast(&camera.ray(&Vec2::zeros()));
+        let nearest = bvh.nearest(&position);
+        debug!(
+            "Bounds: {:?}, visible primitives: {}, within a unit of the camera: {}, ahead: {:?}, nearest: {:?}",
+            bvh.bounds(),
+            visible.len(),
+            around.len(),
+            ahead.map(|hit| (hit.node.borrow().name().map(String::from), hit.distance)),
+            nearest.map(|(nearest, point)| (
+                nearest.node.borrow().name().map(String::from),
+                nearest.primitive,
+                point
+            ))
+        );
+    }
+
+    fn export(&self, binary: bool) -> Result<()> {
+        let Some(scene) = self.root.current_scene() else {
+            return Ok(());
+        };
+        let exported = gltf::export::export(scene)?;
+        if binary {
+            web::download(&exported.to_glb()?, "scene.glb", "model/gltf-binary")
+        } else {
+            let json = exported.to_gltf("scene.bin")?;
+            web::download(json.as_bytes(), "scene.gltf", "model/gltf+json")?;
+            web::download(exported.bin(), "scene.bin", "application/octet-stream")
+        }
+    }
 }
 
 fn example_names<'a>() -> Vec<&'a str> {
@@ -70,6 +248,7 @@ fn example_names<'a>() -> Vec<&'a str> {
         "ReciprocatingSaw",
         "GearboxAssy",
         "Buggy",
+        EMBEDDED_TRIANGLE,
     ]
 }
 
@@ -77,12 +256,17 @@ fn example_names<'a>() -> Vec<&'a str> {
 impl AsyncCreator for Example {
     async fn create(context: &WebGl2RenderingContext) -> Result<Box<Self>> {
         debug!("{:#?}", GlDiagnostics::collect(context)?);
-        let root = gltf::load::load(
-            context,
-            &khronos_sample(example_names()[9], Default::default()),
-        )
-        .await?;
-        Ok(Box::new(Example { root }))
+        let root = self::load(context, example_names()[9]).await?;
+        let bvh = root.current_scene().map(SceneBvh::build);
+        Ok(Box::new(Example {
+            root,
+            export_pressed: false,
+            picking: PickingPass::initialize(context)?,
+            pick_at: Cell::new(None),
+            pick_pressed: false,
+            bvh,
+            query_pressed: false,
+        }))
     }
 }
 
@@ -92,11 +276,35 @@ impl Application for Example {
     }
 
     fn update(&mut self, key_state: &KeyState) {
-        self.root.update(key_state)
+        self.root.update(key_state);
+        let glb = key_state.is_pressed(Self::KEY_EXPORT_GLB);
+        let gltf = key_state.is_pressed(Self::KEY_EXPORT_GLTF);
+        if (glb || gltf) && !self.export_pressed {
+            if let Err(error) = self.export(glb) {
+                error!("Cannot export scene: {:#?}", error);
+            }
+        }
+        self.export_pressed = glb || gltf;
+        let pick = key_state.is_pressed(KeyState::PRIMARY_BUTTON);
+        if pick && !self.pick_pressed {
+            self.pick_at.set(key_state.pointer_position());
+        }
+        self.pick_pressed = pick;
+        if let Some(bvh) = &mut self.bvh {
+            bvh.refit();
+        }
+        let query = key_state.is_pressed(Self::KEY_QUERY);
+        if query && !self.query_pressed {
+            self.query();
+        }
+        self.query_pressed = query;
     }
 
     fn render(&self, context: &WebGl2RenderingContext) {
         self.root.render(context);
+        if let Err(error) = self.pick(context) {
+            error!("Cannot pick: {:#?}", error);
+        }
     }
 }
 
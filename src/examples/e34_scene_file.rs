@@ -0,0 +1,138 @@
+use std::rc::Rc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use web_sys::WebGl2RenderingContext;
+
+use crate::{
+    api::geometry::Geometry,
+    base::{
+        application::{self, Application, AsyncCreator, Loop},
+        asset::FetchSource,
+        convert::FromWithContext,
+        input::KeyState,
+        util::shared_ref,
+        web,
+    },
+    core::{material::Material, mesh::Mesh, node::Node},
+    extras::camera_controller::CameraController,
+    geometry::box_geom::BoxGeometry,
+    material::basic::SurfaceMaterial,
+    scene_file::{
+        data::{GeometryData, MeshData},
+        load::{self, LoadedScene},
+        save,
+    },
+};
+
+struct Example {
+    loaded: LoadedScene,
+    controller: CameraController,
+    box_mesh: Rc<Mesh>,
+    add_pressed: bool,
+    save_pressed: bool,
+}
+
+impl Example {
+    const KEY_ADD: &str = "KeyB";
+    const KEY_SAVE: &str = "KeyX";
+
+    fn add_box(&mut self) {
+        let node = Node::new_with_mesh(Rc::clone(&self.box_mesh));
+        {
+            let camera = self.loaded.camera().borrow();
+            let position = camera.world_position() + camera.world_direction() * 3.0;
+            node.borrow_mut().set_position(&position);
+        }
+        self.loaded.scene_mut().add_node(node);
+    }
+
+    fn save(&self) -> Result<()> {
+        let json = save::save(&self.loaded)?;
+        web::download(json.as_bytes(), "scene.json", "application/json")
+    }
+}
+
+#[async_trait(?Send)]
+impl AsyncCreator for Example {
+    async fn create(context: &WebGl2RenderingContext) -> Result<Box<Self>> {
+        let mut loaded = load::load(context, &FetchSource, "scenes/demo.json").await?;
+        debug!(
+            "Loaded {} nodes and {} lights",
+            loaded.scene().nodes().len(),
+            loaded.lights().light_count()
+        );
+        let controller = CameraController::make_for_node(Rc::clone(loaded.camera()));
+
+        // Boxes added at runtime use the first material of the file, and are
+        // registered so that they are saved with the scene.
+        let material = loaded.sources().materials().iter().next();
+        let (material_name, material) = match material {
+            Some((name, material)) => (
+                Some(name.clone()),
+                <Rc<Material>>::from_with_context(context, material.clone())?,
+            ),
+            None => (
+                None,
+                <Rc<Material>>::from_with_context(
+                    context,
+                    shared_ref::new(SurfaceMaterial::default()),
+                )?,
+            ),
+        };
+        let geometry = BoxGeometry::default();
+        let box_mesh = Mesh::initialize(
+            context,
+            &Geometry::from_with_context(context, geometry.clone())?,
+            material,
+        )?;
+        loaded.sources_mut().add_mesh(
+            &box_mesh,
+            MeshData {
+                geometry: GeometryData::Box(geometry),
+                material: material_name,
+            },
+        );
+
+        Ok(Box::new(Example {
+            loaded,
+            controller,
+            box_mesh,
+            add_pressed: false,
+            save_pressed: false,
+        }))
+    }
+}
+
+impl Application for Example {
+    fn name(&self) -> &str {
+        "Scene file"
+    }
+
+    fn update(&mut self, key_state: &KeyState) {
+        self.controller.update(key_state);
+        self.loaded
+            .scene()
+            .update(Loop::SECS_PER_UPDATE as f32, key_state);
+        let add = key_state.is_pressed(Self::KEY_ADD);
+        if add && !self.add_pressed {
+            self.add_box();
+        }
+        self.add_pressed = add;
+        let save = key_state.is_pressed(Self::KEY_SAVE);
+        if save && !self.save_pressed {
+            if let Err(error) = self.save() {
+                error!("Cannot save scene: {:#?}", error);
+            }
+        }
+        self.save_pressed = save;
+    }
+
+    fn render(&self, context: &WebGl2RenderingContext) {
+        self.loaded.render(context);
+    }
+}
+
+pub fn example() -> Box<dyn Fn()> {
+    Box::new(application::spawn::<Example>)
+}
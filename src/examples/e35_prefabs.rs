@@ -0,0 +1,193 @@
+use std::rc::Rc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use web_sys::WebGl2RenderingContext;
+
+use crate::{
+    api::geometry::Geometry,
+    base::{
+        application::{self, Application, AsyncCreator},
+        color,
+        convert::FromWithContext,
+        input::KeyState,
+        util::shared_ref::SharedRef,
+    },
+    classic::{
+        light::{Light, Lights},
+        renderer::Renderer,
+    },
+    core::{
+        camera::{Camera, Perspective},
+        material::MaterialOverrides,
+        mesh::Mesh,
+        node::Node,
+        prefab::Prefab,
+        scene::Scene,
+    },
+    extras::camera_controller::CameraController,
+    geometry::parametric::{Cone, Cylinder},
+    material::{self, lambert::LambertMaterial},
+};
+
+struct Example {
+    controller: CameraController,
+    renderer: Renderer,
+    scene: Scene,
+    camera: SharedRef<Camera>,
+    lights: Lights,
+    prefab: Rc<Prefab>,
+    tall: bool,
+    was_pressed: [bool; 2],
+}
+
+impl Example {
+    const KEY_EDIT_PREFAB: &str = "KeyP";
+    const KEY_TOGGLE_OVERRIDES: &str = "KeyO";
+    const DIFFUSE_UNIFORM: &str = "material.diffuse";
+
+    /// Stretches the crown in the template and pushes the change to all trees.
+    fn edit_prefab(&mut self) {
+        self.tall = !self.tall;
+        let height = if self.tall { 2.0 } else { 1.0 };
+        if let Some(crown) = Node::find_by_path_in(self.prefab.nodes(), "Trunk/Crown") {
+            crown.borrow_mut().set_scale(&glm::vec3(1.0, height, 1.0));
+        }
+        let count = self.prefab.propagate();
+        debug!("Updated {} instances of {:?}", count, self.prefab.name());
+    }
+
+    /// Returns the first tree to the color of the shared material, or highlights it.
+    fn toggle_overrides(&self) {
+        let Some(first) = self.prefab.instances().into_iter().next() else {
+            return;
+        };
+        let mut first = first.borrow_mut();
+        let mut overrides = first.material_overrides().clone();
+        if overrides.is_empty() {
+            overrides.set(Self::DIFFUSE_UNIFORM, color::red());
+        } else {
+            overrides.remove(Self::DIFFUSE_UNIFORM);
+        }
+        first.set_material_overrides(overrides);
+    }
+}
+
+#[async_trait(?Send)]
+impl AsyncCreator for Example {
+    async fn create(context: &WebGl2RenderingContext) -> Result<Box<Self>> {
+        let renderer = Renderer::initialize(context, Default::default(), None);
+        let mut scene = Scene::new_empty();
+        let mut lights = Lights::new();
+
+        let camera = Node::new_with_camera(Camera::new(Perspective::default()));
+        camera.borrow_mut().set_position(&glm::vec3(0.0, 6.0, 16.0));
+        scene.add_node(Rc::clone(&camera));
+        let controller = CameraController::make_for_node(camera);
+
+        let directional = lights.create_node(Light::directional(
+            color::rgb(0.9, 0.9, 0.9),
+            glm::vec3(-1.0, -2.0, -1.0),
+        ));
+        directional.add_to_scene(&mut scene);
+
+        let material = material::lambert::create(
+            context,
+            LambertMaterial {
+                ambient: color::rgb(0.2, 0.2, 0.2),
+                ..Default::default()
+            },
+        )?;
+        let trunk = Node::new(
+            glm::translation(&glm::vec3(0.0, 0.5, 0.0)),
+            Some(Mesh::initialize(
+                context,
+                &Geometry::from_with_context(
+                    context,
+                    Cylinder {
+                        radius: 0.15,
+                        height: 1.0,
+                        ..Default::default()
+                    },
+                )?,
+                Rc::clone(&material),
+            )?),
+            None,
+            Some(String::from("Trunk")),
+        );
+        let crown = Node::new(
+            glm::translation(&glm::vec3(0.0, 1.0, 0.0)),
+            Some(Mesh::initialize(
+                context,
+                &Geometry::from_with_context(
+                    context,
+                    Cone {
+                        radius: 0.6,
+                        height: 1.5,
+                        ..Default::default()
+                    },
+                )?,
+                material,
+            )?),
+            None,
+            Some(String::from("Crown")),
+        );
+        trunk.borrow_mut().add_child(crown);
+        let prefab = Prefab::new(Some(String::from("Tree")), &[trunk]);
+
+        for index in 0..50 {
+            let (row, column) = (index / 10, index % 10);
+            let shade = index as f32 / 50.0;
+            let tree = prefab.instantiate_with(MaterialOverrides::new().with(
+                Self::DIFFUSE_UNIFORM,
+                color::rgb(0.2 + 0.6 * shade, 0.8 - 0.4 * shade, 0.3),
+            ));
+            tree.borrow_mut().set_position(&glm::vec3(
+                column as f32 * 2.0 - 9.0,
+                0.0,
+                row as f32 * -2.0,
+            ));
+            scene.add_node(tree);
+        }
+
+        Ok(Box::new(Example {
+            camera: controller.camera().expect("Camera is present."),
+            controller,
+            renderer,
+            scene,
+            lights,
+            prefab,
+            tall: false,
+            was_pressed: [false; 2],
+        }))
+    }
+}
+
+impl Application for Example {
+    fn name(&self) -> &str {
+        "Prefabs"
+    }
+
+    fn update(&mut self, key_state: &KeyState) {
+        self.controller.update(key_state);
+        let is_pressed = [Self::KEY_EDIT_PREFAB, Self::KEY_TOGGLE_OVERRIDES]
+            .map(|key| key_state.is_pressed(key));
+        let [edit, toggle] = [0, 1].map(|index| is_pressed[index] && !self.was_pressed[index]);
+        self.was_pressed = is_pressed;
+        if edit {
+            self.edit_prefab();
+        }
+        if toggle {
+            self.toggle_overrides();
+        }
+    }
+
+    fn render(&self, context: &WebGl2RenderingContext) {
+        self.renderer
+            .render_with_lights(context, &self.scene, &self.camera, &self.lights);
+    }
+}
+
+pub fn example() -> Box<dyn Fn()> {
+    Box::new(application::spawn::<Example>)
+}
@@ -22,3 +22,5 @@ pub mod e30_bloom_effect;
 pub mod e31_glow_effect;
 pub mod e32_shadows;
 pub mod e33_gltf;
+pub mod e34_scene_file;
+pub mod e35_prefabs;
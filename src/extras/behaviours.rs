@@ -0,0 +1,129 @@
+use std::f32::consts::TAU;
+
+use glm::Vec3;
+
+use crate::{
+    base::{
+        input::KeyState,
+        math::angle::Angle,
+        util::shared_ref::{SharedRef, WeakRef},
+    },
+    core::{behaviour::Behaviour, node::Node},
+};
+
+/// Rotates around an axis in the node's local space at a constant speed.
+#[derive(Debug, Clone)]
+pub struct Spin {
+    axis: Vec3,
+    speed: Angle,
+}
+
+impl Spin {
+    /// Takes the angle covered per second.
+    pub fn new(axis: Vec3, speed: Angle) -> Self {
+        Self { axis, speed }
+    }
+}
+
+impl Behaviour for Spin {
+    fn update(&mut self, node: &mut Node, dt: f32, _key_state: &KeyState) {
+        node.rotate(&self.axis, self.speed * dt);
+    }
+}
+
+/// Oscillates around the position the node had when the behaviour was attached,
+/// and moves it back there when detached.
+#[derive(Debug, Clone)]
+pub struct Bob {
+    offset: Vec3,
+    frequency: f32,
+    origin: Vec3,
+    time: f32,
+}
+
+impl Bob {
+    /// Takes the largest offset from the origin and the number of cycles per second.
+    pub fn new(offset: Vec3, frequency: f32) -> Self {
+        Self {
+            offset,
+            frequency,
+            origin: Vec3::zeros(),
+            time: 0.0,
+        }
+    }
+}
+
+impl Behaviour for Bob {
+    fn on_attach(&mut self, node: &mut Node) {
+        self.origin = node.position();
+        self.time = 0.0;
+    }
+
+    fn update(&mut self, node: &mut Node, dt: f32, _key_state: &KeyState) {
+        self.time += dt;
+        let phase = (TAU * self.frequency * self.time).sin();
+        node.set_position(&(self.origin + self.offset * phase));
+    }
+
+    fn on_detach(&mut self, node: &mut Node) {
+        node.set_position(&self.origin);
+    }
+}
+
+/// Turns the node towards another node.
+#[derive(Debug, Clone)]
+pub struct LookAt {
+    target: WeakRef<Node>,
+}
+
+impl LookAt {
+    pub fn new(target: &SharedRef<Node>) -> Self {
+        Self {
+            target: target.borrow().weak_ref(),
+        }
+    }
+}
+
+impl Behaviour for LookAt {
+    fn update(&mut self, node: &mut Node, _dt: f32, _key_state: &KeyState) {
+        if let Some(target) = self::world_position(&self.target) {
+            node.look_at(&target);
+        }
+    }
+}
+
+/// Moves the node towards another node plus an offset in world space. The
+/// stiffness is the rate at which the remaining distance shrinks per second.
+#[derive(Debug, Clone)]
+pub struct Follow {
+    target: WeakRef<Node>,
+    offset: Vec3,
+    stiffness: f32,
+}
+
+impl Follow {
+    pub fn new(target: &SharedRef<Node>, offset: Vec3, stiffness: f32) -> Self {
+        Self {
+            target: target.borrow().weak_ref(),
+            offset,
+            stiffness,
+        }
+    }
+}
+
+impl Behaviour for Follow {
+    fn update(&mut self, node: &mut Node, dt: f32, _key_state: &KeyState) {
+        if let Some(target) = self::world_position(&self.target) {
+            let remaining = target + self.offset - node.world_position();
+            let factor = 1.0 - (-self.stiffness * dt).exp();
+            node.translate_world(&(remaining * factor));
+        }
+    }
+}
+
+/// Skips targets that are gone or are the node being updated.
+fn world_position(target: &WeakRef<Node>) -> Option<Vec3> {
+    let target = target.upgrade()?;
+    let position = target.try_borrow().ok()?.world_position();
+    Some(position)
+}
@@ -6,12 +6,14 @@ use crate::{
     base::{
         application::Loop,
         input::KeyState,
-        math::{angle::Angle, matrix},
+        math::{angle::Angle, euler::EulerOrder},
         util::shared_ref::SharedRef,
     },
     core::{camera::Camera, node::Node},
 };
 
+const MAX_PITCH_DEGREES: f32 = 89.0;
+
 #[derive(Debug, Clone)]
 pub struct Properties {
     pub linear_speed: f32,
@@ -79,57 +81,54 @@ impl CameraController {
         let linear_change = self.properties.linear_speed * (Loop::SECS_PER_UPDATE as f32);
         let angular_change = self.properties.angular_speed * (Loop::SECS_PER_UPDATE as f32);
 
+        let mut offset = Vec3::zeros();
         if Self::is_key_pressed(&self.properties.key_move_forwards, key_state) {
-            self.node
-                .borrow_mut()
-                .apply_transform(&matrix::translation(0.0, 0.0, -linear_change))
+            offset.z -= linear_change;
         }
         if Self::is_key_pressed(&self.properties.key_move_backwards, key_state) {
-            self.node
-                .borrow_mut()
-                .apply_transform(&matrix::translation(0.0, 0.0, linear_change))
+            offset.z += linear_change;
         }
         if Self::is_key_pressed(&self.properties.key_move_left, key_state) {
-            self.node
-                .borrow_mut()
-                .apply_transform(&matrix::translation(-linear_change, 0.0, 0.0))
+            offset.x -= linear_change;
         }
         if Self::is_key_pressed(&self.properties.key_move_right, key_state) {
-            self.node
-                .borrow_mut()
-                .apply_transform(&matrix::translation(linear_change, 0.0, 0.0))
+            offset.x += linear_change;
         }
+        self.node.borrow_mut().translate(&offset);
+
+        let mut lift = 0.0;
         if Self::is_key_pressed(&self.properties.key_move_up, key_state) {
-            self.node
-                .borrow_mut()
-                .apply_transform(&matrix::translation(0.0, linear_change, 0.0))
+            lift += linear_change;
         }
         if Self::is_key_pressed(&self.properties.key_move_down, key_state) {
-            self.node
-                .borrow_mut()
-                .apply_transform(&matrix::translation(0.0, -linear_change, 0.0))
+            lift -= linear_change;
         }
+        self.node
+            .borrow_mut()
+            .translate_world(&glm::vec3(0.0, lift, 0.0));
 
+        let up = glm::vec3(0.0, 1.0, 0.0);
         if Self::is_key_pressed(&self.properties.key_turn_right, key_state) {
-            self.node
-                .borrow_mut()
-                .apply_transform(&matrix::rotation_y(-angular_change))
+            self.node.borrow_mut().rotate_world(&up, -angular_change)
         }
         if Self::is_key_pressed(&self.properties.key_turn_left, key_state) {
-            self.node
-                .borrow_mut()
-                .apply_transform(&matrix::rotation_y(angular_change))
+            self.node.borrow_mut().rotate_world(&up, angular_change)
         }
 
+        let mut pitch = 0.0;
         if Self::is_key_pressed(&self.properties.key_look_up, key_state) {
-            self.attachment
-                .borrow_mut()
-                .apply_transform(&matrix::rotation_x(angular_change))
+            pitch += angular_change.to_radians();
         }
         if Self::is_key_pressed(&self.properties.key_look_down, key_state) {
-            self.attachment
-                .borrow_mut()
-                .apply_transform(&matrix::rotation_x(-angular_change))
+            pitch -= angular_change.to_radians();
+        }
+        if pitch != 0.0 {
+            let mut attachment = self.attachment.borrow_mut();
+            let mut euler = attachment.euler_angles(EulerOrder::Yxz);
+            let max_pitch = MAX_PITCH_DEGREES.to_radians();
+            euler.x =
+                Angle::from_radians((euler.x.to_radians() + pitch).clamp(-max_pitch, max_pitch));
+            attachment.set_euler_angles(&euler);
         }
     }
 
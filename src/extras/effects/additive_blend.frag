@@ -2,6 +2,8 @@
 
 precision highp float;
 
+#include <color_space>
+
 in vec2 v_UV;
 uniform sampler2D texture0;
 uniform sampler2D blendTexture;
@@ -13,5 +15,5 @@ void main()
 {
     vec4 originalColor = texture(texture0, v_UV);
     vec4 blendColor = texture(blendTexture, v_UV);
-    fragColor = originalStrength * originalColor + blendStrength * blendColor;
+    fragColor = encodeOutput(originalStrength * originalColor + blendStrength * blendColor);
 }
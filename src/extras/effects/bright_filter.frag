@@ -2,6 +2,8 @@
 
 precision highp float;
 
+#include <color_space>
+
 in vec2 v_UV;
 uniform sampler2D texture0;
 uniform float threshold;
@@ -13,5 +15,5 @@ void main()
     if (color.r + color.g + color.b < threshold) {
         discard;
     }
-    fragColor = color;
+    fragColor = encodeOutput(color);
 }
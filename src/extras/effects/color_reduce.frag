@@ -2,6 +2,8 @@
 
 precision highp float;
 
+#include <color_space>
+
 in vec2 v_UV;
 uniform sampler2D texture0;
 uniform float levels;
@@ -14,5 +16,5 @@ void main()
     vec4 reduced = round(color * levels) / levels;
     reduced.a = 1.0;
 
-    fragColor = reduced;
+    fragColor = encodeOutput(reduced);
 }
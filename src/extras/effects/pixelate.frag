@@ -2,6 +2,8 @@
 
 precision highp float;
 
+#include <color_space>
+
 in vec2 v_UV;
 uniform sampler2D texture0;
 uniform float pixelSize;
@@ -12,5 +14,5 @@ void main()
 {
     vec2 factor = resolution / pixelSize;
     vec2 newUV = floor(v_UV * factor) / factor;
-    fragColor = texture(texture0, newUV);
+    fragColor = encodeOutput(texture(texture0, newUV));
 }
@@ -2,6 +2,8 @@
 
 precision highp float;
 
+#include <color_space>
+
 in vec2 v_UV;
 uniform sampler2D texture0;
 uniform vec4 tintColor;
@@ -11,5 +13,5 @@ void main()
 {
     vec4 color = texture(texture0, v_UV);
     float gray = (color.r + color.g + color.b) / 3.0;
-    fragColor = vec4(gray * tintColor.rgb, 1.0);
+    fragColor = encodeOutput(vec4(gray * srgbToLinear(tintColor.rgb), 1.0));
 }
@@ -2,6 +2,8 @@
 
 precision highp float;
 
+#include <color_space>
+
 in vec2 v_UV;
 uniform sampler2D texture0;
 uniform vec2 textureSize;
@@ -18,5 +20,5 @@ void main()
         averageColor += texture(texture0, v_UV + offsetUV) * weight;
     }
     averageColor /= averageColor.a;
-    fragColor = averageColor;
+    fragColor = encodeOutput(averageColor);
 }
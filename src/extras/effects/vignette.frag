@@ -2,6 +2,8 @@
 
 precision highp float;
 
+#include <color_space>
+
 in vec2 v_UV;
 uniform sampler2D texture0;
 uniform float dimStart;
@@ -18,5 +20,5 @@ void main()
     float b = (d - dimEnd) / (dimStart - dimEnd);
     b = clamp(b, 0.0, 1.0);
 
-    fragColor = b * color + (1.0 - b) * dimColor;
+    fragColor = encodeOutput(b * color + (1.0 - b) * srgbToLinear(dimColor));
 }
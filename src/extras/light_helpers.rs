@@ -31,7 +31,7 @@ impl Default for DirectionalLightHelper {
 impl DirectionalLightHelper {
     pub fn create_mesh(self, context: &WebGl2RenderingContext, light: &Light) -> Result<Rc<Mesh>> {
         assert!(light.is_directional());
-        let color = light.color;
+        let color = light.srgb_color();
         let grid_helper = GridHelper {
             size: self.size,
             divisions: self.divisions,
@@ -68,7 +68,7 @@ impl Default for PointLightHelper {
 
 impl PointLightHelper {
     pub fn create_mesh(self, context: &WebGl2RenderingContext, light: &Light) -> Result<Rc<Mesh>> {
-        let color = light.color;
+        let color = light.srgb_color();
         let geometry = Geometry::from_with_context(
             context,
             Sphere {
@@ -1,4 +1,5 @@
 pub mod axes_helper;
+pub mod behaviours;
 pub mod camera_controller;
 pub mod effects;
 pub mod grid_helper;
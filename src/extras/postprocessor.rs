@@ -85,6 +85,15 @@ impl Postprocessor {
         }
     }
 
+    /// The scene rendered by the first pass.
+    pub fn scene(&self) -> &Scene {
+        &self.scenes[0]
+    }
+
+    pub fn scene_mut(&mut self) -> &mut Scene {
+        &mut self.scenes[0]
+    }
+
     pub fn get_texture(&self, index: usize) -> Option<Rc<Texture>> {
         self.render_targets[index]
             .as_ref()
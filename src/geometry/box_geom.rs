@@ -1,6 +1,7 @@
 use std::rc::Rc;
 
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
 use web_sys::WebGl2RenderingContext;
 
 use crate::{
@@ -11,6 +12,8 @@ use crate::{
 
 use super::util;
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
 pub struct BoxGeometry {
     pub width: f32,
     pub height: f32,
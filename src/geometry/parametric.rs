@@ -5,6 +5,7 @@ use std::{
 
 use anyhow::Result;
 use glm::Vec3;
+use serde::{Deserialize, Serialize};
 use web_sys::WebGl2RenderingContext;
 
 use crate::{
@@ -12,7 +13,7 @@ use crate::{
     base::{
         color,
         convert::FromWithContext,
-        math::{angle::Angle, matrix},
+        math::{angle::Angle, matrix, normal::NormalMode},
     },
 };
 
@@ -92,8 +93,6 @@ impl TryFrom<ParametricSurface> for TypedGeometry {
 
         let mut vertex_normal_data =
             Vec::with_capacity((6 * surface.u_resolution * surface.v_resolution).into());
-        let mut face_normal_data =
-            Vec::with_capacity((6 * surface.u_resolution * surface.v_resolution).into());
 
         for x_index in 0..usize::from(surface.u_resolution) {
             for y_index in 0..usize::from(surface.v_resolution) {
@@ -116,23 +115,19 @@ impl TryFrom<ParametricSurface> for TypedGeometry {
                 let n_d = normals[x_index][y_index + 1];
                 let n_c = normals[x_index + 1][y_index + 1];
                 vertex_normal_data.extend([n_a, n_b, n_c, n_a, n_c, n_d]);
-
-                let fn_0 = calc_normal(&p_a, &p_b, &p_c);
-                let fn_1 = calc_normal(&p_a, &p_c, &p_d);
-                face_normal_data.extend([fn_0, fn_0, fn_0, fn_1, fn_1, fn_1]);
             }
         }
 
-        TypedGeometry::new(
+        let mut geometry = TypedGeometry::new(
             position_data,
             Some(texture_data),
-            Some(if surface.face_normal {
-                face_normal_data
-            } else {
-                vertex_normal_data
-            }),
+            Some(vertex_normal_data),
             Some(color_data),
-        )
+        )?;
+        if surface.face_normal {
+            geometry.compute_normals(NormalMode::Flat)?;
+        }
+        Ok(geometry)
     }
 }
 
@@ -146,11 +141,13 @@ impl FromWithContext<WebGl2RenderingContext, ParametricSurface> for Geometry {
     }
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
 pub struct Plane {
-    width: f32,
-    height: f32,
-    width_segments: u16,
-    height_segments: u16,
+    pub width: f32,
+    pub height: f32,
+    pub width_segments: u16,
+    pub height_segments: u16,
 }
 
 impl Default for Plane {
@@ -228,6 +225,8 @@ impl FromWithContext<WebGl2RenderingContext, Ellipsoid> for Geometry {
     }
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
 pub struct Sphere {
     pub radius: f32,
     pub radius_segments: u16,
@@ -340,6 +339,8 @@ impl FromWithContext<WebGl2RenderingContext, Cylindrical> for Geometry {
     }
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
 pub struct Cylinder {
     pub radius: f32,
     pub height: f32,
@@ -420,6 +421,8 @@ impl FromWithContext<WebGl2RenderingContext, Prism> for Geometry {
     }
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
 pub struct Cone {
     pub radius: f32,
     pub height: f32,
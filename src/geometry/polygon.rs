@@ -1,6 +1,7 @@
 use std::f32::consts::TAU;
 
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
 use web_sys::WebGl2RenderingContext;
 
 use crate::{
@@ -8,6 +9,8 @@ use crate::{
     base::{color, convert::FromWithContext, math::angle::Angle},
 };
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
 pub struct Polygon {
     pub sides: u16,
     pub radius: f32,
@@ -1,11 +1,20 @@
+use std::{collections::HashMap, rc::Rc};
+
 use web_sys::WebGl2RenderingContext;
 
 use crate::{
     base::{
+        application::Loop,
         input::KeyState,
-        util::shared_ref::{self, SharedRef},
+        math::aabb::Aabb,
+        util::{
+            level::Level,
+            shared_ref::{self, SharedRef},
+        },
     },
+    classic::light::{LightNode, Lights},
     core::{
+        animation::Animation,
         camera::Camera,
         node::Node,
         program::{Program, UpdateProgramUniforms, UpdateUniform},
@@ -15,7 +24,10 @@ use crate::{
     extras::camera_controller::CameraController,
 };
 
-use super::user::light_controller::LightController;
+use super::user::{
+    light_controller::LightController,
+    scene_controller::{self, SceneController},
+};
 
 #[derive(Debug)]
 pub struct Root {
@@ -25,16 +37,25 @@ pub struct Root {
     renderer: Renderer,
     current_scene_index: Option<usize>,
     current_camera_index: Option<usize>,
-    camera_controller: Option<CameraController>,
+    camera_controllers: HashMap<usize, CameraController>,
+    scene_controller: SceneController,
     light_controller: SharedRef<LightController>,
+    animations: Vec<Animation>,
+    animation_time: f32,
+    light_nodes: Vec<Rc<LightNode>>,
+    lights: SharedRef<Lights>,
 }
 
 impl Root {
+    const DEFAULT_FIELD_OF_VIEW: f32 = 60.0;
+
     pub fn initialize(
         context: &WebGl2RenderingContext,
         mut cameras: Vec<SharedRef<Camera>>,
         mut scenes: Vec<Scene>,
         scene: Option<usize>,
+        animations: Vec<Animation>,
+        light_nodes: Vec<Rc<LightNode>>,
     ) -> Self {
         scenes
             .iter_mut()
@@ -44,10 +65,14 @@ impl Root {
             scenes.iter().map(|scene| scene.depth()).collect::<Vec<_>>()
         );
         let light_controller = shared_ref::new(LightController::new(Default::default()));
+        let lights = shared_ref::new(Lights::new());
         let renderer = Renderer::initialize(
             context,
             Default::default(),
-            Box::new(GlobalUniformUpdater::new(light_controller.clone())),
+            Box::new(GlobalUniformUpdater::new(
+                light_controller.clone(),
+                lights.clone(),
+            )),
         );
         let mut root = Self {
             cameras,
@@ -56,21 +81,35 @@ impl Root {
             renderer,
             current_scene_index: None,
             current_camera_index: None,
-            camera_controller: None,
+            camera_controllers: HashMap::new(),
+            scene_controller: SceneController::new(scene_controller::Properties::default()),
             light_controller,
+            animations,
+            animation_time: 0.0,
+            light_nodes,
+            lights,
         };
         root.set_default_scene();
         root
     }
 
+    pub fn set_scene_controller_properties(&mut self, properties: scene_controller::Properties) {
+        self.scene_controller = SceneController::new(properties);
+    }
+
     pub fn set_default_scene(&mut self) {
         self.set_scene_by_index(self.scene)
     }
 
     pub fn set_camera_by_index(&mut self, camera_index: Option<usize>) {
         self.current_camera_index = camera_index;
-        self.camera_controller =
-            camera_index.and_then(|index| CameraController::make_for_camera(&self.cameras[index]))
+        if let Some(index) = camera_index {
+            if !self.camera_controllers.contains_key(&index) {
+                if let Some(controller) = CameraController::make_for_camera(&self.cameras[index]) {
+                    self.camera_controllers.insert(index, controller);
+                }
+            }
+        }
     }
 
     pub fn set_scene_by_index(&mut self, scene_index: Option<usize>) {
@@ -85,11 +124,64 @@ impl Root {
     ) {
         self.current_scene_index = scene_index;
         self.set_camera_by_index(camera_index);
+        self.set_lights_for_scene(scene_index);
+    }
+
+    pub fn current_scene(&self) -> Option<&Scene> {
+        self.current_scene_index.map(|index| &self.scenes[index])
+    }
+
+    pub fn current_camera(&self) -> Option<&SharedRef<Camera>> {
+        self.current_camera_index.map(|index| &self.cameras[index])
+    }
+
+    pub fn select_next_scene(&mut self) {
+        if self.scenes.is_empty() {
+            return;
+        }
+        let next = self
+            .current_scene_index
+            .map_or(0, |index| (index + 1) % self.scenes.len());
+        self.set_scene_by_index(Some(next))
+    }
+
+    pub fn select_next_camera(&mut self) {
+        let Some(scene) = self.current_scene() else {
+            return;
+        };
+        let candidates: Vec<usize> = (0..self.cameras.len())
+            .filter(|index| scene.contains_camera(&self.cameras[*index]))
+            .collect();
+        let next = match self
+            .current_camera_index
+            .and_then(|current| candidates.iter().position(|index| *index == current))
+        {
+            Some(position) => candidates.get((position + 1) % candidates.len()),
+            None => candidates.first(),
+        };
+        if let Some(next) = next.copied() {
+            self.set_camera_by_index(Some(next))
+        }
     }
 
-    pub fn update(&self, key_state: &KeyState) {
+    pub fn update(&mut self, key_state: &KeyState) {
+        let selection = self.scene_controller.update(key_state);
+        if selection.next_scene {
+            self.select_next_scene();
+        }
+        if selection.next_camera {
+            self.select_next_camera();
+        }
         self.light_controller.borrow_mut().update(key_state);
-        if let Some(camera_controller) = &self.camera_controller {
+        self.update_animations();
+        if let Some(index) = self.current_scene_index {
+            self.scenes[index].update(Loop::SECS_PER_UPDATE as f32, key_state);
+        }
+        self.lights.borrow().update();
+        if let Some(camera_controller) = self
+            .current_camera_index
+            .and_then(|index| self.camera_controllers.get(&index))
+        {
             camera_controller.update(key_state);
         }
     }
@@ -106,6 +198,26 @@ impl Root {
         }
     }
 
+    fn update_animations(&mut self) {
+        self.animation_time += Loop::SECS_PER_UPDATE as f32;
+        for animation in self.animations.iter() {
+            animation.apply(self.animation_time);
+        }
+    }
+
+    fn set_lights_for_scene(&mut self, scene_index: Option<usize>) {
+        let mut lights = Lights::new();
+        if let Some(scene) = scene_index.and_then(|index| self.scenes.get(index)) {
+            for light_node in self.light_nodes.iter() {
+                if scene.contains_node(light_node.node()) {
+                    lights.add_node(Rc::clone(light_node));
+                }
+            }
+        }
+        lights.update();
+        *self.lights.borrow_mut() = lights;
+    }
+
     fn find_camera_for_scene(&self, scene_index: usize) -> Option<usize> {
         self.scenes.get(scene_index).and_then(|scene| {
             self.cameras
@@ -116,20 +228,31 @@ impl Root {
 
     fn ensure_camera_for_scene(scene: &mut Scene, cameras: &mut Vec<SharedRef<Camera>>) {
         if !scene.has_some_camera() {
-            let camera = Self::default_camera();
+            let (camera, position) = match scene.bounds() {
+                Some(bounds) => Self::framing_camera(&bounds),
+                None => (Self::default_camera(0.01, 100.0), glm::vec3(0.5, 0.5, 2.0)),
+            };
             let node = Node::with_camera_and_name(camera.clone(), "Default camera");
-            node.borrow_mut().set_position(&glm::vec3(0.5, 0.5, 2.0));
+            node.borrow_mut().set_position(&position);
             scene.add_node(node);
             cameras.push(camera);
         }
     }
 
-    fn default_camera() -> SharedRef<Camera> {
+    /// Places a camera on the +Z axis so that the bounding sphere of `bounds` fits the view.
+    fn framing_camera(bounds: &Aabb) -> (SharedRef<Camera>, glm::Vec3) {
+        let radius = (bounds.size().norm() / 2.0).max(1e-3);
+        let distance = radius / (Self::DEFAULT_FIELD_OF_VIEW.to_radians() / 2.0).sin();
+        let camera = Self::default_camera(distance / 100.0, (distance + radius) * 10.0);
+        (camera, bounds.center() + glm::vec3(0.0, 0.0, distance))
+    }
+
+    fn default_camera(z_near: f32, z_far: f32) -> SharedRef<Camera> {
         Camera::perspective(
             1.0,
-            60_f32.to_radians(),
-            0.01,
-            Some(100.0),
+            Self::DEFAULT_FIELD_OF_VIEW.to_radians(),
+            z_near,
+            Some(z_far),
             Some("Default camera".into()),
         )
     }
@@ -138,11 +261,17 @@ impl Root {
 #[derive(Debug, Clone)]
 struct GlobalUniformUpdater {
     light_controller: SharedRef<LightController>,
+    lights: SharedRef<Lights>,
 }
 
 impl GlobalUniformUpdater {
-    fn new(light_controller: SharedRef<LightController>) -> Self {
-        Self { light_controller }
+    const MAX_LIGHTS: usize = 8;
+
+    fn new(light_controller: SharedRef<LightController>, lights: SharedRef<Lights>) -> Self {
+        Self {
+            light_controller,
+            lights,
+        }
     }
 }
 
@@ -150,5 +279,18 @@ impl UpdateProgramUniforms for GlobalUniformUpdater {
     fn update_program_uniforms(&self, context: &WebGl2RenderingContext, program: &Program) {
         let light_direction = self.light_controller.borrow().get_light_direction();
         light_direction.update_uniform(context, "u_Light", program);
+        let lights = self.lights.borrow();
+        let light_count = lights.light_count().min(Self::MAX_LIGHTS);
+        (light_count as i32).update_uniform(context, "u_LightCount", program);
+        lights.for_each_light_indexed(|(index, light)| {
+            if index < light_count {
+                light.borrow().update_uniform_with_level(
+                    context,
+                    &format!("u_Lights[{}]", index),
+                    program,
+                    Level::Ignore,
+                );
+            }
+        });
     }
 }
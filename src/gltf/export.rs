@@ -0,0 +1,890 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use anyhow::{anyhow, bail, Result};
+use serde::Serialize;
+use web_sys::WebGl2RenderingContext;
+
+use crate::{
+    base::util::{shared_ref::WeakRef, value::Value},
+    core::{
+        accessor::{Accessor, AccessorType},
+        camera::{Camera, CameraType},
+        custom_properties::CustomProperties,
+        material::{AlphaMode, Clearcoat, Material, TextureRef, Transmission},
+        mesh::Mesh,
+        node::Node,
+        scene::Scene,
+        skin::Skin,
+        texture::Texture,
+    },
+};
+
+use super::{glb, load::data};
+
+const GENERATOR: &str = "crate glTF exporter";
+const UNLIT_EXTENSION: &str = "KHR_materials_unlit";
+const EMISSIVE_STRENGTH_EXTENSION: &str = "KHR_materials_emissive_strength";
+const TEXTURE_TRANSFORM_EXTENSION: &str = "KHR_texture_transform";
+const CLEARCOAT_EXTENSION: &str = "KHR_materials_clearcoat";
+const TRANSMISSION_EXTENSION: &str = "KHR_materials_transmission";
+const QUANTIZATION_EXTENSION: &str = "KHR_mesh_quantization";
+
+#[derive(Debug)]
+pub struct Exported {
+    gltf: data::Gltf,
+    bin: Vec<u8>,
+}
+
+impl Exported {
+    pub fn to_gltf(&self, bin_uri: &str) -> Result<String> {
+        let mut gltf = self.gltf.clone();
+        for buffer in gltf.buffers.iter_mut().flatten() {
+            buffer.uri = Some(String::from(bin_uri));
+        }
+        self::stringify(&gltf)
+    }
+
+    pub fn to_glb(&self) -> Result<Vec<u8>> {
+        Ok(glb::write(&self::stringify(&self.gltf)?, &self.bin))
+    }
+
+    pub fn bin(&self) -> &[u8] {
+        &self.bin
+    }
+}
+
+pub fn export(scene: &Scene) -> Result<Exported> {
+    let mut exporter = Exporter::default();
+    let nodes = scene
+        .nodes()
+        .iter()
+        .map(|node| exporter.add_node(node))
+        .collect::<Result<_>>()?;
+    exporter.add_skins()?;
+    Ok(exporter.finish(nodes))
+}
+
+fn stringify(gltf: &data::Gltf) -> Result<String> {
+    let serializer = serde_wasm_bindgen::Serializer::new().serialize_maps_as_objects(true);
+    let value = gltf
+        .serialize(&serializer)
+        .map_err(|error| anyhow!("Cannot serialize glTF: {:#?}", error))?;
+    js_sys::JSON::stringify(&value)
+        .map(String::from)
+        .map_err(|error| anyhow!("Cannot stringify glTF: {:#?}", error))
+}
+
+#[derive(Debug, Default)]
+struct Exporter {
+    accessors: Vec<data::Accessor>,
+    buffer_views: Vec<data::BufferView>,
+    cameras: Vec<data::Camera>,
+    images: Vec<data::Image>,
+    materials: Vec<data::Material>,
+    meshes: Vec<data::Mesh>,
+    nodes: Vec<data::Node>,
+    samplers: Vec<data::Sampler>,
+    skins: Vec<data::Skin>,
+    textures: Vec<data::Texture>,
+    extensions_used: Vec<String>,
+    extensions_required: Vec<String>,
+    bin: Vec<u8>,
+    skinned_nodes: Vec<(u32, Rc<Skin>)>,
+    accessor_indices: HashMap<*const Accessor, u32>,
+    camera_indices: HashMap<*const RefCell<Camera>, u32>,
+    material_indices: HashMap<*const Material, u32>,
+    mesh_indices: HashMap<*const Mesh, u32>,
+    node_indices: HashMap<*const RefCell<Node>, u32>,
+    skin_indices: HashMap<*const Skin, u32>,
+    texture_indices: HashMap<*const Texture, u32>,
+}
+
+impl Exporter {
+    fn finish(self, scene_nodes: Vec<u32>) -> Exported {
+        let buffers = if self.bin.is_empty() {
+            vec![]
+        } else {
+            vec![data::Buffer {
+                uri: None,
+                byte_length: self.bin.len() as u32,
+                extensions: None,
+            }]
+        };
+        let gltf = data::Gltf {
+            asset: data::Asset {
+                copyright: None,
+                generator: Some(String::from(GENERATOR)),
+                version: String::from("2.0"),
+            },
+            accessors: self::non_empty(self.accessors),
+            animations: None,
+            buffers: self::non_empty(buffers),
+            buffer_views: self::non_empty(self.buffer_views),
+            cameras: self::non_empty(self.cameras),
+            extensions: None,
+            extensions_used: self::non_empty(self.extensions_used),
+            extensions_required: self::non_empty(self.extensions_required),
+            images: self::non_empty(self.images),
+            materials: self::non_empty(self.materials),
+            meshes: self::non_empty(self.meshes),
+            nodes: self::non_empty(self.nodes),
+            samplers: self::non_empty(self.samplers),
+            scene: Some(0),
+            scenes: Some(vec![data::Scene {
+                name: None,
+                nodes: Some(scene_nodes),
+            }]),
+            skins: self::non_empty(self.skins),
+            textures: self::non_empty(self.textures),
+        };
+        Exported {
+            gltf,
+            bin: self.bin,
+        }
+    }
+
+    fn add_node(&mut self, node_ref: &RefCell<Node>) -> Result<u32> {
+        let node = node_ref.borrow();
+        let children = node
+            .children()
+            .iter()
+            .map(|child| self.add_node(child))
+            .collect::<Result<Vec<_>>>()?;
+        let mesh = node.mesh().map(|mesh| self.add_mesh(mesh)).transpose()?;
+        let camera = node.camera().map(|camera| self.add_camera(camera));
+        let is_morphed = node
+            .mesh()
+            .is_some_and(|mesh| self::has_morph_targets(mesh));
+        let index = self.nodes.len() as u32;
+        if let Some(skin) = node.skin() {
+            self.skinned_nodes.push((index, Rc::clone(skin)));
+        }
+        self.node_indices.insert(node_ref, index);
+        self.nodes.push(data::Node {
+            camera,
+            children: self::non_empty(children),
+            matrix: None,
+            mesh,
+            translation: Some(node.position().into())
+                .filter(|translation| *translation != [0.0, 0.0, 0.0]),
+            rotation: Some(node.rotation().coords.into())
+                .filter(|rotation| *rotation != [0.0, 0.0, 0.0, 1.0]),
+            scale: Some(node.scale().into()).filter(|scale| *scale != [1.0, 1.0, 1.0]),
+            skin: None,
+            weights: node.weights().filter(|_| is_morphed).map(<[f32]>::to_vec),
+            name: node.name().map(String::from),
+            extensions: None,
+            extras: self::extras(node.custom_properties()),
+        });
+        Ok(index)
+    }
+
+    fn add_mesh(&mut self, mesh: &Rc<Mesh>) -> Result<u32> {
+        if let Some(index) = self.mesh_indices.get(&Rc::as_ptr(mesh)) {
+            return Ok(*index);
+        }
+        let mut primitives = Vec::with_capacity(mesh.primitives().len());
+        for primitive in mesh.primitives() {
+            let mut attributes = HashMap::new();
+            for (name, accessor) in primitive.attributes() {
+                attributes.insert(
+                    name.clone(),
+                    self.add_vertex_accessor(name, accessor, false)?,
+                );
+            }
+            let indices = primitive
+                .indices()
+                .map(|accessor| {
+                    self.add_accessor(accessor, Some(WebGl2RenderingContext::ELEMENT_ARRAY_BUFFER))
+                })
+                .transpose()?;
+            let mut targets = Vec::with_capacity(primitive.morph_targets().len());
+            for morph_target in primitive.morph_targets() {
+                let mut target = HashMap::new();
+                for (name, accessor) in morph_target {
+                    target.insert(
+                        name.clone(),
+                        self.add_vertex_accessor(name, accessor, true)?,
+                    );
+                }
+                targets.push(target);
+            }
+            primitives.push(data::Primitive {
+                attributes,
+                indices,
+                material: Some(self.add_material(primitive.material())?),
+                mode: primitive.mode(),
+                targets: self::non_empty(targets),
+            });
+        }
+        let weights = if self::has_morph_targets(mesh) {
+            self::non_empty(mesh.weights().to_vec())
+        } else {
+            None
+        };
+        let index = self.meshes.len() as u32;
+        self.meshes.push(data::Mesh {
+            primitives,
+            weights,
+            name: mesh.name().map(String::from),
+            extensions: None,
+            extras: self::extras(mesh.custom_properties()),
+        });
+        self.mesh_indices.insert(Rc::as_ptr(mesh), index);
+        Ok(index)
+    }
+
+    fn add_vertex_accessor(
+        &mut self,
+        name: &str,
+        accessor: &Rc<Accessor>,
+        is_target: bool,
+    ) -> Result<u32> {
+        self.check_quantization(
+            name,
+            accessor.component_type,
+            accessor.normalized(),
+            is_target,
+        );
+        self.add_accessor(accessor, Some(WebGl2RenderingContext::ARRAY_BUFFER))
+    }
+
+    fn check_quantization(
+        &mut self,
+        name: &str,
+        component_type: u32,
+        normalized: bool,
+        is_target: bool,
+    ) {
+        if !self::is_core_attribute(name, component_type, normalized, is_target) {
+            self.require_extension(QUANTIZATION_EXTENSION);
+        }
+    }
+
+    fn add_accessor(&mut self, accessor: &Rc<Accessor>, target: Option<u32>) -> Result<u32> {
+        if let Some(index) = self.accessor_indices.get(&Rc::as_ptr(accessor)) {
+            return Ok(*index);
+        }
+        let index = if accessor.component_type == WebGl2RenderingContext::UNSIGNED_INT {
+            let values = accessor.read_indices()?;
+            self.add_indices(&values, accessor.accessor_type(), target)
+        } else {
+            let values = accessor.read_f32()?;
+            self.add_values(
+                &values,
+                accessor.accessor_type(),
+                accessor.component_type,
+                accessor.normalized(),
+                target,
+            )?
+        };
+        let exported = &mut self.accessors[index as usize];
+        if let (Some(min), Some(max)) = (accessor.min(), accessor.max()) {
+            exported.min = Some(min.to_vec());
+            exported.max = Some(max.to_vec());
+        }
+        self.accessor_indices.insert(Rc::as_ptr(accessor), index);
+        Ok(index)
+    }
+
+    fn add_values(
+        &mut self,
+        values: &[f32],
+        accessor_type: AccessorType,
+        component_type: u32,
+        normalized: bool,
+        target: Option<u32>,
+    ) -> Result<u32> {
+        let data = self::encode(values, component_type, normalized)?;
+        let size = accessor_type.size() as usize;
+        let (min, max) = self::bounds(values, size);
+        Ok(self.add_data(
+            data,
+            target,
+            data::Accessor {
+                buffer_view: None,
+                byte_offset: 0,
+                component_type,
+                count: (values.len() / size) as i32,
+                accessor_type: self::type_name(accessor_type),
+                min,
+                max,
+                normalized,
+            },
+        ))
+    }
+
+    /// Writes unsigned integers exactly, which `add_values` cannot do above 2^24.
+    fn add_indices(
+        &mut self,
+        values: &[u32],
+        accessor_type: AccessorType,
+        target: Option<u32>,
+    ) -> u32 {
+        let data = values
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect();
+        let size = accessor_type.size() as usize;
+        let bounds: Vec<f32> = values.iter().map(|value| *value as f32).collect();
+        let (min, max) = self::bounds(&bounds, size);
+        self.add_data(
+            data,
+            target,
+            data::Accessor {
+                buffer_view: None,
+                byte_offset: 0,
+                component_type: WebGl2RenderingContext::UNSIGNED_INT,
+                count: (values.len() / size) as i32,
+                accessor_type: self::type_name(accessor_type),
+                min,
+                max,
+                normalized: false,
+            },
+        )
+    }
+
+    fn add_data(&mut self, data: Vec<u8>, target: Option<u32>, accessor: data::Accessor) -> u32 {
+        while !self.bin.len().is_multiple_of(4) {
+            self.bin.push(0);
+        }
+        let buffer_view = self.buffer_views.len() as u32;
+        self.buffer_views.push(data::BufferView {
+            buffer: 0,
+            byte_offset: self.bin.len() as u32,
+            byte_length: data.len() as u32,
+            byte_stride: None,
+            target,
+            extensions: None,
+        });
+        self.bin.extend(data);
+        let index = self.accessors.len() as u32;
+        self.accessors.push(data::Accessor {
+            buffer_view: Some(buffer_view),
+            ..accessor
+        });
+        index
+    }
+
+    /// Runs after all nodes are added, as joints may follow the skinned node.
+    fn add_skins(&mut self) -> Result<()> {
+        for (node, skin) in std::mem::take(&mut self.skinned_nodes) {
+            let index = self.add_skin(&skin)?;
+            self.nodes[node as usize].skin = Some(index);
+        }
+        Ok(())
+    }
+
+    fn add_skin(&mut self, skin: &Rc<Skin>) -> Result<u32> {
+        if let Some(index) = self.skin_indices.get(&Rc::as_ptr(skin)) {
+            return Ok(*index);
+        }
+        let joints = skin
+            .joints()
+            .iter()
+            .map(|joint| self.node_index(joint))
+            .collect::<Result<_>>()?;
+        let skeleton = skin
+            .skeleton()
+            .map(|skeleton| self.node_index(skeleton))
+            .transpose()?;
+        let values: Vec<f32> = skin
+            .inverse_bind_matrices()
+            .iter()
+            .flat_map(|matrix| matrix.as_slice().to_vec())
+            .collect();
+        let inverse_bind_matrices = self.add_values(
+            &values,
+            AccessorType::mat(4),
+            WebGl2RenderingContext::FLOAT,
+            false,
+            None,
+        )?;
+        let index = self.skins.len() as u32;
+        self.skins.push(data::Skin {
+            inverse_bind_matrices: Some(inverse_bind_matrices),
+            skeleton,
+            joints,
+            name: skin.name().map(String::from),
+        });
+        self.skin_indices.insert(Rc::as_ptr(skin), index);
+        Ok(index)
+    }
+
+    fn node_index(&self, node: &WeakRef<Node>) -> Result<u32> {
+        node.upgrade()
+            .and_then(|node| self.node_indices.get(&Rc::as_ptr(&node)).copied())
+            .ok_or_else(|| anyhow!("Skin joint is not part of the exported scene"))
+    }
+
+    fn add_material(&mut self, material: &Rc<Material>) -> Result<u32> {
+        if let Some(index) = self.material_indices.get(&Rc::as_ptr(material)) {
+            return Ok(*index);
+        }
+        let properties = material.pbr_properties();
+        let base_color_texture = properties
+            .base_color_texture
+            .as_ref()
+            .map(|texture_ref| self.add_texture_info(texture_ref))
+            .transpose()?;
+        let metallic_roughness_texture = properties
+            .metallic_roughness_texture
+            .as_ref()
+            .map(|texture_ref| self.add_texture_info(texture_ref))
+            .transpose()?;
+        let emissive_texture = properties
+            .emissive_texture
+            .as_ref()
+            .map(|texture_ref| self.add_texture_info(texture_ref))
+            .transpose()?;
+        let normal_texture = properties
+            .normal_texture
+            .as_ref()
+            .map(|texture_ref| {
+                self.add_texture_info(texture_ref)
+                    .map(|info| data::NormalTextureInfo {
+                        index: info.index,
+                        tex_coord: info.tex_coord,
+                        scale: properties.normal_scale,
+                        extensions: info.extensions,
+                    })
+            })
+            .transpose()?;
+        let emissive_strength = properties.emissive_factor.max();
+        let (emissive_factor, emissive_strength) = if emissive_strength > 1.0 {
+            self.use_extension(EMISSIVE_STRENGTH_EXTENSION);
+            (
+                properties.emissive_factor / emissive_strength,
+                Some(data::MaterialsEmissiveStrength { emissive_strength }),
+            )
+        } else {
+            (properties.emissive_factor, None)
+        };
+        let unlit = if properties.unlit {
+            self.use_extension(UNLIT_EXTENSION);
+            Some(data::MaterialsUnlit {})
+        } else {
+            None
+        };
+        let clearcoat = properties
+            .clearcoat
+            .as_ref()
+            .map(|clearcoat| self.add_clearcoat(clearcoat))
+            .transpose()?;
+        let transmission = properties
+            .transmission
+            .as_ref()
+            .map(|transmission| self.add_transmission(transmission))
+            .transpose()?;
+        let extensions = if unlit.is_some()
+            || emissive_strength.is_some()
+            || clearcoat.is_some()
+            || transmission.is_some()
+        {
+            Some(data::MaterialExtensions {
+                khr_materials_unlit: unlit,
+                khr_materials_emissive_strength: emissive_strength,
+                khr_materials_clearcoat: clearcoat,
+                khr_materials_transmission: transmission,
+                others: Default::default(),
+            })
+        } else {
+            None
+        };
+        let (alpha_mode, alpha_cutoff) = match material.alpha_mode() {
+            AlphaMode::Opaque => ("OPAQUE", 0.5),
+            AlphaMode::Mask { cutoff } => ("MASK", *cutoff),
+            AlphaMode::Blend => ("BLEND", 0.5),
+        };
+        let index = self.materials.len() as u32;
+        self.materials.push(data::Material {
+            name: material.name().map(String::from),
+            pbr_metallic_roughness: data::PbrMetallicRoughness {
+                base_color_factor: properties.base_color_factor.into(),
+                base_color_texture,
+                metallic_factor: properties.metallic_factor,
+                roughness_factor: properties.roughness_factor,
+                metallic_roughness_texture,
+            },
+            normal_texture,
+            emissive_texture,
+            emissive_factor: emissive_factor.into(),
+            alpha_mode: String::from(alpha_mode),
+            alpha_cutoff,
+            double_sided: material.double_sided(),
+            extensions,
+            extras: self::extras(material.custom_properties()),
+        });
+        self.material_indices.insert(Rc::as_ptr(material), index);
+        Ok(index)
+    }
+
+    fn add_clearcoat(&mut self, clearcoat: &Clearcoat) -> Result<data::MaterialsClearcoat> {
+        self.use_extension(CLEARCOAT_EXTENSION);
+        Ok(data::MaterialsClearcoat {
+            clearcoat_factor: clearcoat.factor,
+            clearcoat_texture: clearcoat
+                .texture
+                .as_ref()
+                .map(|texture_ref| self.add_texture_info(texture_ref))
+                .transpose()?,
+            clearcoat_roughness_factor: clearcoat.roughness_factor,
+            clearcoat_roughness_texture: clearcoat
+                .roughness_texture
+                .as_ref()
+                .map(|texture_ref| self.add_texture_info(texture_ref))
+                .transpose()?,
+        })
+    }
+
+    fn add_transmission(
+        &mut self,
+        transmission: &Transmission,
+    ) -> Result<data::MaterialsTransmission> {
+        self.use_extension(TRANSMISSION_EXTENSION);
+        Ok(data::MaterialsTransmission {
+            transmission_factor: transmission.factor,
+            transmission_texture: transmission
+                .texture
+                .as_ref()
+                .map(|texture_ref| self.add_texture_info(texture_ref))
+                .transpose()?,
+        })
+    }
+
+    fn add_texture_info(&mut self, texture_ref: &TextureRef) -> Result<data::TextureInfo> {
+        let index = self.add_texture(texture_ref.texture())?;
+        let transform = texture_ref.transform();
+        let is_identity = transform.offset == glm::vec2(0.0, 0.0)
+            && transform.rotation == 0.0
+            && transform.scale == glm::vec2(1.0, 1.0);
+        let extensions = if is_identity {
+            None
+        } else {
+            self.use_extension(TEXTURE_TRANSFORM_EXTENSION);
+            Some(data::TextureInfoExtensions {
+                khr_texture_transform: Some(data::TextureTransform {
+                    offset: transform.offset.into(),
+                    rotation: transform.rotation,
+                    scale: transform.scale.into(),
+                    tex_coord: None,
+                }),
+            })
+        };
+        Ok(data::TextureInfo {
+            index,
+            tex_coord: texture_ref.tex_coord(),
+            extensions,
+        })
+    }
+
+    fn add_texture(&mut self, texture: &Texture) -> Result<u32> {
+        let key: *const Texture = texture;
+        if let Some(index) = self.texture_indices.get(&key) {
+            return Ok(*index);
+        }
+        let sampler = texture.sampler();
+        let sampler_index = self.samplers.len() as u32;
+        self.samplers.push(data::Sampler {
+            mag_filter: Some(sampler.mag_filter()),
+            min_filter: Some(sampler.min_filter()),
+            wrap_s: sampler.wrap_s(),
+            wrap_t: sampler.wrap_t(),
+        });
+        let image_index = self.images.len() as u32;
+        self.images.push(data::Image {
+            uri: Some(texture.source().to_data_url()?),
+            buffer_view: None,
+            mime_type: Some(String::from("image/png")),
+            name: None,
+        });
+        let index = self.textures.len() as u32;
+        self.textures.push(data::Texture {
+            sampler: Some(sampler_index),
+            source: Some(image_index),
+            extensions: None,
+        });
+        self.texture_indices.insert(key, index);
+        Ok(index)
+    }
+
+    fn add_camera(&mut self, camera: &Rc<RefCell<Camera>>) -> u32 {
+        if let Some(index) = self.camera_indices.get(&Rc::as_ptr(camera)) {
+            return *index;
+        }
+        let camera_ref = camera.borrow();
+        let result = match camera_ref.camera_type() {
+            CameraType::Perspective(perspective) => data::Camera {
+                orthographic: None,
+                perspective: Some(data::Perspective {
+                    aspect_ratio: perspective
+                        .fixed_aspect_ratio
+                        .then_some(perspective.aspect_ratio),
+                    y_fov: perspective.y_fov,
+                    z_far: perspective.z_far,
+                    z_near: perspective.z_near,
+                }),
+                camera_type: String::from("perspective"),
+                name: camera_ref.name().map(String::from),
+            },
+            CameraType::Orthographic(orthographic) => data::Camera {
+                orthographic: Some(data::Orthographic {
+                    x_mag: (orthographic.x_right - orthographic.x_left) / 2.0,
+                    y_mag: (orthographic.y_top - orthographic.y_bottom) / 2.0,
+                    z_far: orthographic.z_far,
+                    z_near: orthographic.z_near,
+                }),
+                perspective: None,
+                camera_type: String::from("orthographic"),
+                name: camera_ref.name().map(String::from),
+            },
+        };
+        let index = self.cameras.len() as u32;
+        self.cameras.push(result);
+        self.camera_indices.insert(Rc::as_ptr(camera), index);
+        index
+    }
+
+    fn use_extension(&mut self, extension: &str) {
+        if !self.extensions_used.iter().any(|used| used == extension) {
+            self.extensions_used.push(String::from(extension));
+        }
+    }
+
+    fn require_extension(&mut self, extension: &str) {
+        self.use_extension(extension);
+        if !self
+            .extensions_required
+            .iter()
+            .any(|required| required == extension)
+        {
+            self.extensions_required.push(String::from(extension));
+        }
+    }
+}
+
+fn has_morph_targets(mesh: &Mesh) -> bool {
+    mesh.primitives()
+        .iter()
+        .any(|primitive| !primitive.morph_targets().is_empty())
+}
+
+/// Whether core glTF allows the attribute's component type without `KHR_mesh_quantization`.
+fn is_core_attribute(name: &str, component_type: u32, normalized: bool, is_target: bool) -> bool {
+    let is_unsigned = matches!(
+        component_type,
+        WebGl2RenderingContext::UNSIGNED_BYTE | WebGl2RenderingContext::UNSIGNED_SHORT
+    );
+    if component_type == WebGl2RenderingContext::FLOAT || name.starts_with('_') {
+        true
+    } else if is_target {
+        false
+    } else if name.starts_with("JOINTS_") {
+        is_unsigned && !normalized
+    } else if ["TEXCOORD_", "COLOR_", "WEIGHTS_"]
+        .iter()
+        .any(|prefix| name.starts_with(prefix))
+    {
+        is_unsigned && normalized
+    } else {
+        false
+    }
+}
+
+fn non_empty<T>(values: Vec<T>) -> Option<Vec<T>> {
+    if values.is_empty() {
+        None
+    } else {
+        Some(values)
+    }
+}
+
+fn extras(custom_properties: &CustomProperties) -> Option<Value> {
+    Some(custom_properties.extras.clone()).filter(|extras| *extras != Value::Null)
+}
+
+fn type_name(accessor_type: AccessorType) -> String {
+    match accessor_type {
+        AccessorType::Scalar => String::from("SCALAR"),
+        AccessorType::Vec { size } => format!("VEC{}", size),
+        AccessorType::Mat { size } => format!("MAT{}", size),
+    }
+}
+
+fn bounds(values: &[f32], size: usize) -> (Option<Vec<f32>>, Option<Vec<f32>>) {
+    if values.is_empty() {
+        return (None, None);
+    }
+    let mut min = vec![f32::INFINITY; size];
+    let mut max = vec![f32::NEG_INFINITY; size];
+    for element in values.chunks(size) {
+        for (i, value) in element.iter().enumerate() {
+            min[i] = min[i].min(*value);
+            max[i] = max[i].max(*value);
+        }
+    }
+    (Some(min), Some(max))
+}
+
+fn encode(values: &[f32], component_type: u32, normalized: bool) -> Result<Vec<u8>> {
+    let quantize = |value: f32, max: f32| {
+        if normalized {
+            (value * max).round()
+        } else {
+            value
+        }
+    };
+    Ok(match component_type {
+        WebGl2RenderingContext::BYTE => values
+            .iter()
+            .flat_map(|value| (quantize(*value, i8::MAX.into()) as i8).to_le_bytes())
+            .collect(),
+        WebGl2RenderingContext::UNSIGNED_BYTE => values
+            .iter()
+            .flat_map(|value| (quantize(*value, u8::MAX.into()) as u8).to_le_bytes())
+            .collect(),
+        WebGl2RenderingContext::SHORT => values
+            .iter()
+            .flat_map(|value| (quantize(*value, i16::MAX.into()) as i16).to_le_bytes())
+            .collect(),
+        WebGl2RenderingContext::UNSIGNED_SHORT => values
+            .iter()
+            .flat_map(|value| (quantize(*value, u16::MAX.into()) as u16).to_le_bytes())
+            .collect(),
+        WebGl2RenderingContext::FLOAT => values
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect(),
+        _ => bail!("Cannot export component type {}", component_type),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        core::camera::Perspective,
+        core::mesh::POSITION_ATTRIBUTE,
+        gltf::load::{build, validation, SUPPORTED_EXTENSIONS},
+    };
+
+    use super::*;
+
+    #[test]
+    fn round_trip_works() {
+        let mut exporter = Exporter::default();
+        let positions = [0.0, 0.0, 0.0, 1.0, -1.0, 0.0, 0.0, 1.0, 0.0];
+        exporter.check_quantization(
+            POSITION_ATTRIBUTE,
+            WebGl2RenderingContext::SHORT,
+            true,
+            false,
+        );
+        let position = exporter
+            .add_values(
+                &positions,
+                AccessorType::vec(3),
+                WebGl2RenderingContext::SHORT,
+                true,
+                Some(WebGl2RenderingContext::ARRAY_BUFFER),
+            )
+            .unwrap();
+        let indices = [0, 1, 16_777_217];
+        let index = exporter.add_indices(
+            &indices,
+            AccessorType::scalar(),
+            Some(WebGl2RenderingContext::ELEMENT_ARRAY_BUFFER),
+        );
+        exporter.meshes.push(data::Mesh {
+            primitives: vec![data::Primitive {
+                attributes: HashMap::from([(String::from(POSITION_ATTRIBUTE), position)]),
+                indices: Some(index),
+                material: None,
+                mode: WebGl2RenderingContext::TRIANGLES,
+                targets: None,
+            }],
+            weights: None,
+            name: None,
+            extensions: None,
+            extras: None,
+        });
+        let cameras = [false, true].map(|fixed_aspect_ratio| {
+            Camera::new(Perspective {
+                aspect_ratio: 2.0,
+                fixed_aspect_ratio,
+                ..Default::default()
+            })
+        });
+        for camera in cameras.iter() {
+            exporter.add_camera(camera);
+        }
+        let exported = exporter.finish(vec![]);
+
+        let json = serde_json::to_string(&exported.gltf).unwrap();
+        let gltf: data::Gltf = serde_json::from_str(&json).unwrap();
+        validation::validate(&gltf).into_result().unwrap();
+        let required = gltf.extensions_required.clone().unwrap_or_default();
+        assert_eq!(required, vec![QUANTIZATION_EXTENSION]);
+        assert!(required
+            .iter()
+            .all(|extension| SUPPORTED_EXTENSIONS.contains(&extension.as_str())));
+
+        let read = |index: u32| {
+            let accessor = &gltf.accessors.as_ref().unwrap()[index as usize];
+            let buffer_view =
+                &gltf.buffer_views.as_ref().unwrap()[accessor.buffer_view.unwrap() as usize];
+            let start = buffer_view.byte_offset as usize;
+            (
+                accessor,
+                &exported.bin[start..start + buffer_view.byte_length as usize],
+            )
+        };
+        let (accessor, bytes) = read(position);
+        assert_eq!(accessor.component_type, WebGl2RenderingContext::SHORT);
+        assert!(accessor.normalized);
+        let read_positions: Vec<f32> = bytes
+            .chunks(2)
+            .map(|chunk| f32::from(i16::from_le_bytes([chunk[0], chunk[1]])) / 32767.0)
+            .collect();
+        assert_eq!(read_positions, positions);
+        let (accessor, bytes) = read(index);
+        assert_eq!(accessor.count, 3);
+        let read_indices: Vec<u32> = bytes
+            .chunks(4)
+            .map(|chunk| u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .collect();
+        assert_eq!(read_indices, indices);
+
+        let cameras = gltf.cameras.as_ref().unwrap().iter().collect();
+        let cameras = build::build_cameras(cameras).unwrap();
+        assert_eq!(cameras[0].borrow().fixed_aspect_ratio(), None);
+        assert_eq!(cameras[1].borrow().fixed_aspect_ratio(), Some(2.0));
+    }
+
+    #[test]
+    fn is_core_attribute_works() {
+        let float = WebGl2RenderingContext::FLOAT;
+        let unsigned_byte = WebGl2RenderingContext::UNSIGNED_BYTE;
+        assert!(is_core_attribute(POSITION_ATTRIBUTE, float, false, false));
+        assert!(!is_core_attribute(
+            POSITION_ATTRIBUTE,
+            unsigned_byte,
+            true,
+            false
+        ));
+        assert!(is_core_attribute("TEXCOORD_0", unsigned_byte, true, false));
+        assert!(!is_core_attribute(
+            "TEXCOORD_0",
+            unsigned_byte,
+            false,
+            false
+        ));
+        assert!(!is_core_attribute("TEXCOORD_0", unsigned_byte, true, true));
+        assert!(is_core_attribute("JOINTS_0", unsigned_byte, false, false));
+        assert!(is_core_attribute(
+            "_CUSTOM",
+            WebGl2RenderingContext::SHORT,
+            false,
+            false
+        ));
+    }
+}
@@ -0,0 +1,129 @@
+use anyhow::{anyhow, bail, Result};
+
+use crate::base::util::validate;
+
+const MAGIC: u32 = 0x4654_6c67;
+const VERSION: u32 = 2;
+const HEADER_LENGTH: usize = 12;
+const CHUNK_HEADER_LENGTH: usize = 8;
+const JSON_CHUNK: u32 = 0x4e4f_534a;
+const BIN_CHUNK: u32 = 0x004e_4942;
+
+#[derive(Debug)]
+pub struct Glb<'a> {
+    pub json: &'a str,
+    pub bin: Option<&'a [u8]>,
+}
+
+pub fn is_glb(data: &[u8]) -> bool {
+    data.len() >= 4 && self::read_u32(data, 0) == MAGIC
+}
+
+pub fn parse(data: &[u8]) -> Result<Glb<'_>> {
+    validate::assert(data.len() >= HEADER_LENGTH, || {
+        anyhow!("GLB header is too short")
+    })?;
+    if !self::is_glb(data) {
+        bail!("Invalid GLB magic");
+    }
+    let version = self::read_u32(data, 4);
+    validate::assert(version == VERSION, || {
+        anyhow!("Unsupported GLB version: {}", version)
+    })?;
+    let length = self::read_u32(data, 8) as usize;
+    validate::assert(length <= data.len(), || {
+        anyhow!("GLB length {} exceeds data length {}", length, data.len())
+    })?;
+    let mut json = None;
+    let mut bin = None;
+    let mut offset = HEADER_LENGTH;
+    while offset + CHUNK_HEADER_LENGTH <= length {
+        let chunk_length = self::read_u32(data, offset) as usize;
+        let chunk_type = self::read_u32(data, offset + 4);
+        let start = offset + CHUNK_HEADER_LENGTH;
+        let end = start
+            .checked_add(chunk_length)
+            .filter(|end| *end <= length)
+            .ok_or_else(|| anyhow!("GLB chunk exceeds data length"))?;
+        let chunk = data
+            .get(start..end)
+            .ok_or_else(|| anyhow!("GLB chunk exceeds data length"))?;
+        match chunk_type {
+            JSON_CHUNK if json.is_none() => {
+                json = Some(
+                    std::str::from_utf8(chunk)
+                        .map_err(|error| anyhow!("Invalid GLB JSON chunk: {}", error))?,
+                )
+            }
+            BIN_CHUNK if bin.is_none() => bin = Some(chunk),
+            _ => {}
+        }
+        offset = end;
+    }
+    Ok(Glb {
+        json: json.ok_or_else(|| anyhow!("Missing GLB JSON chunk"))?,
+        bin,
+    })
+}
+
+pub fn write(json: &str, bin: &[u8]) -> Vec<u8> {
+    let json_length = self::padded_length(json.len());
+    let bin_length = self::padded_length(bin.len());
+    let mut length = HEADER_LENGTH + CHUNK_HEADER_LENGTH + json_length;
+    if !bin.is_empty() {
+        length += CHUNK_HEADER_LENGTH + bin_length;
+    }
+    let mut result = Vec::with_capacity(length);
+    for value in [
+        MAGIC,
+        VERSION,
+        length as u32,
+        json_length as u32,
+        JSON_CHUNK,
+    ] {
+        result.extend(value.to_le_bytes());
+    }
+    result.extend(json.as_bytes());
+    result.resize(HEADER_LENGTH + CHUNK_HEADER_LENGTH + json_length, b' ');
+    if !bin.is_empty() {
+        result.extend((bin_length as u32).to_le_bytes());
+        result.extend(BIN_CHUNK.to_le_bytes());
+        result.extend(bin);
+        result.resize(length, 0);
+    }
+    result
+}
+
+fn padded_length(length: usize) -> usize {
+    length.div_ceil(4) * 4
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([
+        data[offset],
+        data[offset + 1],
+        data[offset + 2],
+        data[offset + 3],
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_and_parse_works() {
+        let json = r#"{"asset":{"version":"2.0"}}"#;
+        let bin = [1, 2, 3, 4, 5];
+        let data = write(json, &bin);
+        assert_eq!(data.len() % 4, 0);
+        let glb = parse(&data).unwrap();
+        assert_eq!(glb.json.trim_end(), json);
+        assert_eq!(&glb.bin.unwrap()[..bin.len()], &bin);
+        assert!(parse(&data[..10]).is_err());
+
+        let mut overflowing = data.clone();
+        overflowing[12..16].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(parse(&overflowing).is_err());
+    }
+}
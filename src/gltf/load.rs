@@ -1,83 +1,360 @@
-use std::rc::Rc;
+use std::{collections::HashMap, rc::Rc};
 
-use anyhow::Result;
+use anyhow::{anyhow, bail, Result};
+use js_sys::ArrayBuffer;
 use url::Url;
 use web_sys::WebGl2RenderingContext;
 
 use crate::{
-    base::util::{coll, shared_ref::SharedRef},
-    core::{buffer::Buffer, camera::Camera, image::Image, scene::Scene},
-    gltf::{core::Root, load::statistics::GltfStatistics},
+    base::{
+        asset::{AssetSource, FetchSource},
+        math::normal::NormalMode,
+        util::{coll, shared_ref::SharedRef},
+    },
+    classic::light::LightNode,
+    core::{
+        animation::Animation, buffer::Buffer, buffer_view::BufferView, camera::Camera,
+        image::Image, scene::Scene, texture::Texture,
+    },
+    gltf::{
+        core::Root,
+        load::{extension::ExtensionHandlers, statistics::GltfStatistics},
+        user::scene_controller,
+    },
 };
 
 pub mod build;
 pub mod data;
+pub mod extension;
 pub mod fetch;
+pub mod meshopt;
 pub mod statistics;
+pub mod validation;
 
-pub async fn load<'a>(context: &WebGl2RenderingContext, uri: &str) -> Result<Root> {
-    let gltf = fetch::fetch_gltf(uri).await?;
+struct Content {
+    scenes: Vec<Scene>,
+    animations: Vec<Animation>,
+    light_nodes: Vec<Rc<LightNode>>,
+}
+
+pub const SUPPORTED_EXTENSIONS: [&str; 9] = [
+    "EXT_meshopt_compression",
+    "KHR_lights_punctual",
+    "KHR_materials_clearcoat",
+    "KHR_materials_emissive_strength",
+    "KHR_materials_transmission",
+    "KHR_materials_unlit",
+    "KHR_mesh_quantization",
+    "KHR_texture_basisu",
+    "KHR_texture_transform",
+];
+
+#[derive(Debug, Clone, Default)]
+pub struct LoadOptions {
+    pub normals: NormalMode,
+    pub extensions: ExtensionHandlers,
+    pub scene_controller: scene_controller::Properties,
+}
+
+pub async fn load(context: &WebGl2RenderingContext, uri: &str) -> Result<Root> {
+    self::load_with_source(context, &FetchSource, uri).await
+}
+
+pub async fn load_with_source(
+    context: &WebGl2RenderingContext,
+    source: &dyn AssetSource,
+    uri: &str,
+) -> Result<Root> {
+    self::load_with_options(context, source, uri, LoadOptions::default()).await
+}
+
+pub async fn load_with_options(
+    context: &WebGl2RenderingContext,
+    source: &dyn AssetSource,
+    uri: &str,
+    options: LoadOptions,
+) -> Result<Root> {
+    let document = fetch::fetch_gltf(source, uri).await?;
+    let gltf = document.gltf;
     debug!("{:#?}", gltf.asset);
     debug!("{:#?}", GltfStatistics::from(&gltf));
+    self::check_extensions(&gltf, &options.extensions)?;
+    let report = validation::validate(&gltf);
+    for warning in report.warnings() {
+        warn!("glTF validation: {}", warning);
+    }
+    report.into_result()?;
     let base_uri = Url::parse(uri)?;
-    let buffers =
-        self::load_buffers(&base_uri, coll::flatten_optional_vector(&gltf.buffers)).await?;
-    let images = self::load_images(&base_uri, coll::flatten_optional_vector(&gltf.images)).await?;
-    let cameras = build::build_cameras(coll::flatten_optional_vector(&gltf.cameras));
-    let scenes = self::load_scenes(context, &gltf, &buffers, &images, &cameras)?;
-    Ok(Root::initialize(
+    let buffers = self::load_buffers(
+        source,
+        &base_uri,
+        coll::flatten_optional_vector(&gltf.buffers),
+        document.binary_chunk.as_ref(),
+    )
+    .await?;
+    let buffer_views =
+        build::build_buffer_views(coll::flatten_optional_vector(&gltf.buffer_views), &buffers)?;
+    let images = self::check_basisu_images(source, &base_uri, &gltf, &buffer_views).await?;
+    let textures =
+        self::load_textures(context, source, &base_uri, &gltf, &buffer_views, images).await?;
+    let cameras = build::build_cameras(coll::flatten_optional_vector(&gltf.cameras))?;
+    let content = self::load_scenes(context, &gltf, &buffer_views, &textures, &cameras, &options)?;
+    let mut root = Root::initialize(
         context,
         cameras,
-        scenes,
+        content.scenes,
         gltf.scene.map(|index| index as usize),
-    ))
+        content.animations,
+        content.light_nodes,
+    );
+    root.set_scene_controller_properties(options.scene_controller);
+    Ok(root)
+}
+
+fn check_extensions(gltf: &data::Gltf, handlers: &ExtensionHandlers) -> Result<()> {
+    let is_supported = |extension: &&String| {
+        SUPPORTED_EXTENSIONS.contains(&extension.as_str()) || handlers.handles(extension)
+    };
+    let unsupported_required: Vec<_> = gltf
+        .extensions_required
+        .iter()
+        .flatten()
+        .filter(|extension| !is_supported(extension))
+        .collect();
+    if !unsupported_required.is_empty() {
+        bail!(
+            "Unsupported required extensions: {:?}",
+            unsupported_required
+        );
+    }
+    for extension in gltf.extensions_used.iter().flatten() {
+        if !is_supported(&extension) {
+            warn!("Unsupported extension used: {}", extension);
+        }
+    }
+    Ok(())
+}
+
+/// KTX2 images without a fallback source are fetched before anything is built,
+/// so that UASTC and other unsupported files reject the whole asset up front.
+async fn check_basisu_images(
+    source: &dyn AssetSource,
+    base_uri: &Url,
+    gltf: &data::Gltf,
+    buffer_views: &[Rc<BufferView>],
+) -> Result<HashMap<u32, Rc<Image>>> {
+    let images = coll::flatten_optional_vector(&gltf.images);
+    let mut result = HashMap::new();
+    for (index, texture) in gltf.textures.iter().flatten().enumerate() {
+        let Some(image_source) = texture.basisu_source() else {
+            continue;
+        };
+        if texture.fallback_source().is_some() || result.contains_key(&image_source) {
+            continue;
+        }
+        let pointer = format!("/textures/{}/extensions/KHR_texture_basisu/source", index);
+        let image = images
+            .get(image_source as usize)
+            .ok_or_else(|| anyhow!("{}: Unknown image {}", pointer, image_source))?;
+        let image_type = fetch::fetch_image(source, base_uri, image, image_source, buffer_views)
+            .await
+            .map_err(|error| {
+                anyhow!(
+                    "{}: Unusable KTX2 image without fallback: {}",
+                    pointer,
+                    error
+                )
+            })?;
+        result.insert(image_source, build::build_image(image, image_type));
+    }
+    Ok(result)
 }
 
-async fn load_buffers(base_uri: &Url, buffers: Vec<&data::Buffer>) -> Result<Vec<Rc<Buffer>>> {
-    let array_buffers = fetch::fetch_buffers(base_uri, &buffers).await?;
+async fn load_buffers(
+    source: &dyn AssetSource,
+    base_uri: &Url,
+    buffers: Vec<&data::Buffer>,
+    binary_chunk: Option<&ArrayBuffer>,
+) -> Result<Vec<Rc<Buffer>>> {
+    let array_buffers = fetch::fetch_buffers(source, base_uri, &buffers, binary_chunk).await?;
     Ok(build::build_buffers(buffers, array_buffers))
 }
 
-async fn load_images(base_uri: &Url, images: Vec<&data::Image>) -> Result<Vec<Rc<Image>>> {
-    let html_images = fetch::fetch_images(base_uri, &images).await?;
-    Ok(build::build_images(images, html_images))
+/// Loads the KTX2 image of `KHR_texture_basisu` textures and falls back to the core
+/// source only for textures whose KTX2 image fails to load or transcode.
+async fn load_textures(
+    context: &WebGl2RenderingContext,
+    source: &dyn AssetSource,
+    base_uri: &Url,
+    gltf: &data::Gltf,
+    buffer_views: &[Rc<BufferView>],
+    fetched: HashMap<u32, Rc<Image>>,
+) -> Result<Vec<Rc<Texture>>> {
+    let images = coll::flatten_optional_vector(&gltf.images);
+    let mut builder = build::TextureBuilder::new(
+        context,
+        build::build_samplers(coll::flatten_optional_vector(&gltf.samplers))?,
+        build::color_texture_indices(coll::flatten_optional_vector(&gltf.materials)),
+    );
+    for (image_source, image) in fetched {
+        builder.add_image(image_source, image);
+    }
+    let mut textures = Vec::new();
+    for (index, texture) in gltf.textures.iter().flatten().enumerate() {
+        let image_source = texture
+            .image_source()
+            .ok_or_else(|| anyhow!("/textures/{}/source: Missing source image", index))?;
+        let loaded = self::load_texture(
+            &mut builder,
+            source,
+            base_uri,
+            &images,
+            buffer_views,
+            (index, texture),
+            image_source,
+        )
+        .await;
+        let loaded = match (loaded, texture.fallback_source()) {
+            (Err(error), Some(fallback)) => {
+                warn!(
+                    "/textures/{}: Cannot use image {}, falling back to image {}: {}",
+                    index, image_source, fallback, error
+                );
+                self::load_texture(
+                    &mut builder,
+                    source,
+                    base_uri,
+                    &images,
+                    buffer_views,
+                    (index, texture),
+                    fallback,
+                )
+                .await?
+            }
+            (loaded, _) => loaded?,
+        };
+        textures.push(loaded);
+    }
+    Ok(textures)
+}
+
+async fn load_texture(
+    builder: &mut build::TextureBuilder<'_>,
+    source: &dyn AssetSource,
+    base_uri: &Url,
+    images: &[&data::Image],
+    buffer_views: &[Rc<BufferView>],
+    (index, texture): (usize, &data::Texture),
+    image_source: u32,
+) -> Result<Rc<Texture>> {
+    if let Some(shared) = builder.shared(index, texture, image_source) {
+        return Ok(shared);
+    }
+    if builder.image(image_source).is_none() {
+        let image = images
+            .get(image_source as usize)
+            .ok_or_else(|| anyhow!("/textures/{}/source: Unknown image {}", index, image_source))?;
+        let image_type =
+            fetch::fetch_image(source, base_uri, image, image_source, buffer_views).await?;
+        builder.add_image(image_source, build::build_image(image, image_type));
+    }
+    builder.build(index, texture, image_source)
 }
 
 fn load_scenes(
     context: &WebGl2RenderingContext,
     gltf: &data::Gltf,
-    buffers: &[Rc<Buffer>],
-    images: &[Rc<Image>],
+    buffer_views: &[Rc<BufferView>],
+    textures: &[Rc<Texture>],
     cameras: &[SharedRef<Camera>],
-) -> Result<Vec<Scene>> {
-    let buffer_views =
-        build::build_buffer_views(coll::flatten_optional_vector(&gltf.buffer_views), buffers)?;
+    options: &LoadOptions,
+) -> Result<Content> {
     let accessors = build::build_accessors(
         context,
         coll::flatten_optional_vector(&gltf.accessors),
-        &buffer_views,
-    )?;
-    let samplers = build::build_samplers(coll::flatten_optional_vector(&gltf.samplers))?;
-    let textures = build::build_textures(
-        context,
-        coll::flatten_optional_vector(&gltf.textures),
-        &samplers,
-        images,
+        buffer_views,
     )?;
     let materials = build::build_materials(
         context,
         coll::flatten_optional_vector(&gltf.materials),
-        &textures,
+        textures,
     )?;
     let meshes = build::build_meshes(
         context,
         coll::flatten_optional_vector(&gltf.meshes),
         &accessors,
         &materials,
+        options.normals,
+    )?;
+    let nodes = build::build_nodes(
+        coll::flatten_optional_vector(&gltf.nodes),
+        &meshes,
+        cameras,
+        &options.extensions,
+    )?;
+    let skins = build::build_skins(
+        coll::flatten_optional_vector(&gltf.skins),
+        &accessors,
+        &nodes,
     )?;
-    let nodes = build::build_nodes(coll::flatten_optional_vector(&gltf.nodes), &meshes, cameras);
-    Ok(build::build_scenes(
-        coll::flatten_optional_vector(&gltf.scenes),
+    build::attach_skins(coll::flatten_optional_vector(&gltf.nodes), &nodes, &skins);
+    let animations = build::build_animations(
+        coll::flatten_optional_vector(&gltf.animations),
+        &accessors,
         &nodes,
-    ))
+    )?;
+    let lights = build::build_lights(
+        gltf.extensions
+            .as_ref()
+            .and_then(|extensions| extensions.khr_lights_punctual.as_ref())
+            .map(|lights_punctual| lights_punctual.lights.iter().collect())
+            .unwrap_or_default(),
+    )?;
+    let light_nodes =
+        build::build_light_nodes(coll::flatten_optional_vector(&gltf.nodes), &nodes, &lights);
+    Ok(Content {
+        scenes: build::build_scenes(coll::flatten_optional_vector(&gltf.scenes), &nodes),
+        animations,
+        light_nodes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor;
+
+    use crate::base::asset::MemorySource;
+
+    use super::*;
+
+    #[test]
+    fn check_basisu_images_works() {
+        let image = |uri: &str| data::Image {
+            uri: Some(String::from(uri)),
+            ..Default::default()
+        };
+        let texture = |source: Option<u32>| data::Texture {
+            sampler: None,
+            source,
+            extensions: Some(data::TextureExtensions {
+                khr_texture_basisu: Some(data::TextureBasisu { source: 0 }),
+            }),
+        };
+        let gltf = |textures| data::Gltf {
+            images: Some(vec![image("broken.ktx2"), image("fallback.png")]),
+            textures: Some(textures),
+            ..Default::default()
+        };
+        let source = MemorySource::new().with_asset("broken.ktx2", vec![0; 16]);
+        let base_uri = Url::parse("file:///").unwrap();
+        let check =
+            |gltf| executor::block_on(self::check_basisu_images(&source, &base_uri, gltf, &[]));
+
+        let with_fallback = gltf(vec![texture(Some(1))]);
+        assert!(check(&with_fallback).unwrap().is_empty());
+
+        let without_fallback = gltf(vec![texture(Some(1)), texture(None)]);
+        let error = check(&without_fallback).unwrap_err().to_string();
+        assert!(error.starts_with("/textures/1/extensions/KHR_texture_basisu/source: "));
+    }
 }
@@ -1,29 +1,50 @@
-use std::{collections::HashMap, rc::Rc};
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap, HashSet},
+    rc::Rc,
+};
 
 use anyhow::{anyhow, Result};
-use glm::{Qua, Vec3, Vec4};
-use js_sys::ArrayBuffer;
-use web_sys::{HtmlImageElement, WebGl2RenderingContext};
+use glm::{Qua, Vec2, Vec3, Vec4};
+use js_sys::{ArrayBuffer, Uint8Array};
+use web_sys::WebGl2RenderingContext;
 
 use crate::{
-    base::util::shared_ref::{self, SharedRef},
+    api::attribute,
+    base::{
+        color::{self, ColorSpace},
+        convert::FromWithContext,
+        math::{
+            matrix,
+            normal::{self, NormalMode},
+        },
+        util::{
+            shared_ref::{self, SharedRef},
+            value::Value,
+        },
+    },
+    classic::light::{Attenuation, Cone, Light, LightNode},
     core::{
         accessor::{Accessor, AccessorProperties, AccessorType},
+        animation::{self, Animation, Channel, Interpolation, Property},
         buffer::Buffer,
         buffer_view::BufferView,
-        camera::Camera,
-        image::Image,
-        material::{AlphaMode, Material, TextureRef},
-        mesh::{Mesh, Primitive},
+        camera::{Camera, CameraType, Perspective},
+        custom_properties::CustomProperties,
+        image::{Image, ImageType},
+        material::{AlphaMode, Clearcoat, Material, TextureRef, TextureTransform, Transmission},
+        mesh::{self, Mesh, Primitive},
+        morph::{MorphTarget, MorphTargets},
         node::Node,
-        sampler::Sampler,
+        sampler::{Sampler, SamplerCache},
         scene::Scene,
+        skin::Skin,
         texture::Texture,
     },
-    gltf::material::TestMaterial,
+    gltf::material::{self, NormalTexture, TestMaterial},
 };
 
-use super::data;
+use super::{data, extension::ExtensionHandlers, meshopt};
 
 pub fn build_buffers(
     buffers: Vec<&data::Buffer>,
@@ -44,10 +65,20 @@ pub fn build_buffer_views(
     buffer_views
         .into_iter()
         .map(|buffer_view| {
-            let buffer = self::get_rc_by_u32(buffers, buffer_view.buffer);
+            let meshopt = buffer_view
+                .extensions
+                .as_ref()
+                .and_then(|extensions| extensions.ext_meshopt_compression.as_ref());
+            let (buffer, byte_offset) = match meshopt {
+                Some(meshopt) => (self::decode_meshopt(meshopt, buffers)?, 0),
+                None => (
+                    self::get_rc_by_u32(buffers, buffer_view.buffer),
+                    buffer_view.byte_offset,
+                ),
+            };
             BufferView::new(
                 buffer,
-                buffer_view.byte_offset,
+                byte_offset,
                 buffer_view.byte_length,
                 buffer_view.byte_stride,
                 buffer_view.target,
@@ -57,6 +88,24 @@ pub fn build_buffer_views(
         .collect()
 }
 
+fn decode_meshopt(
+    meshopt: &data::MeshoptCompression,
+    buffers: &[Rc<Buffer>],
+) -> Result<Rc<Buffer>> {
+    let source = self::get_rc_by_u32(buffers, meshopt.buffer)
+        .get_uint8_array(meshopt.byte_offset, meshopt.byte_length)
+        .to_vec();
+    let decoded = meshopt::decode(
+        &source,
+        meshopt.count as usize,
+        meshopt.byte_stride as usize,
+        meshopt::Mode::try_from(meshopt.mode.as_str())?,
+        meshopt::Filter::try_from(meshopt.filter.as_str())?,
+    )?;
+    let array_buffer = Uint8Array::from(decoded.as_slice()).buffer();
+    Ok(Rc::new(Buffer::new(array_buffer, decoded.len())))
+}
+
 pub fn build_accessors(
     context: &WebGl2RenderingContext,
     accessors: Vec<&data::Accessor>,
@@ -88,67 +137,72 @@ pub fn build_accessors(
         .collect()
 }
 
-fn get_size(accessor_type: &str) -> Result<AccessorType> {
+pub fn get_size(accessor_type: &str) -> Result<AccessorType> {
     match accessor_type {
         "SCALAR" => Ok(AccessorType::scalar()),
         "VEC2" => Ok(AccessorType::vec(2)),
         "VEC3" => Ok(AccessorType::vec(3)),
         "VEC4" => Ok(AccessorType::vec(4)),
+        "MAT2" => Ok(AccessorType::mat(2)),
+        "MAT3" => Ok(AccessorType::mat(3)),
+        "MAT4" => Ok(AccessorType::mat(4)),
         _ => Err(anyhow!("Unknown type: {}", accessor_type)),
     }
 }
 
-pub fn build_cameras(cameras: Vec<&data::Camera>) -> Vec<SharedRef<Camera>> {
+pub fn build_cameras(cameras: Vec<&data::Camera>) -> Result<Vec<SharedRef<Camera>>> {
     cameras
         .into_iter()
-        .map(|camera| match camera.camera_type.as_str() {
+        .enumerate()
+        .map(|(index, camera)| match camera.camera_type.as_str() {
             "perspective" => {
-                let perspective = camera
-                    .perspective
-                    .as_ref()
-                    .expect("Missing perspective camera structure");
-                Camera::perspective(
-                    perspective.aspect_ratio.unwrap_or(1.0),
-                    perspective.y_fov,
-                    perspective.z_near,
-                    perspective.z_far,
+                let perspective = camera.perspective.as_ref().ok_or_else(|| {
+                    anyhow!(
+                        "/cameras/{}/perspective: Missing perspective camera properties",
+                        index
+                    )
+                })?;
+                Ok(Camera::new_with_name(
+                    CameraType::Perspective(Perspective {
+                        aspect_ratio: perspective.aspect_ratio.unwrap_or(1.0),
+                        y_fov: perspective.y_fov,
+                        z_far: perspective.z_far,
+                        z_near: perspective.z_near,
+                        fixed_aspect_ratio: perspective.aspect_ratio.is_some(),
+                    }),
                     camera.name.clone(),
-                )
+                ))
             }
             "orthographic" => {
-                let orthographic = camera
-                    .orthographic
-                    .as_ref()
-                    .expect("Missing orthographic camera structure");
-                Camera::orthographic(
+                let orthographic = camera.orthographic.as_ref().ok_or_else(|| {
+                    anyhow!(
+                        "/cameras/{}/orthographic: Missing orthographic camera properties",
+                        index
+                    )
+                })?;
+                Ok(Camera::orthographic(
                     orthographic.x_mag,
                     orthographic.y_mag,
                     orthographic.z_near,
                     orthographic.z_far,
                     camera.name.clone(),
-                )
+                ))
             }
-            _ => panic!("Unknown camera type: {}", camera.camera_type),
+            _ => Err(anyhow!(
+                "/cameras/{}/type: Unknown camera type: {}",
+                index,
+                camera.camera_type
+            )),
         })
         .collect()
 }
 
-pub fn build_images(
-    images: Vec<&data::Image>,
-    html_images: Vec<HtmlImageElement>,
-) -> Vec<Rc<Image>> {
-    html_images
-        .into_iter()
-        .enumerate()
-        .map(|(index, html_image)| {
-            Image::new(
-                html_image,
-                images.get(index).and_then(|image| image.name.clone()),
-                images.get(index).and_then(|image| image.mime_type.clone()),
-            )
-        })
-        .map(Rc::new)
-        .collect()
+pub fn build_image(image: &data::Image, image_type: ImageType) -> Rc<Image> {
+    Rc::new(Image::new_with_type(
+        image_type,
+        image.name.clone(),
+        image.mime_type.clone(),
+    ))
 }
 
 pub fn build_materials(
@@ -167,10 +221,63 @@ pub fn build_materials(
         }
     }
 
+    let build_texture_ref = |texture_info: &data::TextureInfo| {
+        let texture = self::get_rc_by_u32(textures, texture_info.index);
+        match texture_info
+            .extensions
+            .as_ref()
+            .and_then(|extensions| extensions.khr_texture_transform.as_ref())
+        {
+            Some(transform) => TextureRef::new(
+                texture,
+                transform.tex_coord.unwrap_or(texture_info.tex_coord),
+            )
+            .with_transform(TextureTransform {
+                offset: Vec2::from(transform.offset),
+                rotation: transform.rotation,
+                scale: Vec2::from(transform.scale),
+            }),
+            None => TextureRef::new(texture, texture_info.tex_coord),
+        }
+    };
+
     materials
         .into_iter()
         .map(|material| {
-            let alpha_mode = build_alpha_mode(material)?;
+            let extensions = material.extensions.as_ref();
+            let emissive_strength = extensions
+                .and_then(|extensions| extensions.khr_materials_emissive_strength.as_ref())
+                .map_or(1.0, |extension| extension.emissive_strength);
+            let clearcoat = extensions
+                .and_then(|extensions| extensions.khr_materials_clearcoat.as_ref())
+                .map(|extension| Clearcoat {
+                    factor: extension.clearcoat_factor,
+                    texture: extension.clearcoat_texture.as_ref().map(build_texture_ref),
+                    roughness_factor: extension.clearcoat_roughness_factor,
+                    roughness_texture: extension
+                        .clearcoat_roughness_texture
+                        .as_ref()
+                        .map(build_texture_ref),
+                });
+            let transmission = extensions
+                .and_then(|extensions| extensions.khr_materials_transmission.as_ref())
+                .map(|extension| Transmission {
+                    factor: extension.transmission_factor,
+                    texture: extension
+                        .transmission_texture
+                        .as_ref()
+                        .map(build_texture_ref),
+                });
+            let alpha_mode = match build_alpha_mode(material)? {
+                AlphaMode::Opaque
+                    if transmission
+                        .as_ref()
+                        .is_some_and(|transmission| transmission.factor > 0.0) =>
+                {
+                    AlphaMode::Blend
+                }
+                alpha_mode => alpha_mode,
+            };
             Material::initialize(
                 context,
                 material.name.clone(),
@@ -179,20 +286,39 @@ pub fn build_materials(
                     base_color_factor: Vec4::from(
                         material.pbr_metallic_roughness.base_color_factor,
                     ),
+                    use_light: extensions
+                        .and_then(|extensions| extensions.khr_materials_unlit.as_ref())
+                        .is_none(),
                     base_color_texture: material
                         .pbr_metallic_roughness
                         .base_color_texture
                         .as_ref()
-                        .map(|texture_info| {
-                            TextureRef::new(
-                                self::get_rc_by_u32(textures, texture_info.index),
-                                texture_info.tex_coord,
-                            )
-                        }),
+                        .map(build_texture_ref),
+                    metallic_factor: material.pbr_metallic_roughness.metallic_factor,
+                    roughness_factor: material.pbr_metallic_roughness.roughness_factor,
+                    metallic_roughness_texture: material
+                        .pbr_metallic_roughness
+                        .metallic_roughness_texture
+                        .as_ref()
+                        .map(build_texture_ref),
+                    emissive_factor: Vec3::from(material.emissive_factor) * emissive_strength,
+                    emissive_texture: material.emissive_texture.as_ref().map(build_texture_ref),
+                    normal_texture: material.normal_texture.as_ref().map(|info| NormalTexture {
+                        texture: build_texture_ref(&info.texture_info()),
+                        scale: info.scale,
+                    }),
+                    clearcoat,
+                    transmission,
                     ..Default::default()
                 }),
                 alpha_mode,
             )
+            .map(|result| {
+                result.with_custom_properties(self::custom_properties(
+                    &material.extras,
+                    extensions.map(|extensions| &extensions.others),
+                ))
+            })
         })
         .collect()
 }
@@ -202,13 +328,29 @@ pub fn build_meshes(
     meshes: Vec<&data::Mesh>,
     accessors: &[Rc<Accessor>],
     materials: &[Rc<Material>],
+    normal_mode: NormalMode,
 ) -> Result<Vec<Rc<Mesh>>> {
+    let mut material_variants = HashMap::new();
     meshes
         .into_iter()
         .map(|mesh| {
-            let primitives =
-                self::build_primitives(context, &mesh.primitives, accessors, materials)?;
-            Ok(Mesh::new(primitives, mesh.name.as_ref().map(String::from)))
+            let primitives = self::build_primitives(
+                context,
+                &mesh.primitives,
+                accessors,
+                materials,
+                &mut material_variants,
+                normal_mode,
+            )?;
+            let result = Mesh::new(
+                primitives,
+                mesh.weights.clone().unwrap_or_default(),
+                mesh.name.as_ref().map(String::from),
+            );
+            Ok(result.with_custom_properties(self::custom_properties(
+                &mesh.extras,
+                mesh.extensions.as_ref(),
+            )))
         })
         .collect()
 }
@@ -218,6 +360,8 @@ fn build_primitives(
     primitives: &[data::Primitive],
     accessors: &[Rc<Accessor>],
     materials: &[Rc<Material>],
+    material_variants: &mut HashMap<(Option<u32>, Vec<String>), Rc<Material>>,
+    normal_mode: NormalMode,
 ) -> Result<Vec<Primitive>> {
     primitives
         .iter()
@@ -226,21 +370,135 @@ fn build_primitives(
             let indices = primitive
                 .indices
                 .map(|index| self::get_rc_by_u32(accessors, index));
-            Primitive::new(
+            let material = if let Some(index) = primitive.material {
+                self::get_rc_by_u32(materials, index)
+            } else {
+                self::default_material(context)?
+            };
+            let targets = self::build_targets(primitive.targets.as_deref(), accessors);
+            let (attributes, indices, targets) = self::generate_normals(
                 context,
                 attributes,
                 indices,
-                if let Some(index) = primitive.material {
-                    self::get_rc_by_u32(materials, index)
+                targets,
+                primitive.mode,
+                normal_mode,
+            )?;
+            let mut defines = MorphTargets::defines(&targets);
+            if self::is_skinned(primitive) {
+                defines.push(String::from(material::SKINNING));
+            }
+            let material = if defines.is_empty() {
+                material
+            } else {
+                let key = (primitive.material, defines);
+                if let Some(variant) = material_variants.get(&key) {
+                    Rc::clone(variant)
                 } else {
-                    self::default_material(context)?
-                },
+                    let defines: Vec<_> = key.1.iter().map(String::as_str).collect();
+                    let variant = material.variant(context, &defines)?;
+                    material_variants.insert(key, Rc::clone(&variant));
+                    variant
+                }
+            };
+            Primitive::new(
+                context,
+                attributes,
+                indices,
+                material,
                 primitive.mode,
+                targets,
             )
+            .map(|primitive| primitive.with_vertex_color_space(ColorSpace::Linear))
         })
         .collect()
 }
 
+fn build_targets(
+    targets: Option<&[HashMap<String, u32>]>,
+    accessors: &[Rc<Accessor>],
+) -> Vec<MorphTarget> {
+    targets
+        .into_iter()
+        .flatten()
+        .map(|target| self::build_attributes(target, accessors))
+        .collect()
+}
+
+type PrimitiveData = (
+    HashMap<String, Rc<Accessor>>,
+    Option<Rc<Accessor>>,
+    Vec<MorphTarget>,
+);
+
+fn generate_normals(
+    context: &WebGl2RenderingContext,
+    attributes: HashMap<String, Rc<Accessor>>,
+    indices: Option<Rc<Accessor>>,
+    targets: Vec<MorphTarget>,
+    mode: u32,
+    normal_mode: NormalMode,
+) -> Result<PrimitiveData> {
+    if mode != WebGl2RenderingContext::TRIANGLES
+        || attributes.contains_key(mesh::NORMAL_ATTRIBUTE)
+        || !attributes.contains_key(mesh::POSITION_ATTRIBUTE)
+    {
+        return Ok((attributes, indices, targets));
+    }
+    let position: Vec<_> = attributes[mesh::POSITION_ATTRIBUTE]
+        .read_f32()?
+        .chunks(3)
+        .map(Vec3::from_column_slice)
+        .collect();
+    let index_data = indices
+        .as_ref()
+        .map(|indices| indices.read_indices())
+        .transpose()?;
+    let normals = normal::generate(&position, index_data.as_deref(), normal_mode)?;
+    let corners: Vec<usize> = match &index_data {
+        Some(index_data) => index_data.iter().map(|index| *index as usize).collect(),
+        None => (0..position.len()).collect(),
+    };
+    let deindex = |attributes: HashMap<String, Rc<Accessor>>| {
+        attributes
+            .into_iter()
+            .map(|(name, accessor)| {
+                let size = accessor.accessor_type().size() as usize;
+                let data = accessor.read_f32()?;
+                let data = corners
+                    .iter()
+                    .flat_map(|corner| data[corner * size..(corner + 1) * size].iter().copied())
+                    .collect();
+                let accessor = attribute::create_accessor(context, data, size, corners.len())?;
+                Ok((name, Rc::new(accessor)))
+            })
+            .collect::<Result<HashMap<_, _>>>()
+    };
+    let mut attributes = if index_data.is_some() {
+        deindex(attributes)?
+    } else {
+        attributes
+    };
+    attributes.insert(
+        String::from(mesh::NORMAL_ATTRIBUTE),
+        Rc::new(Accessor::from_with_context(context, &normals)?),
+    );
+    let targets = if index_data.is_some() {
+        targets
+            .into_iter()
+            .map(deindex)
+            .collect::<Result<Vec<_>>>()?
+    } else {
+        targets
+    };
+    Ok((attributes, None, targets))
+}
+
+fn is_skinned(primitive: &data::Primitive) -> bool {
+    primitive.attributes.contains_key(mesh::JOINTS_0_ATTRIBUTE)
+        && primitive.attributes.contains_key(mesh::WEIGHTS_0_ATTRIBUTE)
+}
+
 fn build_attributes(
     attributes: &HashMap<String, u32>,
     accessors: &[Rc<Accessor>],
@@ -264,27 +522,37 @@ pub fn build_nodes(
     gltf_nodes: Vec<&data::Node>,
     meshes: &[Rc<Mesh>],
     cameras: &[SharedRef<Camera>],
-) -> Vec<SharedRef<Node>> {
+    extension_handlers: &ExtensionHandlers,
+) -> Result<Vec<SharedRef<Node>>> {
     let nodes: Vec<_> = gltf_nodes
         .iter()
         .map(|node| {
-            let transform = if let Some(matrix) = node.matrix {
-                glm::make_mat4(&matrix)
-            } else {
-                let translation =
-                    glm::translation(&Vec3::from(node.translation.unwrap_or(DEFAULT_TRANSLATION)));
-                let rotation =
-                    glm::quat_to_mat4(&Qua::from(node.rotation.unwrap_or(DEFAULT_ROTATION)));
-                let scale = glm::scaling(&Vec3::from(node.scale.unwrap_or(DEFAULT_SCALE)));
-                translation * rotation * scale
-            };
-            Node::new(
-                transform,
+            let result = Node::new(
+                node.matrix
+                    .map_or_else(matrix::identity, |matrix| glm::make_mat4(&matrix)),
                 node.mesh.map(|index| self::get_rc_by_u32(meshes, index)),
                 node.camera
                     .map(|index| self::get_cloned_by_u32(cameras, index)),
                 node.name.clone(),
-            )
+            );
+            if node.matrix.is_none() {
+                let mut result = result.borrow_mut();
+                result.set_position(&Vec3::from(node.translation.unwrap_or(DEFAULT_TRANSLATION)));
+                result.set_rotation(&Qua::from(node.rotation.unwrap_or(DEFAULT_ROTATION)));
+                result.set_scale(&Vec3::from(node.scale.unwrap_or(DEFAULT_SCALE)));
+            }
+            if let Some(weights) = &node.weights {
+                result.borrow_mut().set_weights(weights.clone());
+            }
+            result
+                .borrow_mut()
+                .set_custom_properties(self::custom_properties(
+                    &node.extras,
+                    node.extensions
+                        .as_ref()
+                        .map(|extensions| &extensions.others),
+                ));
+            result
         })
         .collect();
     for (i, gltf_node) in gltf_nodes.iter().enumerate() {
@@ -294,10 +562,148 @@ pub fn build_nodes(
             node.borrow_mut().add_child(child);
         }
     }
+    for node in nodes.iter() {
+        extension_handlers.handle_node(node)?;
+    }
+    Ok(nodes)
+}
+
+fn custom_properties(
+    extras: &Option<Value>,
+    extensions: Option<&BTreeMap<String, Value>>,
+) -> CustomProperties {
+    CustomProperties::new(extras.clone(), extensions.cloned().unwrap_or_default())
+}
+
+pub fn build_skins(
+    skins: Vec<&data::Skin>,
+    accessors: &[Rc<Accessor>],
+    nodes: &[SharedRef<Node>],
+) -> Result<Vec<Rc<Skin>>> {
+    skins
+        .into_iter()
+        .map(|skin| {
+            let joints: Vec<_> = skin
+                .joints
+                .iter()
+                .map(|index| self::get_cloned_by_u32(nodes, *index))
+                .collect();
+            let inverse_bind_matrices = skin
+                .inverse_bind_matrices
+                .map(|index| self::get_rc_by_u32(accessors, index).read_f32())
+                .transpose()?
+                .map(|data| data.chunks_exact(16).map(glm::make_mat4).collect());
+            let skeleton = skin
+                .skeleton
+                .map(|index| self::get_cloned_by_u32(nodes, index));
+            Skin::new(
+                &joints,
+                inverse_bind_matrices,
+                skeleton.as_ref(),
+                skin.name.clone(),
+            )
+        })
+        .collect()
+}
+
+pub fn attach_skins(gltf_nodes: Vec<&data::Node>, nodes: &[SharedRef<Node>], skins: &[Rc<Skin>]) {
+    for (node, gltf_node) in nodes.iter().zip(gltf_nodes) {
+        if let Some(index) = gltf_node.skin {
+            node.borrow_mut()
+                .set_skin(self::get_rc_by_u32(skins, index));
+        }
+    }
+}
+
+pub fn build_animations(
+    animations: Vec<&data::Animation>,
+    accessors: &[Rc<Accessor>],
+    nodes: &[SharedRef<Node>],
+) -> Result<Vec<Animation>> {
+    animations
+        .into_iter()
+        .map(|animation| {
+            let channels = animation
+                .channels
+                .iter()
+                .filter_map(|channel| {
+                    channel
+                        .target
+                        .node
+                        .map(|node| (channel, self::get_cloned_by_u32(nodes, node)))
+                })
+                .map(|(channel, node)| {
+                    let sampler = &animation.samplers[channel.sampler as usize];
+                    let sampler = animation::Sampler::new(
+                        self::get_rc_by_u32(accessors, sampler.input).read_f32()?,
+                        self::get_rc_by_u32(accessors, sampler.output).read_f32()?,
+                        Interpolation::try_from(sampler.interpolation.as_str())?,
+                    )?;
+                    let property = Property::try_from(channel.target.path.as_str())?;
+                    Ok(Channel::new(sampler, Rc::downgrade(&node), property))
+                })
+                .collect::<Result<_>>()?;
+            Ok(Animation::new(channels, animation.name.clone()))
+        })
+        .collect()
+}
+
+pub fn build_lights(lights: Vec<&data::PunctualLight>) -> Result<Vec<Light>> {
+    lights
+        .into_iter()
+        .map(|light| {
+            let [red, green, blue] = light.color.map(|value| value * light.intensity);
+            let color = color::rgb(red, green, blue);
+            let position = Vec3::zeros();
+            let direction = glm::vec3(0.0, 0.0, -1.0);
+            let light = match light.light_type.as_str() {
+                "directional" => Light::directional(color, direction),
+                "point" => Light::point(color, position)
+                    .with_attenuation(Attenuation::INVERSE_SQUARE)
+                    .with_range(light.range),
+                "spot" => {
+                    let spot = light
+                        .spot
+                        .as_ref()
+                        .ok_or_else(|| anyhow!("Missing spot light structure"))?;
+                    let cone = Cone {
+                        inner_angle: spot.inner_cone_angle,
+                        outer_angle: spot.outer_cone_angle,
+                    };
+                    Light::spot(color, position, direction, cone)
+                        .with_attenuation(Attenuation::INVERSE_SQUARE)
+                        .with_range(light.range)
+                }
+                _ => return Err(anyhow!("Unknown light type: {}", light.light_type)),
+            };
+            Ok(light.with_color_space(ColorSpace::Linear))
+        })
+        .collect()
+}
+
+pub fn build_light_nodes(
+    gltf_nodes: Vec<&data::Node>,
+    nodes: &[SharedRef<Node>],
+    lights: &[Light],
+) -> Vec<Rc<LightNode>> {
     nodes
+        .iter()
+        .zip(gltf_nodes)
+        .filter_map(|(node, gltf_node)| {
+            gltf_node
+                .extensions
+                .as_ref()
+                .and_then(|extensions| extensions.khr_lights_punctual.as_ref())
+                .map(|reference| {
+                    let light = self::get_cloned_by_u32(lights, reference.light);
+                    LightNode::attach(Rc::clone(node), RefCell::new(light))
+                })
+        })
+        .collect()
 }
 
 pub fn build_samplers(samplers: Vec<&data::Sampler>) -> Result<Vec<Rc<Sampler>>> {
+    let mut cache = SamplerCache::default();
     samplers
         .iter()
         .map(|sampler| {
@@ -307,33 +713,115 @@ pub fn build_samplers(samplers: Vec<&data::Sampler>) -> Result<Vec<Rc<Sampler>>>
                 sampler.wrap_s,
                 sampler.wrap_t,
             )
-            .map(Rc::new)
+            .map(|sampler| cache.get(sampler))
         })
         .collect()
 }
 
-pub fn build_textures(
-    context: &WebGl2RenderingContext,
-    textures: Vec<&data::Texture>,
-    samplers: &[Rc<Sampler>],
-    images: &[Rc<Image>],
-) -> Result<Vec<Rc<Texture>>> {
-    textures
+pub fn color_texture_indices(materials: Vec<&data::Material>) -> HashSet<u32> {
+    materials
         .into_iter()
-        .map(|texture| {
-            let sampler = texture
-                .sampler
-                .map(|index| self::get_rc_by_u32(samplers, index))
-                .unwrap_or_default();
-            let source = texture
-                .source
-                .map(|index| self::get_rc_by_u32(images, index))
-                .expect("Expected source image in texture");
-            Texture::initialize(context, sampler, source)
+        .flat_map(|material| {
+            [
+                material.pbr_metallic_roughness.base_color_texture.as_ref(),
+                material.emissive_texture.as_ref(),
+            ]
         })
+        .flatten()
+        .map(|texture_info| texture_info.index)
         .collect()
 }
 
+/// Builds textures, sharing images and GL textures between textures with the same source.
+pub struct TextureBuilder<'a> {
+    context: &'a WebGl2RenderingContext,
+    samplers: Vec<Rc<Sampler>>,
+    default_sampler: Rc<Sampler>,
+    color_textures: HashSet<u32>,
+    images: HashMap<u32, Rc<Image>>,
+    by_source: HashMap<(u32, ColorSpace), Rc<Texture>>,
+}
+
+impl<'a> TextureBuilder<'a> {
+    pub fn new(
+        context: &'a WebGl2RenderingContext,
+        samplers: Vec<Rc<Sampler>>,
+        color_textures: HashSet<u32>,
+    ) -> Self {
+        Self {
+            context,
+            samplers,
+            default_sampler: Rc::new(Sampler::default()),
+            color_textures,
+            images: HashMap::new(),
+            by_source: HashMap::new(),
+        }
+    }
+
+    pub fn image(&self, source: u32) -> Option<Rc<Image>> {
+        self.images.get(&source).cloned()
+    }
+
+    pub fn add_image(&mut self, source: u32, image: Rc<Image>) {
+        self.images.insert(source, image);
+    }
+
+    /// Returns the texture if its source was already uploaded in the same color space.
+    pub fn shared(
+        &self,
+        index: usize,
+        texture: &data::Texture,
+        source: u32,
+    ) -> Option<Rc<Texture>> {
+        self.by_source
+            .get(&(source, self.color_space(index)))
+            .map(|shared| shared.with_sampler(self.context, self.sampler(texture)))
+    }
+
+    pub fn build(
+        &mut self,
+        index: usize,
+        texture: &data::Texture,
+        source: u32,
+    ) -> Result<Rc<Texture>> {
+        if let Some(shared) = self.shared(index, texture, source) {
+            return Ok(shared);
+        }
+        let image = self.image(source).ok_or_else(|| {
+            anyhow!(
+                "/textures/{}/source: Source image {} was not loaded",
+                index,
+                source
+            )
+        })?;
+        let color_space = self.color_space(index);
+        let built = Texture::initialize_with_color_space(
+            self.context,
+            self.sampler(texture),
+            image,
+            color_space,
+        )?;
+        self.by_source
+            .insert((source, color_space), Rc::clone(&built));
+        Ok(built)
+    }
+
+    fn sampler(&self, texture: &data::Texture) -> Rc<Sampler> {
+        texture
+            .sampler
+            .map(|index| self::get_rc_by_u32(&self.samplers, index))
+            .unwrap_or_else(|| Rc::clone(&self.default_sampler))
+    }
+
+    fn color_space(&self, index: usize) -> ColorSpace {
+        if self.color_textures.contains(&(index as u32)) {
+            ColorSpace::Srgb
+        } else {
+            ColorSpace::Linear
+        }
+    }
+}
+
 pub fn build_scenes(scenes: Vec<&data::Scene>, nodes: &[SharedRef<Node>]) -> Vec<Scene> {
     scenes
         .into_iter()
@@ -367,3 +855,92 @@ fn get_rc_by_u32<T>(slice: &[Rc<T>], index: u32) -> Rc<T> {
 fn get_cloned_by_u32<T: Clone>(slice: &[T], index: u32) -> T {
     slice[index as usize].clone()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::classic::light::LightType;
+
+    #[test]
+    fn build_lights_works() {
+        let light =
+            |light_type: &str, range: Option<f32>, spot: Option<data::Spot>| data::PunctualLight {
+                color: [1.0, 0.0, 0.0],
+                intensity: 1.0,
+                light_type: String::from(light_type),
+                range,
+                spot,
+            };
+        let spot = data::Spot {
+            inner_cone_angle: 0.25,
+            outer_cone_angle: 0.5,
+        };
+        let lights = build_lights(vec![
+            &light("directional", None, None),
+            &light("point", Some(10.0), None),
+            &light("spot", None, Some(spot)),
+        ])
+        .unwrap();
+        assert_eq!(lights[0].color, color::rgb(1.0, 0.0, 0.0));
+        assert_eq!(lights[0].color_space, ColorSpace::Linear);
+        assert_eq!(lights[0].as_directional(), Some(&glm::vec3(0.0, 0.0, -1.0)));
+        assert_eq!(lights[1].range, Some(10.0));
+        assert_eq!(
+            Vec3::from(lights[1].attenuation),
+            Vec3::from(Attenuation::INVERSE_SQUARE)
+        );
+        match lights[2].light_type {
+            Some(LightType::Spot { cone, .. }) => {
+                assert_eq!((cone.inner_angle, cone.outer_angle), (0.25, 0.5))
+            }
+            _ => panic!("Expected a spot light"),
+        }
+        assert!(build_lights(vec![&light("spot", None, None)]).is_err());
+        assert!(build_lights(vec![&light("area", None, None)]).is_err());
+    }
+
+    #[test]
+    fn build_cameras_reports_pointers() {
+        let camera = |camera_type: &str| data::Camera {
+            orthographic: None,
+            perspective: None,
+            camera_type: String::from(camera_type),
+            name: None,
+        };
+        let error = |camera: data::Camera| build_cameras(vec![&camera]).unwrap_err().to_string();
+        assert_eq!(
+            error(camera("perspective")),
+            "/cameras/0/perspective: Missing perspective camera properties"
+        );
+        assert_eq!(
+            error(camera("fisheye")),
+            "/cameras/0/type: Unknown camera type: fisheye"
+        );
+        let orthographic = data::Camera {
+            orthographic: Some(data::Orthographic {
+                x_mag: 1.0,
+                y_mag: 1.0,
+                z_far: 10.0,
+                z_near: 0.1,
+            }),
+            ..camera("orthographic")
+        };
+        assert_eq!(build_cameras(vec![&orthographic]).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn texture_sources_prefer_basisu() {
+        let texture = |source: Option<u32>, basisu: Option<u32>| data::Texture {
+            sampler: None,
+            source,
+            extensions: basisu.map(|source| data::TextureExtensions {
+                khr_texture_basisu: Some(data::TextureBasisu { source }),
+            }),
+        };
+        let sources = |texture: data::Texture| (texture.image_source(), texture.fallback_source());
+        assert_eq!(sources(texture(Some(0), Some(1))), (Some(1), Some(0)));
+        assert_eq!(sources(texture(None, Some(2))), (Some(2), None));
+        assert_eq!(sources(texture(Some(3), None)), (Some(3), None));
+        assert_eq!(sources(texture(None, None)), (None, None));
+    }
+}
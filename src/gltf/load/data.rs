@@ -1,10 +1,12 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use web_sys::WebGl2RenderingContext;
 
-#[derive(Debug, Clone, Deserialize)]
+use crate::base::util::value::Value;
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Accessor {
     pub buffer_view: Option<u32>,
@@ -20,7 +22,44 @@ pub struct Accessor {
     pub normalized: bool,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Animation {
+    pub channels: Vec<AnimationChannel>,
+    pub samplers: Vec<AnimationSampler>,
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnimationChannel {
+    pub sampler: u32,
+    pub target: AnimationChannelTarget,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnimationChannelTarget {
+    pub node: Option<u32>,
+    pub path: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnimationSampler {
+    pub input: u32,
+    #[serde(default = "AnimationSampler::default_interpolation")]
+    pub interpolation: String,
+    pub output: u32,
+}
+
+impl AnimationSampler {
+    fn default_interpolation() -> String {
+        String::from("LINEAR")
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Asset {
     pub copyright: Option<String>,
@@ -28,14 +67,27 @@ pub struct Asset {
     pub version: String,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Buffer {
     pub uri: Option<String>,
     pub byte_length: u32,
+    pub extensions: Option<BufferExtensions>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BufferExtensions {
+    #[serde(rename = "EXT_meshopt_compression")]
+    pub ext_meshopt_compression: Option<MeshoptBuffer>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MeshoptBuffer {
+    #[serde(default)]
+    pub fallback: bool,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BufferView {
     pub buffer: u32,
@@ -44,9 +96,36 @@ pub struct BufferView {
     pub byte_length: u32,
     pub byte_stride: Option<i32>,
     pub target: Option<u32>,
+    pub extensions: Option<BufferViewExtensions>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BufferViewExtensions {
+    #[serde(rename = "EXT_meshopt_compression")]
+    pub ext_meshopt_compression: Option<MeshoptCompression>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MeshoptCompression {
+    pub buffer: u32,
+    #[serde(default)]
+    pub byte_offset: u32,
+    pub byte_length: u32,
+    pub byte_stride: u32,
+    pub count: u32,
+    pub mode: String,
+    #[serde(default = "MeshoptCompression::default_filter")]
+    pub filter: String,
+}
+
+impl MeshoptCompression {
+    fn default_filter() -> String {
+        String::from("NONE")
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Camera {
     pub orthographic: Option<Orthographic>,
@@ -56,7 +135,7 @@ pub struct Camera {
     pub name: Option<String>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Orthographic {
     #[serde(rename = "xmag")]
@@ -69,7 +148,7 @@ pub struct Orthographic {
     pub z_near: f32,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Perspective {
     pub aspect_ratio: Option<f32>,
@@ -81,14 +160,18 @@ pub struct Perspective {
     pub z_near: f32,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Gltf {
     pub asset: Asset,
     pub accessors: Option<Vec<Accessor>>,
+    pub animations: Option<Vec<Animation>>,
     pub buffers: Option<Vec<Buffer>>,
     pub buffer_views: Option<Vec<BufferView>>,
     pub cameras: Option<Vec<Camera>>,
+    pub extensions: Option<Extensions>,
+    pub extensions_used: Option<Vec<String>>,
+    pub extensions_required: Option<Vec<String>>,
     pub images: Option<Vec<Image>>,
     pub materials: Option<Vec<Material>>,
     pub meshes: Option<Vec<Mesh>>,
@@ -96,24 +179,77 @@ pub struct Gltf {
     pub samplers: Option<Vec<Sampler>>,
     pub scene: Option<u32>,
     pub scenes: Option<Vec<Scene>>,
+    pub skins: Option<Vec<Skin>>,
     pub textures: Option<Vec<Texture>>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Extensions {
+    #[serde(rename = "KHR_lights_punctual")]
+    pub khr_lights_punctual: Option<LightsPunctual>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LightsPunctual {
+    pub lights: Vec<PunctualLight>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PunctualLight {
+    #[serde(default = "PunctualLight::default_color")]
+    pub color: [f32; 3],
+    #[serde(default = "PunctualLight::default_intensity")]
+    pub intensity: f32,
+    #[serde(rename = "type")]
+    pub light_type: String,
+    pub range: Option<f32>,
+    pub spot: Option<Spot>,
+}
+
+impl PunctualLight {
+    fn default_color() -> [f32; 3] {
+        [1.0, 1.0, 1.0]
+    }
+
+    fn default_intensity() -> f32 {
+        1.0
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Spot {
+    #[serde(default)]
+    pub inner_cone_angle: f32,
+    #[serde(default = "Spot::default_outer_cone_angle")]
+    pub outer_cone_angle: f32,
+}
+
+impl Spot {
+    fn default_outer_cone_angle() -> f32 {
+        std::f32::consts::FRAC_PI_4
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Image {
     pub uri: Option<String>,
+    pub buffer_view: Option<u32>,
     pub mime_type: Option<String>,
     pub name: Option<String>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Material {
     pub name: Option<String>,
     #[serde(default)]
     pub pbr_metallic_roughness: PbrMetallicRoughness,
     pub normal_texture: Option<NormalTextureInfo>,
+    pub emissive_texture: Option<TextureInfo>,
     #[serde(default)]
     pub emissive_factor: [f32; 3],
     #[serde(default = "Material::default_alpha_mode")]
@@ -122,6 +258,8 @@ pub struct Material {
     pub alpha_cutoff: f32,
     #[serde(default)]
     pub double_sided: bool,
+    pub extensions: Option<MaterialExtensions>,
+    pub extras: Option<Value>,
 }
 
 impl Material {
@@ -134,7 +272,7 @@ impl Material {
     }
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PbrMetallicRoughness {
     #[serde(default = "PbrMetallicRoughness::default_base_color_factor")]
@@ -173,30 +311,124 @@ impl Default for PbrMetallicRoughness {
     }
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct NormalTextureInfo {
     pub index: u32,
     #[serde(default)]
     pub tex_coord: u32,
+    #[serde(default = "NormalTextureInfo::default_scale")]
+    pub scale: f32,
+    pub extensions: Option<TextureInfoExtensions>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+impl NormalTextureInfo {
+    fn default_scale() -> f32 {
+        1.0
+    }
+
+    pub fn texture_info(&self) -> TextureInfo {
+        TextureInfo {
+            index: self.index,
+            tex_coord: self.tex_coord,
+            extensions: self.extensions.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TextureInfo {
     pub index: u32,
     #[serde(default)]
     pub tex_coord: u32,
+    pub extensions: Option<TextureInfoExtensions>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TextureInfoExtensions {
+    #[serde(rename = "KHR_texture_transform")]
+    pub khr_texture_transform: Option<TextureTransform>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextureTransform {
+    #[serde(default)]
+    pub offset: [f32; 2],
+    #[serde(default)]
+    pub rotation: f32,
+    #[serde(default = "TextureTransform::default_scale")]
+    pub scale: [f32; 2],
+    pub tex_coord: Option<u32>,
+}
+
+impl TextureTransform {
+    fn default_scale() -> [f32; 2] {
+        [1.0, 1.0]
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MaterialExtensions {
+    #[serde(rename = "KHR_materials_unlit")]
+    pub khr_materials_unlit: Option<MaterialsUnlit>,
+    #[serde(rename = "KHR_materials_emissive_strength")]
+    pub khr_materials_emissive_strength: Option<MaterialsEmissiveStrength>,
+    #[serde(rename = "KHR_materials_clearcoat")]
+    pub khr_materials_clearcoat: Option<MaterialsClearcoat>,
+    #[serde(rename = "KHR_materials_transmission")]
+    pub khr_materials_transmission: Option<MaterialsTransmission>,
+    #[serde(flatten)]
+    pub others: BTreeMap<String, Value>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MaterialsUnlit {}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaterialsEmissiveStrength {
+    #[serde(default = "MaterialsEmissiveStrength::default_emissive_strength")]
+    pub emissive_strength: f32,
+}
+
+impl MaterialsEmissiveStrength {
+    fn default_emissive_strength() -> f32 {
+        1.0
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaterialsClearcoat {
+    #[serde(default)]
+    pub clearcoat_factor: f32,
+    pub clearcoat_texture: Option<TextureInfo>,
+    #[serde(default)]
+    pub clearcoat_roughness_factor: f32,
+    pub clearcoat_roughness_texture: Option<TextureInfo>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaterialsTransmission {
+    #[serde(default)]
+    pub transmission_factor: f32,
+    pub transmission_texture: Option<TextureInfo>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Mesh {
     pub primitives: Vec<Primitive>,
+    pub weights: Option<Vec<f32>>,
     pub name: Option<String>,
+    pub extensions: Option<BTreeMap<String, Value>>,
+    pub extras: Option<Value>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Primitive {
     pub attributes: HashMap<String, u32>,
@@ -204,6 +436,7 @@ pub struct Primitive {
     pub material: Option<u32>,
     #[serde(default = "Primitive::default_mode")]
     pub mode: u32,
+    pub targets: Option<Vec<HashMap<String, u32>>>,
 }
 
 impl Primitive {
@@ -212,7 +445,7 @@ impl Primitive {
     }
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Node {
     pub camera: Option<u32>,
@@ -222,10 +455,28 @@ pub struct Node {
     pub translation: Option<[f32; 3]>,
     pub rotation: Option<[f32; 4]>,
     pub scale: Option<[f32; 3]>,
+    pub skin: Option<u32>,
+    pub weights: Option<Vec<f32>>,
     pub name: Option<String>,
+    pub extensions: Option<NodeExtensions>,
+    pub extras: Option<Value>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct NodeExtensions {
+    #[serde(rename = "KHR_lights_punctual")]
+    pub khr_lights_punctual: Option<NodeLightsPunctual>,
+    #[serde(flatten)]
+    pub others: BTreeMap<String, Value>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeLightsPunctual {
+    pub light: u32,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Sampler {
     pub mag_filter: Option<i32>,
@@ -246,16 +497,56 @@ impl Sampler {
     }
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Scene {
     pub name: Option<String>,
     pub nodes: Option<Vec<u32>>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Skin {
+    pub inverse_bind_matrices: Option<u32>,
+    pub skeleton: Option<u32>,
+    pub joints: Vec<u32>,
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Texture {
     pub sampler: Option<u32>,
     pub source: Option<u32>,
+    pub extensions: Option<TextureExtensions>,
+}
+
+impl Texture {
+    /// Prefers the KTX2 image of `KHR_texture_basisu` over the core source.
+    pub fn image_source(&self) -> Option<u32> {
+        self.basisu_source().or(self.source)
+    }
+
+    /// The core source to use when the KTX2 image cannot be loaded or transcoded.
+    pub fn fallback_source(&self) -> Option<u32> {
+        self.basisu_source().and(self.source)
+    }
+
+    pub fn basisu_source(&self) -> Option<u32> {
+        self.extensions
+            .as_ref()
+            .and_then(|extensions| extensions.khr_texture_basisu.as_ref())
+            .map(|basisu| basisu.source)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TextureExtensions {
+    #[serde(rename = "KHR_texture_basisu")]
+    pub khr_texture_basisu: Option<TextureBasisu>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TextureBasisu {
+    pub source: u32,
 }
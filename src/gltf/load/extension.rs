@@ -0,0 +1,54 @@
+use std::{collections::HashMap, fmt, rc::Rc};
+
+use anyhow::Result;
+
+use crate::{
+    base::util::{shared_ref::SharedRef, value::Value},
+    core::node::Node,
+};
+
+pub type NodeExtensionHandler = dyn Fn(&SharedRef<Node>, &Value) -> Result<()>;
+
+/// Handlers for application specific glTF extensions, keyed by extension name.
+#[derive(Clone, Default)]
+pub struct ExtensionHandlers {
+    node_handlers: HashMap<String, Rc<NodeExtensionHandler>>,
+}
+
+impl ExtensionHandlers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn on_node<F>(mut self, extension: &str, handler: F) -> Self
+    where
+        F: Fn(&SharedRef<Node>, &Value) -> Result<()> + 'static,
+    {
+        self.node_handlers
+            .insert(String::from(extension), Rc::new(handler));
+        self
+    }
+
+    pub fn handles(&self, extension: &str) -> bool {
+        self.node_handlers.contains_key(extension)
+    }
+
+    pub fn handle_node(&self, node: &SharedRef<Node>) -> Result<()> {
+        let extensions = node.borrow().custom_properties().extensions.clone();
+        for (extension, value) in extensions.iter() {
+            if let Some(handler) = self.node_handlers.get(extension) {
+                handler(node, value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for ExtensionHandlers {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("ExtensionHandlers")
+            .field("node_handlers", &self.node_handlers.keys())
+            .finish()
+    }
+}
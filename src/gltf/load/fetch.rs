@@ -1,45 +1,97 @@
-use anyhow::{anyhow, Result};
+use std::rc::Rc;
 
-use js_sys::ArrayBuffer;
+use anyhow::{anyhow, bail, Result};
+
+use js_sys::{ArrayBuffer, Uint8Array};
 use url::Url;
-use web_sys::HtmlImageElement;
 
-use crate::base::web;
+use crate::{
+    base::asset::AssetSource,
+    core::{buffer_view::BufferView, image::ImageType},
+    gltf::glb,
+};
 
 use super::data::{self, Gltf};
 
-pub async fn fetch_gltf(uri: &str) -> Result<Gltf> {
-    serde_wasm_bindgen::from_value(web::fetch_json(uri).await?)
-        .map_err(|error| anyhow!("Error while fetching glTF from {}: {:#?}", uri, error))
+pub struct Document {
+    pub gltf: Gltf,
+    pub binary_chunk: Option<ArrayBuffer>,
+}
+
+pub async fn fetch_gltf(source: &dyn AssetSource, uri: &str) -> Result<Document> {
+    let data = source.read_bytes(uri).await?;
+    let (json, binary_chunk) = if glb::is_glb(&data) {
+        let glb = glb::parse(&data)?;
+        (glb.json, glb.bin.map(|bin| Uint8Array::from(bin).buffer()))
+    } else {
+        let json = std::str::from_utf8(&data)
+            .map_err(|error| anyhow!("Invalid glTF JSON in {}: {}", uri, error))?;
+        (json, None)
+    };
+    let value = js_sys::JSON::parse(json)
+        .map_err(|error| anyhow!("Error while parsing glTF from {}: {:#?}", uri, error))?;
+    let gltf = serde_wasm_bindgen::from_value(value)
+        .map_err(|error| anyhow!("Error while fetching glTF from {}: {:#?}", uri, error))?;
+    Ok(Document { gltf, binary_chunk })
 }
 
-pub async fn fetch_buffers(base_url: &Url, buffers: &[&data::Buffer]) -> Result<Vec<ArrayBuffer>> {
+pub async fn fetch_buffers(
+    source: &dyn AssetSource,
+    base_url: &Url,
+    buffers: &[&data::Buffer],
+    binary_chunk: Option<&ArrayBuffer>,
+) -> Result<Vec<ArrayBuffer>> {
     let mut result = Vec::with_capacity(buffers.len());
     for (i, buffer) in buffers.iter().enumerate() {
+        if buffer.uri.is_none() && self::is_meshopt_fallback(buffer) {
+            result.push(ArrayBuffer::new(0));
+            continue;
+        }
+        if let (None, Some(binary_chunk)) = (&buffer.uri, binary_chunk.filter(|_| i == 0)) {
+            result.push(binary_chunk.clone());
+            continue;
+        }
         let relative_uri = buffer
             .uri
             .as_ref()
             .ok_or_else(|| anyhow!("Undefined url in buffer[{}]", i))?;
         let url = base_url.join(relative_uri)?;
-        let array_buffer = web::fetch_array_buffer(url.as_str()).await?;
-        result.push(array_buffer);
+        let data = source.read_bytes(url.as_str()).await?;
+        result.push(Uint8Array::from(data.as_slice()).buffer());
     }
     Ok(result)
 }
 
-pub async fn fetch_images(
+fn is_meshopt_fallback(buffer: &data::Buffer) -> bool {
+    buffer
+        .extensions
+        .as_ref()
+        .and_then(|extensions| extensions.ext_meshopt_compression.as_ref())
+        .is_some_and(|meshopt| meshopt.fallback)
+}
+
+pub async fn fetch_image(
+    source: &dyn AssetSource,
     base_url: &Url,
-    images: &[&data::Image],
-) -> Result<Vec<HtmlImageElement>> {
-    let mut result = Vec::with_capacity(images.len());
-    for (n, image) in images.iter().enumerate() {
-        let relative_uri = image
-            .uri
-            .as_ref()
-            .ok_or_else(|| anyhow!("Undefined url in image[{}]", n))?;
-        let url = base_url.join(relative_uri)?;
-        let array_buffer = web::fetch_image(url.as_str()).await?;
-        result.push(array_buffer);
+    image: &data::Image,
+    index: u32,
+    buffer_views: &[Rc<BufferView>],
+) -> Result<ImageType> {
+    match (&image.uri, image.buffer_view) {
+        (Some(relative_uri), _) => {
+            let url = base_url.join(relative_uri)?;
+            ImageType::load(source, url.as_str(), image.mime_type.as_deref()).await
+        }
+        (None, Some(buffer_view)) => {
+            let buffer_view = buffer_views
+                .get(buffer_view as usize)
+                .ok_or_else(|| anyhow!("Unknown buffer view in image[{}]", index))?;
+            let mime_type = image
+                .mime_type
+                .as_deref()
+                .ok_or_else(|| anyhow!("Undefined mime type in image[{}]", index))?;
+            ImageType::decode(&buffer_view.to_vec(), mime_type).await
+        }
+        (None, None) => bail!("Undefined url in image[{}]", index),
     }
-    Ok(result)
 }
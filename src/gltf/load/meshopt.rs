@@ -0,0 +1,556 @@
+use anyhow::{anyhow, bail, Result};
+
+use crate::base::util::validate;
+
+const VERTEX_HEADER: u8 = 0xa0;
+const INDEX_HEADER: u8 = 0xe0;
+const SEQUENCE_HEADER: u8 = 0xd0;
+const BYTE_GROUP_SIZE: usize = 16;
+const VERTEX_BLOCK_SIZE_BYTES: usize = 8192;
+const VERTEX_BLOCK_MAX_SIZE: usize = 256;
+const TAIL_MAX_SIZE: usize = 32;
+const FIFO_SIZE: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Attributes,
+    Triangles,
+    Indices,
+}
+
+impl TryFrom<&str> for Mode {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        match value {
+            "ATTRIBUTES" => Ok(Self::Attributes),
+            "TRIANGLES" => Ok(Self::Triangles),
+            "INDICES" => Ok(Self::Indices),
+            _ => Err(anyhow!("Unknown meshopt compression mode: {}", value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Filter {
+    None,
+    Octahedral,
+    Quaternion,
+    Exponential,
+}
+
+impl TryFrom<&str> for Filter {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        match value {
+            "NONE" => Ok(Self::None),
+            "OCTAHEDRAL" => Ok(Self::Octahedral),
+            "QUATERNION" => Ok(Self::Quaternion),
+            "EXPONENTIAL" => Ok(Self::Exponential),
+            _ => Err(anyhow!("Unknown meshopt compression filter: {}", value)),
+        }
+    }
+}
+
+pub fn decode(
+    source: &[u8],
+    count: usize,
+    byte_stride: usize,
+    mode: Mode,
+    filter: Filter,
+) -> Result<Vec<u8>> {
+    let mut destination = vec![0; count * byte_stride];
+    match mode {
+        Mode::Attributes => {
+            self::decode_vertex_buffer(&mut destination, count, byte_stride, source)?
+        }
+        Mode::Triangles => self::decode_index_buffer(&mut destination, count, byte_stride, source)?,
+        Mode::Indices => self::decode_index_sequence(&mut destination, count, byte_stride, source)?,
+    }
+    match filter {
+        Filter::None => {}
+        Filter::Octahedral => self::decode_filter_octahedral(&mut destination, byte_stride)?,
+        Filter::Quaternion => self::decode_filter_quaternion(&mut destination, byte_stride)?,
+        Filter::Exponential => self::decode_filter_exponential(&mut destination, byte_stride)?,
+    }
+    Ok(destination)
+}
+
+pub fn decode_vertex_buffer(
+    destination: &mut [u8],
+    vertex_count: usize,
+    vertex_size: usize,
+    source: &[u8],
+) -> Result<()> {
+    validate::assert(
+        vertex_size > 0 && vertex_size <= 256 && vertex_size.is_multiple_of(4),
+        || anyhow!("Invalid meshopt vertex size: {}", vertex_size),
+    )?;
+    validate::assert(destination.len() == vertex_count * vertex_size, || {
+        anyhow!(
+            "Invalid meshopt vertex buffer length: {}",
+            destination.len()
+        )
+    })?;
+    let tail_size = vertex_size.max(TAIL_MAX_SIZE);
+    validate::assert(source.len() > tail_size, || {
+        anyhow!("Meshopt vertex buffer is too short: {}", source.len())
+    })?;
+    if source[0] != VERTEX_HEADER {
+        bail!("Unsupported meshopt vertex buffer header: {:#x}", source[0]);
+    }
+    let data_end = source.len() - tail_size;
+    let mut last_vertex = source[source.len() - vertex_size..].to_vec();
+    let block_size = ((VERTEX_BLOCK_SIZE_BYTES / vertex_size) & !(BYTE_GROUP_SIZE - 1))
+        .min(VERTEX_BLOCK_MAX_SIZE);
+    let mut buffer = [0; VERTEX_BLOCK_MAX_SIZE];
+    let mut position = 1;
+    let mut vertex_offset = 0;
+    while vertex_offset < vertex_count {
+        let count = block_size.min(vertex_count - vertex_offset);
+        let count_aligned = (count + BYTE_GROUP_SIZE - 1) & !(BYTE_GROUP_SIZE - 1);
+        for (k, last) in last_vertex.iter_mut().enumerate() {
+            position =
+                self::decode_bytes(source, position, data_end, &mut buffer[..count_aligned])?;
+            let mut previous = *last;
+            for (i, byte) in buffer[..count].iter().enumerate() {
+                let value = self::unzigzag8(*byte).wrapping_add(previous);
+                destination[(vertex_offset + i) * vertex_size + k] = value;
+                previous = value;
+            }
+            *last = previous;
+        }
+        vertex_offset += count;
+    }
+    validate::assert(position == data_end, || {
+        anyhow!("Unexpected meshopt vertex buffer length")
+    })
+}
+
+fn decode_bytes(
+    source: &[u8],
+    position: usize,
+    data_end: usize,
+    buffer: &mut [u8],
+) -> Result<usize> {
+    let header_size = (buffer.len() / BYTE_GROUP_SIZE).div_ceil(4);
+    let header = position;
+    let mut position = position + header_size;
+    for (group, values) in buffer.chunks_exact_mut(BYTE_GROUP_SIZE).enumerate() {
+        validate::assert(position <= data_end, || {
+            anyhow!("Meshopt vertex data overflow")
+        })?;
+        let bits_log2 = (source[header + group / 4] >> ((group % 4) * 2)) & 3;
+        position = match bits_log2 {
+            0 => {
+                values.fill(0);
+                position
+            }
+            1 => self::decode_bytes_group(source, position, data_end, values, 2)?,
+            2 => self::decode_bytes_group(source, position, data_end, values, 4)?,
+            _ => {
+                let end = position + BYTE_GROUP_SIZE;
+                validate::assert(end <= data_end, || anyhow!("Meshopt vertex data overflow"))?;
+                values.copy_from_slice(&source[position..end]);
+                end
+            }
+        };
+    }
+    Ok(position)
+}
+
+fn decode_bytes_group(
+    source: &[u8],
+    position: usize,
+    data_end: usize,
+    values: &mut [u8],
+    bits: u32,
+) -> Result<usize> {
+    let packed_size = BYTE_GROUP_SIZE * bits as usize / 8;
+    let sentinel = (1 << bits) - 1;
+    let values_per_byte = 8 / bits as usize;
+    let mut extra = position + packed_size;
+    validate::assert(extra <= data_end, || {
+        anyhow!("Meshopt vertex data overflow")
+    })?;
+    for (i, value) in values.iter_mut().enumerate() {
+        let byte = source[position + i / values_per_byte];
+        let shift = 8 - bits * (1 + (i % values_per_byte) as u32);
+        let encoded = (byte >> shift) & sentinel;
+        *value = if encoded == sentinel {
+            validate::assert(extra < data_end, || anyhow!("Meshopt vertex data overflow"))?;
+            extra += 1;
+            source[extra - 1]
+        } else {
+            encoded
+        };
+    }
+    Ok(extra)
+}
+
+fn unzigzag8(value: u8) -> u8 {
+    (value >> 1) ^ (value & 1).wrapping_neg()
+}
+
+pub fn decode_index_buffer(
+    destination: &mut [u8],
+    index_count: usize,
+    index_size: usize,
+    source: &[u8],
+) -> Result<()> {
+    self::validate_indices(destination, index_count, index_size)?;
+    validate::assert(index_count.is_multiple_of(3), || {
+        anyhow!(
+            "Meshopt index count is not a multiple of 3: {}",
+            index_count
+        )
+    })?;
+    validate::assert(source.len() >= 1 + index_count / 3 + FIFO_SIZE, || {
+        anyhow!("Meshopt index buffer is too short: {}", source.len())
+    })?;
+    let version = match source[0] {
+        header if header & 0xf0 == INDEX_HEADER && header & 0x0f <= 1 => header & 0x0f,
+        header => bail!("Unsupported meshopt index buffer header: {:#x}", header),
+    };
+    let mut edge_fifo = [[u32::MAX; 2]; FIFO_SIZE];
+    let mut vertex_fifo = [u32::MAX; FIFO_SIZE];
+    let mut edge_offset = 0usize;
+    let mut vertex_offset = 0usize;
+    let mut next = 0u32;
+    let mut last = 0u32;
+    let fec_max = if version >= 1 { 13 } else { 15 };
+
+    let codes = &source[1..1 + index_count / 3];
+    let data_end = source.len() - FIFO_SIZE;
+    let codeaux_table = &source[data_end..];
+    let mut data = 1 + index_count / 3;
+
+    let push_vertex = |fifo: &mut [u32; FIFO_SIZE], offset: &mut usize, value: u32, cond: bool| {
+        fifo[*offset] = value;
+        *offset = (*offset + usize::from(cond)) & (FIFO_SIZE - 1);
+    };
+    let push_edge = |fifo: &mut [[u32; 2]; FIFO_SIZE], offset: &mut usize, a: u32, b: u32| {
+        fifo[*offset] = [a, b];
+        *offset = (*offset + 1) & (FIFO_SIZE - 1);
+    };
+    let vertex_at = |fifo: &[u32; FIFO_SIZE], offset: usize, back: usize| {
+        fifo[offset.wrapping_sub(back) & (FIFO_SIZE - 1)]
+    };
+
+    for (triangle, &code) in codes.iter().enumerate() {
+        validate::assert(data <= data_end, || anyhow!("Meshopt index data overflow"))?;
+        let (a, b, c) = if code < 0xf0 {
+            let fe = (code >> 4) as usize;
+            let [a, b] = edge_fifo[edge_offset.wrapping_sub(1 + fe) & (FIFO_SIZE - 1)];
+            let fec = (code & 15) as usize;
+            let c = if fec < fec_max {
+                let c = if fec == 0 {
+                    next
+                } else {
+                    vertex_at(&vertex_fifo, vertex_offset, 1 + fec)
+                };
+                if fec == 0 {
+                    next += 1;
+                }
+                push_vertex(&mut vertex_fifo, &mut vertex_offset, c, fec == 0);
+                c
+            } else {
+                last = if fec != 15 {
+                    last.wrapping_add(fec as u32).wrapping_sub((fec ^ 3) as u32)
+                } else {
+                    self::decode_index(source, &mut data, data_end, last)?
+                };
+                push_vertex(&mut vertex_fifo, &mut vertex_offset, last, true);
+                last
+            };
+            push_edge(&mut edge_fifo, &mut edge_offset, c, b);
+            push_edge(&mut edge_fifo, &mut edge_offset, a, c);
+            (a, b, c)
+        } else {
+            let (a, b, c, feb, fec) = if code < 0xfe {
+                let codeaux = codeaux_table[(code & 15) as usize];
+                let feb = (codeaux >> 4) as usize;
+                let fec = (codeaux & 15) as usize;
+                let a = next;
+                next += 1;
+                let b = if feb == 0 {
+                    next
+                } else {
+                    vertex_at(&vertex_fifo, vertex_offset, feb)
+                };
+                if feb == 0 {
+                    next += 1;
+                }
+                let c = if fec == 0 {
+                    next
+                } else {
+                    vertex_at(&vertex_fifo, vertex_offset, fec)
+                };
+                if fec == 0 {
+                    next += 1;
+                }
+                (a, b, c, feb, fec)
+            } else {
+                validate::assert(data < data_end, || anyhow!("Meshopt index data overflow"))?;
+                let codeaux = source[data];
+                data += 1;
+                let fea = if code == 0xfe { 0 } else { 15 };
+                let feb = (codeaux >> 4) as usize;
+                let fec = (codeaux & 15) as usize;
+                if codeaux == 0 {
+                    next = 0;
+                }
+                let mut take_next = || {
+                    next += 1;
+                    next - 1
+                };
+                let mut a = if fea == 0 { take_next() } else { 0 };
+                let mut b = if feb == 0 {
+                    take_next()
+                } else {
+                    vertex_at(&vertex_fifo, vertex_offset, feb)
+                };
+                let mut c = if fec == 0 {
+                    take_next()
+                } else {
+                    vertex_at(&vertex_fifo, vertex_offset, fec)
+                };
+                if fea == 15 {
+                    last = self::decode_index(source, &mut data, data_end, last)?;
+                    a = last;
+                }
+                if feb == 15 {
+                    last = self::decode_index(source, &mut data, data_end, last)?;
+                    b = last;
+                }
+                if fec == 15 {
+                    last = self::decode_index(source, &mut data, data_end, last)?;
+                    c = last;
+                }
+                (a, b, c, feb, fec)
+            };
+            let is_new = |fe: usize| fe == 0 || fe == 15;
+            push_vertex(&mut vertex_fifo, &mut vertex_offset, a, true);
+            push_vertex(&mut vertex_fifo, &mut vertex_offset, b, is_new(feb));
+            push_vertex(&mut vertex_fifo, &mut vertex_offset, c, is_new(fec));
+            push_edge(&mut edge_fifo, &mut edge_offset, b, a);
+            push_edge(&mut edge_fifo, &mut edge_offset, c, b);
+            push_edge(&mut edge_fifo, &mut edge_offset, a, c);
+            (a, b, c)
+        };
+        for (i, index) in [a, b, c].into_iter().enumerate() {
+            self::write_index(destination, triangle * 3 + i, index_size, index);
+        }
+    }
+    validate::assert(data == data_end, || {
+        anyhow!("Unexpected meshopt index buffer length")
+    })
+}
+
+pub fn decode_index_sequence(
+    destination: &mut [u8],
+    index_count: usize,
+    index_size: usize,
+    source: &[u8],
+) -> Result<()> {
+    self::validate_indices(destination, index_count, index_size)?;
+    validate::assert(source.len() >= 1 + index_count + 4, || {
+        anyhow!("Meshopt index sequence is too short: {}", source.len())
+    })?;
+    if source[0] & 0xf0 != SEQUENCE_HEADER || source[0] & 0x0f > 1 {
+        bail!(
+            "Unsupported meshopt index sequence header: {:#x}",
+            source[0]
+        );
+    }
+    let data_end = source.len() - 4;
+    let mut data = 1;
+    let mut last = [0u32; 2];
+    for i in 0..index_count {
+        validate::assert(data < data_end, || anyhow!("Meshopt index data overflow"))?;
+        let value = self::decode_vbyte(source, &mut data, data_end)?;
+        let baseline = (value & 1) as usize;
+        let value = value >> 1;
+        let delta = (value >> 1) ^ (value & 1).wrapping_neg();
+        last[baseline] = last[baseline].wrapping_add(delta);
+        self::write_index(destination, i, index_size, last[baseline]);
+    }
+    validate::assert(data == data_end, || {
+        anyhow!("Unexpected meshopt index sequence length")
+    })
+}
+
+fn validate_indices(destination: &[u8], index_count: usize, index_size: usize) -> Result<()> {
+    validate::contains(&index_size, &[2, 4], |value| {
+        anyhow!("Invalid meshopt index size: {}", value)
+    })?;
+    validate::assert(destination.len() == index_count * index_size, || {
+        anyhow!("Invalid meshopt index buffer length: {}", destination.len())
+    })
+}
+
+fn decode_index(source: &[u8], data: &mut usize, data_end: usize, last: u32) -> Result<u32> {
+    let value = self::decode_vbyte(source, data, data_end)?;
+    Ok(last.wrapping_add((value >> 1) ^ (value & 1).wrapping_neg()))
+}
+
+fn decode_vbyte(source: &[u8], data: &mut usize, data_end: usize) -> Result<u32> {
+    let mut result = 0u32;
+    for shift in (0..35).step_by(7) {
+        validate::assert(*data < data_end, || anyhow!("Meshopt index data overflow"))?;
+        let byte = source[*data];
+        *data += 1;
+        result |= u32::from(byte & 0x7f).wrapping_shl(shift);
+        if byte < 0x80 {
+            break;
+        }
+    }
+    Ok(result)
+}
+
+fn write_index(destination: &mut [u8], i: usize, index_size: usize, index: u32) {
+    let bytes = index.to_le_bytes();
+    destination[i * index_size..(i + 1) * index_size].copy_from_slice(&bytes[..index_size]);
+}
+
+fn decode_filter_octahedral(data: &mut [u8], byte_stride: usize) -> Result<()> {
+    match byte_stride {
+        4 => {
+            for vector in data.chunks_exact_mut(4) {
+                let components = [0, 1, 2].map(|i| f32::from(vector[i] as i8));
+                let [x, y, z] = self::decode_octahedral(components, f32::from(i8::MAX));
+                for (i, value) in [x, y, z].into_iter().enumerate() {
+                    vector[i] = value as i8 as u8;
+                }
+            }
+        }
+        8 => {
+            for vector in data.chunks_exact_mut(8) {
+                let components = [0, 1, 2]
+                    .map(|i| f32::from(i16::from_le_bytes([vector[2 * i], vector[2 * i + 1]])));
+                let [x, y, z] = self::decode_octahedral(components, f32::from(i16::MAX));
+                for (i, value) in [x, y, z].into_iter().enumerate() {
+                    vector[2 * i..2 * i + 2].copy_from_slice(&(value as i16).to_le_bytes());
+                }
+            }
+        }
+        _ => bail!("Invalid byte stride for octahedral filter: {}", byte_stride),
+    }
+    Ok(())
+}
+
+fn decode_octahedral([x, y, z]: [f32; 3], max: f32) -> [i32; 3] {
+    let z = z - x.abs() - y.abs();
+    let t = z.min(0.0);
+    let x = x + if x >= 0.0 { t } else { -t };
+    let y = y + if y >= 0.0 { t } else { -t };
+    let scale = max / (x * x + y * y + z * z).sqrt();
+    [x, y, z].map(|value| self::round_to_int(value * scale))
+}
+
+fn decode_filter_quaternion(data: &mut [u8], byte_stride: usize) -> Result<()> {
+    validate::assert(byte_stride == 8, || {
+        anyhow!("Invalid byte stride for quaternion filter: {}", byte_stride)
+    })?;
+    let scale = std::f32::consts::FRAC_1_SQRT_2;
+    for quaternion in data.chunks_exact_mut(8) {
+        let components: [i16; 4] =
+            [0, 1, 2, 3].map(|i| i16::from_le_bytes([quaternion[2 * i], quaternion[2 * i + 1]]));
+        let component_scale = scale / f32::from(components[3] | 3);
+        let [x, y, z] = [0, 1, 2].map(|i| f32::from(components[i]) * component_scale);
+        let w = (1.0 - x * x - y * y - z * z).max(0.0).sqrt();
+        let max_component = (components[3] & 3) as usize;
+        for (i, value) in [w, x, y, z].into_iter().enumerate() {
+            let target = (max_component + i) & 3;
+            let value = self::round_to_int(value * f32::from(i16::MAX)) as i16;
+            quaternion[2 * target..2 * target + 2].copy_from_slice(&value.to_le_bytes());
+        }
+    }
+    Ok(())
+}
+
+fn decode_filter_exponential(data: &mut [u8], byte_stride: usize) -> Result<()> {
+    validate::assert(byte_stride.is_multiple_of(4), || {
+        anyhow!(
+            "Invalid byte stride for exponential filter: {}",
+            byte_stride
+        )
+    })?;
+    for component in data.chunks_exact_mut(4) {
+        let value = i32::from_le_bytes([component[0], component[1], component[2], component[3]]);
+        let mantissa = (value << 8) >> 8;
+        let exponent = value >> 24;
+        let power = f32::from_bits(((exponent + 127) as u32) << 23);
+        component.copy_from_slice(&(power * mantissa as f32).to_le_bytes());
+    }
+    Ok(())
+}
+
+fn round_to_int(value: f32) -> i32 {
+    (value + if value >= 0.0 { 0.5 } else { -0.5 }) as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_vertex_buffer_works() {
+        let expected = include_bytes!("fixtures/meshopt/vertex_raw.bin");
+        let decoded = decode(
+            include_bytes!("fixtures/meshopt/vertex_encoded.bin"),
+            40,
+            8,
+            Mode::Attributes,
+            Filter::None,
+        )
+        .unwrap();
+        assert_eq!(decoded, expected);
+    }
+
+    #[test]
+    fn decode_index_buffer_works() {
+        let expected = include_bytes!("fixtures/meshopt/index_buffer_raw.bin");
+        let decoded = decode(
+            include_bytes!("fixtures/meshopt/index_buffer_encoded.bin"),
+            12,
+            2,
+            Mode::Triangles,
+            Filter::None,
+        )
+        .unwrap();
+        assert_eq!(decoded, expected);
+    }
+
+    #[test]
+    fn decode_index_sequence_works() {
+        let expected = include_bytes!("fixtures/meshopt/index_sequence_raw.bin");
+        let decoded = decode(
+            include_bytes!("fixtures/meshopt/index_sequence_encoded.bin"),
+            15,
+            4,
+            Mode::Indices,
+            Filter::None,
+        )
+        .unwrap();
+        assert_eq!(decoded, expected);
+    }
+
+    #[test]
+    fn decode_filters_works() {
+        let mut quaternion = [0, 0, 0, 0, 0, 0, 3, 0];
+        decode_filter_quaternion(&mut quaternion, 8).unwrap();
+        assert_eq!(quaternion, [0, 0, 0, 0, 0, 0, 0xff, 0x7f]);
+
+        let mut normal = [0, 0, 127, 0];
+        decode_filter_octahedral(&mut normal, 4).unwrap();
+        assert_eq!(normal, [0, 0, 127, 0]);
+
+        let mut value = ((-2i32 << 24) | 3).to_le_bytes();
+        decode_filter_exponential(&mut value, 4).unwrap();
+        assert_eq!(f32::from_le_bytes(value), 0.75);
+
+        assert!(decode(&[0xa1; 40], 1, 4, Mode::Attributes, Filter::None).is_err());
+    }
+}
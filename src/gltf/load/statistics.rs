@@ -3,6 +3,7 @@ use super::data::Gltf;
 #[derive(Debug, Clone)]
 pub struct GltfStatistics {
     pub accessor_count: usize,
+    pub animation_count: usize,
     pub buffer_count: usize,
     pub buffer_byte_length: u32,
     pub buffer_view_count: usize,
@@ -12,6 +13,7 @@ pub struct GltfStatistics {
     pub primitive_count: usize,
     pub node_count: usize,
     pub scene_count: usize,
+    pub skin_count: usize,
     pub vertex_count: i32,
     pub index_count: i32,
 }
@@ -20,6 +22,7 @@ impl From<&Gltf> for GltfStatistics {
     fn from(gltf: &Gltf) -> Self {
         GltfStatistics {
             accessor_count: self::get_count(&gltf.accessors),
+            animation_count: self::get_count(&gltf.animations),
             buffer_count: self::get_count(&gltf.buffers),
             buffer_byte_length: gltf
                 .buffers
@@ -39,6 +42,7 @@ impl From<&Gltf> for GltfStatistics {
                 .sum(),
             node_count: self::get_count(&gltf.nodes),
             scene_count: self::get_count(&gltf.scenes),
+            skin_count: self::get_count(&gltf.skins),
             vertex_count: self::get_vertex_count(gltf),
             index_count: self::get_index_count(gltf),
         }
@@ -0,0 +1,788 @@
+use std::fmt;
+
+use anyhow::{anyhow, Result};
+use web_sys::WebGl2RenderingContext;
+
+use super::{
+    build,
+    data::{self, Gltf},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub pointer: String,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.pointer, self.message)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ValidationReport {
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl ValidationReport {
+    pub fn errors(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.with_severity(Severity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.with_severity(Severity::Warning)
+    }
+
+    pub fn into_result(self) -> Result<()> {
+        let errors: Vec<_> = self.errors().map(ToString::to_string).collect();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow!("Invalid glTF:\n{}", errors.join("\n")))
+        }
+    }
+
+    fn with_severity(&self, severity: Severity) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics
+            .iter()
+            .filter(move |diagnostic| diagnostic.severity == severity)
+    }
+}
+
+pub fn validate(gltf: &Gltf) -> ValidationReport {
+    let mut validator = Validator {
+        gltf,
+        report: ValidationReport::default(),
+    };
+    validator.validate_scenes();
+    validator.validate_nodes();
+    validator.validate_meshes();
+    validator.validate_accessors();
+    validator.validate_buffer_views();
+    validator.validate_cameras();
+    validator.validate_materials();
+    validator.validate_textures();
+    validator.validate_images();
+    validator.validate_skins();
+    validator.validate_animations();
+    validator.report
+}
+
+struct Validator<'a> {
+    gltf: &'a Gltf,
+    report: ValidationReport,
+}
+
+impl Validator<'_> {
+    const ACCESSOR_TYPES: [&'static str; 7] =
+        ["SCALAR", "VEC2", "VEC3", "VEC4", "MAT2", "MAT3", "MAT4"];
+    const ALPHA_MODES: [&'static str; 3] = ["OPAQUE", "MASK", "BLEND"];
+    const CAMERA_TYPES: [&'static str; 2] = ["perspective", "orthographic"];
+    const PATHS: [&'static str; 4] = ["translation", "rotation", "scale", "weights"];
+    const INTERPOLATIONS: [&'static str; 3] = ["LINEAR", "STEP", "CUBICSPLINE"];
+
+    fn validate_scenes(&mut self) {
+        let node_count = self::count(&self.gltf.nodes);
+        let scene_count = self::count(&self.gltf.scenes);
+        if let Some(scene) = self.gltf.scene {
+            self.index("/scene".into(), scene, scene_count);
+        }
+        for (i, scene) in self.gltf.scenes.iter().flatten().enumerate() {
+            for (j, node) in scene.nodes.iter().flatten().enumerate() {
+                self.index(format!("/scenes/{}/nodes/{}", i, j), *node, node_count);
+            }
+        }
+    }
+
+    fn validate_nodes(&mut self) {
+        let nodes = self::slice(&self.gltf.nodes);
+        let light_count = self
+            .gltf
+            .extensions
+            .as_ref()
+            .and_then(|extensions| extensions.khr_lights_punctual.as_ref())
+            .map_or(0, |lights| lights.lights.len());
+        let mut parents = vec![None; nodes.len()];
+        for (i, node) in nodes.iter().enumerate() {
+            let pointer = format!("/nodes/{}", i);
+            if let Some(camera) = node.camera {
+                let count = self::count(&self.gltf.cameras);
+                self.index(format!("{}/camera", pointer), camera, count);
+            }
+            if let Some(mesh) = node.mesh {
+                let count = self::count(&self.gltf.meshes);
+                self.index(format!("{}/mesh", pointer), mesh, count);
+            }
+            if let Some(skin) = node.skin {
+                let count = self::count(&self.gltf.skins);
+                self.index(format!("{}/skin", pointer), skin, count);
+            }
+            if let Some(light) = node
+                .extensions
+                .as_ref()
+                .and_then(|extensions| extensions.khr_lights_punctual.as_ref())
+            {
+                let pointer = format!("{}/extensions/KHR_lights_punctual/light", pointer);
+                self.index(pointer, light.light, light_count);
+            }
+            for (j, child) in node.children.iter().flatten().enumerate() {
+                let child_pointer = format!("{}/children/{}", pointer, j);
+                if !self.index(child_pointer.clone(), *child, nodes.len()) {
+                    continue;
+                }
+                match parents[*child as usize] {
+                    Some(parent) if parent != i => self.error(
+                        child_pointer,
+                        format!("Node {} already has parent {}", child, parent),
+                    ),
+                    _ => parents[*child as usize] = Some(i),
+                }
+            }
+        }
+        for (i, child) in self::find_cycles(nodes) {
+            self.error(
+                format!("/nodes/{}/children/{}", i, child),
+                String::from("Node hierarchy contains a cycle"),
+            );
+        }
+    }
+
+    fn validate_meshes(&mut self) {
+        let accessor_count = self::count(&self.gltf.accessors);
+        let material_count = self::count(&self.gltf.materials);
+        for (i, mesh) in self.gltf.meshes.iter().flatten().enumerate() {
+            let pointer = format!("/meshes/{}", i);
+            if mesh.primitives.is_empty() {
+                self.error(format!("{}/primitives", pointer), "Empty primitives".into());
+            }
+            for (j, primitive) in mesh.primitives.iter().enumerate() {
+                let pointer = format!("{}/primitives/{}", pointer, j);
+                if !primitive.attributes.contains_key("POSITION") {
+                    self.error(
+                        format!("{}/attributes", pointer),
+                        "Missing POSITION attribute".into(),
+                    );
+                }
+                for (name, accessor) in primitive.attributes.iter() {
+                    let pointer = format!("{}/attributes/{}", pointer, name);
+                    self.index(pointer, *accessor, accessor_count);
+                }
+                if let Some(indices) = primitive.indices {
+                    self.index(format!("{}/indices", pointer), indices, accessor_count);
+                }
+                if let Some(material) = primitive.material {
+                    self.index(format!("{}/material", pointer), material, material_count);
+                }
+                if primitive.mode > WebGl2RenderingContext::TRIANGLE_FAN {
+                    self.error(
+                        format!("{}/mode", pointer),
+                        format!("Unknown mode: {}", primitive.mode),
+                    );
+                }
+                for (k, target) in primitive.targets.iter().flatten().enumerate() {
+                    for (name, accessor) in target.iter() {
+                        let pointer = format!("{}/targets/{}/{}", pointer, k, name);
+                        self.index(pointer, *accessor, accessor_count);
+                    }
+                }
+            }
+        }
+    }
+
+    fn validate_accessors(&mut self) {
+        let buffer_views = self::slice(&self.gltf.buffer_views);
+        for (i, accessor) in self.gltf.accessors.iter().flatten().enumerate() {
+            let pointer = format!("/accessors/{}", i);
+            if accessor.count < 1 {
+                self.error(
+                    format!("{}/count", pointer),
+                    format!("Invalid count: {}", accessor.count),
+                );
+            }
+            let component_size = self::component_size(accessor.component_type);
+            if component_size.is_none() {
+                self.error(
+                    format!("{}/componentType", pointer),
+                    format!("Unknown component type: {}", accessor.component_type),
+                );
+            }
+            let size = build::get_size(&accessor.accessor_type).ok();
+            if size.is_none() {
+                self.contains(
+                    format!("{}/type", pointer),
+                    &accessor.accessor_type,
+                    &Self::ACCESSOR_TYPES,
+                );
+            }
+            for (name, bound) in [("min", &accessor.min), ("max", &accessor.max)] {
+                match (bound, size) {
+                    (Some(bound), Some(size)) if bound.len() != size.size() as usize => self
+                        .warning(
+                            format!("{}/{}", pointer, name),
+                            format!("Expected {} components, found {}", size.size(), bound.len()),
+                        ),
+                    _ => {}
+                }
+            }
+            let Some(buffer_view_index) = accessor.buffer_view else {
+                continue;
+            };
+            if !self.index(
+                format!("{}/bufferView", pointer),
+                buffer_view_index,
+                buffer_views.len(),
+            ) {
+                continue;
+            }
+            if let (Some(component_size), Some(size)) = (component_size, size) {
+                let buffer_view = &buffer_views[buffer_view_index as usize];
+                let element_size = component_size * size.size() as u64;
+                let stride = buffer_view
+                    .byte_stride
+                    .map_or(element_size, |stride| stride as u64);
+                let count = accessor.count.max(1) as u64;
+                let end = accessor.byte_offset as u64 + stride * (count - 1) + element_size;
+                if end > buffer_view.byte_length as u64 {
+                    self.error(
+                        pointer.clone(),
+                        format!(
+                            "Accessor range {} exceeds bufferView {} length {}",
+                            end, buffer_view_index, buffer_view.byte_length
+                        ),
+                    );
+                }
+                if !(accessor.byte_offset as u64).is_multiple_of(component_size) {
+                    self.error(
+                        format!("{}/byteOffset", pointer),
+                        String::from("Offset is not a multiple of component size"),
+                    );
+                }
+            }
+        }
+    }
+
+    fn validate_buffer_views(&mut self) {
+        let buffers = self::slice(&self.gltf.buffers);
+        for (i, buffer_view) in self.gltf.buffer_views.iter().flatten().enumerate() {
+            let pointer = format!("/bufferViews/{}", i);
+            if let Some(stride) = buffer_view.byte_stride {
+                if !(4..=252).contains(&stride) || stride % 4 != 0 {
+                    self.error(
+                        format!("{}/byteStride", pointer),
+                        format!("Invalid stride: {}", stride),
+                    );
+                }
+            }
+            let meshopt = buffer_view
+                .extensions
+                .as_ref()
+                .and_then(|extensions| extensions.ext_meshopt_compression.as_ref());
+            if let Some(meshopt) = meshopt {
+                let pointer = format!("{}/extensions/EXT_meshopt_compression", pointer);
+                self.buffer_range(
+                    &pointer,
+                    buffers,
+                    meshopt.buffer,
+                    meshopt.byte_offset,
+                    meshopt.byte_length,
+                );
+            } else {
+                self.buffer_range(
+                    &pointer,
+                    buffers,
+                    buffer_view.buffer,
+                    buffer_view.byte_offset,
+                    buffer_view.byte_length,
+                );
+            }
+        }
+    }
+
+    fn validate_cameras(&mut self) {
+        for (i, camera) in self.gltf.cameras.iter().flatten().enumerate() {
+            let pointer = format!("/cameras/{}", i);
+            let type_pointer = format!("{}/type", pointer);
+            if !self.contains(type_pointer, &camera.camera_type, &Self::CAMERA_TYPES) {
+                continue;
+            }
+            let missing = match camera.camera_type.as_str() {
+                "perspective" => camera.perspective.is_none(),
+                _ => camera.orthographic.is_none(),
+            };
+            if missing {
+                self.error(
+                    format!("{}/{}", pointer, camera.camera_type),
+                    format!("Missing {} camera properties", camera.camera_type),
+                );
+            }
+        }
+    }
+
+    fn validate_materials(&mut self) {
+        for (i, material) in self.gltf.materials.iter().flatten().enumerate() {
+            let pointer = format!("/materials/{}", i);
+            let pbr = &material.pbr_metallic_roughness;
+            let extensions = material.extensions.as_ref();
+            let clearcoat =
+                extensions.and_then(|extensions| extensions.khr_materials_clearcoat.as_ref());
+            let transmission =
+                extensions.and_then(|extensions| extensions.khr_materials_transmission.as_ref());
+            let texture_infos = [
+                (
+                    "pbrMetallicRoughness/baseColorTexture",
+                    pbr.base_color_texture.as_ref().map(|info| info.index),
+                ),
+                (
+                    "pbrMetallicRoughness/metallicRoughnessTexture",
+                    pbr.metallic_roughness_texture
+                        .as_ref()
+                        .map(|info| info.index),
+                ),
+                (
+                    "normalTexture",
+                    material.normal_texture.as_ref().map(|info| info.index),
+                ),
+                (
+                    "emissiveTexture",
+                    material.emissive_texture.as_ref().map(|info| info.index),
+                ),
+                (
+                    "extensions/KHR_materials_clearcoat/clearcoatTexture",
+                    clearcoat
+                        .and_then(|clearcoat| clearcoat.clearcoat_texture.as_ref())
+                        .map(|info| info.index),
+                ),
+                (
+                    "extensions/KHR_materials_clearcoat/clearcoatRoughnessTexture",
+                    clearcoat
+                        .and_then(|clearcoat| clearcoat.clearcoat_roughness_texture.as_ref())
+                        .map(|info| info.index),
+                ),
+                (
+                    "extensions/KHR_materials_transmission/transmissionTexture",
+                    transmission
+                        .and_then(|transmission| transmission.transmission_texture.as_ref())
+                        .map(|info| info.index),
+                ),
+            ];
+            let texture_count = self::count(&self.gltf.textures);
+            for (name, index) in texture_infos {
+                if let Some(index) = index {
+                    self.index(format!("{}/{}/index", pointer, name), index, texture_count);
+                }
+            }
+            self.contains(
+                format!("{}/alphaMode", pointer),
+                &material.alpha_mode,
+                &Self::ALPHA_MODES,
+            );
+        }
+    }
+
+    fn validate_textures(&mut self) {
+        let sampler_count = self::count(&self.gltf.samplers);
+        let image_count = self::count(&self.gltf.images);
+        for (i, texture) in self.gltf.textures.iter().flatten().enumerate() {
+            let pointer = format!("/textures/{}", i);
+            if let Some(sampler) = texture.sampler {
+                self.index(format!("{}/sampler", pointer), sampler, sampler_count);
+            }
+            if let Some(source) = texture.source {
+                self.index(format!("{}/source", pointer), source, image_count);
+            }
+            if let Some(basisu) = texture
+                .extensions
+                .as_ref()
+                .and_then(|extensions| extensions.khr_texture_basisu.as_ref())
+            {
+                let pointer = format!("{}/extensions/KHR_texture_basisu/source", pointer);
+                self.index(pointer, basisu.source, image_count);
+            }
+            if texture.image_source().is_none() {
+                self.error(format!("{}/source", pointer), "Missing source image".into());
+            }
+        }
+    }
+
+    fn validate_images(&mut self) {
+        let buffer_view_count = self::count(&self.gltf.buffer_views);
+        for (i, image) in self.gltf.images.iter().flatten().enumerate() {
+            let pointer = format!("/images/{}", i);
+            match (&image.uri, image.buffer_view) {
+                (Some(_), Some(_)) => self.error(
+                    format!("{}/bufferView", pointer),
+                    "Image must not have both uri and bufferView".into(),
+                ),
+                (None, Some(buffer_view)) => {
+                    self.index(
+                        format!("{}/bufferView", pointer),
+                        buffer_view,
+                        buffer_view_count,
+                    );
+                    if image.mime_type.is_none() {
+                        self.error(
+                            format!("{}/mimeType", pointer),
+                            "Missing mime type of image in buffer view".into(),
+                        );
+                    }
+                }
+                (None, None) => self.error(
+                    format!("{}/uri", pointer),
+                    "Missing image uri or bufferView".into(),
+                ),
+                (Some(_), None) => {}
+            }
+        }
+    }
+
+    fn validate_skins(&mut self) {
+        let accessor_count = self::count(&self.gltf.accessors);
+        let node_count = self::count(&self.gltf.nodes);
+        for (i, skin) in self.gltf.skins.iter().flatten().enumerate() {
+            let pointer = format!("/skins/{}", i);
+            if let Some(accessor) = skin.inverse_bind_matrices {
+                let pointer = format!("{}/inverseBindMatrices", pointer);
+                self.index(pointer, accessor, accessor_count);
+            }
+            if let Some(skeleton) = skin.skeleton {
+                self.index(format!("{}/skeleton", pointer), skeleton, node_count);
+            }
+            if skin.joints.is_empty() {
+                self.error(format!("{}/joints", pointer), "Empty joints".into());
+            }
+            for (j, joint) in skin.joints.iter().enumerate() {
+                self.index(format!("{}/joints/{}", pointer, j), *joint, node_count);
+            }
+        }
+    }
+
+    fn validate_animations(&mut self) {
+        let accessor_count = self::count(&self.gltf.accessors);
+        let node_count = self::count(&self.gltf.nodes);
+        for (i, animation) in self.gltf.animations.iter().flatten().enumerate() {
+            let pointer = format!("/animations/{}", i);
+            for (j, channel) in animation.channels.iter().enumerate() {
+                let pointer = format!("{}/channels/{}", pointer, j);
+                let sampler_count = animation.samplers.len();
+                self.index(
+                    format!("{}/sampler", pointer),
+                    channel.sampler,
+                    sampler_count,
+                );
+                if let Some(node) = channel.target.node {
+                    self.index(format!("{}/target/node", pointer), node, node_count);
+                }
+                if self.contains(
+                    format!("{}/target/path", pointer),
+                    &channel.target.path,
+                    &Self::PATHS,
+                ) {
+                    if let Some(sampler) = animation.samplers.get(channel.sampler as usize) {
+                        self.validate_channel_output(&pointer, channel, sampler);
+                    }
+                }
+            }
+            for (j, sampler) in animation.samplers.iter().enumerate() {
+                let pointer = format!("{}/samplers/{}", pointer, j);
+                self.index(format!("{}/input", pointer), sampler.input, accessor_count);
+                self.index(
+                    format!("{}/output", pointer),
+                    sampler.output,
+                    accessor_count,
+                );
+                self.contains(
+                    format!("{}/interpolation", pointer),
+                    &sampler.interpolation,
+                    &Self::INTERPOLATIONS,
+                );
+            }
+        }
+    }
+
+    /// Checks that the output of the sampler has the type the target path
+    /// needs and one value per keyframe, or per keyframe and morph target.
+    fn validate_channel_output(
+        &mut self,
+        pointer: &str,
+        channel: &data::AnimationChannel,
+        sampler: &data::AnimationSampler,
+    ) {
+        let accessors = self::slice(&self.gltf.accessors);
+        let (Some(input), Some(output)) = (
+            accessors.get(sampler.input as usize),
+            accessors.get(sampler.output as usize),
+        ) else {
+            return;
+        };
+        let (accessor_type, values_per_key) = match channel.target.path.as_str() {
+            "rotation" => ("VEC4", 1),
+            "weights" => {
+                let target_count = channel
+                    .target
+                    .node
+                    .and_then(|node| self::slice(&self.gltf.nodes).get(node as usize))
+                    .and_then(|node| node.mesh)
+                    .and_then(|mesh| self::slice(&self.gltf.meshes).get(mesh as usize))
+                    .and_then(|mesh| mesh.primitives.first())
+                    .map_or(0, |primitive| self::count(&primitive.targets));
+                ("SCALAR", target_count)
+            }
+            _ => ("VEC3", 1),
+        };
+        if output.accessor_type != accessor_type {
+            self.error(
+                format!("{}/sampler", pointer),
+                format!(
+                    "Output type {} does not match path {}, expected {}",
+                    output.accessor_type, channel.target.path, accessor_type
+                ),
+            );
+            return;
+        }
+        let tangents = if sampler.interpolation == "CUBICSPLINE" {
+            3
+        } else {
+            1
+        };
+        let expected = input.count as i64 * values_per_key as i64 * tangents;
+        if output.count as i64 != expected {
+            self.error(
+                format!("{}/sampler", pointer),
+                format!("Output has {} values, expected {}", output.count, expected),
+            );
+        }
+    }
+
+    fn buffer_range(
+        &mut self,
+        pointer: &str,
+        buffers: &[data::Buffer],
+        buffer: u32,
+        byte_offset: u32,
+        byte_length: u32,
+    ) {
+        if !self.index(format!("{}/buffer", pointer), buffer, buffers.len()) {
+            return;
+        }
+        let buffer_length = buffers[buffer as usize].byte_length;
+        if byte_offset as u64 + byte_length as u64 > buffer_length as u64 {
+            self.error(
+                pointer.into(),
+                format!(
+                    "Range {}..{} exceeds buffer {} length {}",
+                    byte_offset,
+                    byte_offset as u64 + byte_length as u64,
+                    buffer,
+                    buffer_length
+                ),
+            );
+        }
+    }
+
+    fn index(&mut self, pointer: String, index: u32, count: usize) -> bool {
+        let valid = (index as usize) < count;
+        if !valid {
+            self.error(
+                pointer,
+                format!("Index {} out of bounds (count {})", index, count),
+            );
+        }
+        valid
+    }
+
+    fn contains(&mut self, pointer: String, value: &str, values: &[&str]) -> bool {
+        let valid = values.contains(&value);
+        if !valid {
+            self.error(pointer, format!("Unknown value: {}", value));
+        }
+        valid
+    }
+
+    fn error(&mut self, pointer: String, message: String) {
+        self.push(Severity::Error, pointer, message)
+    }
+
+    fn warning(&mut self, pointer: String, message: String) {
+        self.push(Severity::Warning, pointer, message)
+    }
+
+    fn push(&mut self, severity: Severity, pointer: String, message: String) {
+        self.report.diagnostics.push(Diagnostic {
+            severity,
+            pointer,
+            message,
+        })
+    }
+}
+
+fn count<T>(values: &Option<Vec<T>>) -> usize {
+    values.as_ref().map_or(0, Vec::len)
+}
+
+fn slice<T>(values: &Option<Vec<T>>) -> &[T] {
+    values.as_deref().unwrap_or_default()
+}
+
+fn component_size(component_type: u32) -> Option<u64> {
+    match component_type {
+        WebGl2RenderingContext::BYTE | WebGl2RenderingContext::UNSIGNED_BYTE => Some(1),
+        WebGl2RenderingContext::SHORT | WebGl2RenderingContext::UNSIGNED_SHORT => Some(2),
+        WebGl2RenderingContext::UNSIGNED_INT | WebGl2RenderingContext::FLOAT => Some(4),
+        _ => None,
+    }
+}
+
+fn find_cycles(nodes: &[data::Node]) -> Vec<(usize, usize)> {
+    #[derive(Clone, Copy, PartialEq)]
+    enum State {
+        New,
+        Active,
+        Done,
+    }
+    fn visit(
+        nodes: &[data::Node],
+        i: usize,
+        states: &mut [State],
+        result: &mut Vec<(usize, usize)>,
+    ) {
+        states[i] = State::Active;
+        for (j, child) in nodes[i].children.iter().flatten().enumerate() {
+            let child = *child as usize;
+            match states.get(child) {
+                Some(State::Active) => result.push((i, j)),
+                Some(State::New) => visit(nodes, child, states, result),
+                _ => {}
+            }
+        }
+        states[i] = State::Done;
+    }
+    let mut states = vec![State::New; nodes.len()];
+    let mut result = vec![];
+    for i in 0..nodes.len() {
+        if states[i] == State::New {
+            visit(nodes, i, &mut states, &mut result);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_works() {
+        let node = |children: Vec<u32>, mesh: Option<u32>| data::Node {
+            children: Some(children),
+            mesh,
+            ..Default::default()
+        };
+        let gltf = Gltf {
+            nodes: Some(vec![node(vec![1], None), node(vec![0], Some(3))]),
+            scene: Some(1),
+            ..Default::default()
+        };
+        let report = validate(&gltf);
+        let pointers: Vec<_> = report
+            .errors()
+            .map(|error| error.pointer.as_str())
+            .collect();
+        assert_eq!(
+            pointers,
+            vec!["/scene", "/nodes/1/mesh", "/nodes/1/children/0"]
+        );
+        assert!(report.into_result().is_err());
+        assert!(validate(&Gltf::default()).into_result().is_ok());
+
+        let embedded = Gltf {
+            images: Some(vec![
+                data::Image {
+                    buffer_view: Some(0),
+                    ..Default::default()
+                },
+                data::Image::default(),
+            ]),
+            ..Default::default()
+        };
+        let report = validate(&embedded);
+        let pointers: Vec<_> = report
+            .errors()
+            .map(|error| error.pointer.as_str())
+            .collect();
+        assert_eq!(
+            pointers,
+            vec![
+                "/images/0/bufferView",
+                "/images/0/mimeType",
+                "/images/1/uri"
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_animations_works() {
+        let accessor = |accessor_type: &str, count| data::Accessor {
+            buffer_view: None,
+            byte_offset: 0,
+            component_type: WebGl2RenderingContext::FLOAT,
+            count,
+            accessor_type: String::from(accessor_type),
+            min: None,
+            max: None,
+            normalized: false,
+        };
+        let channel = |sampler, path: &str| data::AnimationChannel {
+            sampler,
+            target: data::AnimationChannelTarget {
+                node: Some(0),
+                path: String::from(path),
+            },
+        };
+        let sampler = |output| data::AnimationSampler {
+            input: 0,
+            interpolation: String::from("LINEAR"),
+            output,
+        };
+        let gltf = Gltf {
+            accessors: Some(vec![
+                accessor("SCALAR", 2),
+                accessor("VEC3", 2),
+                accessor("VEC4", 2),
+                accessor("SCALAR", 2),
+            ]),
+            nodes: Some(vec![data::Node::default()]),
+            animations: Some(vec![data::Animation {
+                channels: vec![
+                    channel(0, "translation"),
+                    channel(1, "rotation"),
+                    channel(0, "scale"),
+                    channel(1, "translation"),
+                    channel(2, "weights"),
+                ],
+                samplers: vec![sampler(1), sampler(2), sampler(3)],
+                name: None,
+            }]),
+            ..Default::default()
+        };
+        let report = validate(&gltf);
+        let pointers: Vec<_> = report
+            .errors()
+            .map(|error| error.pointer.as_str())
+            .collect();
+        assert_eq!(
+            pointers,
+            vec![
+                "/animations/0/channels/3/sampler",
+                "/animations/0/channels/4/sampler"
+            ]
+        );
+    }
+}
@@ -1,10 +1,10 @@
-use glm::Vec4;
+use glm::{Vec3, Vec4};
 use web_sys::WebGl2RenderingContext;
 
 use crate::{
     base::color,
     core::{
-        material::{GenericMaterial, Source, TextureRef},
+        material::{Clearcoat, GenericMaterial, PbrProperties, Source, TextureRef, Transmission},
         program::{Program, UpdateProgramUniforms, UpdateUniform},
         texture::TextureUnit,
     },
@@ -12,12 +12,22 @@ use crate::{
 
 const USE_LIGHT: bool = true;
 
+pub const SKINNING: &str = "SKINNING";
+
 #[derive(Debug)]
 pub struct TestMaterial {
     pub base_color_factor: Vec4,
     pub use_light: bool,
     pub min_factor: f32,
     pub base_color_texture: Option<TextureRef>,
+    pub metallic_factor: f32,
+    pub roughness_factor: f32,
+    pub metallic_roughness_texture: Option<TextureRef>,
+    pub emissive_factor: Vec3,
+    pub emissive_texture: Option<TextureRef>,
+    pub normal_texture: Option<NormalTexture>,
+    pub clearcoat: Option<Clearcoat>,
+    pub transmission: Option<Transmission>,
 }
 
 impl Default for TestMaterial {
@@ -27,10 +37,47 @@ impl Default for TestMaterial {
             use_light: USE_LIGHT,
             min_factor: 0.2,
             base_color_texture: None,
+            metallic_factor: 1.0,
+            roughness_factor: 1.0,
+            metallic_roughness_texture: None,
+            emissive_factor: Vec3::zeros(),
+            emissive_texture: None,
+            normal_texture: None,
+            clearcoat: None,
+            transmission: None,
         }
     }
 }
 
+#[derive(Debug)]
+pub struct NormalTexture {
+    pub texture: TextureRef,
+    pub scale: f32,
+}
+
+impl TestMaterial {
+    fn update_texture_uniforms(
+        context: &WebGl2RenderingContext,
+        program: &Program,
+        texture: Option<&TextureRef>,
+        unit: TextureUnit,
+        name: &str,
+    ) {
+        if let Some(texture) = texture {
+            texture.texture().bind_to_unit(context, unit);
+            unit.update_uniform(context, &format!("u_{}Sampler", name), program);
+            texture.transform().matrix().update_uniform(
+                context,
+                &format!("u_{}Transform", name),
+                program,
+            );
+        }
+        texture
+            .is_some()
+            .update_uniform(context, &format!("u_Use{}Texture", name), program);
+    }
+}
+
 impl GenericMaterial for TestMaterial {
     fn vertex_shader(&self) -> Source<'_> {
         include_str!("test.vert").into()
@@ -39,6 +86,33 @@ impl GenericMaterial for TestMaterial {
     fn fragment_shader(&self) -> Source<'_> {
         include_str!("test.frag").into()
     }
+
+    fn pbr_properties(&self) -> PbrProperties {
+        PbrProperties {
+            base_color_factor: self.base_color_factor,
+            base_color_texture: self.base_color_texture.clone(),
+            metallic_factor: self.metallic_factor,
+            roughness_factor: self.roughness_factor,
+            metallic_roughness_texture: self.metallic_roughness_texture.clone(),
+            emissive_factor: self.emissive_factor,
+            emissive_texture: self.emissive_texture.clone(),
+            normal_texture: self
+                .normal_texture
+                .as_ref()
+                .map(|normal_texture| normal_texture.texture.clone()),
+            normal_scale: self
+                .normal_texture
+                .as_ref()
+                .map_or(1.0, |normal_texture| normal_texture.scale),
+            unlit: !self.use_light,
+            clearcoat: self.clearcoat.clone(),
+            transmission: self.transmission.clone(),
+        }
+    }
+
+    fn needs_tangents(&self) -> bool {
+        self.normal_texture.is_some()
+    }
 }
 
 impl UpdateProgramUniforms for TestMaterial {
@@ -52,12 +126,72 @@ impl UpdateProgramUniforms for TestMaterial {
 
         if let Some(base_color_texture) = &self.base_color_texture {
             let sampler = TextureUnit(0);
-            sampler.active_texture(context);
-            base_color_texture.texture().bind(context);
+            base_color_texture.texture().bind_to_unit(context, sampler);
             sampler.update_uniform(context, "u_BaseColorSampler", program);
+            base_color_texture.transform().matrix().update_uniform(
+                context,
+                "u_BaseColorTransform",
+                program,
+            );
         }
         self.base_color_texture
             .is_some()
             .update_uniform(context, "u_UseTexture", program);
+
+        self.emissive_factor
+            .update_uniform(context, "u_EmissiveFactor", program);
+        Self::update_texture_uniforms(
+            context,
+            program,
+            self.emissive_texture.as_ref(),
+            TextureUnit(1),
+            "Emissive",
+        );
+
+        let normal_texture = self.normal_texture.as_ref();
+        normal_texture
+            .map_or(1.0, |normal_texture| normal_texture.scale)
+            .update_uniform(context, "u_NormalScale", program);
+        Self::update_texture_uniforms(
+            context,
+            program,
+            normal_texture.map(|normal_texture| &normal_texture.texture),
+            TextureUnit(5),
+            "Normal",
+        );
+
+        let clearcoat = self.clearcoat.as_ref();
+        clearcoat
+            .map_or(0.0, |clearcoat| clearcoat.factor)
+            .update_uniform(context, "u_ClearcoatFactor", program);
+        clearcoat
+            .map_or(0.0, |clearcoat| clearcoat.roughness_factor)
+            .update_uniform(context, "u_ClearcoatRoughnessFactor", program);
+        Self::update_texture_uniforms(
+            context,
+            program,
+            clearcoat.and_then(|clearcoat| clearcoat.texture.as_ref()),
+            TextureUnit(2),
+            "Clearcoat",
+        );
+        Self::update_texture_uniforms(
+            context,
+            program,
+            clearcoat.and_then(|clearcoat| clearcoat.roughness_texture.as_ref()),
+            TextureUnit(3),
+            "ClearcoatRoughness",
+        );
+
+        let transmission = self.transmission.as_ref();
+        transmission
+            .map_or(0.0, |transmission| transmission.factor)
+            .update_uniform(context, "u_TransmissionFactor", program);
+        Self::update_texture_uniforms(
+            context,
+            program,
+            transmission.and_then(|transmission| transmission.texture.as_ref()),
+            TextureUnit(4),
+            "Transmission",
+        );
     }
 }
@@ -2,39 +2,200 @@
 
 precision highp float;
 
+#include <color_space>
+
 const int OPAQUE_ALPHA_MODE = 0;
 const int MASK_ALPHA_MODE = 1;
 const int BLEND_ALPHA_MODE = 2;
 
+const int DIRECTIONAL = 1;
+const int POINT = 2;
+const int SPOT = 3;
+const int MAX_LIGHTS = 8;
+
+struct Light {
+    int lightType;
+    vec4 color;
+    vec3 direction;
+    vec3 position;
+    vec3 attenuation;
+    float range;
+    float innerConeCos;
+    float outerConeCos;
+};
+
+in vec3 v_Position;
+in vec3 v_CameraPosition;
 in vec3 v_Normal;
+in vec4 v_Tangent;
 in vec2 v_TexCoord_0;
 in vec4 v_Color_0;
 
 uniform vec4 u_BaseColorFactor;
 uniform sampler2D u_BaseColorSampler;
 uniform bool u_UseTexture;
+uniform mat3 u_BaseColorTransform;
+uniform vec3 u_EmissiveFactor;
+uniform sampler2D u_EmissiveSampler;
+uniform bool u_UseEmissiveTexture;
+uniform mat3 u_EmissiveTransform;
+uniform float u_ClearcoatFactor;
+uniform sampler2D u_ClearcoatSampler;
+uniform bool u_UseClearcoatTexture;
+uniform mat3 u_ClearcoatTransform;
+uniform float u_ClearcoatRoughnessFactor;
+uniform sampler2D u_ClearcoatRoughnessSampler;
+uniform bool u_UseClearcoatRoughnessTexture;
+uniform mat3 u_ClearcoatRoughnessTransform;
+uniform sampler2D u_NormalSampler;
+uniform bool u_UseNormalTexture;
+uniform mat3 u_NormalTransform;
+uniform float u_NormalScale;
+uniform float u_TransmissionFactor;
+uniform sampler2D u_TransmissionSampler;
+uniform bool u_UseTransmissionTexture;
+uniform mat3 u_TransmissionTransform;
 uniform bool u_UseLight;
 uniform vec3 u_Light;
+uniform Light u_Lights[MAX_LIGHTS];
+uniform int u_LightCount;
 uniform float u_MinFactor;
 uniform bool u_UseColor_0;
+uniform bool u_LinearVertexColors;
 
 uniform int u_AlphaMode;
 uniform float u_AlphaCutoff;
 
 out vec4 FragColor;
 
-vec4 getLightFactor() {
-    if (u_UseLight) {
-        float factor = max(dot(normalize(v_Normal), normalize(-u_Light)), u_MinFactor);
-        return vec4(factor, factor, factor, 1.0);
+float lightAttenuation(vec3 attenuation, float distance) {
+    return 1.0 / max(attenuation[0] + attenuation[1] * distance + attenuation[2] * distance * distance, 0.0001);
+}
+
+float rangeAttenuation(float range, float distance) {
+    if (range <= 0.0) {
+        return 1.0;
+    }
+    return clamp(1.0 - pow(distance / range, 4.0), 0.0, 1.0);
+}
+
+vec2 transformTexCoord(mat3 transform) {
+    return (transform * vec3(v_TexCoord_0, 1.0)).xy;
+}
+
+vec3 getNormal() {
+    vec3 normal = normalize(v_Normal);
+    if (!u_UseNormalTexture || dot(v_Tangent.xyz, v_Tangent.xyz) == 0.0) {
+        return normal;
+    }
+    vec3 tangent = normalize(v_Tangent.xyz - normal * dot(normal, v_Tangent.xyz));
+    vec3 bitangent = cross(normal, tangent) * v_Tangent.w;
+    vec3 mapped = texture(u_NormalSampler, transformTexCoord(u_NormalTransform)).rgb * 2.0 - 1.0;
+    mapped.xy *= u_NormalScale;
+    return normalize(mat3(tangent, bitangent, normal) * mapped);
+}
+
+void lightParameters(Light light, out vec3 lightDirection, out float attenuation) {
+    attenuation = 1.0;
+    if (light.lightType == DIRECTIONAL) {
+        lightDirection = normalize(light.direction);
     } else {
+        lightDirection = normalize(v_Position - light.position);
+        float distance = length(light.position - v_Position);
+        attenuation = lightAttenuation(light.attenuation, distance);
+        attenuation *= rangeAttenuation(light.range, distance);
+        if (light.lightType == SPOT) {
+            float cosine = dot(normalize(light.direction), lightDirection);
+            attenuation *= smoothstep(light.outerConeCos, light.innerConeCos, cosine);
+        }
+    }
+}
+
+vec3 lightCalc(Light light, vec3 normal) {
+    vec3 lightDirection;
+    float attenuation;
+    lightParameters(light, lightDirection, attenuation);
+    float diffuse = max(dot(normal, -lightDirection), 0.0);
+    return light.color.rgb * diffuse * attenuation;
+}
+
+float clearcoatSpecular(vec3 lightDirection, vec3 normal, vec3 viewDirection, float shininess) {
+    vec3 halfway = normalize(viewDirection - lightDirection);
+    return pow(max(dot(normal, halfway), 0.0), shininess);
+}
+
+vec3 getClearcoat() {
+    float factor = u_ClearcoatFactor;
+    float roughness = u_ClearcoatRoughnessFactor;
+    if (u_UseClearcoatTexture) {
+        factor *= texture(u_ClearcoatSampler, transformTexCoord(u_ClearcoatTransform)).r;
+    }
+    if (u_UseClearcoatRoughnessTexture) {
+        roughness *= texture(u_ClearcoatRoughnessSampler, transformTexCoord(u_ClearcoatRoughnessTransform)).g;
+    }
+    if (!u_UseLight || factor <= 0.0) {
+        return vec3(0.0);
+    }
+    vec3 normal = getNormal();
+    vec3 viewDirection = normalize(v_CameraPosition - v_Position);
+    float alpha = max(roughness * roughness, 0.01);
+    float shininess = 2.0 / (alpha * alpha) - 2.0;
+    float fresnel = 0.04 + 0.96 * pow(1.0 - max(dot(normal, viewDirection), 0.0), 5.0);
+    vec3 specular = vec3(0.0);
+    if (u_LightCount == 0) {
+        specular = vec3(clearcoatSpecular(normalize(u_Light), normal, viewDirection, shininess));
+    }
+    for (int i = 0; i < MAX_LIGHTS; i++) {
+        if (i >= u_LightCount) {
+            break;
+        }
+        vec3 lightDirection;
+        float attenuation;
+        lightParameters(u_Lights[i], lightDirection, attenuation);
+        specular += u_Lights[i].color.rgb * attenuation *
+            clearcoatSpecular(lightDirection, normal, viewDirection, shininess);
+    }
+    return factor * fresnel * (specular + vec3(0.04));
+}
+
+vec3 getEmissive() {
+    vec3 emissive = u_EmissiveFactor;
+    if (u_UseEmissiveTexture) {
+        emissive *= texture(u_EmissiveSampler, transformTexCoord(u_EmissiveTransform)).rgb;
+    }
+    return emissive;
+}
+
+float getTransmission() {
+    float transmission = u_TransmissionFactor;
+    if (u_UseTransmissionTexture) {
+        transmission *= texture(u_TransmissionSampler, transformTexCoord(u_TransmissionTransform)).r;
+    }
+    return transmission;
+}
+
+vec4 getLightFactor() {
+    if (!u_UseLight) {
         return vec4(1.0);
     }
+    vec3 normal = getNormal();
+    if (u_LightCount == 0) {
+        float factor = max(dot(normal, normalize(-u_Light)), u_MinFactor);
+        return vec4(factor, factor, factor, 1.0);
+    }
+    vec3 total = vec3(0.0);
+    for (int i = 0; i < MAX_LIGHTS; i++) {
+        if (i >= u_LightCount) {
+            break;
+        }
+        total += lightCalc(u_Lights[i], normal);
+    }
+    return vec4(max(total, vec3(u_MinFactor)), 1.0);
 }
 
 vec4 getTextureColor() {
     if (u_UseTexture) {
-        return texture(u_BaseColorSampler, v_TexCoord_0);
+        return texture(u_BaseColorSampler, transformTexCoord(u_BaseColorTransform));
     } else {
         return vec4(1.0);
     }
@@ -42,7 +203,7 @@ vec4 getTextureColor() {
 
 vec4 getVertexColor() {
     if (u_UseColor_0) {
-        return v_Color_0;
+        return u_LinearVertexColors ? v_Color_0 : srgbToLinear(v_Color_0);
     } else {
         return vec4(1.0);
     }
@@ -52,6 +213,8 @@ void main() {
     vec4 baseColor = getLightFactor() * 
                         getTextureColor() * 
                         getVertexColor() * u_BaseColorFactor;
+    baseColor.rgb += getClearcoat() + getEmissive();
+    baseColor.a *= 1.0 - getTransmission();
     if (u_AlphaMode == OPAQUE_ALPHA_MODE) {
         baseColor.a = 1.0;
     } else if (u_AlphaMode == MASK_ALPHA_MODE) {
@@ -60,5 +223,5 @@ void main() {
         }
         baseColor.a = 1.0;
     }
-    FragColor = baseColor;
+    FragColor = encodeOutput(baseColor);
 }
@@ -2,20 +2,157 @@
 
 in vec3 a_position;
 in vec3 a_normal;
+in vec4 a_tangent;
 in vec2 a_texcoord_0;
 in vec4 a_color_0;
 
 uniform mat4 u_ModelMatrix;
 uniform mat4 u_ViewProjectionMatrix;
+uniform mat4 u_ViewMatrix;
 uniform mat4 u_NormalMatrix;
 
+out vec3 v_Position;
+out vec3 v_CameraPosition;
 out vec3 v_Normal;
+out vec4 v_Tangent;
 out vec2 v_TexCoord_0;
 out vec4 v_Color_0;
 
+#ifdef MORPH_TARGETS
+uniform float u_MorphWeights[MORPH_TARGET_COUNT];
+
+#ifdef MORPH_TEXTURE
+uniform highp sampler2D u_MorphTexture;
+uniform int u_MorphVertexCount;
+
+vec3 getMorphDelta(int target, int slot) {
+    int index = (target * MORPH_ATTRIBUTE_COUNT + slot) * u_MorphVertexCount + gl_VertexID;
+    ivec2 texel = ivec2(index % MORPH_TEXTURE_WIDTH, index / MORPH_TEXTURE_WIDTH);
+    return texelFetch(u_MorphTexture, texel, 0).xyz;
+}
+
+void applyMorphTargets(inout vec3 position, inout vec3 normal, inout vec3 tangent) {
+    for (int i = 0; i < MORPH_TARGET_COUNT; i++) {
+        float weight = u_MorphWeights[i];
+        if (weight == 0.0) {
+            continue;
+        }
+#ifdef MORPH_POSITION
+        position += weight * getMorphDelta(i, MORPH_POSITION);
+#endif
+#ifdef MORPH_NORMAL
+        normal += weight * getMorphDelta(i, MORPH_NORMAL);
+#endif
+#ifdef MORPH_TANGENT
+        tangent += weight * getMorphDelta(i, MORPH_TANGENT);
+#endif
+    }
+}
+#else
+#ifdef MORPH_POSITION
+in vec3 a_morph_position_0;
+#if MORPH_TARGET_COUNT > 1
+in vec3 a_morph_position_1;
+#endif
+#if MORPH_TARGET_COUNT > 2
+in vec3 a_morph_position_2;
+#endif
+#if MORPH_TARGET_COUNT > 3
+in vec3 a_morph_position_3;
+#endif
+#endif
+#ifdef MORPH_NORMAL
+in vec3 a_morph_normal_0;
+#if MORPH_TARGET_COUNT > 1
+in vec3 a_morph_normal_1;
+#endif
+#if MORPH_TARGET_COUNT > 2
+in vec3 a_morph_normal_2;
+#endif
+#if MORPH_TARGET_COUNT > 3
+in vec3 a_morph_normal_3;
+#endif
+#endif
+
+void applyMorphTargets(inout vec3 position, inout vec3 normal, inout vec3 tangent) {
+#ifdef MORPH_POSITION
+    position += u_MorphWeights[0] * a_morph_position_0;
+#if MORPH_TARGET_COUNT > 1
+    position += u_MorphWeights[1] * a_morph_position_1;
+#endif
+#if MORPH_TARGET_COUNT > 2
+    position += u_MorphWeights[2] * a_morph_position_2;
+#endif
+#if MORPH_TARGET_COUNT > 3
+    position += u_MorphWeights[3] * a_morph_position_3;
+#endif
+#endif
+#ifdef MORPH_NORMAL
+    normal += u_MorphWeights[0] * a_morph_normal_0;
+#if MORPH_TARGET_COUNT > 1
+    normal += u_MorphWeights[1] * a_morph_normal_1;
+#endif
+#if MORPH_TARGET_COUNT > 2
+    normal += u_MorphWeights[2] * a_morph_normal_2;
+#endif
+#if MORPH_TARGET_COUNT > 3
+    normal += u_MorphWeights[3] * a_morph_normal_3;
+#endif
+#endif
+}
+#endif
+#endif
+
+#ifdef SKINNING
+in vec4 a_joints_0;
+in vec4 a_weights_0;
+
+uniform bool u_UseSkinning;
+uniform highp sampler2D u_JointTexture;
+
+mat4 getJointMatrix(float joint) {
+    int x = 4 * int(joint);
+    return mat4(
+        texelFetch(u_JointTexture, ivec2(x, 0), 0),
+        texelFetch(u_JointTexture, ivec2(x + 1, 0), 0),
+        texelFetch(u_JointTexture, ivec2(x + 2, 0), 0),
+        texelFetch(u_JointTexture, ivec2(x + 3, 0), 0)
+    );
+}
+
+mat4 getSkinMatrix() {
+    if (!u_UseSkinning) {
+        return mat4(1.0);
+    }
+    return a_weights_0.x * getJointMatrix(a_joints_0.x) +
+        a_weights_0.y * getJointMatrix(a_joints_0.y) +
+        a_weights_0.z * getJointMatrix(a_joints_0.z) +
+        a_weights_0.w * getJointMatrix(a_joints_0.w);
+}
+#endif
+
 void main() {
-    gl_Position = u_ViewProjectionMatrix * u_ModelMatrix * vec4(a_position, 1.0);
-    v_Normal = vec3(u_NormalMatrix * vec4(a_normal, 0.0));
+    vec3 morphedPosition = a_position;
+    vec3 morphedNormal = a_normal;
+    vec3 morphedTangent = a_tangent.xyz;
+#ifdef MORPH_TARGETS
+    applyMorphTargets(morphedPosition, morphedNormal, morphedTangent);
+#endif
+    vec4 position = vec4(morphedPosition, 1.0);
+    vec4 normal = vec4(morphedNormal, 0.0);
+    vec4 tangent = vec4(morphedTangent, 0.0);
+#ifdef SKINNING
+    mat4 skinMatrix = getSkinMatrix();
+    position = skinMatrix * position;
+    normal = skinMatrix * normal;
+    tangent = skinMatrix * tangent;
+#endif
+    vec4 worldPosition = u_ModelMatrix * position;
+    gl_Position = u_ViewProjectionMatrix * worldPosition;
+    v_Position = vec3(worldPosition);
+    v_CameraPosition = vec3(inverse(u_ViewMatrix)[3]);
+    v_Normal = vec3(u_NormalMatrix * normal);
+    v_Tangent = vec4(vec3(u_ModelMatrix * tangent), a_tangent.w);
     v_TexCoord_0 = a_texcoord_0;
     v_Color_0 = a_color_0;
 }
@@ -1,4 +1,6 @@
 pub mod core;
+pub mod export;
+pub mod glb;
 pub mod load;
 pub mod material;
 pub mod user;
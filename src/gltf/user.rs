@@ -1 +1,2 @@
 pub mod light_controller;
+pub mod scene_controller;
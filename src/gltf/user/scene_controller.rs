@@ -0,0 +1,56 @@
+use crate::base::input::KeyState;
+
+#[derive(Debug, Clone)]
+pub struct Properties {
+    pub key_next_scene: Option<String>,
+    pub key_next_camera: Option<String>,
+}
+
+impl Default for Properties {
+    fn default() -> Self {
+        Self {
+            key_next_scene: Some("KeyN".into()),
+            key_next_camera: Some("KeyC".into()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Selection {
+    pub next_scene: bool,
+    pub next_camera: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct SceneController {
+    properties: Properties,
+    pressed: Selection,
+}
+
+impl SceneController {
+    pub fn new(properties: Properties) -> Self {
+        Self {
+            properties,
+            pressed: Selection::default(),
+        }
+    }
+
+    pub fn update(&mut self, key_state: &KeyState) -> Selection {
+        let pressed = Selection {
+            next_scene: Self::is_key_pressed(&self.properties.key_next_scene, key_state),
+            next_camera: Self::is_key_pressed(&self.properties.key_next_camera, key_state),
+        };
+        let selection = Selection {
+            next_scene: pressed.next_scene && !self.pressed.next_scene,
+            next_camera: pressed.next_camera && !self.pressed.next_camera,
+        };
+        self.pressed = pressed;
+        selection
+    }
+
+    fn is_key_pressed(key: &Option<String>, key_state: &KeyState) -> bool {
+        key.as_ref()
+            .filter(|key| key_state.is_pressed(key))
+            .is_some()
+    }
+}
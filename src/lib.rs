@@ -12,6 +12,7 @@ mod geometry;
 mod gltf;
 mod material;
 mod run_example;
+mod scene_file;
 
 use wasm_bindgen::prelude::*;
 
@@ -2,18 +2,21 @@
 
 precision highp float;
 
+#include <color_space>
+
 uniform vec4 baseColor;
 uniform bool useVertexColors;
+uniform bool u_LinearVertexColors;
 
 in vec4 v_Color;
 
 out vec4 fragColor;
 
 void main() {
-    vec4 tempColor = baseColor;
+    vec4 tempColor = srgbToLinear(baseColor);
 
     if (useVertexColors) {
-        tempColor *= v_Color;
+        tempColor *= u_LinearVertexColors ? v_Color : srgbToLinear(v_Color);
     }
-    fragColor = tempColor;
+    fragColor = encodeOutput(tempColor);
 }
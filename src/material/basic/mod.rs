@@ -3,7 +3,7 @@ use web_sys::WebGl2RenderingContext;
 use crate::{
     base::color::{self, Color},
     core::{
-        material::{GenericMaterial, Source},
+        material::{GenericMaterial, PbrProperties, Source},
         program::{Program, UpdateProgramUniforms, UpdateUniform},
     },
 };
@@ -31,6 +31,14 @@ impl GenericMaterial for BasicMaterial {
     fn fragment_shader(&self) -> Source<'_> {
         include_str!("fragment.glsl").into()
     }
+
+    fn pbr_properties(&self) -> PbrProperties {
+        PbrProperties {
+            base_color_factor: self.base_color,
+            unlit: true,
+            ..Default::default()
+        }
+    }
 }
 
 impl UpdateProgramUniforms for BasicMaterial {
@@ -69,6 +77,10 @@ impl GenericMaterial for PointMaterial {
     fn preferred_mode(&self) -> Option<u32> {
         Some(WebGl2RenderingContext::POINTS)
     }
+
+    fn pbr_properties(&self) -> PbrProperties {
+        self.basic.pbr_properties()
+    }
 }
 
 impl UpdateProgramUniforms for PointMaterial {
@@ -124,17 +136,18 @@ impl GenericMaterial for LineMaterial {
             LineType::Segments => WebGl2RenderingContext::LINES,
         })
     }
+
+    fn pbr_properties(&self) -> PbrProperties {
+        self.basic.pbr_properties()
+    }
 }
 
-#[derive(Debug)]
-#[derive(Default)]
+#[derive(Debug, Default)]
 pub struct SurfaceMaterial {
     pub basic: BasicMaterial,
     pub double_side: bool,
 }
 
-
-
 impl UpdateProgramUniforms for SurfaceMaterial {
     fn update_program_uniforms(&self, context: &WebGl2RenderingContext, program: &Program) {
         self.basic.update_program_uniforms(context, program);
@@ -157,4 +170,8 @@ impl GenericMaterial for SurfaceMaterial {
     fn double_sided(&self) -> bool {
         self.double_side
     }
+
+    fn pbr_properties(&self) -> PbrProperties {
+        self.basic.pbr_properties()
+    }
 }
@@ -2,6 +2,8 @@
 
 precision highp float;
 
+#include <color_space>
+
 struct Material {
     vec4 ambient;
     vec4 diffuse;
@@ -17,10 +19,10 @@ in vec4 v_Light;
 out vec4 fragColor;
 
 void main() {
-    vec4 color = material.diffuse;
+    vec4 color = srgbToLinear(material.diffuse);
     if (material.useTexture) {
         color *= texture(material.texture0, v_UV);
     }
     color *= vec4(v_Light.rgb, 1.0);
-    fragColor = material.ambient + color;
+    fragColor = encodeOutput(srgbToLinear(material.ambient) + color);
 }
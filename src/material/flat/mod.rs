@@ -10,7 +10,7 @@ use crate::{
     },
     classic::texture::Sampler2D,
     core::{
-        material::{GenericMaterial, Material, Source},
+        material::{GenericMaterial, Material, PbrProperties, Source, TextureRef},
         program::{self, Program, UpdateProgramUniforms, UpdateUniform},
     },
 };
@@ -74,6 +74,18 @@ impl GenericMaterial for FlatMaterial {
     fn double_sided(&self) -> bool {
         self.double_side
     }
+
+    fn pbr_properties(&self) -> PbrProperties {
+        PbrProperties {
+            base_color_factor: self.diffuse,
+            base_color_texture: self
+                .texture
+                .as_ref()
+                .map(|sampler| TextureRef::new(Rc::clone(&sampler.texture), 0)),
+            roughness_factor: 1.0,
+            ..Default::default()
+        }
+    }
 }
 
 pub fn create(
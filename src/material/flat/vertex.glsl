@@ -1,7 +1,10 @@
 #version 300 es
 
+#include <color_space>
+
 const int DIRECTIONAL = 1;
 const int POINT = 2;
+const int SPOT = 3;
 
 struct Light {
     int lightType;
@@ -9,6 +12,9 @@ struct Light {
     vec3 direction;
     vec3 position;
     vec3 attenuation;
+    float range;
+    float innerConeCos;
+    float outerConeCos;
 };
 
 uniform Light light0;
@@ -20,16 +26,32 @@ float lightAttenuation(vec3 attenuation, float distance) {
     return 1.0 / (attenuation[0] + attenuation[1] * distance + attenuation[2] * distance * distance);
 }
 
+float rangeAttenuation(float range, float distance) {
+    if (range <= 0.0) {
+        return 1.0;
+    }
+    return clamp(1.0 - pow(distance / range, 4.0), 0.0, 1.0);
+}
+
+float spotAttenuation(Light light, vec3 lightDirection) {
+    float cosine = dot(normalize(light.direction), lightDirection);
+    return smoothstep(light.outerConeCos, light.innerConeCos, cosine);
+}
+
 vec4 lightCalc(Light light, vec3 pointPosition, vec3 pointNormal) {
     float diffuse = 0.0;
     vec3 lightDirection;
     float attenuation = 1.0;
     if (light.lightType == DIRECTIONAL) {
         lightDirection = normalize(light.direction);
-    } else if (light.lightType == POINT) {
+    } else if (light.lightType == POINT || light.lightType == SPOT) {
         lightDirection = normalize(pointPosition - light.position);
         float distance = length(light.position - pointPosition);
         attenuation = lightAttenuation(light.attenuation, distance);
+        attenuation *= rangeAttenuation(light.range, distance);
+        if (light.lightType == SPOT) {
+            attenuation *= spotAttenuation(light, lightDirection);
+        }
     }
     if (light.lightType > 0) {
         pointNormal = normalize(pointNormal);
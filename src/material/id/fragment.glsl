@@ -0,0 +1,41 @@
+#version 300 es
+
+precision highp float;
+precision highp int;
+
+uniform uint u_ObjectId;
+uniform uint u_PrimitiveId;
+
+#ifdef ALPHA_MASK
+in vec2 v_TexCoord_0;
+in vec4 v_Color_0;
+
+uniform vec4 u_BaseColorFactor;
+uniform sampler2D u_BaseColorSampler;
+uniform bool u_UseTexture;
+uniform mat3 u_BaseColorTransform;
+uniform bool u_UseColor_0;
+uniform float u_AlphaCutoff;
+
+float getAlpha() {
+    float alpha = u_BaseColorFactor.a;
+    if (u_UseTexture) {
+        alpha *= texture(u_BaseColorSampler, (u_BaseColorTransform * vec3(v_TexCoord_0, 1.0)).xy).a;
+    }
+    if (u_UseColor_0) {
+        alpha *= v_Color_0.a;
+    }
+    return alpha;
+}
+#endif
+
+out uvec4 fragId;
+
+void main() {
+#ifdef ALPHA_MASK
+    if (getAlpha() < u_AlphaCutoff) {
+        discard;
+    }
+#endif
+    fragId = uvec4(u_ObjectId, u_PrimitiveId, 0u, 0u);
+}
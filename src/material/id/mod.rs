@@ -0,0 +1,77 @@
+use std::{
+    cell::RefCell,
+    ptr,
+    rc::{Rc, Weak},
+};
+
+use anyhow::Result;
+use web_sys::WebGl2RenderingContext;
+
+use crate::{
+    base::util::level::Level,
+    core::material::{AlphaMode, Material},
+};
+
+/// Writes object and primitive IDs into an unsigned integer color target.
+/// The vertex stage of the original material is kept, so vertex displacement
+/// and skinning show up in the IDs as they do on screen. Masked materials
+/// discard the same fragments as with the glTF shader, whose inputs they use.
+#[derive(Debug, Default)]
+pub struct IdMaterial {
+    variants: RefCell<Vec<(Weak<Material>, Rc<Material>)>>,
+}
+
+impl IdMaterial {
+    const OBJECT_ID_UNIFORM: &'static str = "u_ObjectId";
+    const PRIMITIVE_ID_UNIFORM: &'static str = "u_PrimitiveId";
+    const ALPHA_MASK_DEFINE: &'static str = "ALPHA_MASK";
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn variant(
+        &self,
+        context: &WebGl2RenderingContext,
+        material: &Rc<Material>,
+        object_id: u32,
+        primitive_id: u32,
+    ) -> Result<Rc<Material>> {
+        let variant = self.find_or_create(context, material)?;
+        variant.update_uniform(context, Self::OBJECT_ID_UNIFORM, &object_id, Level::Ignore);
+        variant.update_uniform(
+            context,
+            Self::PRIMITIVE_ID_UNIFORM,
+            &primitive_id,
+            Level::Ignore,
+        );
+        Ok(variant)
+    }
+
+    fn find_or_create(
+        &self,
+        context: &WebGl2RenderingContext,
+        material: &Rc<Material>,
+    ) -> Result<Rc<Material>> {
+        let mut variants = self.variants.borrow_mut();
+        variants.retain(|(source, _)| source.strong_count() > 0);
+        let existing = variants
+            .iter()
+            .find(|(source, _)| ptr::eq(source.as_ptr(), Rc::as_ptr(material)))
+            .map(|(_, variant)| Rc::clone(variant));
+        if let Some(variant) = existing {
+            return Ok(variant);
+        }
+        let defines: &[&str] = match material.alpha_mode() {
+            AlphaMode::Mask { .. } => &[Self::ALPHA_MASK_DEFINE],
+            _ => &[],
+        };
+        let variant = material.with_fragment_shader(
+            context,
+            include_str!("fragment.glsl").into(),
+            defines,
+        )?;
+        variants.push((Rc::downgrade(material), Rc::clone(&variant)));
+        Ok(variant)
+    }
+}
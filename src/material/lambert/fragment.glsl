@@ -2,8 +2,11 @@
 
 precision highp float;
 
+#include <color_space>
+
 const int DIRECTIONAL = 1;
 const int POINT = 2;
+const int SPOT = 3;
 
 struct Light {
     int lightType;
@@ -11,6 +14,9 @@ struct Light {
     vec3 direction;
     vec3 position;
     vec3 attenuation;
+    float range;
+    float innerConeCos;
+    float outerConeCos;
 };
 
 uniform Light light0;
@@ -47,16 +53,32 @@ float lightAttenuation(vec3 attenuation, float distance) {
     return 1.0 / (attenuation[0] + attenuation[1] * distance + attenuation[2] * distance * distance);
 }
 
+float rangeAttenuation(float range, float distance) {
+    if (range <= 0.0) {
+        return 1.0;
+    }
+    return clamp(1.0 - pow(distance / range, 4.0), 0.0, 1.0);
+}
+
+float spotAttenuation(Light light, vec3 lightDirection) {
+    float cosine = dot(normalize(light.direction), lightDirection);
+    return smoothstep(light.outerConeCos, light.innerConeCos, cosine);
+}
+
 vec4 lightCalc(Light light, vec3 pointPosition, vec3 pointNormal) {
     float diffuse = 0.0;
     vec3 lightDirection;
     float attenuation = 1.0;
     if (light.lightType == DIRECTIONAL) {
         lightDirection = normalize(light.direction);
-    } else if (light.lightType == POINT) {
+    } else if (light.lightType == POINT || light.lightType == SPOT) {
         lightDirection = normalize(pointPosition - light.position);
         float distance = length(light.position - pointPosition);
         attenuation = lightAttenuation(light.attenuation, distance);
+        attenuation *= rangeAttenuation(light.range, distance);
+        if (light.lightType == SPOT) {
+            attenuation *= spotAttenuation(light, lightDirection);
+        }
     }
     if (light.lightType > 0) {
         pointNormal = normalize(pointNormal);
@@ -69,6 +91,7 @@ vec4 lightCalc(Light light, vec3 pointPosition, vec3 pointNormal) {
 in vec3 v_Position;
 in vec2 v_UV;
 in vec3 v_Normal;
+in vec4 v_Tangent;
 
 bool fragmentInShadow() {
     if (dot(normalize(v_Normal), -normalize(shadow0.lightDirection)) <= 0.01) {
@@ -80,17 +103,31 @@ bool fragmentInShadow() {
     return fragmentDistanceToLight > closestDistanceToLight + shadow0.bias;
 }
 
+vec3 perturbNormal(vec3 normal, vec4 tangent, vec3 texel, float strength) {
+    vec3 n = normalize(normal);
+    vec3 t = normalize(tangent.xyz - n * dot(n, tangent.xyz));
+    vec3 b = cross(n, t) * tangent.w;
+    vec3 mapped = texel * 2.0 - 1.0;
+    mapped.xy *= strength;
+    return normalize(mat3(t, b, n) * mapped);
+}
+
 out vec4 fragColor;
 
 void main() {
-    vec4 color = material.diffuse;
+    vec4 color = srgbToLinear(material.diffuse);
     if (material.useTexture) {
         color *= texture(material.texture0, v_UV);
     }
 
     vec3 bumpNormal = v_Normal;
     if (material.useBumpTexture) {
-        bumpNormal += material.bumpStrength * vec3(texture(material.bumpTexture, v_UV));
+        vec3 bump = vec3(texture(material.bumpTexture, v_UV));
+        if (dot(v_Tangent.xyz, v_Tangent.xyz) > 0.0) {
+            bumpNormal = perturbNormal(v_Normal, v_Tangent, bump, material.bumpStrength);
+        } else {
+            bumpNormal += material.bumpStrength * bump;
+        }
     }
 
     vec4 total = vec4(0.0, 0.0, 0.0, 0.0);
@@ -98,11 +135,11 @@ void main() {
     total += lightCalc(light1, v_Position, bumpNormal);
     total += lightCalc(light2, v_Position, bumpNormal);
     total += lightCalc(light3, v_Position, bumpNormal);
-    total += material.ambient;
+    total += srgbToLinear(material.ambient);
     color *= vec4(total.xyz, 1.0);
     if (useShadow && fragmentInShadow()) {
         float s = 1.0 - shadow0.strength;
         color *= vec4(s, s, s, 1.0);
     }
-    fragColor = color;
+    fragColor = encodeOutput(color);
 }
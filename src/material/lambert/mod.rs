@@ -10,7 +10,7 @@ use crate::{
     },
     classic::texture::Sampler2D,
     core::{
-        material::{GenericMaterial, Material, Source},
+        material::{GenericMaterial, Material, PbrProperties, Source, TextureRef},
         program::{self, Program, UpdateProgramUniforms, UpdateUniform},
     },
 };
@@ -92,6 +92,22 @@ impl GenericMaterial for LambertMaterial {
     fn fragment_shader(&self) -> Source<'_> {
         include_str!("fragment.glsl").into()
     }
+
+    fn pbr_properties(&self) -> PbrProperties {
+        PbrProperties {
+            base_color_factor: self.diffuse,
+            base_color_texture: self
+                .texture
+                .as_ref()
+                .map(|sampler| TextureRef::new(Rc::clone(&sampler.texture), 0)),
+            roughness_factor: 1.0,
+            ..Default::default()
+        }
+    }
+
+    fn needs_tangents(&self) -> bool {
+        self.bump_texture.is_some()
+    }
 }
 
 pub fn create(
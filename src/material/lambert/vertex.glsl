@@ -6,10 +6,12 @@ uniform mat4 u_ViewProjectionMatrix;
 in vec3 a_position;
 in vec2 a_texcoord_0;
 in vec3 a_normal;
+in vec4 a_tangent;
 
 out vec3 v_Position;
 out vec2 v_UV;
 out vec3 v_Normal;
+out vec4 v_Tangent;
 
 struct Shadow {
     vec3 lightDirection;
@@ -34,6 +36,7 @@ void main() {
     v_Position = vec3(worldPosition);
     v_UV = a_texcoord_0;
     v_Normal = normalize(mat3(u_ModelMatrix) * a_normal);
+    v_Tangent = vec4(mat3(u_ModelMatrix) * a_tangent.xyz, a_tangent.w);
     if (useShadow) {
         shadowPosition0 = vec3(shadowPosition(worldPosition));
     }
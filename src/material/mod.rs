@@ -1,6 +1,7 @@
 pub mod basic;
 pub mod depth;
 pub mod flat;
+pub mod id;
 pub mod lambert;
 pub mod phong;
 pub mod sprite;
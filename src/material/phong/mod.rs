@@ -10,7 +10,7 @@ use crate::{
     },
     classic::texture::Sampler2D,
     core::{
-        material::{GenericMaterial, Material, Source},
+        material::{GenericMaterial, Material, PbrProperties, Source, TextureRef},
         program::{self, Program, UpdateProgramUniforms, UpdateUniform},
     },
 };
@@ -103,6 +103,22 @@ impl GenericMaterial for PhongMaterial {
     fn fragment_shader(&self) -> Source<'_> {
         include_str!("fragment.glsl").into()
     }
+
+    fn pbr_properties(&self) -> PbrProperties {
+        PbrProperties {
+            base_color_factor: self.diffuse,
+            base_color_texture: self
+                .texture
+                .as_ref()
+                .map(|sampler| TextureRef::new(Rc::clone(&sampler.texture), 0)),
+            roughness_factor: (2.0 / (self.shininess + 2.0)).sqrt(),
+            ..Default::default()
+        }
+    }
+
+    fn needs_tangents(&self) -> bool {
+        self.bump_texture.is_some()
+    }
 }
 
 pub fn create(
@@ -61,8 +61,7 @@ impl SpriteMaterial {
 impl UpdateProgramUniforms for SpriteMaterial {
     fn update_program_uniforms(&self, context: &WebGl2RenderingContext, program: &Program) {
         self.properties.update_program_uniforms(context, program);
-        self.unit.active_texture(context);
-        self.texture.bind(context);
+        self.texture.bind_to_unit(context, self.unit);
         self.unit.update_uniform(context, "texture0", program);
     }
 }
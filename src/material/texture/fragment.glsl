@@ -2,15 +2,17 @@
 
 precision highp float;
 
+#include <color_space>
+
 uniform vec4 baseColor;
 uniform sampler2D textureSampler;
 in vec2 uv;
 out vec4 fragColor;
 
 void main() {
-    vec4 color = baseColor * texture(textureSampler, uv);
+    vec4 color = srgbToLinear(baseColor) * texture(textureSampler, uv);
     if (color.a < 0.10) {
         discard;
     }
-    fragColor = color;
+    fragColor = encodeOutput(color);
 }
@@ -12,7 +12,7 @@ use crate::{
     },
     classic::texture::Sampler2D,
     core::{
-        material::{GenericMaterial, Material, Source},
+        material::{GenericMaterial, Material, PbrProperties, Source, TextureRef},
         program::{Program, UpdateProgramUniforms, UpdateUniform},
         texture::{Texture, TextureUnit},
     },
@@ -53,6 +53,15 @@ impl GenericMaterial for TextureMaterial {
     fn double_sided(&self) -> bool {
         self.properties.double_side
     }
+
+    fn pbr_properties(&self) -> PbrProperties {
+        PbrProperties {
+            base_color_factor: self.properties.base_color,
+            base_color_texture: Some(TextureRef::new(Rc::clone(&self.sampler.texture), 0)),
+            unlit: true,
+            ..Default::default()
+        }
+    }
 }
 
 #[derive(Debug)]
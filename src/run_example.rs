@@ -6,7 +6,7 @@ use crate::examples::{
     e19_blend_textures, e20_distort_texture, e21_procedural_texture, e22_text_texture,
     e23_billboarding, e24_sprite_material, e25_heads_up_display, e26_render_to_texture,
     e27_compound_effect, e28_lights, e29_bump_mapping, e30_bloom_effect, e31_glow_effect,
-    e32_shadows, e33_gltf,
+    e32_shadows, e33_gltf, e34_scene_file, e35_prefabs,
 };
 
 pub fn run_example() {
@@ -39,6 +39,8 @@ fn examples() -> Vec<Box<dyn Fn()>> {
         e31_glow_effect::example(),
         e32_shadows::example(),
         e33_gltf::example(),
+        e34_scene_file::example(),
+        e35_prefabs::example(),
     ]
 }
 
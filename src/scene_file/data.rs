@@ -0,0 +1,386 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    base::math::{
+        angle::Angle,
+        euler::{Euler, EulerOrder},
+    },
+    classic::shadow::{CameraBounds, ShadowOptions},
+    core::node::Node,
+    geometry::{
+        box_geom::BoxGeometry,
+        parametric::{Cone, Cylinder, Plane, Sphere},
+        polygon::Polygon,
+    },
+    material::{lambert::LambertMaterial, phong::PhongMaterial, sprite},
+};
+
+/// Relative URIs are resolved against the file's own URI.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SceneFile {
+    pub clear_color: Option<[f32; 4]>,
+    pub materials: BTreeMap<String, MaterialData>,
+    pub nodes: Vec<NodeData>,
+    pub lights: Vec<LightData>,
+    pub shadow: Option<ShadowData>,
+    pub postprocessing: Vec<EffectData>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct NodeData {
+    pub name: Option<String>,
+    pub translation: [f32; 3],
+    /// Quaternion as `[x, y, z, w]`.
+    pub rotation: [f32; 4],
+    /// XYZ order; takes precedence over `rotation`.
+    pub euler_degrees: Option<[f32; 3]>,
+    pub scale: [f32; 3],
+    pub visible: bool,
+    pub cast_shadow: bool,
+    pub receive_shadow: bool,
+    pub mesh: Option<MeshData>,
+    pub camera: Option<CameraData>,
+    /// Default scene of a glTF file, added below this node.
+    pub gltf: Option<String>,
+    pub children: Vec<NodeData>,
+}
+
+impl Default for NodeData {
+    fn default() -> Self {
+        Self {
+            name: None,
+            translation: [0.0; 3],
+            rotation: [0.0, 0.0, 0.0, 1.0],
+            euler_degrees: None,
+            scale: [1.0; 3],
+            visible: true,
+            cast_shadow: true,
+            receive_shadow: true,
+            mesh: None,
+            camera: None,
+            gltf: None,
+            children: vec![],
+        }
+    }
+}
+
+impl NodeData {
+    pub fn apply_to(&self, node: &mut Node) {
+        node.set_position(&self.translation.into());
+        match self.euler_degrees {
+            Some([x, y, z]) => node.set_euler_angles(&Euler {
+                x: Angle::from_degrees(x),
+                y: Angle::from_degrees(y),
+                z: Angle::from_degrees(z),
+                order: EulerOrder::Xyz,
+            }),
+            None => {
+                let [x, y, z, w] = self.rotation;
+                node.set_rotation(&glm::quat(x, y, z, w));
+            }
+        }
+        node.set_scale(&self.scale.into());
+        node.set_visible(self.visible);
+        node.set_cast_shadow(self.cast_shadow);
+        node.set_receive_shadow(self.receive_shadow);
+    }
+
+    /// Replaces Euler angles with the quaternion.
+    pub fn update_from(&mut self, node: &Node) {
+        self.translation = node.position().into();
+        self.rotation = node.rotation().coords.into();
+        self.euler_degrees = None;
+        self.scale = node.scale().into();
+        self.visible = node.is_visible();
+        self.cast_shadow = node.casts_shadow();
+        self.receive_shadow = node.receives_shadow();
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MeshData {
+    pub geometry: GeometryData,
+    /// White surface when absent.
+    pub material: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum GeometryData {
+    Box(BoxGeometry),
+    Sphere(Sphere),
+    Plane(Plane),
+    Cylinder(Cylinder),
+    Cone(Cone),
+    Polygon(Polygon),
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum MaterialData {
+    Basic(BasicData),
+    Lambert(LambertData),
+    Phong(PhongData),
+    Sprite(SpriteData),
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct BasicData {
+    pub color: [f32; 4],
+    pub vertex_colors: bool,
+    pub double_side: bool,
+}
+
+impl Default for BasicData {
+    fn default() -> Self {
+        Self {
+            color: [1.0; 4],
+            vertex_colors: false,
+            double_side: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct LambertData {
+    pub texture: Option<String>,
+    pub ambient: [f32; 4],
+    pub diffuse: [f32; 4],
+    pub bump_texture: Option<String>,
+    pub bump_strength: f32,
+    pub double_side: bool,
+    pub use_shadow: bool,
+}
+
+impl Default for LambertData {
+    fn default() -> Self {
+        let material = LambertMaterial::default();
+        Self {
+            texture: None,
+            ambient: material.ambient.into(),
+            diffuse: material.diffuse.into(),
+            bump_texture: None,
+            bump_strength: material.bump_strength,
+            double_side: material.double_side,
+            use_shadow: material.use_shadow,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct PhongData {
+    pub texture: Option<String>,
+    pub ambient: [f32; 4],
+    pub diffuse: [f32; 4],
+    pub specular_strength: f32,
+    pub shininess: f32,
+    pub bump_texture: Option<String>,
+    pub bump_strength: f32,
+    pub double_side: bool,
+    pub use_shadow: bool,
+}
+
+impl Default for PhongData {
+    fn default() -> Self {
+        let material = PhongMaterial::default();
+        Self {
+            texture: None,
+            ambient: material.ambient.into(),
+            diffuse: material.diffuse.into(),
+            specular_strength: material.specular_strength,
+            shininess: material.shininess,
+            bump_texture: None,
+            bump_strength: material.bump_strength,
+            double_side: material.double_side,
+            use_shadow: material.use_shadow,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SpriteData {
+    pub texture: String,
+    pub color: [f32; 4],
+    pub billboard: bool,
+    pub tile_number: f32,
+    pub tile_count: [f32; 2],
+    pub double_side: bool,
+}
+
+impl Default for SpriteData {
+    fn default() -> Self {
+        let properties = sprite::Properties::default();
+        Self {
+            texture: String::new(),
+            color: properties.base_color.into(),
+            billboard: properties.billboard,
+            tile_number: properties.tile_number,
+            tile_count: properties.tile_count.into(),
+            double_side: properties.double_side,
+        }
+    }
+}
+
+/// Field of view in degrees.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum CameraData {
+    Perspective {
+        fov: f32,
+        near: f32,
+        far: Option<f32>,
+    },
+    Orthographic {
+        left: f32,
+        right: f32,
+        bottom: f32,
+        top: f32,
+        near: f32,
+        far: f32,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LightType {
+    Directional,
+    Point,
+    Spot,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct LightData {
+    #[serde(rename = "type")]
+    pub light_type: LightType,
+    pub color: [f32; 4],
+    pub position: [f32; 3],
+    pub direction: [f32; 3],
+    /// Constant, linear and quadratic factors.
+    pub attenuation: Option<[f32; 3]>,
+    pub range: Option<f32>,
+    pub inner_cone_degrees: f32,
+    pub outer_cone_degrees: f32,
+}
+
+impl Default for LightData {
+    fn default() -> Self {
+        Self {
+            light_type: LightType::Directional,
+            color: [1.0; 4],
+            position: [0.0; 3],
+            direction: [0.0, -1.0, 0.0],
+            attenuation: None,
+            range: None,
+            inner_cone_degrees: 20.0,
+            outer_cone_degrees: 30.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ShadowData {
+    /// Index of a directional light in `lights`.
+    pub light: usize,
+    pub strength: f32,
+    pub bias: f32,
+    pub resolution_scale: f32,
+    pub camera_min: [f32; 3],
+    pub camera_max: [f32; 3],
+}
+
+impl Default for ShadowData {
+    fn default() -> Self {
+        let options = ShadowOptions::default();
+        Self {
+            light: 0,
+            strength: options.strength,
+            bias: options.bias,
+            resolution_scale: 1.0,
+            camera_min: options.camera_bounds.min.into(),
+            camera_max: options.camera_bounds.max.into(),
+        }
+    }
+}
+
+impl From<&ShadowData> for ShadowOptions {
+    fn from(shadow: &ShadowData) -> Self {
+        Self {
+            strength: shadow.strength,
+            camera_bounds: CameraBounds {
+                min: shadow.camera_min.into(),
+                max: shadow.camera_max.into(),
+            },
+            bias: shadow.bias,
+        }
+    }
+}
+
+/// `pass` of `additiveBlend` indexes earlier outputs, 0 being the rendered scene.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum EffectData {
+    Tint {
+        color: [f32; 4],
+    },
+    Pixelate {
+        size: u16,
+    },
+    ColorReduce {
+        levels: u16,
+    },
+    BrightFilter {
+        threshold: f32,
+    },
+    HorizontalBlur {
+        radius: i32,
+    },
+    VerticalBlur {
+        radius: i32,
+    },
+    AdditiveBlend {
+        pass: usize,
+        original: f32,
+        blend: f32,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn node_data_works() {
+        let data = NodeData {
+            translation: [1.0, 2.0, 3.0],
+            euler_degrees: Some([0.0, 90.0, 0.0]),
+            scale: [2.0; 3],
+            cast_shadow: false,
+            ..Default::default()
+        };
+        let node = Node::new_empty();
+        data.apply_to(&mut node.borrow_mut());
+        let direction = node.borrow().direction();
+        assert!(glm::distance(&direction, &glm::vec3(-1.0, 0.0, 0.0)) < 1e-6);
+        assert!(!node.borrow().casts_shadow());
+
+        let mut saved = NodeData::default();
+        saved.update_from(&node.borrow());
+        assert_eq!(saved.translation, data.translation);
+        assert_eq!(saved.scale, data.scale);
+        assert!(saved.euler_degrees.is_none());
+        let copy = Node::new_empty();
+        saved.apply_to(&mut copy.borrow_mut());
+        assert_eq!(copy.borrow().rotation(), node.borrow().rotation());
+        assert!(!copy.borrow().casts_shadow());
+    }
+}
@@ -0,0 +1,617 @@
+use std::{collections::HashMap, rc::Rc};
+
+use anyhow::{anyhow, bail, Result};
+use url::Url;
+use web_sys::WebGl2RenderingContext;
+
+use crate::{
+    api::geometry::Geometry,
+    base::{
+        asset::AssetSource,
+        color::ColorSpace,
+        convert::FromWithContext,
+        math::matrix,
+        util::{
+            shared_ref::{self, SharedRef},
+            validate,
+        },
+        web,
+    },
+    classic::{
+        light::{Attenuation, Cone, Light, Lights},
+        renderer::{self, Renderer, RendererOptions},
+        shadow::Shadow,
+        texture::Sampler2D,
+    },
+    core::{
+        camera::{Camera, Orthographic, Perspective},
+        material::Material,
+        mesh::Mesh,
+        node::Node,
+        prefab::Prefab,
+        scene::Scene,
+        texture::{Texture, TextureUnit},
+    },
+    extras::{
+        effects::{self, Blend, Blur, BrightFilter},
+        postprocessor::Postprocessor,
+    },
+    gltf,
+    material::{
+        basic::{BasicMaterial, SurfaceMaterial},
+        lambert::LambertMaterial,
+        phong::PhongMaterial,
+        sprite::{self, SpriteMaterial},
+    },
+};
+
+use super::{
+    data::{
+        CameraData, EffectData, GeometryData, LightData, LightType, MaterialData, NodeData,
+        SceneFile,
+    },
+    save::Sources,
+};
+
+const SHADOW_TEXTURE_UNIT: TextureUnit = TextureUnit(15);
+
+enum View {
+    Direct { renderer: Renderer, scene: Scene },
+    Postprocessed(Postprocessor),
+}
+
+#[derive(Debug, Clone)]
+pub enum SceneMaterial {
+    Basic(SharedRef<SurfaceMaterial>),
+    Lambert(SharedRef<LambertMaterial>),
+    Phong(SharedRef<PhongMaterial>),
+    Sprite(SharedRef<SpriteMaterial>),
+}
+
+impl FromWithContext<WebGl2RenderingContext, SceneMaterial> for Rc<Material> {
+    fn from_with_context(context: &WebGl2RenderingContext, value: SceneMaterial) -> Result<Self> {
+        match value {
+            SceneMaterial::Basic(basic) => Self::from_with_context(context, basic),
+            SceneMaterial::Lambert(lambert) => Self::from_with_context(context, lambert),
+            SceneMaterial::Phong(phong) => Self::from_with_context(context, phong),
+            SceneMaterial::Sprite(sprite) => Self::from_with_context(context, sprite),
+        }
+    }
+}
+
+pub struct LoadedScene {
+    file: SceneFile,
+    sources: Sources,
+    lights: Lights,
+    camera: SharedRef<Node>,
+    view: View,
+}
+
+impl LoadedScene {
+    pub fn file(&self) -> &SceneFile {
+        &self.file
+    }
+
+    pub fn sources(&self) -> &Sources {
+        &self.sources
+    }
+
+    /// Meshes, materials and prefabs added later must be registered to be saved.
+    pub fn sources_mut(&mut self) -> &mut Sources {
+        &mut self.sources
+    }
+
+    pub fn lights(&self) -> &Lights {
+        &self.lights
+    }
+
+    pub fn camera(&self) -> &SharedRef<Node> {
+        &self.camera
+    }
+
+    pub fn scene(&self) -> &Scene {
+        match &self.view {
+            View::Direct { scene, .. } => scene,
+            View::Postprocessed(postprocessor) => postprocessor.scene(),
+        }
+    }
+
+    pub fn scene_mut(&mut self) -> &mut Scene {
+        match &mut self.view {
+            View::Direct { scene, .. } => scene,
+            View::Postprocessed(postprocessor) => postprocessor.scene_mut(),
+        }
+    }
+
+    pub fn render(&self, context: &WebGl2RenderingContext) {
+        self.lights.update();
+        match &self.view {
+            View::Direct { renderer, scene } => {
+                if let Some(camera) = self.camera.borrow().camera() {
+                    renderer.render_with_lights(context, scene, camera, &self.lights);
+                }
+            }
+            View::Postprocessed(postprocessor) => postprocessor.render(context, &self.lights),
+        }
+    }
+}
+
+type Textures = HashMap<(String, ColorSpace), (Rc<Texture>, TextureUnit)>;
+
+struct Builder<'a> {
+    context: &'a WebGl2RenderingContext,
+    textures: Textures,
+    materials: HashMap<String, Rc<Material>>,
+    default_material: Option<Rc<Material>>,
+    prefabs: HashMap<String, Rc<Prefab>>,
+    sources: Sources,
+}
+
+pub async fn load(
+    context: &WebGl2RenderingContext,
+    source: &dyn AssetSource,
+    uri: &str,
+) -> Result<LoadedScene> {
+    let base_url = self::resolve(uri)?;
+    let data = source.read_bytes(base_url.as_str()).await?;
+    let json = std::str::from_utf8(&data)
+        .map_err(|error| anyhow!("Invalid scene JSON in {}: {}", uri, error))?;
+    let file = self::parse(json)
+        .map_err(|error| error.context(format!("Cannot load scene from {}", uri)))?;
+    self::build(context, source, &base_url, file).await
+}
+
+pub fn parse(json: &str) -> Result<SceneFile> {
+    let value = js_sys::JSON::parse(json)
+        .map_err(|error| anyhow!("Error while parsing scene: {:#?}", error))?;
+    serde_wasm_bindgen::from_value(value)
+        .map_err(|error| anyhow!("Invalid scene description: {:#?}", error))
+}
+
+pub async fn build(
+    context: &WebGl2RenderingContext,
+    source: &dyn AssetSource,
+    base_url: &Url,
+    file: SceneFile,
+) -> Result<LoadedScene> {
+    let mut options = RendererOptions::default();
+    validate::assert(file.lights.len() <= options.light_count, || {
+        anyhow!(
+            "A scene supports at most {} lights, found {}",
+            options.light_count,
+            file.lights.len()
+        )
+    })?;
+    if let Some(clear_color) = file.clear_color {
+        options.clear_color = clear_color.into();
+    }
+
+    let mut builder = Builder {
+        context,
+        textures: self::load_textures(context, source, base_url, &file).await?,
+        materials: HashMap::new(),
+        default_material: None,
+        prefabs: self::load_prefabs(context, source, base_url, &file.nodes).await?,
+        sources: Sources::new(),
+    };
+    for ((uri, _), (texture, _)) in builder.textures.iter() {
+        builder.sources.add_texture(texture, uri);
+    }
+    for (uri, prefab) in builder.prefabs.iter() {
+        builder.sources.add_prefab(prefab, uri);
+    }
+    for (name, material) in file.materials.iter() {
+        let (built, material) = builder
+            .build_material(material)
+            .map_err(|error| error.context(format!("Cannot build material {}", name)))?;
+        builder.materials.insert(name.clone(), built);
+        builder.sources.add_material(name, material);
+    }
+
+    let mut scene = Scene::new_empty();
+    for node in file.nodes.iter() {
+        scene.add_node(builder.build_node(node)?);
+    }
+    let camera = match scene.find(|node| node.camera().is_some()) {
+        Some(camera) => camera,
+        None => {
+            let camera = Node::new_with_camera(Camera::new(Perspective::default()));
+            scene.add_node(Rc::clone(&camera));
+            camera
+        }
+    };
+
+    let mut lights = Lights::new();
+    for data in file.lights.iter() {
+        let light_node = lights.create_node(self::build_light(data));
+        light_node.set_position(&data.position.into());
+        if data.light_type != LightType::Point {
+            light_node.set_direction(&data.direction.into());
+        }
+        light_node.add_to_scene(&mut scene);
+    }
+
+    let shadow = match &file.shadow {
+        Some(shadow) => {
+            let light_node = lights
+                .light_nodes()
+                .get(shadow.light)
+                .filter(|light_node| light_node.is_directional())
+                .ok_or_else(|| {
+                    anyhow!("Shadow light {} is not a directional light", shadow.light)
+                })?;
+            Some(Shadow::initialize(
+                context,
+                Rc::clone(light_node),
+                renderer::get_canvas_resolution(context).scale(shadow.resolution_scale),
+                SHADOW_TEXTURE_UNIT,
+                shadow.into(),
+            )?)
+        }
+        None => None,
+    };
+    let renderer = Renderer::initialize(context, options, shadow);
+
+    let view = if file.postprocessing.is_empty() {
+        View::Direct { renderer, scene }
+    } else {
+        let camera = camera
+            .borrow()
+            .camera()
+            .cloned()
+            .ok_or_else(|| anyhow!("Camera node has no camera"))?;
+        let mut unit = builder.textures.len() as i32;
+        let mut postprocessor = Postprocessor::initialize(
+            context,
+            Rc::new(renderer),
+            scene,
+            camera,
+            None,
+            self::texture_unit(&mut unit)?,
+        )?;
+        for effect in file.postprocessing.iter() {
+            self::add_effect(context, &mut postprocessor, effect, &mut unit)?;
+        }
+        View::Postprocessed(postprocessor)
+    };
+
+    Ok(LoadedScene {
+        file,
+        sources: builder.sources,
+        lights,
+        camera,
+        view,
+    })
+}
+
+impl Builder<'_> {
+    fn build_node(&mut self, data: &NodeData) -> Result<SharedRef<Node>> {
+        let mesh = match &data.mesh {
+            Some(mesh_data) => {
+                let material = match &mesh_data.material {
+                    Some(name) => self
+                        .materials
+                        .get(name)
+                        .cloned()
+                        .ok_or_else(|| anyhow!("Unknown material {}", name))?,
+                    None => self.default_material()?,
+                };
+                let geometry = self::build_geometry(self.context, &mesh_data.geometry)?;
+                let mesh = Mesh::initialize(self.context, &geometry, material)?;
+                self.sources.add_mesh(&mesh, mesh_data.clone());
+                Some(mesh)
+            }
+            None => None,
+        };
+        let camera = data.camera.as_ref().map(self::build_camera);
+        let node = Node::new(matrix::identity(), mesh, camera, data.name.clone());
+        data.apply_to(&mut node.borrow_mut());
+
+        if let Some(uri) = &data.gltf {
+            let prefab = self
+                .prefabs
+                .get(uri)
+                .ok_or_else(|| anyhow!("glTF file {} is not loaded", uri))?;
+            node.borrow_mut().add_child(prefab.instantiate());
+        }
+        for child in data.children.iter() {
+            let child = self.build_node(child)?;
+            node.borrow_mut().add_child(child);
+        }
+        Ok(node)
+    }
+
+    fn default_material(&mut self) -> Result<Rc<Material>> {
+        if let Some(material) = &self.default_material {
+            return Ok(Rc::clone(material));
+        }
+        let material = <Rc<Material>>::from_with_context(
+            self.context,
+            shared_ref::new(SurfaceMaterial::default()),
+        )?;
+        Ok(Rc::clone(self.default_material.insert(material)))
+    }
+
+    fn texture(&self, uri: &str, color_space: ColorSpace) -> Result<Sampler2D> {
+        let (texture, unit) = self
+            .textures
+            .get(&(String::from(uri), color_space))
+            .ok_or_else(|| anyhow!("Texture {} is not loaded", uri))?;
+        Ok(Sampler2D::new(Rc::clone(texture), *unit))
+    }
+
+    fn build_material(&self, data: &MaterialData) -> Result<(Rc<Material>, SceneMaterial)> {
+        let texture = |uri: &Option<String>, color_space| {
+            uri.as_deref()
+                .map(|uri| self.texture(uri, color_space))
+                .transpose()
+        };
+        let material = match data {
+            MaterialData::Basic(basic) => SceneMaterial::Basic(shared_ref::new(SurfaceMaterial {
+                basic: BasicMaterial {
+                    base_color: basic.color.into(),
+                    use_vertex_colors: basic.vertex_colors,
+                },
+                double_side: basic.double_side,
+            })),
+            MaterialData::Lambert(lambert) => {
+                SceneMaterial::Lambert(shared_ref::new(LambertMaterial {
+                    double_side: lambert.double_side,
+                    texture: texture(&lambert.texture, ColorSpace::Srgb)?,
+                    ambient: lambert.ambient.into(),
+                    diffuse: lambert.diffuse.into(),
+                    bump_texture: texture(&lambert.bump_texture, ColorSpace::Linear)?,
+                    bump_strength: lambert.bump_strength,
+                    use_shadow: lambert.use_shadow,
+                }))
+            }
+            MaterialData::Phong(phong) => SceneMaterial::Phong(shared_ref::new(PhongMaterial {
+                double_side: phong.double_side,
+                texture: texture(&phong.texture, ColorSpace::Srgb)?,
+                ambient: phong.ambient.into(),
+                diffuse: phong.diffuse.into(),
+                specular_strength: phong.specular_strength,
+                shininess: phong.shininess,
+                bump_texture: texture(&phong.bump_texture, ColorSpace::Linear)?,
+                bump_strength: phong.bump_strength,
+                use_shadow: phong.use_shadow,
+            })),
+            MaterialData::Sprite(sprite) => {
+                let (texture, unit) = self
+                    .textures
+                    .get(&(sprite.texture.clone(), ColorSpace::Srgb))
+                    .cloned()
+                    .ok_or_else(|| anyhow!("Texture {} is not loaded", sprite.texture))?;
+                SceneMaterial::Sprite(shared_ref::new(SpriteMaterial {
+                    properties: sprite::Properties {
+                        base_color: sprite.color.into(),
+                        billboard: sprite.billboard,
+                        tile_number: sprite.tile_number,
+                        tile_count: sprite.tile_count.into(),
+                        double_side: sprite.double_side,
+                    },
+                    texture,
+                    unit,
+                }))
+            }
+        };
+        let built = <Rc<Material>>::from_with_context(self.context, material.clone())?;
+        Ok((built, material))
+    }
+}
+
+fn resolve(uri: &str) -> Result<Url> {
+    match Url::parse(uri) {
+        Err(url::ParseError::RelativeUrlWithoutBase) => {
+            let base = web::document()?
+                .url()
+                .map_err(|error| anyhow!("Cannot get document URL: {:#?}", error))?;
+            Ok(Url::parse(&base)?.join(uri)?)
+        }
+        url => Ok(url?),
+    }
+}
+
+fn texture_unit(next: &mut i32) -> Result<TextureUnit> {
+    validate::assert(*next < SHADOW_TEXTURE_UNIT.0, || {
+        anyhow!(
+            "Scene uses more than {} texture units",
+            SHADOW_TEXTURE_UNIT.0
+        )
+    })?;
+    let unit = TextureUnit(*next);
+    *next += 1;
+    Ok(unit)
+}
+
+async fn load_textures(
+    context: &WebGl2RenderingContext,
+    source: &dyn AssetSource,
+    base_url: &Url,
+    file: &SceneFile,
+) -> Result<Textures> {
+    let mut textures = Textures::new();
+    let uris = file.materials.values().flat_map(|material| match material {
+        MaterialData::Basic(_) => vec![],
+        MaterialData::Lambert(lambert) => vec![
+            (lambert.texture.as_ref(), ColorSpace::Srgb),
+            (lambert.bump_texture.as_ref(), ColorSpace::Linear),
+        ],
+        MaterialData::Phong(phong) => vec![
+            (phong.texture.as_ref(), ColorSpace::Srgb),
+            (phong.bump_texture.as_ref(), ColorSpace::Linear),
+        ],
+        MaterialData::Sprite(sprite) => vec![(Some(&sprite.texture), ColorSpace::Srgb)],
+    });
+    for (uri, color_space) in uris {
+        let Some(uri) = uri else {
+            continue;
+        };
+        let key = (uri.clone(), color_space);
+        if textures.contains_key(&key) {
+            continue;
+        }
+        let mut next_unit = textures.len() as i32;
+        let unit = self::texture_unit(&mut next_unit)?;
+        let url = base_url.join(uri)?;
+        let texture =
+            Texture::load_with_color_space(context, source, url.as_str(), color_space).await?;
+        textures.insert(key, (texture, unit));
+    }
+    Ok(textures)
+}
+
+async fn load_prefabs(
+    context: &WebGl2RenderingContext,
+    source: &dyn AssetSource,
+    base_url: &Url,
+    nodes: &[NodeData],
+) -> Result<HashMap<String, Rc<Prefab>>> {
+    let mut uris = vec![];
+    self::collect_gltf_uris(nodes, &mut uris);
+    let mut result = HashMap::new();
+    for uri in uris {
+        if result.contains_key(uri) {
+            continue;
+        }
+        let url = base_url.join(uri)?;
+        let root = gltf::load::load_with_source(context, source, url.as_str()).await?;
+        let nodes = root.current_scene().map_or(&[][..], Scene::nodes);
+        result.insert(uri.clone(), Prefab::new(Some(uri.clone()), nodes));
+    }
+    Ok(result)
+}
+
+fn collect_gltf_uris<'a>(nodes: &'a [NodeData], uris: &mut Vec<&'a String>) {
+    for node in nodes {
+        uris.extend(node.gltf.as_ref());
+        self::collect_gltf_uris(&node.children, uris);
+    }
+}
+
+fn build_geometry(context: &WebGl2RenderingContext, data: &GeometryData) -> Result<Geometry> {
+    match data.clone() {
+        GeometryData::Box(geometry) => Geometry::from_with_context(context, geometry),
+        GeometryData::Sphere(geometry) => Geometry::from_with_context(context, geometry),
+        GeometryData::Plane(geometry) => Geometry::from_with_context(context, geometry),
+        GeometryData::Cylinder(geometry) => Geometry::from_with_context(context, geometry),
+        GeometryData::Cone(geometry) => Geometry::from_with_context(context, geometry),
+        GeometryData::Polygon(geometry) => Geometry::from_with_context(context, geometry),
+    }
+}
+
+fn build_camera(data: &CameraData) -> SharedRef<Camera> {
+    match *data {
+        CameraData::Perspective { fov, near, far } => Camera::new(Perspective {
+            y_fov: fov.to_radians(),
+            z_near: near,
+            z_far: far,
+            ..Default::default()
+        }),
+        CameraData::Orthographic {
+            left,
+            right,
+            bottom,
+            top,
+            near,
+            far,
+        } => Camera::new(Orthographic {
+            x_left: left,
+            x_right: right,
+            y_bottom: bottom,
+            y_top: top,
+            z_near: near,
+            z_far: far,
+        }),
+    }
+}
+
+fn build_light(data: &LightData) -> Light {
+    let color = data.color.into();
+    let light = match data.light_type {
+        LightType::Directional => return Light::directional(color, data.direction.into()),
+        LightType::Point => Light::point(color, data.position.into()),
+        LightType::Spot => Light::spot(
+            color,
+            data.position.into(),
+            data.direction.into(),
+            Cone {
+                inner_angle: data.inner_cone_degrees.to_radians(),
+                outer_angle: data.outer_cone_degrees.to_radians(),
+            },
+        ),
+    };
+    let light = match data.attenuation {
+        Some([constant, linear, quadratic]) => {
+            light.with_attenuation(Attenuation::new(constant, linear, quadratic))
+        }
+        None => light,
+    };
+    light.with_range(data.range)
+}
+
+fn add_effect(
+    context: &WebGl2RenderingContext,
+    postprocessor: &mut Postprocessor,
+    effect: &EffectData,
+    unit: &mut i32,
+) -> Result<()> {
+    match *effect {
+        EffectData::Tint { color } => postprocessor.add_effect(context, |sampler| {
+            effects::tint(context, sampler, color.into())
+        }),
+        EffectData::Pixelate { size } => postprocessor.add_effect(context, |sampler| {
+            let resolution = sampler.resolution();
+            effects::pixelate(context, sampler, size, resolution)
+        }),
+        EffectData::ColorReduce { levels } => postprocessor.add_effect(context, |sampler| {
+            effects::color_reduce(context, sampler, levels)
+        }),
+        EffectData::BrightFilter { threshold } => postprocessor.add_effect(context, |sampler| {
+            effects::bright_filter(context, sampler, BrightFilter { threshold })
+        }),
+        EffectData::HorizontalBlur { radius } => postprocessor.add_effect(context, |sampler| {
+            let texture_size = sampler.resolution();
+            effects::horizontal_blur(
+                context,
+                sampler,
+                Blur {
+                    texture_size,
+                    blur_radius: radius,
+                },
+            )
+        }),
+        EffectData::VerticalBlur { radius } => postprocessor.add_effect(context, |sampler| {
+            let texture_size = sampler.resolution();
+            effects::vertical_blur(
+                context,
+                sampler,
+                Blur {
+                    texture_size,
+                    blur_radius: radius,
+                },
+            )
+        }),
+        EffectData::AdditiveBlend {
+            pass,
+            original,
+            blend,
+        } => {
+            let Some(texture) = postprocessor.get_texture(pass) else {
+                bail!("Post-processing pass {} has no output to blend", pass);
+            };
+            let blend_texture = Sampler2D::new(texture, self::texture_unit(unit)?);
+            postprocessor.add_effect(context, |sampler| {
+                effects::additive_blend(
+                    context,
+                    sampler,
+                    blend_texture.clone(),
+                    Blend {
+                        original_strength: original,
+                        blend_strength: blend,
+                    },
+                )
+            })
+        }
+    }
+}
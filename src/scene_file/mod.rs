@@ -0,0 +1,3 @@
+pub mod data;
+pub mod load;
+pub mod save;
@@ -0,0 +1,381 @@
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap, HashSet},
+    rc::Rc,
+};
+
+use anyhow::{anyhow, bail, Result};
+use serde::Serialize;
+
+use crate::{
+    base::util::{shared_ref::SharedRef, validate},
+    classic::{
+        light::{self, LightNode, Lights},
+        texture::Sampler2D,
+    },
+    core::{
+        camera::{Camera, CameraType},
+        mesh::Mesh,
+        node::Node,
+        prefab::Prefab,
+        scene::Scene,
+        texture::Texture,
+    },
+};
+
+use super::{
+    data::{
+        BasicData, CameraData, LambertData, LightData, LightType, MaterialData, MeshData, NodeData,
+        PhongData, SceneFile, SpriteData,
+    },
+    load::{LoadedScene, SceneMaterial},
+};
+
+/// What a live scene cannot tell about itself, such as mesh generators and URIs.
+#[derive(Debug, Default)]
+pub struct Sources {
+    materials: BTreeMap<String, SceneMaterial>,
+    meshes: HashMap<*const Mesh, (Rc<Mesh>, MeshData)>,
+    textures: HashMap<*const Texture, (Rc<Texture>, String)>,
+    prefabs: HashMap<*const Prefab, (Rc<Prefab>, String)>,
+}
+
+impl Sources {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn materials(&self) -> &BTreeMap<String, SceneMaterial> {
+        &self.materials
+    }
+
+    pub fn add_material(&mut self, name: &str, material: SceneMaterial) {
+        self.materials.insert(String::from(name), material);
+    }
+
+    pub fn add_mesh(&mut self, mesh: &Rc<Mesh>, data: MeshData) {
+        self.meshes
+            .insert(Rc::as_ptr(mesh), (Rc::clone(mesh), data));
+    }
+
+    pub fn add_texture(&mut self, texture: &Rc<Texture>, uri: &str) {
+        self.textures
+            .insert(Rc::as_ptr(texture), (Rc::clone(texture), String::from(uri)));
+    }
+
+    pub fn add_prefab(&mut self, prefab: &Rc<Prefab>, uri: &str) {
+        self.prefabs
+            .insert(Rc::as_ptr(prefab), (Rc::clone(prefab), String::from(uri)));
+    }
+
+    fn mesh(&self, mesh: &Rc<Mesh>) -> Option<&MeshData> {
+        self.meshes.get(&Rc::as_ptr(mesh)).map(|(_, data)| data)
+    }
+
+    fn texture_uri(&self, texture: &Rc<Texture>) -> Result<String> {
+        self.textures
+            .get(&Rc::as_ptr(texture))
+            .map(|(_, uri)| uri.clone())
+            .ok_or_else(|| anyhow!("Texture has no URI"))
+    }
+
+    fn prefab_uri(&self, prefab: &Rc<Prefab>) -> Option<&String> {
+        self.prefabs.get(&Rc::as_ptr(prefab)).map(|(_, uri)| uri)
+    }
+}
+
+/// Clear color, shadow and post-processing are kept from the loaded file.
+pub fn save(loaded: &LoadedScene) -> Result<String> {
+    let file = loaded.file();
+    self::stringify(&SceneFile {
+        clear_color: file.clear_color,
+        shadow: file.shadow.clone(),
+        postprocessing: file.postprocessing.clone(),
+        ..self::describe(loaded.scene(), loaded.lights(), loaded.sources())?
+    })
+}
+
+pub fn describe(scene: &Scene, lights: &Lights, sources: &Sources) -> Result<SceneFile> {
+    let materials = sources
+        .materials
+        .iter()
+        .map(|(name, material)| {
+            let data = self::describe_material(material, sources)
+                .map_err(|error| error.context(format!("Cannot save material {}", name)))?;
+            Ok((name.clone(), data))
+        })
+        .collect::<Result<_>>()?;
+    let describer = Describer {
+        sources,
+        light_nodes: lights
+            .light_nodes()
+            .iter()
+            .map(|light_node| Rc::as_ptr(light_node.node()))
+            .collect(),
+    };
+    let mut nodes = vec![];
+    for node in scene.nodes() {
+        nodes.extend(describer.describe_node(node)?);
+    }
+    let lights = lights
+        .light_nodes()
+        .iter()
+        .map(|light_node| self::describe_light(light_node))
+        .collect::<Result<_>>()?;
+    Ok(SceneFile {
+        materials,
+        nodes,
+        lights,
+        ..Default::default()
+    })
+}
+
+struct Describer<'a> {
+    sources: &'a Sources,
+    light_nodes: HashSet<*const RefCell<Node>>,
+}
+
+impl Describer<'_> {
+    fn describe_node(&self, node: &SharedRef<Node>) -> Result<Option<NodeData>> {
+        let is_light = self.light_nodes.contains(&Rc::as_ptr(node));
+        let node = node.borrow();
+        let name = node.name().unwrap_or("unnamed");
+        if is_light {
+            validate::assert(node.children().is_empty(), || {
+                anyhow!("Light node {} has children, which cannot be saved", name)
+            })?;
+            return Ok(None);
+        }
+        let mut data = NodeData {
+            name: node.name().map(String::from),
+            ..Default::default()
+        };
+        data.update_from(&node);
+        if let Some(prefab) = node.prefab() {
+            let uri = self
+                .sources
+                .prefab_uri(prefab)
+                .ok_or_else(|| anyhow!("Prefab of node {} has no glTF file", name))?;
+            data.gltf = Some(uri.clone());
+            return Ok(Some(data));
+        }
+        data.mesh = match node.mesh() {
+            Some(mesh) => Some(
+                self.sources
+                    .mesh(mesh)
+                    .cloned()
+                    .ok_or_else(|| anyhow!("Mesh of node {} has no geometry", name))?,
+            ),
+            None => None,
+        };
+        data.camera = node
+            .camera()
+            .map(|camera| self::describe_camera(&camera.borrow()));
+        for child in node.children() {
+            match self.describe_node(child)? {
+                Some(child) if data.gltf.is_none() && self::is_plain_instance(&child) => {
+                    data.gltf = child.gltf;
+                }
+                Some(child) => data.children.push(child),
+                None => {}
+            }
+        }
+        Ok(Some(data))
+    }
+}
+
+/// An unchanged instance as loaded from the `gltf` of its parent, folded back into it.
+fn is_plain_instance(data: &NodeData) -> bool {
+    let default = NodeData::default();
+    data.gltf.is_some()
+        && data.name == data.gltf
+        && data.translation == default.translation
+        && data.rotation == default.rotation
+        && data.scale == default.scale
+        && data.visible == default.visible
+        && data.cast_shadow == default.cast_shadow
+        && data.receive_shadow == default.receive_shadow
+}
+
+fn describe_camera(camera: &Camera) -> CameraData {
+    match camera.camera_type() {
+        CameraType::Perspective(perspective) => CameraData::Perspective {
+            fov: perspective.y_fov.to_degrees(),
+            near: perspective.z_near,
+            far: perspective.z_far,
+        },
+        CameraType::Orthographic(orthographic) => CameraData::Orthographic {
+            left: orthographic.x_left,
+            right: orthographic.x_right,
+            bottom: orthographic.y_bottom,
+            top: orthographic.y_top,
+            near: orthographic.z_near,
+            far: orthographic.z_far,
+        },
+    }
+}
+
+fn describe_light(light_node: &LightNode) -> Result<LightData> {
+    let light = light_node.light().borrow();
+    let node = light_node.node().borrow();
+    let mut data = LightData {
+        color: light.srgb_color().into(),
+        range: light.range,
+        ..Default::default()
+    };
+    match light.light_type {
+        Some(light::LightType::Directional { .. }) => {
+            data.direction = node.world_direction().into();
+            return Ok(data);
+        }
+        Some(light::LightType::Point { .. }) => {
+            data.light_type = LightType::Point;
+            data.position = node.world_position().into();
+        }
+        Some(light::LightType::Spot { cone, .. }) => {
+            data.light_type = LightType::Spot;
+            data.position = node.world_position().into();
+            data.direction = node.world_direction().into();
+            data.inner_cone_degrees = cone.inner_angle.to_degrees();
+            data.outer_cone_degrees = cone.outer_angle.to_degrees();
+        }
+        None => bail!("Light has no type"),
+    }
+    data.attenuation = Some(glm::Vec3::from(light.attenuation).into());
+    Ok(data)
+}
+
+fn describe_material(material: &SceneMaterial, sources: &Sources) -> Result<MaterialData> {
+    let texture = |sampler: &Option<Sampler2D>| {
+        sampler
+            .as_ref()
+            .map(|sampler| sources.texture_uri(&sampler.texture))
+            .transpose()
+    };
+    Ok(match material {
+        SceneMaterial::Basic(material) => {
+            let material = material.borrow();
+            MaterialData::Basic(BasicData {
+                color: material.basic.base_color.into(),
+                vertex_colors: material.basic.use_vertex_colors,
+                double_side: material.double_side,
+            })
+        }
+        SceneMaterial::Lambert(material) => {
+            let material = material.borrow();
+            MaterialData::Lambert(LambertData {
+                texture: texture(&material.texture)?,
+                ambient: material.ambient.into(),
+                diffuse: material.diffuse.into(),
+                bump_texture: texture(&material.bump_texture)?,
+                bump_strength: material.bump_strength,
+                double_side: material.double_side,
+                use_shadow: material.use_shadow,
+            })
+        }
+        SceneMaterial::Phong(material) => {
+            let material = material.borrow();
+            MaterialData::Phong(PhongData {
+                texture: texture(&material.texture)?,
+                ambient: material.ambient.into(),
+                diffuse: material.diffuse.into(),
+                specular_strength: material.specular_strength,
+                shininess: material.shininess,
+                bump_texture: texture(&material.bump_texture)?,
+                bump_strength: material.bump_strength,
+                double_side: material.double_side,
+                use_shadow: material.use_shadow,
+            })
+        }
+        SceneMaterial::Sprite(material) => {
+            let material = material.borrow();
+            let properties = &material.properties;
+            MaterialData::Sprite(SpriteData {
+                texture: sources.texture_uri(&material.texture)?,
+                color: properties.base_color.into(),
+                billboard: properties.billboard,
+                tile_number: properties.tile_number,
+                tile_count: properties.tile_count.into(),
+                double_side: properties.double_side,
+            })
+        }
+    })
+}
+
+pub fn stringify(file: &SceneFile) -> Result<String> {
+    let serializer = serde_wasm_bindgen::Serializer::new().serialize_maps_as_objects(true);
+    let value = file
+        .serialize(&serializer)
+        .map_err(|error| anyhow!("Cannot serialize scene: {:#?}", error))?;
+    js_sys::JSON::stringify_with_replacer_and_space(&value, &wasm_bindgen::JsValue::NULL, &2.into())
+        .map(String::from)
+        .map_err(|error| anyhow!("Cannot stringify scene: {:#?}", error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        base::{color, util::shared_ref},
+        classic::light::Light,
+        core::camera::Perspective,
+        geometry::box_geom::BoxGeometry,
+        material::basic::SurfaceMaterial,
+    };
+
+    use super::super::data::GeometryData;
+
+    #[test]
+    fn describe_works() {
+        let mut sources = Sources::new();
+        let material = shared_ref::new(SurfaceMaterial::default());
+        sources.add_material("white", SceneMaterial::Basic(material));
+        let mesh = Mesh::new(vec![], vec![], None);
+        sources.add_mesh(
+            &mesh,
+            MeshData {
+                geometry: GeometryData::Box(BoxGeometry::default()),
+                material: Some(String::from("white")),
+            },
+        );
+        let prefab = Prefab::new(Some(String::from("duck.gltf")), &[Node::with_name("Duck")]);
+        sources.add_prefab(&prefab, "duck.gltf");
+
+        let mut scene = Scene::new_empty();
+        let parent = Node::with_name("Parent");
+        parent.borrow_mut().add_child(prefab.instantiate());
+        parent.borrow_mut().add_child(Node::new_with_mesh(mesh));
+        scene.add_node(parent);
+        let moved = prefab.instantiate();
+        moved.borrow_mut().set_position(&glm::vec3(1.0, 2.0, 3.0));
+        scene.add_node(moved);
+        scene.add_node(Node::new_with_camera(Camera::new(Perspective::default())));
+        let mut lights = Lights::new();
+        let position = glm::vec3(0.0, 4.0, 0.0);
+        let light_node = lights.create_node(Light::point(color::white(), position));
+        light_node.add_to_scene(&mut scene);
+
+        let file = self::describe(&scene, &lights, &sources).unwrap();
+        assert!(matches!(file.materials["white"], MaterialData::Basic(_)));
+        assert_eq!(file.nodes.len(), 3);
+        let parent = &file.nodes[0];
+        assert_eq!(parent.gltf.as_deref(), Some("duck.gltf"));
+        assert_eq!(parent.children.len(), 1);
+        let mesh = parent.children[0].mesh.as_ref().unwrap();
+        assert_eq!(mesh.material.as_deref(), Some("white"));
+        assert_eq!(file.nodes[1].gltf.as_deref(), Some("duck.gltf"));
+        assert_eq!(file.nodes[1].translation, [1.0, 2.0, 3.0]);
+        assert!(file.nodes[1].children.is_empty());
+        assert!(matches!(
+            file.nodes[2].camera,
+            Some(CameraData::Perspective { .. })
+        ));
+        assert_eq!(file.lights.len(), 1);
+        assert_eq!(file.lights[0].light_type, LightType::Point);
+        assert_eq!(file.lights[0].position, [0.0, 4.0, 0.0]);
+
+        scene.add_node(Node::new_with_mesh(Mesh::new(vec![], vec![], None)));
+        assert!(self::describe(&scene, &lights, &sources).is_err());
+    }
+}